@@ -6,7 +6,7 @@ use self::tags::EvalTag;
 use self::tags::{operator_to_constructor, TermTag};
 
 use hvm::syntax::Term;
-use kind_span::Range;
+use kind_span::{Range, RangeTable};
 use kind_tree::desugared::{self, Book, Expr};
 use kind_tree::symbol::{Ident, QualifiedIdent};
 
@@ -81,6 +81,17 @@ fn mk_single_ctr(head: String) -> Box<Term> {
     })
 }
 
+/// The signed operators are wrapped so the checker can tell that
+/// the operands are `I60` numbers.
+fn codegen_operator(op: kind_tree::Operator, signed: bool) -> Box<Term> {
+    let op = mk_single_ctr(operator_to_constructor(op).to_owned());
+    if signed {
+        mk_ctr("Kind.Operator.signed".to_string(), vec![op])
+    } else {
+        op
+    }
+}
+
 fn mk_ctr_name(ident: &QualifiedIdent) -> Box<Term> {
     mk_single_ctr(format!("{}.", ident))
 }
@@ -89,20 +100,20 @@ fn mk_ctr_name_from_str(ident: &str) -> Box<Term> {
     mk_single_ctr(format!("{}.", ident))
 }
 
-fn range_to_num(lhs: bool, range: Range) -> Box<Term> {
+fn range_to_num(ranges: &mut RangeTable, lhs: bool, range: Range) -> Box<Term> {
     if lhs {
         mk_var("orig")
     } else {
         Box::new(Term::U6O {
-            numb: u60::new(range.encode().0),
+            numb: u60::new(ranges.encode(range).0),
         })
     }
 }
 
-fn set_origin(ident: &Ident) -> Box<Term> {
+fn set_origin(ranges: &mut RangeTable, ident: &Ident) -> Box<Term> {
     mk_lifted_ctr(
         "Kind.Term.set_origin".to_owned(),
-        vec![range_to_num(false, ident.range), mk_var(ident.to_str())],
+        vec![range_to_num(ranges, false, ident.range), mk_var(ident.to_str())],
     )
 }
 
@@ -121,7 +132,7 @@ fn desugar_str(input: &str, range: Range) -> Box<desugared::Expr> {
         .rfold(desugared::Expr::ctr(range, nil, vec![]), |right, chr| {
             desugared::Expr::ctr(
                 range,
-                cons.clone(),
+                cons,
                 vec![desugared::Expr::num_u60(range, chr as u64), right],
             )
         })
@@ -145,6 +156,7 @@ fn codegen_str(input: &str) -> Box<Term> {
 }
 
 fn codegen_all_expr(
+    ranges: &mut RangeTable,
     lhs_rule: bool,
     lhs: bool,
     num: &mut usize,
@@ -155,22 +167,26 @@ fn codegen_all_expr(
     match &expr.data {
         Typ => mk_lifted_ctr(
             eval_ctr(quote, TermTag::Typ),
-            vec![range_to_num(lhs, expr.range)],
+            vec![range_to_num(ranges, lhs, expr.range)],
         ),
         NumTypeU60 => mk_lifted_ctr(
             eval_ctr(quote, TermTag::U60),
-            vec![range_to_num(lhs, expr.range)],
+            vec![range_to_num(ranges, lhs, expr.range)],
         ),
         NumTypeF60 => todo!(),
+        NumTypeI60 => mk_lifted_ctr(
+            eval_ctr(quote, TermTag::I60),
+            vec![range_to_num(ranges, lhs, expr.range)],
+        ),
         Var { name } => {
             if quote && !lhs {
-                set_origin(name)
+                set_origin(ranges, name)
             } else if lhs_rule {
                 *num += 1;
                 mk_lifted_ctr(
                     eval_ctr(quote, TermTag::Var),
                     vec![
-                        range_to_num(lhs, expr.range),
+                        range_to_num(ranges, lhs, expr.range),
                         mk_u60(name.encode()),
                         mk_u60((*num - 1) as u64),
                     ],
@@ -187,10 +203,10 @@ fn codegen_all_expr(
         } => mk_lifted_ctr(
             eval_ctr(quote, TermTag::All),
             vec![
-                range_to_num(lhs, expr.range),
+                range_to_num(ranges, lhs, expr.range),
                 mk_u60(param.encode()),
-                codegen_all_expr(lhs_rule, lhs, num, quote, typ),
-                lam(param, codegen_all_expr(lhs_rule, lhs, num, quote, body)),
+                codegen_all_expr(ranges, lhs_rule, lhs, num, quote, typ),
+                lam(param, codegen_all_expr(ranges, lhs_rule, lhs, num, quote, body)),
             ],
         ),
         Lambda {
@@ -200,20 +216,20 @@ fn codegen_all_expr(
         } => mk_lifted_ctr(
             eval_ctr(quote, TermTag::Lambda),
             vec![
-                range_to_num(lhs, expr.range),
+                range_to_num(ranges, lhs, expr.range),
                 mk_u60(param.encode()),
-                lam(param, codegen_all_expr(lhs_rule, lhs, num, quote, body)),
+                lam(param, codegen_all_expr(ranges, lhs_rule, lhs, num, quote, body)),
             ],
         ),
         App { fun, args } => args.iter().fold(
-            codegen_all_expr(lhs_rule, lhs, num, quote, fun),
+            codegen_all_expr(ranges, lhs_rule, lhs, num, quote, fun),
             |left, right| {
                 mk_lifted_ctr(
                     eval_ctr(quote, TermTag::App),
                     vec![
-                        range_to_num(lhs, expr.range),
+                        range_to_num(ranges, lhs, expr.range),
                         left,
-                        codegen_all_expr(lhs_rule, lhs, num, quote, &right.data),
+                        codegen_all_expr(ranges, lhs_rule, lhs, num, quote, &right.data),
                     ],
                 )
             },
@@ -222,22 +238,22 @@ fn codegen_all_expr(
             eval_ctr(quote, TermTag::Ctr(args.len())),
             vec_preppend![
                 mk_ctr_name(name),
-                range_to_num(lhs, expr.range);
-                args.iter().cloned().map(|x| codegen_all_expr(lhs_rule, lhs, num, quote, &x)).collect::<Vec<Box<Term>>>()
+                range_to_num(ranges, lhs, expr.range);
+                args.iter().cloned().map(|x| codegen_all_expr(ranges, lhs_rule, lhs, num, quote, &x)).collect::<Vec<Box<Term>>>()
             ],
         ),
         Fun { name, args } => {
             let new_spine: Vec<Box<Term>> = args
                 .iter()
                 .cloned()
-                .map(|x| codegen_all_expr(lhs_rule, lhs, num, quote, &x))
+                .map(|x| codegen_all_expr(ranges, lhs_rule, lhs, num, quote, &x))
                 .collect();
             if quote {
                 mk_lifted_ctr(
                     eval_ctr(quote, TermTag::Fun(new_spine.len())),
                     vec_preppend![
                         mk_ctr_name(name),
-                        range_to_num(lhs, expr.range);
+                        range_to_num(ranges, lhs, expr.range);
                         new_spine
                     ],
                 )
@@ -245,7 +261,7 @@ fn codegen_all_expr(
                 mk_ctr(
                     TermTag::HoasF(name.to_string()).to_string(),
                     vec_preppend![
-                        range_to_num(lhs, expr.range);
+                        range_to_num(ranges, lhs, expr.range);
                         new_spine
                     ],
                 )
@@ -254,18 +270,18 @@ fn codegen_all_expr(
         Let { name, val, next } => mk_ctr(
             eval_ctr(quote, TermTag::Let),
             vec![
-                range_to_num(lhs, expr.range),
+                range_to_num(ranges, lhs, expr.range),
                 mk_u60(name.encode()),
-                codegen_all_expr(lhs_rule, lhs, num, quote, val),
-                lam(name, codegen_all_expr(lhs_rule, lhs, num, quote, next)),
+                codegen_all_expr(ranges, lhs_rule, lhs, num, quote, val),
+                lam(name, codegen_all_expr(ranges, lhs_rule, lhs, num, quote, next)),
             ],
         ),
         Ann { expr, typ } => mk_ctr(
             eval_ctr(quote, TermTag::Ann),
             vec![
-                range_to_num(lhs, expr.range),
-                codegen_all_expr(lhs_rule, lhs, num, quote, expr),
-                codegen_all_expr(lhs_rule, lhs, num, quote, typ),
+                range_to_num(ranges, lhs, expr.range),
+                codegen_all_expr(ranges, lhs_rule, lhs, num, quote, expr),
+                codegen_all_expr(ranges, lhs_rule, lhs, num, quote, typ),
             ],
         ),
         Sub {
@@ -276,62 +292,71 @@ fn codegen_all_expr(
         } => mk_ctr(
             eval_ctr(quote, TermTag::Sub),
             vec![
-                range_to_num(lhs, expr.range),
+                range_to_num(ranges, lhs, expr.range),
                 mk_u60(name.encode()),
                 mk_u60(*indx as u64),
                 mk_u60(*redx as u64),
-                codegen_all_expr(lhs_rule, lhs, num, quote, expr),
+                codegen_all_expr(ranges, lhs_rule, lhs, num, quote, expr),
             ],
         ),
         NumU60 { numb } => mk_lifted_ctr(
             eval_ctr(quote, TermTag::NUMU60),
-            vec![range_to_num(lhs, expr.range), mk_u60(*numb)],
+            vec![range_to_num(ranges, lhs, expr.range), mk_u60(*numb)],
         ),
         NumF60 { numb: _ } => todo!(),
-        Binary { op, left, right } => mk_lifted_ctr(
+        NumI60 { numb } => mk_lifted_ctr(
+            eval_ctr(quote, TermTag::NUMI60),
+            vec![range_to_num(ranges, lhs, expr.range), mk_u60(*numb as u64)],
+        ),
+        Binary {
+            op,
+            left,
+            right,
+            signed,
+        } => mk_lifted_ctr(
             eval_ctr(quote, TermTag::Binary),
             vec![
-                range_to_num(lhs, expr.range),
-                mk_single_ctr(operator_to_constructor(*op).to_owned()),
-                codegen_all_expr(lhs_rule, lhs, num, quote, left),
-                codegen_all_expr(lhs_rule, lhs, num, quote, right),
+                range_to_num(ranges, lhs, expr.range),
+                codegen_operator(*op, *signed),
+                codegen_all_expr(ranges, lhs_rule, lhs, num, quote, left),
+                codegen_all_expr(ranges, lhs_rule, lhs, num, quote, right),
             ],
         ),
         Hole { num } => mk_lifted_ctr(
             eval_ctr(quote, TermTag::Hole),
-            vec![range_to_num(lhs, expr.range), mk_u60(*num)],
+            vec![range_to_num(ranges, lhs, expr.range), mk_u60(*num)],
         ),
-        Str { val } => codegen_all_expr(lhs_rule, lhs, num, quote, &desugar_str(val, expr.range)),
+        Str { val } => codegen_all_expr(ranges, lhs_rule, lhs, num, quote, &desugar_str(val, expr.range)),
         Hlp(_) => mk_lifted_ctr(
             eval_ctr(quote, TermTag::Hlp),
-            vec![range_to_num(lhs, expr.range)],
+            vec![range_to_num(ranges, lhs, expr.range)],
         ),
         Err => panic!("Internal Error: Was not expecting an ERR node inside the HVM checker"),
     }
 }
 
-fn codegen_expr(quote: bool, expr: &Expr) -> Box<Term> {
-    codegen_all_expr(false, false, &mut 0, quote, expr)
+fn codegen_expr(ranges: &mut RangeTable, quote: bool, expr: &Expr) -> Box<Term> {
+    codegen_all_expr(ranges, false, false, &mut 0, quote, expr)
 }
 
-fn codegen_pattern(args: &mut usize, quote: bool, expr: &Expr) -> Box<Term> {
-    codegen_all_expr(false, true, args, quote, expr)
+fn codegen_pattern(ranges: &mut RangeTable, args: &mut usize, quote: bool, expr: &Expr) -> Box<Term> {
+    codegen_all_expr(ranges, false, true, args, quote, expr)
 }
 
-fn codegen_type(args: &[desugared::Argument], typ: &desugared::Expr) -> Box<lang::Term> {
+fn codegen_type(ranges: &mut RangeTable, args: &[desugared::Argument], typ: &desugared::Expr) -> Box<lang::Term> {
     if !args.is_empty() {
         let arg = &args[0];
         mk_lifted_ctr(
             eval_ctr(true, TermTag::All),
             vec![
-                range_to_num(false, arg.range),
+                range_to_num(ranges, false, arg.range),
                 mk_u60(arg.name.encode()),
-                codegen_expr(true, &arg.typ),
-                lam(&arg.name, codegen_type(&args[1..], typ)),
+                codegen_expr(ranges, true, &arg.typ),
+                lam(&arg.name, codegen_type(ranges, &args[1..], typ)),
             ],
         )
     } else {
-        codegen_expr(true, typ)
+        codegen_expr(ranges, true, typ)
     }
 }
 
@@ -386,13 +411,13 @@ fn codegen_rule_end(file: &mut lang::File, rule: &desugared::Rule) {
     });
 }
 
-fn codegen_rule(file: &mut lang::File, rule: &desugared::Rule) {
+fn codegen_rule(ranges: &mut RangeTable, file: &mut lang::File, rule: &desugared::Rule) {
     let mut count = 0;
 
     let lhs_args = rule
         .pats
         .iter()
-        .map(|x| codegen_pattern(&mut count, false, x))
+        .map(|x| codegen_pattern(ranges, &mut count, false, x))
         .collect::<Vec<Box<Term>>>();
 
     file.rules.push(lang::Rule {
@@ -403,7 +428,7 @@ fn codegen_rule(file: &mut lang::File, rule: &desugared::Rule) {
                 lhs_args
             ],
         ),
-        rhs: codegen_expr(true, &rule.body),
+        rhs: codegen_expr(ranges, true, &rule.body),
     });
 
     if rule.name.to_string().as_str() == "HVM.log" {
@@ -435,12 +460,13 @@ fn codegen_rule(file: &mut lang::File, rule: &desugared::Rule) {
                     lhs_args
                 ],
             ),
-            rhs: codegen_expr(false, &rule.body),
+            rhs: codegen_expr(ranges, false, &rule.body),
         });
     }
 }
 
 fn codegen_entry_rules(
+    ranges: &mut RangeTable,
     count: &mut usize,
     index: usize,
     args: &mut Vec<Box<Term>>,
@@ -454,26 +480,26 @@ fn codegen_entry_rules(
                 format!("QT{}", index),
                 vec_preppend![
                     mk_ctr_name(&entry.name),
-                    range_to_num(false, entry.range);
+                    range_to_num(ranges, false, entry.range);
                     args
                 ],
             )],
         )
     } else {
         let pat = &pats[0];
-        let expr = codegen_all_expr(true, false, count, false, pat);
+        let expr = codegen_all_expr(ranges, true, false, count, false, pat);
         args.push(expr.clone());
         mk_ctr(
             "Kind.Rule.lhs".to_owned(),
             vec![
                 expr,
-                codegen_entry_rules(count, index + 1, args, entry, &pats[1..]),
+                codegen_entry_rules(ranges, count, index + 1, args, entry, &pats[1..]),
             ],
         )
     }
 }
 
-fn codegen_entry(file: &mut lang::File, entry: &desugared::Entry) {
+fn codegen_entry(ranges: &mut RangeTable, file: &mut lang::File, entry: &desugared::Entry) {
     file.rules.push(lang::Rule {
         lhs: mk_ctr(
             "Kind.Axiom.NameOf".to_owned(),
@@ -487,7 +513,7 @@ fn codegen_entry(file: &mut lang::File, entry: &desugared::Entry) {
             "Kind.Axiom.OrigOf".to_owned(),
             vec![mk_ctr_name(&entry.name)],
         ),
-        rhs: range_to_num(false, entry.name.range),
+        rhs: range_to_num(ranges, false, entry.name.range),
     });
 
     file.rules.push(lang::Rule {
@@ -503,7 +529,7 @@ fn codegen_entry(file: &mut lang::File, entry: &desugared::Entry) {
             "Kind.Axiom.TypeOf".to_owned(),
             vec![mk_ctr_name(&entry.name)],
         ),
-        rhs: codegen_type(&entry.args, &entry.typ),
+        rhs: codegen_type(ranges, &entry.args, &entry.typ),
     });
 
     let base_vars = (0..entry.args.len())
@@ -547,7 +573,7 @@ fn codegen_entry(file: &mut lang::File, entry: &desugared::Entry) {
     });
 
     for rule in &entry.rules {
-        codegen_rule(file, rule);
+        codegen_rule(ranges, file, rule);
     }
 
     if !entry.rules.is_empty() {
@@ -557,7 +583,7 @@ fn codegen_entry(file: &mut lang::File, entry: &desugared::Entry) {
     let rules = entry
         .rules
         .iter()
-        .map(|rule| codegen_entry_rules(&mut 0, 0, &mut Vec::new(), rule, &rule.pats));
+        .map(|rule| codegen_entry_rules(ranges, &mut 0, 0, &mut Vec::new(), rule, &rule.pats));
 
     file.rules.push(lang::Rule {
         lhs: mk_ctr(
@@ -568,7 +594,7 @@ fn codegen_entry(file: &mut lang::File, entry: &desugared::Entry) {
     });
 }
 
-pub fn codegen_coverage(file: &mut lang::File, book: &Book) {
+pub fn codegen_coverage(ranges: &mut RangeTable, file: &mut lang::File, book: &Book) {
     for entry in book.entrs.values() {
         if !entry.rules.is_empty() && !entry.rules[0].pats.is_empty() && !entry.attrs.partial && !entry.attrs.axiom {
             file.rules.push(lang::Rule {
@@ -614,15 +640,15 @@ pub fn codegen_coverage(file: &mut lang::File, book: &Book) {
 
             let mut maker = mk_ctr(
                 "Kind.Coverage.Maker.End".to_string(),
-                vec![codegen_expr(
+                vec![codegen_expr(ranges, 
                     false,
                     &kind_tree::desugared::Expr::ctr(
                         constructor.range,
-                        constructor.clone(),
+                        *constructor,
                         entry
                             .args
                             .iter()
-                            .map(|x| kind_tree::desugared::Expr::var(x.name.clone()))
+                            .map(|x| kind_tree::desugared::Expr::var(x.name))
                             .collect(),
                     ),
                 )],
@@ -632,8 +658,8 @@ pub fn codegen_coverage(file: &mut lang::File, book: &Book) {
                 maker = mk_ctr(
                     "Kind.Coverage.Maker.Cons".to_string(),
                     vec![
-                        range_to_num(false, arg.range),
-                        codegen_all_expr(false, false, &mut 0, false, &arg.typ),
+                        range_to_num(ranges, false, arg.range),
+                        codegen_all_expr(ranges, false, false, &mut 0, false, &arg.typ),
                         lam(&arg.name, maker),
                     ],
                 );
@@ -649,7 +675,7 @@ pub fn codegen_coverage(file: &mut lang::File, book: &Book) {
                             eval_ctr(true, TermTag::Ctr(args.len())),
                             vec_preppend![
                                 mk_ctr_name(&family.name),
-                                range_to_num(true, constructor.range);
+                                range_to_num(ranges, true, constructor.range);
                                 args
                             ],
                         ),
@@ -686,8 +712,14 @@ pub fn codegen_coverage(file: &mut lang::File, book: &Book) {
 }
 
 /// Compiles a book into an format that is executed by the
-/// type checker in HVM.
-pub fn codegen_book(book: &Book, check_coverage: bool, functions_to_check: Vec<String>) -> lang::File {
+/// type checker in HVM. The ranges are sent as ids that are
+/// registered in the `ranges` table.
+pub fn codegen_book(
+    book: &Book,
+    ranges: &mut RangeTable,
+    check_coverage: bool,
+    functions_to_check: Vec<String>,
+) -> lang::File {
     let mut file = lang::File {
         rules: vec![],
         smaps: vec![],
@@ -715,11 +747,11 @@ pub fn codegen_book(book: &Book, check_coverage: bool, functions_to_check: Vec<S
     }
 
     for entry in book.entrs.values() {
-        codegen_entry(&mut file, entry);
+        codegen_entry(ranges, &mut file, entry);
     }
 
     if check_coverage {
-        codegen_coverage(&mut file, book);
+        codegen_coverage(ranges, &mut file, book);
     }
 
     file.rules.push(lang::Rule {
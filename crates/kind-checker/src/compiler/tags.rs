@@ -23,6 +23,8 @@ pub enum TermTag {
     Typ,
     U60,
     NUMU60,
+    I60,
+    NUMI60,
     Binary,
     Hole,
     Hlp,
@@ -58,6 +60,8 @@ impl fmt::Display for TermTag {
             TermTag::Typ => write!(f, "Kind.Term.typ"),
             TermTag::U60 => write!(f, "Kind.Term.U60"),
             TermTag::NUMU60 => write!(f, "Kind.Term.u60"),
+            TermTag::I60 => write!(f, "Kind.Term.I60"),
+            TermTag::NUMI60 => write!(f, "Kind.Term.i60"),
             TermTag::Binary => write!(f, "Kind.Term.op2"),
             TermTag::Hole => write!(f, "Kind.Term.hol"),
             TermTag::Hlp => write!(f, "Kind.Term.hlp"),
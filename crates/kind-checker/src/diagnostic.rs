@@ -1,10 +1,14 @@
 //! Errors created by the type checker.
 
 use kind_report::data::{Color, Diagnostic, DiagnosticFrame, Marker, Severity, Subtitle, Word};
+use kind_pretty::Doc;
+use std::sync::Arc;
+
 use kind_span::Range;
-use kind_tree::desugared::Expr;
+use kind_tree::desugared::{Book, Expr, ExprKind, Notation};
 
 use crate::report::Context;
+use crate::resugar::show;
 
 #[derive(Debug)]
 pub(crate) enum TypeDiagnostic {
@@ -16,7 +20,36 @@ pub(crate) enum TypeDiagnostic {
     Inspection(Context, Range, Box<Expr>),
     TooManyArguments(Context, Range),
     TypeMismatch(Context, Range, Box<Expr>, Box<Expr>),
-    UncoveredPattern(Context, Range, Vec<Box<Expr>>)
+    UncoveredPattern(Context, Range, Vec<Box<Expr>>),
+    /// An expression inside of an interpolated string that is not a
+    /// `String`, with its type and the `show` function of the type.
+    InterpolationNotString(Context, Range, Box<Expr>, Option<String>),
+    /// An `I60` operand of a binary operation that was compiled as
+    /// unsigned because its type was not known before type checking.
+    UnknownSignedness(Context, Range),
+}
+
+impl TypeDiagnostic {
+    fn context_mut(&mut self) -> &mut Context {
+        match self {
+            TypeDiagnostic::UnboundVariable(ctx, _)
+            | TypeDiagnostic::CantInferHole(ctx, _)
+            | TypeDiagnostic::CantInferLambda(ctx, _)
+            | TypeDiagnostic::InvalidCall(ctx, _)
+            | TypeDiagnostic::TooManyArguments(ctx, _)
+            | TypeDiagnostic::ImpossibleCase(ctx, _, _, _)
+            | TypeDiagnostic::TypeMismatch(ctx, _, _, _)
+            | TypeDiagnostic::Inspection(ctx, _, _)
+            | TypeDiagnostic::InterpolationNotString(ctx, _, _, _)
+            | TypeDiagnostic::UncoveredPattern(ctx, _, _)
+            | TypeDiagnostic::UnknownSignedness(ctx, _) => ctx,
+        }
+    }
+
+    /// Shows the expressions with the notations of the book.
+    pub(crate) fn resugar(&mut self, notations: Arc<Vec<Notation>>) {
+        self.context_mut().1 = notations;
+    }
 }
 
 fn context_to_subtitles(ctx: &Context, subtitles: &mut Vec<Subtitle>) {
@@ -42,7 +75,7 @@ fn context_to_subtitles(ctx: &Context, subtitles: &mut Vec<Subtitle>) {
             vec![
                 Word::Dimmed(" ".to_string()),
                 Word::White(format!("{:<width$} :", name, width = biggest)),
-                Word::Painted(Color::Snd, typ.to_string()),
+                Word::Pretty(Color::Snd, show(typ, &ctx.1)),
             ],
         ));
         for val in vals {
@@ -51,13 +84,50 @@ fn context_to_subtitles(ctx: &Context, subtitles: &mut Vec<Subtitle>) {
                 vec![
                     Word::Dimmed(" ".to_string()),
                     Word::Dimmed(format!("{:<width$} =", name, width = biggest)),
-                    Word::Dimmed(val.to_string()),
+                    Word::Dimmed(show(val, &ctx.1).group().render(usize::MAX / 2)),
                 ],
             ))
         }
     }
 }
 
+/// The name of the type that a `show` function would be prefixed by.
+pub(crate) fn type_name(typ: &Expr) -> Option<String> {
+    match &typ.data {
+        ExprKind::Ctr { name, .. } | ExprKind::Fun { name, .. } => Some(name.to_string()),
+        ExprKind::NumTypeU60 => Some("U60".to_string()),
+        ExprKind::NumTypeF60 => Some("F60".to_string()),
+        ExprKind::NumTypeI60 => Some("I60".to_string()),
+        _ => None,
+    }
+}
+
+impl TypeDiagnostic {
+    /// Turns a mismatch on an expression inside of an interpolated
+    /// string into an error that points to the `show` function of
+    /// its type, if the book has one, and a mismatch on an `I60`
+    /// operand of an unsigned operation into an error that asks for
+    /// an annotation.
+    pub(crate) fn explain(self, book: &Book) -> TypeDiagnostic {
+        match self {
+            TypeDiagnostic::TypeMismatch(ctx, range, expected, got)
+                if book.unsigned_operands.contains(&range)
+                    && matches!(expected.data, ExprKind::NumTypeU60)
+                    && matches!(got.data, ExprKind::NumTypeI60) =>
+            {
+                TypeDiagnostic::UnknownSignedness(ctx, range)
+            }
+            TypeDiagnostic::TypeMismatch(ctx, range, _, got) if book.interpolations.contains(&range) => {
+                let show = type_name(&got)
+                    .map(|name| format!("{}.show", name))
+                    .filter(|show| book.entrs.contains_key(show));
+                TypeDiagnostic::InterpolationNotString(ctx, range, got, show)
+            }
+            other => other,
+        }
+    }
+}
+
 impl Diagnostic for TypeDiagnostic {
     fn get_syntax_ctx(&self) -> Option<kind_span::SyntaxCtxIndex> {
         match self {
@@ -70,6 +140,8 @@ impl Diagnostic for TypeDiagnostic {
             TypeDiagnostic::TooManyArguments(_, range) => Some(range.ctx),
             TypeDiagnostic::TypeMismatch(_, range, _, _) => Some(range.ctx),
             TypeDiagnostic::UncoveredPattern(_, range, _) => Some(range.ctx),
+            TypeDiagnostic::InterpolationNotString(_, range, _, _) => Some(range.ctx),
+            TypeDiagnostic::UnknownSignedness(_, range) => Some(range.ctx),
         }
     }
 
@@ -81,14 +153,14 @@ impl Diagnostic for TypeDiagnostic {
                         Color::Fst,
                         vec![
                             Word::White("Got      :".to_string()),
-                            Word::Painted(Color::Fst, expected.to_string()),
+                            Word::Pretty(Color::Fst, show(expected, &ctx.1)),
                         ],
                     ),
                     Subtitle::Phrase(
                         Color::Snd,
                         vec![
                             Word::White("Expected :".to_string()),
-                            Word::Painted(Color::Snd, detected.to_string()),
+                            Word::Pretty(Color::Snd, show(detected, &ctx.1)),
                         ],
                     ),
                 ];
@@ -113,7 +185,7 @@ impl Diagnostic for TypeDiagnostic {
                     Color::Snd,
                     vec![
                         Word::White("Expected:".to_string()),
-                        Word::Painted(Color::Snd, expected.to_string()),
+                        Word::Pretty(Color::Snd, show(expected, &ctx.1)),
                     ],
                 )];
 
@@ -134,7 +206,7 @@ impl Diagnostic for TypeDiagnostic {
                     }],
                 }
             }
-            TypeDiagnostic::ImpossibleCase(_, range, detected, expected) => DiagnosticFrame {
+            TypeDiagnostic::ImpossibleCase(ctx, range, detected, expected) => DiagnosticFrame {
                 code: 101,
                 severity: Severity::Error,
                 title: "Impossible case.".to_string(),
@@ -143,14 +215,14 @@ impl Diagnostic for TypeDiagnostic {
                         Color::Fst,
                         vec![
                             Word::White("Expected :".to_string()),
-                            Word::Painted(Color::Fst, detected.to_string()),
+                            Word::Pretty(Color::Fst, show(detected, &ctx.1)),
                         ],
                     ),
                     Subtitle::Phrase(
                         Color::Snd,
                         vec![
                             Word::White("Got      :".to_string()),
-                            Word::Painted(Color::Snd, expected.to_string()),
+                            Word::Pretty(Color::Snd, show(expected, &ctx.1)),
                         ],
                     ),
                 ],
@@ -191,7 +263,7 @@ impl Diagnostic for TypeDiagnostic {
                     main: true,
                 }],
             },
-            TypeDiagnostic::UncoveredPattern(_, range, terms) => DiagnosticFrame {
+            TypeDiagnostic::UncoveredPattern(ctx, range, terms) => DiagnosticFrame {
                 code: 101,
                 severity: Severity::Warning,
                 title: "This function does not cover all the possibilities!".to_string(),
@@ -199,7 +271,10 @@ impl Diagnostic for TypeDiagnostic {
                     Color::For,
                     vec![
                         Word::White("Missing case :".to_string()),
-                        Word::Painted(Color::For, terms.iter().map(|x| format!("{}", x)).collect::<Vec<_>>().join(" ")),
+                        Word::Pretty(
+                            Color::For,
+                            Doc::join(terms.iter().map(|x| show(x, &ctx.1)), Doc::line()).group(),
+                        ),
                     ],
                 ),],
                 hints: vec![],
@@ -211,6 +286,62 @@ impl Diagnostic for TypeDiagnostic {
                     main: true,
                 }],
             },
+            TypeDiagnostic::InterpolationNotString(ctx, range, got, show_fn) => {
+                let mut subtitles = vec![Subtitle::Phrase(
+                    Color::Fst,
+                    vec![
+                        Word::White("Got      :".to_string()),
+                        Word::Pretty(Color::Fst, show(got, &ctx.1)),
+                    ],
+                )];
+                context_to_subtitles(ctx, &mut subtitles);
+                let hint = match show_fn {
+                    Some(show_fn) => format!("Use '{}' to turn it into a String", show_fn),
+                    None => "Turn it into a String with a 'show' function".to_string(),
+                };
+                DiagnosticFrame {
+                    code: 101,
+                    severity: Severity::Error,
+                    title: "Only strings can be interpolated".to_string(),
+                    subtitles,
+                    hints: vec![hint],
+                    positions: vec![Marker {
+                        position: *range,
+                        color: Color::Fst,
+                        text: "Here!".to_string(),
+                        no_code: false,
+                        main: true,
+                    }],
+                }
+            }
+            TypeDiagnostic::UnknownSignedness(ctx, range) => {
+                let mut subtitles = vec![Subtitle::Phrase(
+                    Color::Fst,
+                    vec![Word::White(
+                        "This operand is an I60 but the operation is unsigned".to_string(),
+                    )],
+                )];
+                context_to_subtitles(ctx, &mut subtitles);
+                DiagnosticFrame {
+                    code: 101,
+                    severity: Severity::Error,
+                    title: "Can't tell if this operation is signed".to_string(),
+                    subtitles,
+                    hints: vec![
+                        "An operation is signed only if one of its operands is an I60 literal or \
+                         comes from a signature or an annotation"
+                            .to_string(),
+                        "Annotate the operand like '(x :: I60)'".to_string(),
+                    ],
+                    positions: vec![Marker {
+                        position: *range,
+                        color: Color::Fst,
+                        text: "Here!".to_string(),
+                        no_code: false,
+                        main: true,
+                    }],
+                }
+            }
             TypeDiagnostic::CantInferLambda(_, range) => DiagnosticFrame {
                 code: 101,
                 severity: Severity::Error,
@@ -268,7 +399,9 @@ impl Diagnostic for TypeDiagnostic {
             // it halts the compiler pipeline.
             | Inspection(_, _, _)
             | TooManyArguments(_, _)
-            | TypeMismatch(_, _, _, _) => Severity::Error,
+            | TypeMismatch(_, _, _, _)
+            | InterpolationNotString(_, _, _, _)
+            | UnknownSignedness(_, _) => Severity::Error,
             | UncoveredPattern(_, _, _) => Severity::Warning,
         }
     }
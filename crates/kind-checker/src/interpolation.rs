@@ -0,0 +1,98 @@
+//! Inserts the `show` functions in the interpolated strings. The
+//! desugarer doesn't know the types of the interpolated expressions,
+//! so it annotates them as `String` and, when the checker finds
+//! one of type `T` instead, the expression is wrapped with `T.show`
+//! and the book is checked again.
+
+use fxhash::{FxHashMap, FxHashSet};
+use kind_span::Range;
+use kind_tree::desugared::visit::Visit;
+use kind_tree::desugared::visitor::{walk_expr, Visitor};
+use kind_tree::desugared::{Book, Expr, ExprKind};
+use kind_tree::symbol::QualifiedIdent;
+
+use crate::diagnostic::{type_name, TypeDiagnostic};
+
+/// Finds the `show` functions that fix the errors of the interpolated
+/// expressions. A function is only used if it takes a single argument
+/// and only once for each expression, so the errors of the `show`
+/// calls are reported instead of being fixed again.
+pub(crate) fn find_shows(
+    book: &Book,
+    errs: &[TypeDiagnostic],
+    inserted: &mut FxHashSet<Range>,
+) -> FxHashMap<Range, QualifiedIdent> {
+    let mut shows = FxHashMap::default();
+
+    for err in errs {
+        if let TypeDiagnostic::TypeMismatch(_, range, _, got) = err {
+            if !book.interpolations.contains(range) || inserted.contains(range) {
+                continue;
+            }
+
+            let show = type_name(got)
+                .and_then(|name| book.entrs.get(&format!("{}.show", name)))
+                .filter(|entry| entry.args.len() == 1 && !entry.args[0].hidden);
+
+            if let Some(entry) = show {
+                let mut name = entry.name;
+                name.range = *range;
+                inserted.insert(*range);
+                shows.insert(*range, name);
+            }
+        }
+    }
+
+    shows
+}
+
+struct InsertShows<'a> {
+    shows: &'a FxHashMap<Range, QualifiedIdent>,
+}
+
+impl<'a> Visitor for InsertShows<'a> {
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        if let ExprKind::Ann { expr: val, .. } = &mut expr.data {
+            if let Some(show) = self.shows.get(&expr.range) {
+                let range = val.range;
+                let inner = std::mem::replace(val, Expr::err(range));
+                *val = Expr::fun(range, *show, vec![inner]);
+            }
+        }
+        walk_expr(self, expr)
+    }
+}
+
+/// Wraps the interpolated expressions with their `show` functions.
+pub(crate) fn insert_shows(book: &mut Book, shows: &FxHashMap<Range, QualifiedIdent>) {
+    InsertShows { shows }.visit_book(book)
+}
+
+struct FindInterpolations<'a> {
+    interpolations: &'a FxHashSet<Range>,
+    found: bool,
+}
+
+impl<'a> Visit for FindInterpolations<'a> {
+    fn visit_range(&mut self, range: &Range) {
+        self.found |= self.interpolations.contains(range);
+    }
+}
+
+/// The names of the entries that interpolate some expression.
+pub(crate) fn with_interpolations(book: &Book) -> Vec<String> {
+    let mut names = Vec::new();
+
+    for (name, entry) in &book.entrs {
+        let mut finder = FindInterpolations {
+            interpolations: &book.interpolations,
+            found: false,
+        };
+        finder.visit_entry(entry);
+        if finder.found {
+            names.push(name.clone());
+        }
+    }
+
+    names
+}
@@ -5,15 +5,21 @@
 
 pub mod compiler;
 mod diagnostic;
+mod interpolation;
 pub mod report;
+mod resugar;
 
 use std::sync::mpsc::Sender;
+use std::sync::Arc;
 
+use fxhash::FxHashSet;
 use hvm::{language, get_cost};
 use hvm::{runtime, syntax::Term};
+use kind_report::cancel::CancellationToken;
 use kind_report::data::Diagnostic;
+use kind_span::RangeTable;
 use kind_tree::desugared::Book;
-use report::parse_report;
+use report::{parse_expr, parse_report};
 
 pub const CHECKER: &str = include_str!("checker.hvm");
 
@@ -46,62 +52,116 @@ pub fn eval(file: &str, term: &str, dbug: bool, tids: Option<usize>) -> Result<(
 }
 
 /// Generates the checker in a string format that can be
-/// parsed by HVM.
-pub fn gen_checker(book: &Book, check_coverage: bool, functions_to_check: Vec<String>) -> String {
+/// parsed by HVM. The `ranges` are needed to read the
+/// locations in the answer of the checker.
+pub fn gen_checker(
+    book: &Book,
+    ranges: &mut RangeTable,
+    check_coverage: bool,
+    functions_to_check: Vec<String>,
+) -> String {
     let mut checker = CHECKER.to_string();
-    checker.push_str(&compiler::codegen_book(book, check_coverage, functions_to_check).to_string());
+    checker.push_str(&compiler::codegen_book(book, ranges, check_coverage, functions_to_check).to_string());
     checker
 }
 
 /// Type checks a dessugared book. It spawns an HVM instance in order
-/// to run a compiled version of the book
+/// to run a compiled version of the book. The HVM cannot be interrupted
+/// so the cancellation token is only polled before and after running it.
+///
+/// The interpolated expressions that are not strings are wrapped with
+/// the `show` functions of their types and the book is checked again,
+/// so the book is changed in place.
 pub fn type_check(
-    book: &Book,
+    book: &mut Book,
     tx: Sender<Box<dyn Diagnostic>>,
     functions_to_check: Vec<String>,
     check_coverage: bool,
-    tids: Option<usize>
+    tids: Option<usize>,
+    cancel: &CancellationToken,
 ) -> Option<u64> {
-    let file = gen_checker(book, check_coverage, functions_to_check);
-
-    match eval(&file, "Main", false, tids) {
-        Ok((term, rewrites)) => {
-            let errs = parse_report(&term).unwrap_or_else(|_| {
-                panic!(
-                    "Internal Error: Cannot parse the report message from the type checker: {}",
-                    term
-                )
-            });
-
-            let succeeded = errs.is_empty();
-
-            for err in errs {
-                tx.send(Box::new(err)).unwrap()
-            }
-
-            if succeeded {
-                Some(rewrites)
-            } else {
-                None
-            }
+    let mut inserted = FxHashSet::default();
+
+    loop {
+        if cancel.is_cancelled() {
+            return None;
+        }
+
+        let mut ranges = RangeTable::new();
+        let file = gen_checker(book, &mut ranges, check_coverage, functions_to_check.clone());
+
+        if cancel.is_cancelled() {
+            return None;
+        }
+
+        let (term, rewrites) = match eval(&file, "Main", false, tids) {
+            Ok(_) if cancel.is_cancelled() => return None,
+            Ok(res) => res,
+            Err(res) => panic!("{}", res),
+        };
+
+        let errs = parse_report(&ranges, &term).unwrap_or_else(|_| {
+            panic!(
+                "Internal Error: Cannot parse the report message from the type checker: {}",
+                term
+            )
+        });
+
+        let shows = interpolation::find_shows(book, &errs, &mut inserted);
+
+        if !shows.is_empty() {
+            interpolation::insert_shows(book, &shows);
+            continue;
+        }
+
+        let succeeded = errs.is_empty();
+
+        let notations = Arc::new(book.notations.values().cloned().collect::<Vec<_>>());
+
+        for err in errs {
+            let mut err = err.explain(book);
+            err.resugar(notations.clone());
+            tx.send(Box::new(err)).unwrap()
         }
-        Err(res) => panic!("{}", res),
+
+        return if succeeded { Some(rewrites) } else { None };
+    }
+}
+
+/// Inserts the `show` functions of the interpolated strings in a book
+/// that is compiled without being type checked. Only the entries with
+/// interpolations are checked, and the errors found in them are
+/// reported like in [type_check].
+pub fn resolve_interpolations(
+    book: &mut Book,
+    tx: Sender<Box<dyn Diagnostic>>,
+    tids: Option<usize>,
+    cancel: &CancellationToken,
+) -> Option<()> {
+    let names = interpolation::with_interpolations(book);
+
+    if names.is_empty() {
+        return Some(());
     }
+
+    type_check(book, tx, names, false, tids, cancel).map(|_| ())
 }
 
 /// Runs the type checker but instead of running the check all function
 /// we run the "eval_main" that runs the generated version that both HVM and
-/// and the checker can understand.
+/// and the checker can understand. The result is shown with the
+/// notations and the sugars of the book.
 pub fn eval_api(book: &Book) -> (String, u64) {
+    let mut ranges = RangeTable::new();
 
-    let file = gen_checker(book, false, Vec::new());
+    let file = gen_checker(book, &mut ranges, false, Vec::new());
 
     let file = language::syntax::read_file(&file).unwrap();
 
-    let book = language::rulebook::gen_rulebook(&file);
+    let rules = language::rulebook::gen_rulebook(&file);
 
     let mut prog = runtime::Program::new();
-    prog.add_book(&book);
+    prog.add_book(&rules);
 
     let heap = runtime::new_heap(runtime::default_heap_size(), runtime::default_heap_tids());
     let tids = runtime::new_tids(runtime::default_heap_tids());
@@ -110,19 +170,29 @@ pub fn eval_api(book: &Book) -> (String, u64) {
     runtime::link(
         &heap,
         0,
-        runtime::Fun(*book.name_to_id.get("Kind.API.eval_main").unwrap(), 0),
+        runtime::Fun(*rules.name_to_id.get("Kind.API.eval_main").unwrap(), 0),
     );
     let host = 0;
 
     // Normalizes it
     runtime::normalize(&heap, &prog, &tids, host, false);
 
-    // Reads it back to a string
-    let term = language::readback::as_string(&heap, &prog, &tids, host).unwrap();
+    // Reads back the quoted term
+    let term = language::readback::as_term(&heap, &prog, host);
 
     // Frees used memory
     runtime::collect(&heap, &prog.aris, tids[0], runtime::load_ptr(&heap, host));
     runtime::free(&heap, 0, 0, 1);
 
-    (term, get_cost(&heap))
+    let expr = parse_expr(&ranges, &term).unwrap_or_else(|_| {
+        panic!(
+            "Internal Error: Cannot parse the result of the evaluation: {}",
+            term
+        )
+    });
+
+    let notations: Vec<_> = book.notations.values().cloned().collect();
+    let expr = resugar::show(&expr, &notations).group().render(usize::MAX / 2);
+
+    (expr, get_cost(&heap))
 }
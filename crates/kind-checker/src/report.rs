@@ -1,7 +1,9 @@
 //! Transforms a answer from the type checker in
 //! a Expr of the kind-tree package.
 
-use kind_span::{EncodedRange, Range};
+use std::sync::Arc;
+
+use kind_span::{EncodedRange, Range, RangeTable};
 use kind_tree::symbol::{Ident, QualifiedIdent};
 use kind_tree::{desugared, Operator};
 
@@ -12,8 +14,10 @@ use desugared::Expr;
 
 type Entry = (String, Box<Expr>, Vec<Box<Expr>>);
 
+/// The variables in scope of an error and the notations that are
+/// used to show them.
 #[derive(Debug)]
-pub struct Context(pub Vec<Entry>);
+pub struct Context(pub Vec<Entry>, pub Arc<Vec<desugared::Notation>>);
 
 macro_rules! match_opt {
     ($expr:expr, $pat:pat => $end:expr) => {{
@@ -24,8 +28,8 @@ macro_rules! match_opt {
     }};
 }
 
-fn parse_orig(term: &Term) -> Result<Range, String> {
-    match_opt!(term, Term::U6O { numb } => EncodedRange(*numb).to_range())
+fn parse_orig(ranges: &RangeTable, term: &Term) -> Result<Range, String> {
+    match_opt!(term, Term::U6O { numb } => ranges.decode(EncodedRange(*numb)))
 }
 
 fn parse_num(term: &Term) -> Result<u64, String> {
@@ -57,6 +61,14 @@ fn parse_op(term: &Term) -> Result<Operator, String> {
     }
 }
 
+/// The signed operators are wrapped by `Kind.Operator.signed`.
+fn parse_signed_op(term: &Term) -> Result<(Operator, bool), String> {
+    match term {
+        Term::Ctr { name, args } if name == "Kind.Operator.signed" => Ok((parse_op(&args[0])?, true)),
+        _ => Ok((parse_op(term)?, false)),
+    }
+}
+
 fn parse_name(term: &Term) -> Result<String, String> {
     match term {
         Term::U6O { numb } => Ok(Ident::decode(*numb)),
@@ -81,91 +93,104 @@ fn parse_qualified(term: &Term) -> Result<QualifiedIdent, String> {
     }
 }
 
-fn parse_expr(term: &Term) -> Result<Box<desugared::Expr>, String> {
-    parse_all_expr(Default::default(), term)
+pub(crate) fn parse_expr(ranges: &RangeTable, term: &Term) -> Result<Box<desugared::Expr>, String> {
+    parse_all_expr(ranges, Default::default(), term)
 }
 
 fn parse_all_expr(
+    ranges: &RangeTable,
     names: im_rc::HashMap<String, String>,
     term: &Term,
 ) -> Result<Box<desugared::Expr>, String> {
     match term {
         Term::Ctr { name, args } => match name.as_str() {
             "Kind.Term.Quoted.all" => Ok(Expr::all(
-                parse_orig(&args[0])?,
+                parse_orig(ranges, &args[0])?,
                 Ident::generate(&parse_name(&args[1])?),
-                parse_all_expr(names.clone(), &args[2])?,
-                parse_all_expr(names, &args[3])?,
+                parse_all_expr(ranges, names.clone(), &args[2])?,
+                parse_all_expr(ranges, names, &args[3])?,
                 false, // TODO: Fix
             )),
             "Kind.Term.Quoted.lam" => Ok(Expr::lambda(
-                parse_orig(&args[0])?,
+                parse_orig(ranges, &args[0])?,
                 Ident::generate(&parse_name(&args[1])?),
-                parse_all_expr(names, &args[2])?,
+                parse_all_expr(ranges, names, &args[2])?,
                 false, // TODO: Fix
             )),
             "Kind.Term.Quoted.let" => Ok(Expr::let_(
-                parse_orig(&args[0])?,
+                parse_orig(ranges, &args[0])?,
                 Ident::generate(&parse_name(&args[1])?),
-                parse_all_expr(names.clone(), &args[2])?,
-                parse_all_expr(names, &args[3])?,
+                parse_all_expr(ranges, names.clone(), &args[2])?,
+                parse_all_expr(ranges, names, &args[3])?,
             )),
-            "Kind.Term.Quoted.typ" => Ok(Expr::typ(parse_orig(&args[0])?)),
+            "Kind.Term.Quoted.typ" => Ok(Expr::typ(parse_orig(ranges, &args[0])?)),
             "Kind.Term.Quoted.var" => Ok(Expr::var(Ident::new(
                 parse_name(&args[1])?,
-                parse_orig(&args[0])?,
+                parse_orig(ranges, &args[0])?,
             ))),
-            "Kind.Term.Quoted.hol" => Ok(Expr::hole(parse_orig(&args[0])?, parse_num(&args[1])?)),
+            "Kind.Term.Quoted.hol" => Ok(Expr::hole(parse_orig(ranges, &args[0])?, parse_num(&args[1])?)),
             "Kind.Term.Quoted.ann" => Ok(Expr::ann(
-                parse_orig(&args[0])?,
-                parse_all_expr(names.clone(), &args[1])?,
-                parse_all_expr(names, &args[2])?,
+                parse_orig(ranges, &args[0])?,
+                parse_all_expr(ranges, names.clone(), &args[1])?,
+                parse_all_expr(ranges, names, &args[2])?,
             )),
             "Kind.Term.Quoted.sub" => Ok(Expr::sub(
-                parse_orig(&args[0])?,
+                parse_orig(ranges, &args[0])?,
                 Ident::generate(&parse_name(&args[1])?),
                 parse_num(&args[2])? as usize,
                 parse_num(&args[3])? as usize,
-                parse_all_expr(names, &args[4])?,
+                parse_all_expr(ranges, names, &args[4])?,
             )),
             "Kind.Term.Quoted.app" => Ok(Expr::app(
-                parse_orig(&args[0])?,
-                parse_all_expr(names.clone(), &args[1])?,
+                parse_orig(ranges, &args[0])?,
+                parse_all_expr(ranges, names.clone(), &args[1])?,
                 vec![desugared::AppBinding {
-                    data: parse_all_expr(names, &args[2])?,
+                    data: parse_all_expr(ranges, names, &args[2])?,
                     erased: false,
                 }],
             )),
             "Kind.Term.Quoted.ctr" => {
                 let name = parse_qualified(&args[0])?;
-                let orig = parse_orig(&args[1])?;
+                let orig = parse_orig(ranges, &args[1])?;
                 let mut res = Vec::new();
                 for arg in parse_list(&args[2])? {
-                    res.push(parse_all_expr(names.clone(), &arg)?);
+                    res.push(parse_all_expr(ranges, names.clone(), &arg)?);
                 }
                 Ok(Expr::ctr(orig, name, res))
             }
             "Kind.Term.Quoted.fun" => Ok(Expr::fun(
-                parse_orig(&args[1])?,
+                parse_orig(ranges, &args[1])?,
                 parse_qualified(&args[0])?,
                 {
                     let mut res = Vec::new();
                     for arg in parse_list(&args[2])? {
-                        res.push(parse_all_expr(names.clone(), &arg)?);
+                        res.push(parse_all_expr(ranges, names.clone(), &arg)?);
                     }
                     res
                 },
             )),
-            "Kind.Term.Quoted.hlp" => Ok(Expr::hlp(parse_orig(&args[0])?, Ident::generate("?"))),
-            "Kind.Term.Quoted.u60" => Ok(Expr::type_u60(parse_orig(&args[0])?)),
-            "Kind.Term.Quoted.num" => Ok(Expr::num_u60(parse_orig(&args[0])?, parse_num(&args[1])?)),
-            // TODO: Change quoting to support floats
-            "Kind.Term.Quoted.op2" => Ok(Expr::binary(
-                parse_orig(&args[0])?,
-                parse_op(&args[1])?,
-                parse_all_expr(names.clone(), &args[2])?,
-                parse_all_expr(names, &args[3])?,
+            "Kind.Term.Quoted.hlp" => Ok(Expr::hlp(parse_orig(ranges, &args[0])?, Ident::generate("?"))),
+            "Kind.Term.Quoted.u60" => Ok(Expr::type_u60(parse_orig(ranges, &args[0])?)),
+            "Kind.Term.Quoted.num" => Ok(Expr::num_u60(parse_orig(ranges, &args[0])?, parse_num(&args[1])?)),
+            "Kind.Term.Quoted.i60" => Ok(Expr::type_i60(parse_orig(ranges, &args[0])?)),
+            "Kind.Term.Quoted.numi60" => Ok(Expr::num_i60(
+                parse_orig(ranges, &args[0])?,
+                // Extends the sign of the 60 bits to 64 bits.
+                ((parse_num(&args[1])? << 4) as i64) >> 4,
             )),
+            // TODO: Change quoting to support floats
+            "Kind.Term.Quoted.op2" => {
+                let (op, signed) = parse_signed_op(&args[1])?;
+                Ok(Box::new(Expr {
+                    range: parse_orig(ranges, &args[0])?,
+                    data: desugared::ExprKind::Binary {
+                        op,
+                        left: parse_all_expr(ranges, names.clone(), &args[2])?,
+                        right: parse_all_expr(ranges, names, &args[3])?,
+                        signed,
+                    },
+                }))
+            }
             tag => Err(format!(
                 "Unexpected tag on transforming quoted term {:?}",
                 tag
@@ -197,15 +222,15 @@ fn parse_list(term: &Term) -> Result<Vec<Box<Term>>, String> {
 }
 
 /// Transforms a HVM quoted entry into a easy to manipulate structure.
-pub fn transform_entry(term: &Term) -> Result<Entry, String> {
+pub fn transform_entry(ranges: &RangeTable, term: &Term) -> Result<Entry, String> {
     match term {
         Term::Ctr { name, args } if name == "Pair.new" => {
             let fst = parse_name(&args[0])?;
             match &*args[1] {
                 Term::Ctr { name, args } if name == "Pair.new" => {
-                    let snd = parse_expr(&args[0])?;
+                    let snd = parse_expr(ranges, &args[0])?;
                     let trd = parse_list(&args[1])?;
-                    let trd = trd.iter().flat_map(|x| parse_expr(x)).collect();
+                    let trd = trd.iter().flat_map(|x| parse_expr(ranges, x)).collect();
                     Ok((fst, snd, trd))
                 }
                 _ => Err("Unexpected value on entry second pair".to_string()),
@@ -215,22 +240,22 @@ pub fn transform_entry(term: &Term) -> Result<Entry, String> {
     }
 }
 
-fn parse_type_error(expr: &Term) -> Result<TypeDiagnostic, String> {
+fn parse_type_error(ranges: &RangeTable, expr: &Term) -> Result<TypeDiagnostic, String> {
     match expr {
         Term::Ctr { name, args } => {
             if args.len() < 2 {
                 return Err("Invalid argument length for constructor".to_string());
             }
             let ls = parse_list(&args[0])?;
-            let entries = ls.iter().flat_map(|x| transform_entry(x));
-            let ctx = Context(entries.collect());
-            let orig = match_opt!(*args[1], Term::U6O { numb } => EncodedRange(numb).to_range())?;
+            let entries = ls.iter().flat_map(|x| transform_entry(ranges, x));
+            let ctx = Context(entries.collect(), Default::default());
+            let orig = match_opt!(*args[1], Term::U6O { numb } => ranges.decode(EncodedRange(numb)))?;
             match name.as_str() {
                 "Kind.Error.Quoted.uncovered_pattern" => Ok(TypeDiagnostic::UncoveredPattern(ctx, orig, {
                     let args = parse_list(&args[2])?;
                     let mut new_args = Vec::with_capacity(args.len());
                     for arg in &args {
-                        new_args.push(parse_all_expr(im_rc::HashMap::new(), arg)?);
+                        new_args.push(parse_all_expr(ranges, im_rc::HashMap::new(), arg)?);
                     }
                     new_args
                 })),
@@ -241,13 +266,13 @@ fn parse_type_error(expr: &Term) -> Result<TypeDiagnostic, String> {
                 "Kind.Error.Quoted.impossible_case" => Ok(TypeDiagnostic::ImpossibleCase(
                     ctx,
                     orig,
-                    parse_all_expr(im_rc::HashMap::new(), &args[2])?,
-                    parse_all_expr(im_rc::HashMap::new(), &args[3])?,
+                    parse_all_expr(ranges, im_rc::HashMap::new(), &args[2])?,
+                    parse_all_expr(ranges, im_rc::HashMap::new(), &args[3])?,
                 )),
                 "Kind.Error.Quoted.inspection" => Ok(TypeDiagnostic::Inspection(
                     ctx,
                     orig,
-                    parse_all_expr(im_rc::HashMap::new(), &args[2])?,
+                    parse_all_expr(ranges, im_rc::HashMap::new(), &args[2])?,
                 )),
                 "Kind.Error.Quoted.too_many_arguments" => {
                     Ok(TypeDiagnostic::TooManyArguments(ctx, orig))
@@ -255,8 +280,8 @@ fn parse_type_error(expr: &Term) -> Result<TypeDiagnostic, String> {
                 "Kind.Error.Quoted.type_mismatch" => Ok(TypeDiagnostic::TypeMismatch(
                     ctx,
                     orig,
-                    parse_all_expr(im_rc::HashMap::new(), &args[2])?,
-                    parse_all_expr(im_rc::HashMap::new(), &args[3])?,
+                    parse_all_expr(ranges, im_rc::HashMap::new(), &args[2])?,
+                    parse_all_expr(ranges, im_rc::HashMap::new(), &args[3])?,
                 )),
                 _ => Err("Unexpected tag on quoted value".to_string()),
             }
@@ -265,12 +290,12 @@ fn parse_type_error(expr: &Term) -> Result<TypeDiagnostic, String> {
     }
 }
 
-pub(crate) fn parse_report(expr: &Term) -> Result<Vec<TypeDiagnostic>, String> {
+pub(crate) fn parse_report(ranges: &RangeTable, expr: &Term) -> Result<Vec<TypeDiagnostic>, String> {
     let args = parse_list(expr)?;
     let mut errs = Vec::new();
 
     for arg in args {
-        errs.push(parse_type_error(&arg)?);
+        errs.push(parse_type_error(ranges, &arg)?);
     }
 
     Ok(errs)
@@ -0,0 +1,172 @@
+//! Finds the user notations and the syntax sugars again in the
+//! expressions that come back from the type checker so the messages
+//! look like the code that the user wrote. The expressions are not
+//! changed, the sugars are only used to lay them out.
+
+use kind_pretty::Doc;
+use kind_tree::concrete::expr::escape_str;
+use kind_tree::desugared::{Expr, ExprKind, Notation};
+
+fn bind<'a>(slot: &mut Option<&'a Expr>, expr: &'a Expr) -> bool {
+    match slot {
+        Some(old) => old.to_string() == expr.to_string(),
+        None => {
+            *slot = Some(expr);
+            true
+        }
+    }
+}
+
+/// Matches the body of a notation against an expression. The holes
+/// of the body match anything and the two sides of the notation are
+/// stored in `sides`.
+fn matches<'a>(
+    notation: &Notation,
+    pat: &Expr,
+    expr: &'a Expr,
+    sides: &mut [Option<&'a Expr>; 2],
+) -> bool {
+    use ExprKind::*;
+
+    match (&pat.data, &expr.data) {
+        (Hole { .. }, _) => true,
+        (Var { name }, _) if name.to_str() == notation.fst.to_str() => bind(&mut sides[0], expr),
+        (Var { name }, _) if name.to_str() == notation.snd.to_str() => bind(&mut sides[1], expr),
+        (Var { name: a }, Var { name: b }) => a.to_str() == b.to_str(),
+        (Fun { name: a, args: xs }, Fun { name: b, args: ys })
+        | (Ctr { name: a, args: xs }, Ctr { name: b, args: ys }) => {
+            a.to_str() == b.to_str()
+                && xs.len() == ys.len()
+                && xs.iter().zip(ys).all(|(x, y)| matches(notation, x, y, sides))
+        }
+        (App { fun: f, args: xs }, App { fun: g, args: ys }) => {
+            matches(notation, f, g, sides)
+                && xs.len() == ys.len()
+                && xs.iter().zip(ys).all(|(x, y)| {
+                    x.erased == y.erased && matches(notation, &x.data, &y.data, sides)
+                })
+        }
+        (
+            Binary { op: a, left: l, right: r, signed: x },
+            Binary { op: b, left: m, right: s, signed: y },
+        ) => a == b && x == y && matches(notation, l, m, sides) && matches(notation, r, s, sides),
+        (NumU60 { numb: a }, NumU60 { numb: b }) => a == b,
+        (NumI60 { numb: a }, NumI60 { numb: b }) => a == b,
+        (Str { val: a }, Str { val: b }) => a == b,
+        (Typ, Typ) | (NumTypeU60, NumTypeU60) | (NumTypeF60, NumTypeF60) | (NumTypeI60, NumTypeI60) => true,
+        _ => false,
+    }
+}
+
+fn find_notation(expr: &Expr, notations: &[Notation]) -> Option<Doc> {
+    for notation in notations {
+        // A body that is only a variable or a hole would match
+        // every expression.
+        if matches!(notation.body.data, ExprKind::Var { .. } | ExprKind::Hole { .. }) {
+            continue;
+        }
+
+        let mut sides = [None, None];
+        if let (true, [Some(fst), Some(snd)]) = (matches(notation, &notation.body, expr, &mut sides), sides) {
+            return Some(Doc::binder(
+                show(fst, notations),
+                Doc::text(format!("{} ", notation.symbol)).append(show(snd, notations)),
+            ));
+        }
+    }
+    None
+}
+
+fn string_chars(expr: &Expr, res: &mut String) -> bool {
+    match &expr.data {
+        ExprKind::Ctr { name, args } if name.to_str() == "String.nil" => args.is_empty(),
+        ExprKind::Ctr { name, args } if name.to_str() == "String.cons" && args.len() == 2 => {
+            match args[0].data {
+                ExprKind::NumU60 { numb } => match u32::try_from(numb).ok().and_then(char::from_u32) {
+                    Some(chr) => {
+                        res.push(chr);
+                        string_chars(&args[1], res)
+                    }
+                    _ => false,
+                },
+                _ => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Splits a list cell into its head and tail. It's `Some(None)` at
+/// the end of the list and `None` when it's not a list. The arguments
+/// of `List.nil` and the ones before the head of `List.cons` are
+/// the type parameters.
+fn list_cell(expr: &Expr) -> Option<Option<(&Expr, &Expr)>> {
+    match &expr.data {
+        ExprKind::Ctr { name, .. } if name.to_str() == "List.nil" => Some(None),
+        ExprKind::Ctr { name, args } if name.to_str() == "List.cons" && args.len() >= 2 => {
+            Some(Some((&args[args.len() - 2], &args[args.len() - 1])))
+        }
+        _ => None,
+    }
+}
+
+/// Lays out the constructors that the desugarer creates for the
+/// built-in syntax sugars like the sugars themselves.
+fn find_sugar(expr: &Expr, notations: &[Notation]) -> Option<Doc> {
+    let (name, args) = match &expr.data {
+        ExprKind::Ctr { name, args } => (name, args),
+        _ => return None,
+    };
+
+    match name.to_str() {
+        "String.cons" | "String.nil" => {
+            let mut val = String::new();
+            if !string_chars(expr, &mut val) {
+                return None;
+            }
+            Some(Doc::text(format!("\"{}\"", escape_str(&val))))
+        }
+        "List.cons" | "List.nil" => {
+            let mut elems = Vec::new();
+            let mut cell = expr;
+            while let Some((head, tail)) = list_cell(cell)? {
+                elems.push(show(head, notations));
+                cell = tail;
+            }
+            Some(
+                Doc::concat([
+                    Doc::text("["),
+                    Doc::join(elems, Doc::line()).align(),
+                    Doc::text("]"),
+                ])
+                .group(),
+            )
+        }
+        "Sigma" if args.len() == 2 => match &args[1].data {
+            ExprKind::Lambda { param, body, .. } => Some(Doc::binder(
+                Doc::concat([
+                    Doc::text(format!("[{} : ", param)),
+                    show(&args[0], notations),
+                    Doc::text("] ->"),
+                ]),
+                show(body, notations),
+            )),
+            _ => None,
+        },
+        "Sigma.new" if args.len() >= 2 => Some(Doc::app(
+            Doc::text("$"),
+            [show(&args[args.len() - 2], notations), show(&args[args.len() - 1], notations)],
+        )),
+        _ => None,
+    }
+}
+
+/// Lays out the expression with the notations in place of their
+/// expansions and the sugars of strings, lists, sigmas and pairs in
+/// place of their constructors. The outermost match wins. The
+/// natural numbers are already shown as `2n` by [Display].
+///
+/// [Display]: std::fmt::Display
+pub(crate) fn show(expr: &Expr, notations: &[Notation]) -> Doc {
+    expr.to_doc_with(&|expr| find_notation(expr, notations).or_else(|| find_sugar(expr, notations)))
+}
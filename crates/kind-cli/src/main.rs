@@ -3,11 +3,13 @@ use std::time::Instant;
 use std::{fmt, io};
 
 use clap::{Parser, Subcommand};
+use serde::Serialize;
 use driver::resolution::ResolutionError;
 use kind_driver::session::Session;
 
 use kind_report::data::{Diagnostic, Log, Severity};
-use kind_report::report::{FileCache, Report};
+use kind_pretty::{Pretty, DEFAULT_WIDTH};
+use kind_report::report::{organize_diagnostics, FileCache, Report};
 use kind_report::RenderConfig;
 
 use kind_driver as driver;
@@ -54,6 +56,19 @@ pub struct Cli {
     #[arg(short, long, value_name = "FILE")]
     pub root: Option<PathBuf>,
 
+    /// Maximum number of diagnostics that are shown
+    #[arg(long, value_name = "N")]
+    pub error_limit: Option<usize>,
+
+    /// Maximum width of the pretty printed code and types
+    #[arg(long, value_name = "N")]
+    pub width: Option<usize>,
+
+    /// Creates or rewrites the lockfile with the current
+    /// content of the dependencies of the project.
+    #[arg(long)]
+    pub update_lock: bool,
+
     #[command(subcommand)]
     pub command: Command,
 }
@@ -74,10 +89,22 @@ pub enum Command {
     Eval { file: String },
 
     #[clap(aliases = &["k"])]
-    ToKindCore { file: String },
+    ToKindCore {
+        /// Prints the book and the files of its ranges as JSON
+        #[arg(long)]
+        emit_json: bool,
+
+        file: String,
+    },
 
     #[clap(aliases = &["e"])]
-    Erase { file: String },
+    Erase {
+        /// Prints the book and the files of its ranges as JSON
+        #[arg(long)]
+        emit_json: bool,
+
+        file: String,
+    },
 
     /// Runs Main on the HVM
     #[clap(aliases = &["r"])]
@@ -94,7 +121,13 @@ pub enum Command {
 
     /// Stringifies a file
     #[clap(aliases = &["s"])]
-    Show { file: String },
+    Show {
+        /// Prints the book and the files of its ranges as JSON
+        #[arg(long)]
+        emit_json: bool,
+
+        file: String,
+    },
 
     /// Compiles a file to Kindelia (.kdl)
     #[clap(aliases = &["kdl"])]
@@ -107,7 +140,14 @@ pub enum Command {
 
     /// Compiles a file to HVM (.hvm)
     #[clap(aliases = &["hvm"])]
-    ToHVM { file: String },
+    ToHVM {
+        /// Adds a comment with the location in the Kind code
+        /// before each rule
+        #[arg(long)]
+        locations: bool,
+
+        file: String,
+    },
 }
 
 /// Helper structure to use stderr as fmt::Write
@@ -136,11 +176,34 @@ where
     .unwrap();
 }
 
+/// Prints a book with the pretty printer or as JSON so it
+/// can be read by other tools. The JSON has the paths of the
+/// files that the ranges point to.
+pub fn print_book<T>(
+    book: &T,
+    files: &[PathBuf],
+    emit_json: bool,
+    width: usize,
+) -> anyhow::Result<()>
+where
+    T: Pretty + Serialize,
+{
+    if emit_json {
+        let json = driver::JsonBook { files, book };
+        println!("{}", serde_json::to_string_pretty(&json)?);
+    } else {
+        print!("{}", book.pretty(width));
+    }
+    Ok(())
+}
+
 pub fn compile_in_session<T>(
     render_config: &RenderConfig,
     root: PathBuf,
     file: String,
     compiled: bool,
+    update_lock: bool,
+    error_limit: Option<usize>,
     fun: &mut dyn FnMut(&mut Session) -> anyhow::Result<T>,
 ) -> anyhow::Result<T> {
     let (rx, tx) = std::sync::mpsc::channel();
@@ -157,20 +220,25 @@ pub fn compile_in_session<T>(
 
     let start = Instant::now();
 
-    let res = fun(&mut session);
+    let res = driver::package::load_dependencies(&mut session, update_lock)
+        .and_then(|_| fun(&mut session));
 
-    let diagnostics = tx.try_iter().collect::<Vec<Box<dyn Diagnostic>>>();
+    let mut diagnostics = tx.try_iter().collect::<Vec<Box<dyn Diagnostic>>>();
 
-    let mut contains_error = false;
+    let contains_error = diagnostics
+        .iter()
+        .any(|x| x.get_severity() == Severity::Error);
 
-    for diagnostic in diagnostics {
-        if diagnostic.get_severity() == Severity::Error {
-            contains_error = true;
-        }
+    let omitted = organize_diagnostics(&session, &mut diagnostics, error_limit);
 
+    for diagnostic in diagnostics {
         render_to_stderr(render_config, &session, &diagnostic)
     }
 
+    if omitted > 0 {
+        render_to_stderr(render_config, &session, &Log::Omitted(omitted));
+    }
+
     if !contains_error {
         render_to_stderr(
             render_config,
@@ -199,7 +267,8 @@ pub fn compile_in_session<T>(
 pub fn run_cli(config: Cli) -> anyhow::Result<()> {
     kind_report::check_if_colors_are_supported(config.no_color);
 
-    let render_config = kind_report::check_if_utf8_is_supported(config.ascii, 2);
+    let mut render_config = kind_report::check_if_utf8_is_supported(config.ascii, 2);
+    render_config.width = config.width.unwrap_or(DEFAULT_WIDTH);
     let root = config.root.unwrap_or_else(|| PathBuf::from("."));
 
     let mut entrypoints = vec!["Main".to_string()];
@@ -210,7 +279,7 @@ pub fn run_cli(config: Cli) -> anyhow::Result<()> {
 
     match config.command {
         Command::Check { file, coverage } => {
-            compile_in_session(&render_config, root, file.clone(), false, &mut |session| {
+            compile_in_session(&render_config, root, file.clone(), false, config.update_lock, config.error_limit, &mut |session| {
                 let (_, rewrites) = driver::type_check_book(
                     session,
                     &PathBuf::from(file.clone()),
@@ -224,22 +293,26 @@ pub fn run_cli(config: Cli) -> anyhow::Result<()> {
                 Ok(())
             })?;
         }
-        Command::ToHVM { file } => {
+        Command::ToHVM { file, locations } => {
             let result =
-                compile_in_session(&render_config, root, file.clone(), true, &mut |session| {
+                compile_in_session(&render_config, root, file.clone(), true, config.update_lock, config.error_limit, &mut |session| {
                     let book = driver::erase_book(
                         session,
                         &PathBuf::from(file.clone()),
                         entrypoints.clone(),
                     )?;
-                    Ok(driver::compile_book_to_hvm(book, config.trace))
+                    if locations {
+                        Ok(driver::compile_book_to_hvm_with_locations(session, book, config.trace))
+                    } else {
+                        Ok(driver::compile_book_to_hvm(book, config.trace).to_string())
+                    }
                 })?;
 
             println!("{}", result);
         }
         Command::Run { file } => {
             let res =
-                compile_in_session(&render_config, root, file.clone(), true, &mut |session| {
+                compile_in_session(&render_config, root, file.clone(), true, config.update_lock, config.error_limit, &mut |session| {
                     let path = PathBuf::from(file.clone());
                     let book = driver::erase_book(session, &path, entrypoints.clone())?;
                     driver::check_main_entry(session, &book)?;
@@ -252,39 +325,41 @@ pub fn run_cli(config: Cli) -> anyhow::Result<()> {
                 })?;
             println!("{}", res);
         }
-        Command::Show { file } => {
-            compile_in_session(&render_config, root, file.clone(), true, &mut |session| {
-                driver::to_book(session, &PathBuf::from(file.clone()))
-            })
-            .map(|res| {
-                print!("{}", res);
-                res
-            })?;
+        Command::Show { file, emit_json } => {
+            let (book, files) =
+                compile_in_session(&render_config, root, file.clone(), true, config.update_lock, config.error_limit, &mut |session| {
+                    let book = driver::to_book(session, &PathBuf::from(file.clone()))?;
+                    Ok((book, session.relative_paths()))
+                })?;
+            print_book(&book, &files, emit_json, render_config.width)?;
         }
-        Command::ToKindCore { file } => {
-            let res =
-                compile_in_session(&render_config, root, file.clone(), true, &mut |session| {
-                    driver::desugar_book(session, &PathBuf::from(file.clone()))
+        Command::ToKindCore { file, emit_json } => {
+            let (book, files) =
+                compile_in_session(&render_config, root, file.clone(), true, config.update_lock, config.error_limit, &mut |session| {
+                    let book = driver::desugar_book(session, &PathBuf::from(file.clone()))?;
+                    Ok((book, session.relative_paths()))
                 })?;
-            print!("{}", res);
+            print_book(&book, &files, emit_json, render_config.width)?;
         }
-        Command::Erase { file } => {
-            let res =
-                compile_in_session(&render_config, root, file.clone(), true, &mut |session| {
-                    driver::erase_book(session, &PathBuf::from(file.clone()), entrypoints.clone())
+        Command::Erase { file, emit_json } => {
+            let (book, files) =
+                compile_in_session(&render_config, root, file.clone(), true, config.update_lock, config.error_limit, &mut |session| {
+                    let book = driver::erase_book(session, &PathBuf::from(file.clone()), entrypoints.clone())?;
+                    Ok((book, session.relative_paths()))
                 })?;
-            print!("{}", res);
+            print_book(&book, &files, emit_json, render_config.width)?;
         }
         Command::GenChecker { file, coverage } => {
             let res =
-                compile_in_session(&render_config, root, file.clone(), true, &mut |session| {
-                    driver::check_erasure_book(session, &PathBuf::from(file.clone()))
+                compile_in_session(&render_config, root, file.clone(), true, config.update_lock, config.error_limit, &mut |session| {
+                    let book = driver::check_erasure_book(session, &PathBuf::from(file.clone()))?;
+                    Ok(driver::generate_checker(session, &book, coverage))
                 })?;
-            print!("{}", driver::generate_checker(&res, coverage));
+            print!("{}", res);
         }
         Command::Eval { file } => {
             let res =
-                compile_in_session(&render_config, root, file.clone(), true, &mut |session| {
+                compile_in_session(&render_config, root, file.clone(), true, config.update_lock, config.error_limit, &mut |session| {
                     let book = driver::desugar_book(session, &PathBuf::from(file.clone()))?;
                     driver::check_main_desugared_entry(session, &book)?;
                     let (res, rewrites) = driver::eval_in_checker(&book);
@@ -297,7 +372,7 @@ pub fn run_cli(config: Cli) -> anyhow::Result<()> {
         }
         Command::ToKDL { file, namespace } => {
             let res =
-                compile_in_session(&render_config, root, file.clone(), true, &mut |session| {
+                compile_in_session(&render_config, root, file.clone(), true, config.update_lock, config.error_limit, &mut |session| {
                     driver::compile_book_to_kdl(
                         &PathBuf::from(file.clone()),
                         session,
@@ -2,89 +2,38 @@
 
 use kind_span::Range;
 
-use kind_tree::concrete::expr::Expr;
-use kind_tree::concrete::pat::{Pat, PatIdent};
 use kind_tree::concrete::*;
 use kind_tree::concrete::{self};
-use kind_tree::symbol::{Ident, QualifiedIdent};
-use kind_tree::telescope::Telescope;
+
+use crate::quote::Quote;
+use crate::{quote_expr, quote_pat};
 
 pub fn derive_getters(range: Range, rec: &RecordDecl) -> Vec<concrete::Entry> {
-    let mk_var = |name: Ident| -> Box<Expr> {
-        Box::new(Expr {
-            data: ExprKind::Var { name },
-            range,
-        })
-    };
-
-    let mk_cons = |name: QualifiedIdent, args: Vec<Binding>| -> Box<Expr> {
-        Box::new(Expr {
-            data: ExprKind::Constr { name, args },
-            range,
-        })
-    };
-
-    let mut types = Telescope::default();
-
-    for arg in rec.parameters.iter() {
-        types.push(arg.to_implicit())
-    }
+    let q = Quote::new(range);
+    let decl = Quote::new(rec.constructor.range);
 
-    // The type
+    let mut types = rec.parameters.map(|x| x.to_implicit());
 
-    let all_args = rec.parameters.clone();
+    // The type
 
-    let res_motive_ty = mk_cons(
-        rec.name.clone(),
-        all_args
-            .iter()
-            .cloned()
-            .map(|x| Binding::Positional(mk_var(x.name)))
-            .collect(),
-    );
+    let params = rec.parameters.iter().map(|x| q.var(x.name));
+    let res_motive_ty = quote_expr!(q; ({rec.name} ..{params}));
 
     // Sccrutinzies
 
-    types.push(Argument {
-        hidden: false,
-        erased: false,
-        name: Ident::generate("scrutinee"),
-        typ: Some(res_motive_ty),
-        range,
-    });
+    types.push(q.arg(q.ident("scrutinee"), res_motive_ty));
 
     // Motive with indices
 
-    let mut pats: Vec<Box<Pat>> = Vec::new();
-
-    let spine: Vec<_> = rec
-        .fields
-        .iter()
-        .map(|(name, _, ty)| (name, ty))
-        .collect();
-
-    pats.push(Box::new(Pat {
-        data: concrete::pat::PatKind::App(
-            rec.name.add_segment(rec.constructor.to_str()),
-            spine
-                .iter()
-                .cloned()
-                .map(|x| {
-                    Box::new(Pat {
-                        data: concrete::pat::PatKind::Var(PatIdent(x.0.clone().with_name(|f| format!("{}_", f)))),
-                        range,
-                    })
-                })
-                .collect(),
-        ),
-        range,
-    }));
+    let cons_name = rec.name.add_segment(rec.constructor.to_str());
+    let fields = rec.fields.iter().map(|(name, _, _)| q.pat_var(name.add_underscore()));
+    let pat = quote_pat!(q; ({cons_name} ..{fields}));
 
     let mut entries = vec![];
 
-    for (arg, typ) in spine {
-        let body = mk_var(arg.with_name(|f| format!("{}_", f)).clone());
-    
+    for (arg, _, typ) in &rec.fields {
+        let body = q.var(arg.add_underscore());
+
         let mut name = rec
             .name
             .add_segment(arg.to_str())
@@ -92,26 +41,9 @@ pub fn derive_getters(range: Range, rec: &RecordDecl) -> Vec<concrete::Entry> {
 
         name.range = rec.constructor.range;
 
+        let rule = decl.rule(name, [pat.clone()], body);
 
-        let rules = vec![Box::new(Rule {
-            name: name.clone(),
-            pats: pats.clone(),
-            body,
-            range: rec.constructor.range,
-        })];
-    
-        let entry = Entry {
-            name: name.clone(),
-            docs: Vec::new(),
-            args: types.clone(),
-            typ: typ.clone(),
-            rules,
-            range: rec.constructor.range,
-            attrs: Vec::new(),
-            generated_by: Some(rec.name.to_string().clone()),
-        };
-    
-        entries.push(entry)
+        entries.push(decl.entry(name, types.clone(), typ.clone(), [rule], rec.name))
     }
 
     entries
@@ -5,6 +5,7 @@
 pub mod diagnostic;
 pub mod matching;
 pub mod open;
+pub mod quote;
 pub mod subst;
 pub mod getters;
 pub mod setters;
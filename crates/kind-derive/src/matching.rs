@@ -6,13 +6,14 @@ use kind_report::data::Diagnostic;
 use kind_span::Range;
 
 use kind_tree::concrete::expr::Expr;
-use kind_tree::concrete::pat::{Pat, PatIdent, PatKind};
 use kind_tree::concrete::*;
 use kind_tree::concrete::{self};
 use kind_tree::symbol::Ident;
 use kind_tree::telescope::Telescope;
 
 use crate::diagnostic::DeriveDiagnostic;
+use crate::quote::Quote;
+use crate::{quote_expr, quote_pat};
 use crate::subst::substitute_in_expr;
 
 type Errs = Vec<Box<dyn Diagnostic>>;
@@ -34,6 +35,8 @@ pub fn to_app_binding(errs: &mut Errs, binding: &Binding) -> AppBinding {
 pub fn derive_match(range: Range, sum: &SumTypeDecl) -> (concrete::Entry, Errs) {
     let mut errs: Errs = Vec::new();
 
+    let q = Quote::new(range);
+
     let new_entry_name = sum.name.add_segment("match");
 
     let all_arguments = sum.parameters.extend(&sum.indices);
@@ -42,111 +45,83 @@ pub fn derive_match(range: Range, sum: &SumTypeDecl) -> (concrete::Entry, Errs)
 
     let mut types = all_arguments.map(|x| x.to_implicit());
 
-    let all_bindings = all_arguments
-        .iter()
-        .cloned()
-        .map(|x| Binding::Positional(Expr::var(x.name)))
-        .collect();
+    let all_bindings = all_arguments.iter().map(|x| Expr::var(x.name));
 
-    let current_return_type = Expr::cons(sum.name.clone(), all_bindings, range);
+    let current_return_type = quote_expr!(q; ({sum.name} ..{all_bindings}));
 
-    types.push(Argument {
-        hidden: false,
-        erased: false,
-        name: Ident::generate("scrutinee"),
-        typ: Some(current_return_type.clone()),
-        range,
-    });
+    types.push(q.arg(q.ident("scrutinee"), current_return_type.clone()));
 
     // Motive
 
     let motive_ident = Ident::new_static("motive", range);
+    let motive_val = Ident::new_static("val_", range);
 
-    let motive_return = Expr::all(
-        Ident::new_static("val_", range),
-        current_return_type,
-        Expr::typ(range),
-        false,
-        range,
-    );
+    let motive_return = quote_expr!(q; (({motive_val} : {current_return_type}) -> Type));
 
     let motive_type = sum.indices.iter().rfold(motive_return, |out, arg| {
-        let typ = arg.typ.clone().unwrap_or_else(|| Expr::typ(range));
-        Expr::all(arg.name.clone(), typ, out, false, range)
+        let typ = arg.typ.clone().unwrap_or_else(|| q.typ());
+        q.all(arg.name, typ, out)
     });
 
     types.push(Argument {
         hidden: false,
         erased: true,
-        name: motive_ident.clone(),
+        name: motive_ident,
         typ: Some(motive_type),
         range,
     });
 
     // Constructors
 
-    let indice_names: Vec<AppBinding> = sum
-        .indices
-        .iter()
-        .map(|x| AppBinding::explicit(Expr::var(x.name.clone())))
-        .collect();
+    let indice_names = sum.indices.map(|x| Expr::var(x.name));
 
     // Parameter binding telescope
 
-    let params = sum
-        .parameters
-        .map(|x| Binding::Positional(Expr::var(x.name.clone())));
-
-    let indices = sum
-        .indices
-        .map(|x| Binding::Positional(Expr::var(x.name.clone())));
+    let params = sum.parameters.map(|x| Expr::var(x.name));
 
     // Types
 
     for cons in &sum.constructors {
         // Constructor arguments bindings
-        let vars = cons
-            .args
-            .map(|x| Binding::Positional(Expr::var(x.name.clone())));
+        let vars = cons.args.map(|x| Expr::var(x.name));
 
         let constructor_name = sum.name.add_segment(cons.name.to_str());
 
-        let default = &Telescope::default();
-        let indices = &indices;
-
-        let partial_indices = if cons.typ.is_none() { indices } else { default };
+        let partial_indices = if cons.typ.is_none() {
+            indice_names.clone()
+        } else {
+            Telescope::default()
+        };
 
-        let args = params.extend(partial_indices).extend(&vars);
+        let args = params.extend(&partial_indices).extend(&vars);
 
-        let instantation_of_the_cons = Expr::cons(constructor_name.clone(), args.to_vec(), range);
+        let instantation_of_the_cons = quote_expr!(q; ({constructor_name} ..{args}));
 
-        let mut cons_indices = if let Some(res) = &cons.typ {
+        let cons_indices = if let Some(res) = &cons.typ {
             if let ExprKind::Constr { args, .. } = &res.data {
                 let mut new_args = Vec::with_capacity(args.len());
                 for arg in &args[sum.parameters.len()..] {
                     new_args.push(match arg {
-                        Binding::Positional(expr) => AppBinding::explicit(expr.clone()),
+                        Binding::Positional(expr) => expr.clone(),
                         Binding::Named(range, _, expr) => {
                             errs.push(Box::new(DeriveDiagnostic::CannotUseNamedVariable(*range)));
-                            AppBinding::explicit(expr.clone())
+                            expr.clone()
                         }
                     });
                 }
                 new_args
             } else if let ExprKind::All { .. } = &res.data {
                 errs.push(Box::new(DeriveDiagnostic::CannotUseAll(res.range)));
-                [indice_names.as_slice()].concat()
+                indice_names.clone().to_vec()
             } else {
                 errs.push(Box::new(DeriveDiagnostic::InvalidReturnType(res.range)));
-                [indice_names.as_slice()].concat()
+                indice_names.clone().to_vec()
             }
         } else {
-            [indice_names.as_slice()].concat()
+            indice_names.clone().to_vec()
         };
 
-        cons_indices.push(AppBinding::explicit(instantation_of_the_cons));
-
-        let cons_tipo = Expr::app(Expr::var(motive_ident.clone()), cons_indices, range);
+        let cons_tipo = quote_expr!(q; (@ {q.var(motive_ident)} ..{cons_indices} {instantation_of_the_cons}));
 
         let args = if cons.typ.is_some() {
             cons.args.clone()
@@ -154,40 +129,12 @@ pub fn derive_match(range: Range, sum: &SumTypeDecl) -> (concrete::Entry, Errs)
             sum.indices.extend(&cons.args)
         };
 
-        let cons_type = args.iter().rfold(cons_tipo, |out, arg| {
-            Expr::all(
-                arg.name.clone(),
-                arg.typ.clone().unwrap_or_else(|| Expr::typ(range)),
-                out,
-                arg.erased,
-                range,
-            )
-        });
-
-        types.push(Argument::new_explicit(
-            Ident::new_static(&format!("{}_", cons.name), range),
-            cons_type,
-            range,
-        ));
+        let name = Ident::new_static(&format!("{}_", cons.name), range);
+
+        types.push(q.arg(name, q.pi(&args, cons_tipo)));
     }
 
-    let make_incomplete_entry = || {
-        let typ = Box::new(Expr {
-            data: ExprKind::Hole,
-            range,
-        });
-
-        Entry {
-            name: new_entry_name.clone(),
-            docs: Vec::new(),
-            args: types.clone(),
-            typ,
-            rules: vec![],
-            range,
-            attrs: Vec::new(),
-            generated_by: Some(sum.name.to_string()),
-        }
-    };
+    let make_incomplete_entry = || q.entry(new_entry_name, types.clone(), q.hole(), [], sum.name);
 
     if !errs.is_empty() {
         return (make_incomplete_entry(), errs);
@@ -195,10 +142,7 @@ pub fn derive_match(range: Range, sum: &SumTypeDecl) -> (concrete::Entry, Errs)
 
     let scrutinee_ident = Expr::var(Ident::generate("scrutinee"));
 
-    let mut return_args = indice_names.clone();
-    return_args.push(AppBinding::explicit(scrutinee_ident));
-
-    let return_type = Expr::app(Expr::var(motive_ident.clone()), return_args, range);
+    let return_type = quote_expr!(q; (@ {q.var(motive_ident)} ..{indice_names.clone()} {scrutinee_ident}));
 
     // Rules
 
@@ -209,14 +153,13 @@ pub fn derive_match(range: Range, sum: &SumTypeDecl) -> (concrete::Entry, Errs)
 
         let params = sum.parameters.map(|x| x.name.add_underscore());
 
-        let mut indices_and_args: Telescope<AppBinding>;
+        let indices: Telescope<Box<Expr>>;
 
         let args = if let Some(res) = &cons.typ {
             if let ExprKind::Constr { args, .. } = &res.data {
-                indices_and_args =
-                    Telescope::new(args.clone()).map(|x| to_app_binding(&mut errs, x));
-
-                let mut indices = indices_and_args.to_vec()[sum.parameters.len()..].to_vec();
+                let mut bindings = Telescope::new(args.clone())
+                    .map(|x| to_app_binding(&mut errs, x).data)
+                    .drop(sum.parameters.len());
 
                 let renames = FxHashMap::from_iter(
                     sum.parameters
@@ -226,11 +169,11 @@ pub fn derive_match(range: Range, sum: &SumTypeDecl) -> (concrete::Entry, Errs)
                         .cloned(),
                 );
 
-                for indice in &mut indices {
-                    substitute_in_expr(&mut indice.data, &renames)
+                for indice in bindings.iter_mut() {
+                    substitute_in_expr(indice, &renames)
                 }
 
-                indices_and_args = Telescope::new(indices);
+                indices = bindings;
 
                 cons.args.clone()
             } else {
@@ -239,7 +182,7 @@ pub fn derive_match(range: Range, sum: &SumTypeDecl) -> (concrete::Entry, Errs)
                 )
             }
         } else {
-            indices_and_args = sum.indices.map(|x| AppBinding::from_ident(x.name.clone()));
+            indices = sum.indices.map(|x| Expr::var(x.name));
             sum.indices.extend(&cons.args)
         };
 
@@ -247,90 +190,39 @@ pub fn derive_match(range: Range, sum: &SumTypeDecl) -> (concrete::Entry, Errs)
         let spine = args.map(|x| x.name.add_underscore());
 
         let params_and_spine = params.extend(&spine);
-        let mut pats = Vec::new();
-
-        pats.push(Box::new(Pat {
-            data: PatKind::App(
-                constructor_name.clone(),
-                params_and_spine
-                    .iter()
-                    .cloned()
-                    .map(|x| {
-                        Box::new(Pat {
-                            data: PatKind::Var(PatIdent(x)),
-                            range,
-                        })
-                    })
-                    .collect(),
-            ),
-            range,
-        }));
-
-        pats.push(Box::new(Pat {
-            data: PatKind::Var(PatIdent(Ident::generate("motive"))),
-            range,
-        }));
-
-        for cons in &sum.constructors {
-            pats.push(Box::new(Pat {
-                data: PatKind::Var(PatIdent(cons.name.clone())),
-                range,
-            }));
-        }
 
-        let mut args = indices_and_args.clone();
+        let pats = [
+            quote_pat!(q; ({constructor_name} ..{params_and_spine.iter().map(|x| q.pat_var(*x))})),
+            quote_pat!(q; motive),
+        ];
+
+        let pats = pats
+            .into_iter()
+            .chain(sum.constructors.iter().map(|cons| q.pat_var(cons.name)));
 
-        args.push(AppBinding::explicit(Expr::cons(
-            constructor_name.clone(),
-            params_and_spine
-                .map(|x| Binding::Positional(Expr::var(x.clone())))
-                .to_vec(),
-            range,
-        )));
+        let instance = quote_expr!(q; ({constructor_name} ..{params_and_spine.iter().map(|x| Expr::var(*x))}));
 
-        let body_typ = Expr::app(Expr::var(motive_ident.clone()), args.to_vec(), range);
+        let body_typ = quote_expr!(q; (@ {q.var(motive_ident)} ..{indices} {instance}));
 
         let body_val = Expr::app(
-            Expr::var(cons.name.clone()),
+            Expr::var(cons.name),
             spine
                 .iter()
                 .zip(irrelevances)
                 .map(|(arg, erased)| AppBinding {
-                    data: Expr::var(arg.clone()),
+                    data: Expr::var(*arg),
                     erased,
                 })
                 .collect(),
             cons.name.range,
         );
 
-        let body = Box::new(Expr {
-            data: ExprKind::Ann {
-                val: body_val,
-                typ: body_typ,
-            },
-            range,
-        });
-
-        let rule = Box::new(Rule {
-            name: new_entry_name.clone(),
-            pats,
-            body,
-            range: cons.name.range,
-        });
-
-        rules.push(rule)
+        let body = quote_expr!(q; ({body_val} :: {body_typ}));
+
+        rules.push(Quote::new(cons.name.range).rule(new_entry_name, pats, body))
     }
 
-    let entry = Entry {
-        name: new_entry_name,
-        docs: Vec::new(),
-        args: types,
-        typ: return_type,
-        rules,
-        range,
-        attrs: Vec::new(),
-        generated_by: Some(sum.name.to_string().clone()),
-    };
+    let entry = q.entry(new_entry_name, types, return_type, rules, sum.name);
 
     (entry, errs)
 }
@@ -2,78 +2,41 @@
 
 use kind_span::Range;
 
-use kind_tree::concrete::expr::Expr;
-use kind_tree::concrete::pat::{Pat, PatIdent};
 use kind_tree::concrete::*;
 use kind_tree::concrete::{self};
-use kind_tree::symbol::{Ident};
-use kind_tree::telescope::Telescope;
+use kind_tree::concrete::expr::Expr;
+use kind_tree::concrete::pat::Pat;
+use kind_tree::symbol::Ident;
+
+use crate::quote::Quote;
+use crate::{quote_expr, quote_pat};
 
 pub fn derive_mutters(range: Range, rec: &RecordDecl) -> Vec<concrete::Entry> {
-    let mut types = Telescope::default();
+    let q = Quote::new(range);
+    let decl = Quote::new(rec.constructor.range);
 
-    for arg in rec.parameters.iter() {
-        types.push(arg.to_implicit())
-    }
+    let mut types = rec.parameters.map(|x| x.to_implicit());
 
     // The type
 
-    let all_args = rec.parameters.clone();
-
-    let res_motive_ty = Expr::cons(
-        rec.name.clone(),
-        all_args
-            .iter()
-            .cloned()
-            .map(|x| Binding::Positional(Expr::var(x.name)))
-            .collect(),
-        range
-    );
+    let params = rec.parameters.iter().map(|x| Expr::var(x.name));
+    let res_motive_ty = quote_expr!(q; ({rec.name} ..{params}));
 
     // Sccrutinzies
 
-    types.push(Argument {
-        hidden: false,
-        erased: false,
-        name: Ident::generate("scrutinee"),
-        typ: Some(res_motive_ty.clone()),
-        range,
-    });
+    types.push(q.arg(q.ident("scrutinee"), res_motive_ty.clone()));
 
     // Motive with indices
 
-    let mut pats: Vec<Box<Pat>> = Vec::new();
-
-    let fields_spine: Vec<_> = rec
-        .fields
-        .iter()
-        .map(|(name, _, typ)| (name.clone(), typ.clone()))
-        .collect();
-
-    let params_spine: Vec<_> = rec
+    let spine: Vec<Ident> = rec
         .parameters
         .iter()
-        .map(|arg| {
-            (
-                arg.name.clone(),
-                arg.typ.clone().unwrap_or_else(|| Expr::typ(arg.range.clone())),
-            )
-        })
+        .map(|arg| arg.name)
+        .chain(rec.fields.iter().map(|(name, _, _)| *name))
         .collect();
 
-    let spine = [params_spine.as_slice(), fields_spine.as_slice()].concat();
-
-    pats.push(Box::new(Pat {
-        data: concrete::pat::PatKind::App(
-            rec.name.add_segment(rec.constructor.to_str()),
-            spine
-                .iter()
-                .cloned()
-                .map(|(name, _)| Pat::var(name))
-                .collect(),
-        ),
-        range,
-    }));
+    let pat_name = rec.name.add_segment(rec.constructor.to_str());
+    let pat = quote_pat!(q; ({pat_name} ..{spine.iter().map(|x| Pat::var(*x))}));
 
     let mut entries = vec![];
 
@@ -81,67 +44,26 @@ pub fn derive_mutters(range: Range, rec: &RecordDecl) -> Vec<concrete::Entry> {
 
     cons_name.range = rec.constructor.range;
 
-    for (i, (arg, cons_typ)) in fields_spine.iter().enumerate() {
+    for (i, (arg, _, cons_typ)) in rec.fields.iter().enumerate() {
         let mut types = types.clone();
 
         let place = rec.parameters.len() + i;
 
-        types.push(Argument {
-            hidden: false,
-            erased: false,
-            name: Ident::generate("mut"),
-            typ: Some(Expr::all(Ident::generate("_"), cons_typ.clone(), cons_typ.clone(), false, range)),
-            range,
-        });
-
-        let new_var = Ident::generate("_fn");
+        types.push(q.arg(q.ident("mut"), quote_expr!(q; ({cons_typ.clone()} -> {cons_typ.clone()}))));
 
-        let mut pats = pats.clone();
+        let mut args: Vec<_> = spine.iter().map(|x| Expr::var(*x)).collect();
 
-        pats.push(Box::new(Pat {
-            data: concrete::pat::PatKind::Var(PatIdent(new_var.clone())),
-            range,
-        }));
+        args[place] = quote_expr!(q; (@ {Expr::var(q.ident("_fn"))} {Expr::var(spine[place])}));
 
-        let mut args: Vec<_> = spine
-            .iter()
-            .cloned()
-            .map(|x| Binding::Positional(Expr::var(x.0)))
-            .collect();
-
-        args[place] = Binding::Positional(Expr::app(Expr::var(new_var), vec![args[place].to_app_binding()], range));
-
-        let body = Box::new(Expr {
-            data: ExprKind::Constr {
-                name: cons_name.clone(),
-                args,
-            },
-            range,
-        });
+        let body = quote_expr!(q; ({cons_name} ..{args}));
 
         let mut name = rec.name.add_segment(arg.to_str()).add_segment("mut");
 
         name.range = rec.constructor.range;
 
-        let rules = vec![Box::new(Rule {
-            name: name.clone(),
-            pats: pats.clone(),
-            body,
-            range: rec.constructor.range,
-        })];
-
-        let entry = Entry {
-            name: name.clone(),
-            docs: Vec::new(),
-            args: types.clone(),
-            typ: res_motive_ty.clone(),
-            rules,
-            range: rec.constructor.range,
-            attrs: Vec::new(),
-            generated_by: Some(rec.name.to_string().clone()),
-        };
-
-        entries.push(entry)
+        let rule = decl.rule(name, [pat.clone(), quote_pat!(q; _fn)], body);
+
+        entries.push(decl.entry(name, types, res_motive_ty.clone(), [rule], rec.name))
     }
 
     entries
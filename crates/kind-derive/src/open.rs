@@ -10,17 +10,17 @@ use crate::matching::derive_match;
 
 pub fn derive_match_rec(range: Range, rec: &RecordDecl) -> concrete::Entry {
     let cons = Constructor {
-        name: rec.constructor.clone(),
+        name: rec.constructor,
         docs: vec![],
         attrs: rec.cons_attrs.clone(),
-        args: Telescope::new(rec.fields.clone()).map(|x| Argument::new_explicit(x.0.clone(), x.2.clone(), x.2.range)),
+        args: Telescope::new(rec.fields.clone()).map(|x| Argument::new_explicit(x.0, x.2.clone(), x.2.range)),
         typ: None,
     };
 
     let mut entry = derive_match(
         range,
         &SumTypeDecl {
-            name: rec.name.clone(),
+            name: rec.name,
             docs: rec.docs.clone(),
             parameters: rec.parameters.clone(),
             indices: Telescope::default(),
@@ -34,7 +34,7 @@ pub fn derive_match_rec(range: Range, rec: &RecordDecl) -> concrete::Entry {
         .add_segment("match");
 
     for rule in &mut entry.rules {
-        rule.name = entry.name.clone();
+        rule.name = entry.name;
     }
 
     entry
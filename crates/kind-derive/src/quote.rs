@@ -0,0 +1,261 @@
+//! Quasi-quotation of concrete syntax for the derivations.
+//!
+//! Every node of a derived definition points to the same range, so
+//! [Quote] keeps it and builds the nodes out of it. On top of that,
+//! the [quote_expr!](crate::quote_expr) and
+//! [quote_pat!](crate::quote_pat) macros describe the trees with a
+//! syntax close to the Kind one, where `{..}` is a hole that takes a
+//! Rust value:
+//!
+//! ```ignore
+//! let q = Quote::new(range);
+//!
+//! // (Maybe.some t x) : (Maybe t)
+//! let body = quote_expr!(q; ("Maybe.some" {var_t} {var_x}));
+//! let typ = quote_expr!(q; ((t : Type) -> ("Maybe" t)));
+//! let pat = quote_pat!(q; ({cons_name} ..{field_pats}));
+//! ```
+//!
+//! The syntax of the expressions is:
+//!
+//! - `Type`, the type of types.
+//! - `x`, a variable with a generated name.
+//! - `{e}`, the expression `e`.
+//! - `((x : A) -> B)` and `(A -> B)`, dependent and simple functions.
+//! - `(@ f a b)`, the application of an expression `f`.
+//! - `(a :: A)`, an annotation.
+//! - `("Name" a b)` and `({name} a b)`, a constructor or function
+//!   application where the name is a literal or a [QualifiedIdent].
+//!
+//! Inside of the arguments, `..{xs}` splices an iterator of trees.
+//! Patterns use the same forms for variables, holes and constructors.
+
+use kind_span::Range;
+use kind_tree::concrete::expr::{AppBinding, Binding, Expr};
+use kind_tree::concrete::pat::{Pat, PatIdent, PatKind};
+use kind_tree::concrete::{Argument, Entry, ExprKind, Rule};
+use kind_tree::symbol::{Ident, QualifiedIdent};
+use kind_tree::telescope::Telescope;
+
+/// Builds concrete syntax that is located at a single range.
+#[derive(Clone, Copy)]
+pub struct Quote {
+    pub range: Range,
+}
+
+impl Quote {
+    pub fn new(range: Range) -> Quote {
+        Quote { range }
+    }
+
+    /// A generated name for a variable.
+    pub fn ident(&self, name: &str) -> Ident {
+        Ident::generate(name)
+    }
+
+    pub fn name(&self, name: &str) -> QualifiedIdent {
+        QualifiedIdent::new_static(name, None, self.range)
+    }
+
+    pub fn var(&self, name: Ident) -> Box<Expr> {
+        Box::new(Expr {
+            data: ExprKind::Var { name },
+            range: self.range,
+        })
+    }
+
+    pub fn typ(&self) -> Box<Expr> {
+        Expr::typ(self.range)
+    }
+
+    pub fn hole(&self) -> Box<Expr> {
+        Expr::hole(self.range)
+    }
+
+    pub fn cons(
+        &self,
+        name: QualifiedIdent,
+        args: impl IntoIterator<Item = Box<Expr>>,
+    ) -> Box<Expr> {
+        let args = args.into_iter().map(Binding::Positional).collect();
+        Expr::cons(name, args, self.range)
+    }
+
+    pub fn app(&self, fun: Box<Expr>, args: impl IntoIterator<Item = Box<Expr>>) -> Box<Expr> {
+        let args = args.into_iter().map(AppBinding::explicit).collect();
+        Expr::app(fun, args, self.range)
+    }
+
+    pub fn all(&self, param: Ident, typ: Box<Expr>, body: Box<Expr>) -> Box<Expr> {
+        Expr::all(param, typ, body, false, self.range)
+    }
+
+    /// A function type that binds every argument of the telescope,
+    /// the ones without a type are of type `Type`.
+    pub fn pi(&self, args: &Telescope<Argument>, body: Box<Expr>) -> Box<Expr> {
+        args.iter().rfold(body, |out, arg| {
+            let typ = arg.typ.clone().unwrap_or_else(|| self.typ());
+            Expr::all(arg.name, typ, out, arg.erased, self.range)
+        })
+    }
+
+    /// A non dependent function type.
+    pub fn arrow(&self, typ: Box<Expr>, body: Box<Expr>) -> Box<Expr> {
+        self.all(Ident::generate("_"), typ, body)
+    }
+
+    pub fn ann(&self, val: Box<Expr>, typ: Box<Expr>) -> Box<Expr> {
+        Box::new(Expr {
+            data: ExprKind::Ann { val, typ },
+            range: self.range,
+        })
+    }
+
+    pub fn lambda(&self, param: Ident, body: Box<Expr>) -> Box<Expr> {
+        Expr::lambda(param, None, body, false, self.range)
+    }
+
+    pub fn pat_var(&self, name: Ident) -> Box<Pat> {
+        Box::new(Pat {
+            data: PatKind::Var(PatIdent(name)),
+            range: self.range,
+        })
+    }
+
+    pub fn pat_cons(
+        &self,
+        name: QualifiedIdent,
+        args: impl IntoIterator<Item = Box<Pat>>,
+    ) -> Box<Pat> {
+        Box::new(Pat {
+            data: PatKind::App(name, args.into_iter().collect()),
+            range: self.range,
+        })
+    }
+
+    pub fn arg(&self, name: Ident, typ: Box<Expr>) -> Argument {
+        Argument::new_explicit(name, typ, self.range)
+    }
+
+    pub fn rule(
+        &self,
+        name: QualifiedIdent,
+        pats: impl IntoIterator<Item = Box<Pat>>,
+        body: Box<Expr>,
+    ) -> Box<Rule> {
+        Box::new(Rule {
+            name,
+            pats: pats.into_iter().collect(),
+            body,
+            wheres: Vec::new(),
+            range: self.range,
+        })
+    }
+
+    /// An entry without docs and attributes that is marked as
+    /// generated by the declaration `by`.
+    pub fn entry(
+        &self,
+        name: QualifiedIdent,
+        args: Telescope<Argument>,
+        typ: Box<Expr>,
+        rules: impl IntoIterator<Item = Box<Rule>>,
+        by: QualifiedIdent,
+    ) -> Entry {
+        Entry {
+            name,
+            docs: Vec::new(),
+            args,
+            typ,
+            rules: rules.into_iter().collect(),
+            range: self.range,
+            attrs: Vec::new(),
+            generated_by: Some(by.to_string()),
+        }
+    }
+}
+
+/// Quotes an expression. See the [module documentation](self) for
+/// the syntax.
+#[macro_export]
+macro_rules! quote_expr {
+    (@ident $q:expr; {$e:expr}) => { $e };
+    (@ident $q:expr; $x:ident) => { $q.ident(stringify!($x)) };
+
+    (@name $q:expr; {$e:expr}) => { $e };
+    (@name $q:expr; $n:literal) => { $q.name($n) };
+
+    (@args $q:expr;) => { ::std::iter::empty() };
+    (@args $q:expr; ..{$e:expr} $($rest:tt)*) => {
+        ::std::iter::IntoIterator::into_iter($e).chain($crate::quote_expr!(@args $q; $($rest)*))
+    };
+    (@args $q:expr; $a:tt $($rest:tt)*) => {
+        ::std::iter::once($crate::quote_expr!($q; $a)).chain($crate::quote_expr!(@args $q; $($rest)*))
+    };
+
+    ($q:expr; Type) => { $q.typ() };
+    ($q:expr; {$e:expr}) => { $e };
+    ($q:expr; $x:ident) => { $q.var($crate::quote_expr!(@ident $q; $x)) };
+    ($q:expr; (($x:tt : $t:tt) -> $b:tt)) => {
+        $q.all(
+            $crate::quote_expr!(@ident $q; $x),
+            $crate::quote_expr!($q; $t),
+            $crate::quote_expr!($q; $b),
+        )
+    };
+    ($q:expr; ($t:tt -> $b:tt)) => {
+        $q.arrow($crate::quote_expr!($q; $t), $crate::quote_expr!($q; $b))
+    };
+    ($q:expr; ($v:tt :: $t:tt)) => {
+        $q.ann($crate::quote_expr!($q; $v), $crate::quote_expr!($q; $t))
+    };
+    ($q:expr; (@ $f:tt $($a:tt)*)) => {
+        $q.app($crate::quote_expr!($q; $f), $crate::quote_expr!(@args $q; $($a)*))
+    };
+    ($q:expr; ($n:tt $($a:tt)*)) => {
+        $q.cons($crate::quote_expr!(@name $q; $n), $crate::quote_expr!(@args $q; $($a)*))
+    };
+}
+
+/// Quotes a pattern. See the [module documentation](self) for the
+/// syntax.
+#[macro_export]
+macro_rules! quote_pat {
+    (@args $q:expr;) => { ::std::iter::empty() };
+    (@args $q:expr; ..{$e:expr} $($rest:tt)*) => {
+        ::std::iter::IntoIterator::into_iter($e).chain($crate::quote_pat!(@args $q; $($rest)*))
+    };
+    (@args $q:expr; $a:tt $($rest:tt)*) => {
+        ::std::iter::once($crate::quote_pat!($q; $a)).chain($crate::quote_pat!(@args $q; $($rest)*))
+    };
+
+    ($q:expr; {$e:expr}) => { $e };
+    ($q:expr; $x:ident) => { $q.pat_var($crate::quote_expr!(@ident $q; $x)) };
+    ($q:expr; ($n:tt $($a:tt)*)) => {
+        $q.pat_cons($crate::quote_expr!(@name $q; $n), $crate::quote_pat!(@args $q; $($a)*))
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use kind_span::Range;
+
+    use super::Quote;
+
+    #[test]
+    fn quotes_expressions_and_patterns() {
+        let q = Quote::new(Range::ghost_range());
+        let name = q.name("Maybe");
+        let args = vec![q.var(q.ident("a")), q.var(q.ident("b"))];
+
+        let typ = quote_expr!(q; ((t : Type) -> (t -> ({name} t))));
+        let app = quote_expr!(q; (@ f ("Pair.new" ..{args} Type) x));
+        let ann = quote_expr!(q; (x :: ({name} Type)));
+        let pat = quote_pat!(q; ("Maybe.some" ..{["x", "y"].map(|x| q.pat_var(q.ident(x)))} z));
+
+        assert_eq!(typ.to_string(), "((t : Type) -> (_ : t) -> (Maybe t))");
+        assert_eq!(app.to_string(), "(f (Pair.new a b Type) x)");
+        assert_eq!(ann.to_string(), "(x :: (Maybe Type))");
+        assert_eq!(pat.to_string(), "(Maybe.some x y z)");
+    }
+}
@@ -2,105 +2,40 @@
 
 use kind_span::Range;
 
-use kind_tree::concrete::expr::Expr;
-use kind_tree::concrete::pat::{Pat, PatIdent};
 use kind_tree::concrete::*;
 use kind_tree::concrete::{self};
-use kind_tree::symbol::{Ident, QualifiedIdent};
-use kind_tree::telescope::Telescope;
+use kind_tree::concrete::pat::Pat;
+use kind_tree::symbol::Ident;
+
+use crate::quote::Quote;
+use crate::{quote_expr, quote_pat};
 
 pub fn derive_setters(range: Range, rec: &RecordDecl) -> Vec<concrete::Entry> {
-    let mk_var = |name: Ident| -> Box<Expr> {
-        Box::new(Expr {
-            data: ExprKind::Var { name },
-            range,
-        })
-    };
-
-    let mk_cons = |name: QualifiedIdent, args: Vec<Binding>| -> Box<Expr> {
-        Box::new(Expr {
-            data: ExprKind::Constr { name, args },
-            range,
-        })
-    };
-
-    let typ = |range: Range| -> Box<Expr> {
-        Box::new(Expr {
-            data: ExprKind::Lit { lit: Literal::Type },
-            range,
-        })
-    };
-
-    let mk_pat_var = |name: Ident| {
-        Box::new(Pat {
-            range: name.range,
-            data: concrete::pat::PatKind::Var(PatIdent(name)),
-        })
-    };
-
-    let mut types = Telescope::default();
-
-    for arg in rec.parameters.iter() {
-        types.push(arg.to_implicit())
-    }
+    let q = Quote::new(range);
+    let decl = Quote::new(rec.constructor.range);
 
-    // The type
+    let mut types = rec.parameters.map(|x| x.to_implicit());
 
-    let all_args = rec.parameters.clone();
+    // The type
 
-    let res_motive_ty = mk_cons(
-        rec.name.clone(),
-        all_args
-            .iter()
-            .cloned()
-            .map(|x| Binding::Positional(mk_var(x.name)))
-            .collect(),
-    );
+    let params = rec.parameters.iter().map(|x| q.var(x.name));
+    let res_motive_ty = quote_expr!(q; ({rec.name} ..{params}));
 
     // Sccrutinzies
 
-    types.push(Argument {
-        hidden: false,
-        erased: false,
-        name: Ident::generate("scrutinee"),
-        typ: Some(res_motive_ty.clone()),
-        range,
-    });
+    types.push(q.arg(q.ident("scrutinee"), res_motive_ty.clone()));
 
     // Motive with indices
 
-    let mut pats: Vec<Box<Pat>> = Vec::new();
-
-    let fields_spine: Vec<_> = rec
-        .fields
-        .iter()
-        .map(|(name, _, typ)| (name.clone(), typ.clone()))
-        .collect();
-
-    let params_spine: Vec<_> = rec
+    let spine: Vec<Ident> = rec
         .parameters
         .iter()
-        .map(|arg| {
-            (
-                arg.name.clone(),
-                arg.typ.clone().unwrap_or_else(|| typ(arg.range.clone())),
-            )
-        })
+        .map(|arg| arg.name)
+        .chain(rec.fields.iter().map(|(name, _, _)| *name))
         .collect();
 
-    let spine = [params_spine.as_slice(), fields_spine.as_slice()].concat();
-
-    pats.push(Box::new(Pat {
-        data: concrete::pat::PatKind::App(
-            rec.name.add_segment(rec.constructor.to_str()),
-            spine
-                .iter()
-                .cloned()
-                .map(|(name, _)| mk_pat_var(name))
-                .collect(),
-        ),
-        range,
-    }));
+    let pat_name = rec.name.add_segment(rec.constructor.to_str());
+    let pat = quote_pat!(q; ({pat_name} ..{spine.iter().map(|x| Pat::var(*x))}));
 
     let mut entries = vec![];
 
@@ -108,67 +43,26 @@ pub fn derive_setters(range: Range, rec: &RecordDecl) -> Vec<concrete::Entry> {
 
     cons_name.range = rec.constructor.range;
 
-    for (i, (arg, cons_typ)) in fields_spine.iter().enumerate() {
+    for (i, (arg, _, cons_typ)) in rec.fields.iter().enumerate() {
         let mut types = types.clone();
 
         let place = rec.parameters.len() + i;
 
-        types.push(Argument {
-            hidden: false,
-            erased: false,
-            name: Ident::generate("set"),
-            typ: Some(cons_typ.clone()),
-            range,
-        });
-
-        let new_var = Ident::generate("_new_var");
+        types.push(q.arg(q.ident("set"), cons_typ.clone()));
 
-        let mut pats = pats.clone();
+        let mut args: Vec<_> = spine.iter().map(|x| q.var(*x)).collect();
 
-        pats.push(Box::new(Pat {
-            data: concrete::pat::PatKind::Var(PatIdent(new_var.clone())),
-            range,
-        }));
+        args[place] = quote_expr!(q; _new_var);
 
-        let mut args: Vec<_> = spine
-            .iter()
-            .cloned()
-            .map(|x| Binding::Positional(mk_var(x.0)))
-            .collect();
-
-        args[place] = Binding::Positional(mk_var(new_var));
-
-        let body = Box::new(Expr {
-            data: ExprKind::Constr {
-                name: cons_name.clone(),
-                args,
-            },
-            range,
-        });
+        let body = quote_expr!(q; ({cons_name} ..{args}));
 
         let mut name = rec.name.add_segment(arg.to_str()).add_segment("set");
 
         name.range = rec.constructor.range;
 
-        let rules = vec![Box::new(Rule {
-            name: name.clone(),
-            pats: pats.clone(),
-            body,
-            range: rec.constructor.range,
-        })];
-
-        let entry = Entry {
-            name: name.clone(),
-            docs: Vec::new(),
-            args: types.clone(),
-            typ: res_motive_ty.clone(),
-            rules,
-            range: rec.constructor.range,
-            attrs: Vec::new(),
-            generated_by: Some(rec.name.to_string().clone()),
-        };
-
-        entries.push(entry)
+        let rule = decl.rule(name, [pat.clone(), quote_pat!(q; _new_var)], body);
+
+        entries.push(decl.entry(name, types, res_motive_ty.clone(), [rule], rec.name))
     }
 
     entries
@@ -2,7 +2,10 @@ use fxhash::FxHashMap;
 
 use kind_span::Range;
 
-use kind_tree::concrete::expr::{Binding, Case, CaseBinding, Destruct, Expr, ExprKind, SttmKind};
+use kind_tree::concrete::arena::ExprArena;
+use kind_tree::concrete::expr::{
+    Binding, Case, CaseBinding, Destruct, Expr, ExprKind, SttmKind, StrPart,
+};
 use kind_tree::concrete::pat::{Pat, PatIdent, PatKind};
 use kind_tree::concrete::visitor::Visitor;
 use kind_tree::symbol::{Ident, QualifiedIdent, Symbol};
@@ -106,6 +109,7 @@ impl<'a> Visitor for Subst<'a> {
             CaseBinding::Field(ident) | CaseBinding::Renamed(_, ident) => {
                 self.context_vars.push((ident.range, ident.to_string()))
             }
+            CaseBinding::Pattern(pat) => self.visit_pat(pat),
         }
     }
 
@@ -114,6 +118,11 @@ impl<'a> Visitor for Subst<'a> {
         for binding in &mut case.bindings {
             self.visit_case_binding(binding);
         }
+        for head in &mut case.others {
+            for binding in &mut head.bindings {
+                self.visit_case_binding(binding);
+            }
+        }
         self.visit_expr(&mut case.value);
         self.context_vars = vars;
     }
@@ -125,6 +134,13 @@ impl<'a> Visitor for Subst<'a> {
             self.visit_expr(res);
         }
 
+        for other in &mut matcher.others {
+            self.visit_ident(&mut other.name);
+            if let Some(res) = &mut other.value {
+                self.visit_expr(res);
+            }
+        }
+
         for (name, expr) in &mut matcher.with_vars {
             self.visit_ident(name);
             self.context_vars.push((name.range, name.to_string()));
@@ -201,6 +217,12 @@ impl<'a> Visitor for Subst<'a> {
                 self.visit_expr(fst);
                 self.visit_expr(snd);
             }
+            ExprKind::Infix { fst, rest } => {
+                self.visit_expr(fst);
+                for (_, _, expr) in rest {
+                    self.visit_expr(expr);
+                }
+            }
             ExprKind::Let { name, val, next } => {
                 self.visit_expr(val);
                 let vars = self.context_vars.clone();
@@ -250,6 +272,7 @@ impl<'a> Visitor for Subst<'a> {
                 self.visit_expr(&mut subst.expr)
             }
             ExprKind::Hole => {}
+            ExprKind::Error => {}
             ExprKind::Do { typ, sttm } => {
                 self.visit_qualified_ident(&mut typ.add_segment("pure").to_generated());
                 self.visit_qualified_ident(&mut typ.add_segment("bind").to_generated());
@@ -279,6 +302,16 @@ impl<'a> Visitor for Subst<'a> {
                 ));
                 visit_vec!(args.iter_mut(), arg => self.visit_expr(arg));
             }
+            ExprKind::Interpolation { parts } => {
+                self.visit_qualified_ident(&mut QualifiedIdent::new_sugared(
+                    "String", "concat", expr.range,
+                ));
+                for part in parts {
+                    if let StrPart::Expr(expr) = part {
+                        self.visit_expr(expr);
+                    }
+                }
+            }
             ExprKind::Open { type_name, var_name, motive, next } => {
                 self.visit_qualified_ident(type_name);
                 self.visit_ident(var_name);
@@ -302,10 +335,11 @@ impl<'a> Visitor for Subst<'a> {
     }
 }
 
+/// Renames the free variables of an expression. The [Subst] visitor
+/// is kept for the patterns of the rules.
 pub fn substitute_in_expr(expr: &mut Expr, names: &FxHashMap<String, String>) {
-    let mut session = Subst {
-        context_vars: Default::default(),
-        names,
-    };
-    session.visit_expr(expr)
+    let mut arena = ExprArena::new();
+    let id = arena.from_boxed(expr);
+    let res = arena.rename(id, names);
+    *expr = *arena.to_boxed(res);
 }
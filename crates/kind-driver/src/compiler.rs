@@ -0,0 +1,336 @@
+//! A builder for compiling kind2 files from other rust programs.
+//! It creates the session, runs the passes and collects all of
+//! the diagnostics so the caller does not have to deal with the
+//! channels by itself.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use hvm::language::syntax as backend;
+use kind_pass::{desugar, erasure, inline::inline_book};
+use kind_report::cancel::{CancellationToken, Cancelled};
+use kind_report::data::{Diagnostic, Severity};
+use kind_report::report::{organize_diagnostics, Report};
+use kind_report::RenderConfig;
+use kind_tree::{desugared, untyped};
+
+use crate::resolution::ResolutionError;
+use crate::session::Session;
+use crate::{package, resolve_interpolations, to_recovered_book};
+
+/// Time spent in each one of the phases of a compilation.
+#[derive(Debug, Clone, Default)]
+pub struct Timings {
+    pub phases: Vec<(&'static str, Duration)>,
+    pub total: Duration,
+}
+
+/// Everything that is common to the results of a compilation.
+pub struct Outcome {
+    pub session: Session,
+    /// Sorted and deduplicated diagnostics.
+    pub diagnostics: Vec<Box<dyn Diagnostic>>,
+    /// Number of diagnostics that were cut by the error limit.
+    pub omitted: usize,
+    pub timings: Timings,
+    /// True if the compilation stopped because of the
+    /// cancellation token. The diagnostics are the ones that
+    /// were reported before it stopped.
+    pub cancelled: bool,
+    failed: bool,
+}
+
+impl Outcome {
+    /// True if any error was reported, even if it was omitted.
+    pub fn has_errors(&self) -> bool {
+        self.failed
+    }
+
+    /// Renders all of the diagnostics to a string.
+    pub fn render(&self, config: &RenderConfig) -> String {
+        let mut res = String::new();
+        for diagnostic in &self.diagnostics {
+            diagnostic.render(&self.session, config, &mut res).unwrap();
+        }
+        res
+    }
+}
+
+/// Result of type checking a file. `book` and `rewrites` are only
+/// available if the check succeeded.
+pub struct CheckReport {
+    pub outcome: Outcome,
+    pub book: Option<untyped::Book>,
+    pub rewrites: Option<u64>,
+}
+
+impl CheckReport {
+    pub fn succeeded(&self) -> bool {
+        self.book.is_some() && !self.outcome.has_errors()
+    }
+}
+
+/// Result of compiling a file to HVM.
+pub struct CompiledHvm {
+    pub outcome: Outcome,
+    pub file: Option<backend::File>,
+}
+
+impl CompiledHvm {
+    pub fn succeeded(&self) -> bool {
+        self.file.is_some() && !self.outcome.has_errors()
+    }
+}
+
+/// Result of compiling a file to KDL.
+pub struct CompiledKdl {
+    pub outcome: Outcome,
+    pub file: Option<kind_target_kdl::File>,
+}
+
+impl CompiledKdl {
+    pub fn succeeded(&self) -> bool {
+        self.file.is_some() && !self.outcome.has_errors()
+    }
+}
+
+/// Builder for the compilation of kind2 files. Each of the
+/// `check`, `compile_hvm` and `compile_kdl` methods creates
+/// a new session so the same compiler can be reused.
+#[derive(Debug, Clone)]
+pub struct Compiler {
+    root: PathBuf,
+    entrypoints: Vec<String>,
+    tids: Option<usize>,
+    coverage: bool,
+    trace: bool,
+    namespace: String,
+    update_lock: bool,
+    error_limit: Option<usize>,
+    cancel: CancellationToken,
+}
+
+impl Compiler {
+    pub fn new(root: impl Into<PathBuf>) -> Compiler {
+        Compiler {
+            root: root.into(),
+            entrypoints: vec!["Main".to_string()],
+            tids: None,
+            coverage: false,
+            trace: false,
+            namespace: String::new(),
+            update_lock: false,
+            error_limit: None,
+            cancel: CancellationToken::new(),
+        }
+    }
+
+    /// Adds an entrypoint that should not be removed by the erasure.
+    pub fn entrypoint(mut self, name: impl Into<String>) -> Self {
+        self.entrypoints.push(name.into());
+        self
+    }
+
+    /// Number of threads used by the HVM that runs the type checker.
+    pub fn tids(mut self, tids: usize) -> Self {
+        self.tids = Some(tids);
+        self
+    }
+
+    pub fn coverage(mut self, coverage: bool) -> Self {
+        self.coverage = coverage;
+        self
+    }
+
+    pub fn trace(mut self, trace: bool) -> Self {
+        self.trace = trace;
+        self
+    }
+
+    /// Namespace that goes before each name compiled to KDL.
+    pub fn namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = namespace.into();
+        self
+    }
+
+    pub fn update_lock(mut self, update_lock: bool) -> Self {
+        self.update_lock = update_lock;
+        self
+    }
+
+    /// Maximum number of diagnostics that are kept in the outcome.
+    pub fn error_limit(mut self, limit: usize) -> Self {
+        self.error_limit = Some(limit);
+        self
+    }
+
+    /// Token that aborts the compilations started by this compiler.
+    /// Cancelling it makes the current pass stop at the next top
+    /// level definition.
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = token;
+        self
+    }
+
+    fn run<T>(&self, fun: impl FnOnce(&mut Session, &mut Timer) -> anyhow::Result<T>) -> (Outcome, Option<T>) {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let mut session = Session::new(self.root.clone(), sender);
+        session.cancel = self.cancel.clone();
+        let mut timer = Timer::new();
+
+        let res = timer
+            .phase("packages", || package::load_dependencies(&mut session, self.update_lock))
+            .and_then(|_| fun(&mut session, &mut timer));
+
+        let mut diagnostics: Vec<Box<dyn Diagnostic>> = receiver.try_iter().collect();
+
+        let failed = diagnostics
+            .iter()
+            .any(|x| x.get_severity() == Severity::Error);
+
+        let cancelled = matches!(&res, Err(err) if err.is::<Cancelled>());
+
+        let omitted = organize_diagnostics(&session, &mut diagnostics, self.error_limit);
+
+        let outcome = Outcome {
+            diagnostics,
+            omitted,
+            session,
+            timings: timer.finish(),
+            cancelled,
+            failed,
+        };
+
+        (outcome, res.ok())
+    }
+
+    /// Desugars a book that can contain syntax errors, the boolean
+    /// is true if it has them.
+    fn desugar_recovered(
+        &self,
+        session: &mut Session,
+        timer: &mut Timer,
+        path: &Path,
+    ) -> anyhow::Result<(desugared::Book, bool)> {
+        let (concrete_book, failed) =
+            timer.phase("resolution", || to_recovered_book(session, &path.to_path_buf()))?;
+        let book = timer.phase("desugar", || {
+            desugar::desugar_book(session.diagnostic_sender.clone(), &concrete_book, &session.cancel)
+        })?;
+        Ok((book, failed))
+    }
+
+    fn desugar(&self, session: &mut Session, timer: &mut Timer, path: &Path) -> anyhow::Result<desugared::Book> {
+        match self.desugar_recovered(session, timer, path)? {
+            (_, true) => Err(ResolutionError.into()),
+            (book, false) => Ok(book),
+        }
+    }
+
+    fn erase(&self, session: &mut Session, timer: &mut Timer, book: &desugared::Book) -> anyhow::Result<untyped::Book> {
+        timer.phase("erasure", || {
+            let mut book = erasure::erase_book(
+                book,
+                session.diagnostic_sender.clone(),
+                self.entrypoints.clone(),
+                &session.cancel,
+            )?;
+            inline_book(&mut book);
+            Ok(book)
+        })
+    }
+
+    /// Type checks a file and all of its dependencies.
+    pub fn check(&self, path: impl AsRef<Path>) -> CheckReport {
+        let (outcome, res) = self.run(|session, timer| {
+            let (mut book, failed) = self.desugar_recovered(session, timer, path.as_ref())?;
+
+            let all = book.entrs.keys().cloned().collect();
+
+            let rewrites = timer.phase("type check", || {
+                kind_checker::type_check(
+                    &mut book,
+                    session.diagnostic_sender.clone(),
+                    all,
+                    self.coverage,
+                    self.tids,
+                    &session.cancel,
+                )
+            });
+
+            session.cancel.check()?;
+
+            if failed {
+                return Err(ResolutionError.into());
+            }
+
+            let rewrites = rewrites.ok_or(ResolutionError)?;
+            let erased = self.erase(session, timer, &book)?;
+
+            Ok((erased, rewrites))
+        });
+
+        let (book, rewrites) = res.unzip();
+
+        CheckReport {
+            outcome,
+            book,
+            rewrites,
+        }
+    }
+
+    /// Compiles a file to HVM without type checking it.
+    pub fn compile_hvm(&self, path: impl AsRef<Path>) -> CompiledHvm {
+        let (outcome, file) = self.run(|session, timer| {
+            let mut book = self.desugar(session, timer, path.as_ref())?;
+            timer.phase("interpolation", || resolve_interpolations(session, &mut book, self.tids))?;
+            let book = self.erase(session, timer, &book)?;
+            Ok(timer.phase("codegen", || kind_target_hvm::compile_book(book, self.trace)))
+        });
+
+        CompiledHvm { outcome, file }
+    }
+
+    /// Compiles a file to KDL without type checking it.
+    pub fn compile_kdl(&self, path: impl AsRef<Path>) -> CompiledKdl {
+        let (outcome, file) = self.run(|session, timer| {
+            let mut book = self.desugar(session, timer, path.as_ref())?;
+            timer.phase("interpolation", || resolve_interpolations(session, &mut book, self.tids))?;
+            let book = self.erase(session, timer, &book)?;
+            let file = timer.phase("codegen", || {
+                kind_target_kdl::compile_book(book, session.diagnostic_sender.clone(), &self.namespace)
+            })?;
+            Ok(file)
+        });
+
+        CompiledKdl { outcome, file }
+    }
+}
+
+struct Timer {
+    start: Instant,
+    phases: Vec<(&'static str, Duration)>,
+}
+
+impl Timer {
+    fn new() -> Timer {
+        Timer {
+            start: Instant::now(),
+            phases: Vec::new(),
+        }
+    }
+
+    fn phase<T>(&mut self, name: &'static str, fun: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let res = fun();
+        self.phases.push((name, start.elapsed()));
+        res
+    }
+
+    fn finish(self) -> Timings {
+        Timings {
+            phases: self.phases,
+            total: self.start.elapsed(),
+        }
+    }
+}
@@ -4,6 +4,7 @@
 use std::{path::PathBuf, fmt::Display, error::Error};
 
 use kind_report::data::{Color, Diagnostic, DiagnosticFrame, Marker, Severity, Subtitle, Word};
+use kind_span::Range;
 use kind_tree::symbol::{Ident, QualifiedIdent};
 
 #[derive(Debug)]
@@ -24,7 +25,13 @@ pub(crate) enum DriverDiagnostic {
     UnboundVariable(Vec<Ident>, Vec<String>),
     MultiplePaths(QualifiedIdent, Vec<PathBuf>),
     DefinedMultipleTimes(QualifiedIdent, QualifiedIdent),
+    NotationDefinedMultipleTimes(String, Range, Range),
     ThereIsntAMain,
+    InvalidPackage(String, String),
+    VersionConflict(String, String, String),
+    NamespaceConflict(String, String, String),
+    LockMismatch(String, Option<String>, Option<String>),
+    MissingLockfile,
 }
 
 impl Diagnostic for DriverDiagnostic {
@@ -32,9 +39,15 @@ impl Diagnostic for DriverDiagnostic {
         match self {
             DriverDiagnostic::CannotFindFile(_) => None,
             DriverDiagnostic::ThereIsntAMain => None,
+            DriverDiagnostic::InvalidPackage(_, _) => None,
+            DriverDiagnostic::VersionConflict(_, _, _) => None,
+            DriverDiagnostic::NamespaceConflict(_, _, _) => None,
+            DriverDiagnostic::LockMismatch(_, _, _) => None,
+            DriverDiagnostic::MissingLockfile => None,
             DriverDiagnostic::UnboundVariable(v, _) => Some(v[0].range.ctx),
             DriverDiagnostic::MultiplePaths(id, _) => Some(id.range.ctx),
             DriverDiagnostic::DefinedMultipleTimes(fst, _) => Some(fst.range.ctx),
+            DriverDiagnostic::NotationDefinedMultipleTimes(_, fst, _) => Some(fst.ctx),
         }
     }
 
@@ -104,6 +117,29 @@ impl Diagnostic for DriverDiagnostic {
                     },
                 ],
             },
+            DriverDiagnostic::NotationDefinedMultipleTimes(symbol, fst, snd) => DiagnosticFrame {
+                code: 108,
+                severity: Severity::Error,
+                title: format!("The notation '{}' is defined multiple times", symbol),
+                subtitles: vec![],
+                hints: vec!["Use another symbol for one of the notations".to_string()],
+                positions: vec![
+                    Marker {
+                        position: *fst,
+                        color: Color::Fst,
+                        text: "The first ocorrence".to_string(),
+                        no_code: false,
+                        main: true,
+                    },
+                    Marker {
+                        position: *snd,
+                        color: Color::Snd,
+                        text: "Second occorrence here!".to_string(),
+                        no_code: false,
+                        main: false,
+                    },
+                ],
+            },
             DriverDiagnostic::CannotFindFile(file) => DiagnosticFrame {
                 code: 103,
                 severity: Severity::Error,
@@ -121,6 +157,67 @@ impl Diagnostic for DriverDiagnostic {
                 hints: vec![],
                 positions: vec![],
             },
+            DriverDiagnostic::InvalidPackage(path, reason) => DiagnosticFrame {
+                code: 104,
+                severity: Severity::Error,
+                title: format!("Invalid package at '{}'", path),
+                subtitles: vec![Subtitle::Phrase(Color::Fst, vec![Word::White(reason.clone())])],
+                hints: vec![],
+                positions: vec![],
+            },
+            DriverDiagnostic::VersionConflict(name, fst, snd) => DiagnosticFrame {
+                code: 105,
+                severity: Severity::Error,
+                title: format!("Conflicting versions of the package '{}'", name),
+                subtitles: vec![
+                    Subtitle::Phrase(Color::Fst, vec![Word::White(fst.clone())]),
+                    Subtitle::Phrase(Color::Snd, vec![Word::White(snd.clone())]),
+                ],
+                hints: vec!["All of the dependencies should agree on the same version of a package".to_string()],
+                positions: vec![],
+            },
+            DriverDiagnostic::NamespaceConflict(namespace, fst, snd) => DiagnosticFrame {
+                code: 106,
+                severity: Severity::Error,
+                title: format!("The namespace '{}' is provided by more than one package", namespace),
+                subtitles: vec![
+                    Subtitle::Phrase(Color::Fst, vec![Word::White(fst.clone())]),
+                    Subtitle::Phrase(Color::Snd, vec![Word::White(snd.clone())]),
+                ],
+                hints: vec![],
+                positions: vec![],
+            },
+            DriverDiagnostic::LockMismatch(name, locked, found) => DiagnosticFrame {
+                code: 107,
+                severity: Severity::Error,
+                title: format!("The package '{}' does not match the lockfile", name),
+                subtitles: vec![
+                    Subtitle::Phrase(
+                        Color::Fst,
+                        vec![
+                            Word::White("Locked :".to_string()),
+                            Word::Painted(Color::Fst, locked.clone().unwrap_or_else(|| "not locked".to_string())),
+                        ],
+                    ),
+                    Subtitle::Phrase(
+                        Color::Snd,
+                        vec![
+                            Word::White("Found  :".to_string()),
+                            Word::Painted(Color::Snd, found.clone().unwrap_or_else(|| "not required".to_string())),
+                        ],
+                    ),
+                ],
+                hints: vec!["Run with '--update-lock' if the change was intended".to_string()],
+                positions: vec![],
+            },
+            DriverDiagnostic::MissingLockfile => DiagnosticFrame {
+                code: 109,
+                severity: Severity::Error,
+                title: format!("The project has dependencies but there is no '{}'", crate::package::LOCKFILE),
+                subtitles: vec![],
+                hints: vec!["Run with '--update-lock' to create it".to_string()],
+                positions: vec![],
+            },
         }
     }
 
@@ -131,7 +228,13 @@ impl Diagnostic for DriverDiagnostic {
             | UnboundVariable(_, _)
             | MultiplePaths(_, _)
             | DefinedMultipleTimes(_, _)
-            | ThereIsntAMain => Severity::Error
+            | NotationDefinedMultipleTimes(_, _, _)
+            | ThereIsntAMain
+            | InvalidPackage(_, _)
+            | VersionConflict(_, _, _)
+            | NamespaceConflict(_, _, _)
+            | LockMismatch(_, _, _)
+            | MissingLockfile => Severity::Error
         }
     }
 }
@@ -1,18 +1,21 @@
 use checker::eval;
 use diagnostic::{DriverDiagnostic, GenericDriverError};
 use kind_pass::{desugar, erasure, inline::inline_book};
-use kind_report::report::FileCache;
-use kind_span::SyntaxCtxIndex;
+use kind_report::report::{FileCache, Locator};
+use kind_span::{RangeTable, SyntaxCtxIndex};
 
 use hvm::language::{syntax as backend};
 use kind_tree::{concrete, desugared, untyped};
 use resolution::ResolutionError;
+use serde::Serialize;
 use session::Session;
 use std::{path::PathBuf};
 
 use kind_checker as checker;
 
+pub mod compiler;
 pub mod diagnostic;
+pub mod package;
 pub mod resolution;
 pub mod session;
 
@@ -23,6 +26,14 @@ impl FileCache for Session {
     }
 }
 
+/// A book with the files that its ranges point to. The `ctx` of a
+/// range is an index in `files`.
+#[derive(Serialize)]
+pub struct JsonBook<'a, T> {
+    pub files: &'a [PathBuf],
+    pub book: &'a T,
+}
+
 pub fn type_check_book(
     session: &mut Session,
     path: &PathBuf,
@@ -30,20 +41,26 @@ pub fn type_check_book(
     tids: Option<usize>,
     check_coverage: bool,
 ) -> anyhow::Result<(untyped::Book, u64)> {
-    let concrete_book = to_book(session, path)?;
-    let desugared_book = desugar::desugar_book(session.diagnostic_sender.clone(), &concrete_book)?;
+    let (concrete_book, failed) = to_recovered_book(session, path)?;
+    let mut desugared_book = desugar::desugar_book(
+        session.diagnostic_sender.clone(),
+        &concrete_book,
+        &session.cancel,
+    )?;
 
     let all = desugared_book.entrs.iter().map(|x| x.0).cloned().collect();
 
     let result = checker::type_check(
-        &desugared_book,
+        &mut desugared_book,
         session.diagnostic_sender.clone(),
         all,
         check_coverage,
         tids,
+        &session.cancel,
     );
 
-    if result.is_none() {
+    if result.is_none() || failed {
+        session.cancel.check()?;
         return Err(ResolutionError.into());
     }
 
@@ -51,18 +68,49 @@ pub fn type_check_book(
         &desugared_book,
         session.diagnostic_sender.clone(),
         entrypoints,
+        &session.cancel,
     )?;
     inline_book(&mut book);
 
     Ok((book, result.unwrap()))
 }
 
+/// Inserts the `show` functions of the interpolated strings in a book
+/// that is compiled without being type checked.
+pub(crate) fn resolve_interpolations(
+    session: &mut Session,
+    book: &mut desugared::Book,
+    tids: Option<usize>,
+) -> anyhow::Result<()> {
+    let resolved = checker::resolve_interpolations(
+        book,
+        session.diagnostic_sender.clone(),
+        tids,
+        &session.cancel,
+    );
+
+    session.cancel.check()?;
+    resolved.ok_or_else(|| ResolutionError.into())
+}
+
 pub fn to_book(session: &mut Session, path: &PathBuf) -> anyhow::Result<concrete::Book> {
-    let mut concrete_book = resolution::parse_and_store_book(session, path)?;
+    let (concrete_book, failed) = to_recovered_book(session, path)?;
+
+    if failed {
+        Err(ResolutionError.into())
+    } else {
+        Ok(concrete_book)
+    }
+}
+
+/// Resolves the book even if some file has syntax errors. The boolean
+/// tells if any error was found and the book has error nodes.
+pub fn to_recovered_book(session: &mut Session, path: &PathBuf) -> anyhow::Result<(concrete::Book, bool)> {
+    let (mut concrete_book, failed) = resolution::parse_and_recover_book(session, path)?;
 
     resolution::check_unbound_top_level(session, &mut concrete_book)?;
 
-    Ok(concrete_book)
+    Ok((concrete_book, failed))
 }
 
 pub fn erase_book(
@@ -71,12 +119,19 @@ pub fn erase_book(
     entrypoints: Vec<String>,
 ) -> anyhow::Result<untyped::Book> {
     let concrete_book = to_book(session, path)?;
-    let desugared_book = desugar::desugar_book(session.diagnostic_sender.clone(), &concrete_book)?;
+    let mut desugared_book = desugar::desugar_book(
+        session.diagnostic_sender.clone(),
+        &concrete_book,
+        &session.cancel,
+    )?;
+
+    resolve_interpolations(session, &mut desugared_book, None)?;
 
     let mut book = erasure::erase_book(
         &desugared_book,
         session.diagnostic_sender.clone(),
         entrypoints,
+        &session.cancel,
     )?;
 
     inline_book(&mut book);
@@ -85,7 +140,11 @@ pub fn erase_book(
 
 pub fn desugar_book(session: &mut Session, path: &PathBuf) -> anyhow::Result<desugared::Book> {
     let concrete_book = to_book(session, path)?;
-    desugar::desugar_book(session.diagnostic_sender.clone(), &concrete_book)
+    desugar::desugar_book(
+        session.diagnostic_sender.clone(),
+        &concrete_book,
+        &session.cancel,
+    )
 }
 
 pub fn check_erasure_book(
@@ -93,13 +152,34 @@ pub fn check_erasure_book(
     path: &PathBuf,
 ) -> anyhow::Result<desugared::Book> {
     let concrete_book = to_book(session, path)?;
-    desugar::desugar_book(session.diagnostic_sender.clone(), &concrete_book)
+    desugar::desugar_book(
+        session.diagnostic_sender.clone(),
+        &concrete_book,
+        &session.cancel,
+    )
 }
 
 pub fn compile_book_to_hvm(book: untyped::Book, trace: bool) -> backend::File {
     kind_target_hvm::compile_book(book, trace)
 }
 
+/// Compiles the book to HVM with a comment before each rule that
+/// points to the Kind code that generated it.
+pub fn compile_book_to_hvm_with_locations(session: &Session, book: untyped::Book, trace: bool) -> String {
+    let (file, map) = kind_target_hvm::compile_book_with_source_map(book, trace);
+    let mut locator = Locator::new(session);
+    let mut lines = Vec::new();
+
+    for (i, rule) in file.rules.iter().enumerate() {
+        if let Some(location) = map.rule(i).and_then(|range| locator.locate(range)) {
+            lines.push(format!("// {}", location));
+        }
+        lines.push(rule.to_string());
+    }
+
+    lines.join("\n")
+}
+
 pub fn compile_book_to_kdl(
     path: &PathBuf,
     session: &mut Session,
@@ -107,12 +187,19 @@ pub fn compile_book_to_kdl(
     entrypoints: Vec<String>,
 ) -> anyhow::Result<kind_target_kdl::File> {
     let concrete_book = to_book(session, path)?;
-    let desugared_book = desugar::desugar_book(session.diagnostic_sender.clone(), &concrete_book)?;
+    let mut desugared_book = desugar::desugar_book(
+        session.diagnostic_sender.clone(),
+        &concrete_book,
+        &session.cancel,
+    )?;
+
+    resolve_interpolations(session, &mut desugared_book, None)?;
 
     let mut book = erasure::erase_book(
         &desugared_book,
         session.diagnostic_sender.clone(),
         entrypoints,
+        &session.cancel,
     )?;
 
     inline_book(&mut book);
@@ -158,6 +245,22 @@ pub fn eval_in_checker(book: &desugared::Book) -> (String, u64) {
     checker::eval_api(book)
 }
 
-pub fn generate_checker(book: &desugared::Book, check_coverage: bool) -> String {
-    checker::gen_checker(book, check_coverage, book.entrs.keys().cloned().collect())
+/// Generates the checker of the book. The ranges inside of it are ids
+/// so the table that maps them to locations is written at the end as
+/// comments.
+pub fn generate_checker(session: &Session, book: &desugared::Book, check_coverage: bool) -> String {
+    let mut ranges = RangeTable::new();
+    let mut code = checker::gen_checker(book, &mut ranges, check_coverage, book.entrs.keys().cloned().collect());
+    let mut locator = Locator::new(session);
+
+    code.push_str("\n// Ranges of the ids used above:\n");
+
+    for (id, range) in ranges.iter() {
+        match locator.locate(range) {
+            Some(location) => code.push_str(&format!("// {} = {}\n", id.0, location)),
+            None => code.push_str(&format!("// {} = (no file)\n", id.0)),
+        }
+    }
+
+    code
 }
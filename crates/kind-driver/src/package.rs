@@ -0,0 +1,529 @@
+//! Reads the manifest of a project and resolves all of its
+//! dependencies (local paths or vendored archives) into a map
+//! from namespace to the root of the package that provides it.
+//! The content hash of each dependency is recorded in a lockfile
+//! so that the builds are reproducible.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+use fxhash::FxHashMap;
+use serde::{Deserialize, Serialize};
+use tiny_keccak::{Hasher, Sha3};
+
+use crate::diagnostic::DriverDiagnostic;
+use crate::resolution::ResolutionError;
+use crate::session::Session;
+
+/// The name of the file that describes a package.
+pub const MANIFEST: &str = "kind2.toml";
+
+/// The name of the file that pins the content of the dependencies.
+pub const LOCKFILE: &str = "kind2.lock";
+
+/// Directory (relative to the project root) where the vendored
+/// archives are unpacked.
+const VENDOR_DIR: &str = ".kind2/vendor";
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    pub package: PackageInfo,
+    #[serde(default)]
+    pub dependencies: BTreeMap<String, Dependency>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PackageInfo {
+    pub name: String,
+    pub version: String,
+}
+
+/// A dependency declared in the manifest. Exactly one of `path`
+/// and `archive` must be given, both are relative to the package
+/// that declares it.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Dependency {
+    pub path: Option<PathBuf>,
+    pub archive: Option<PathBuf>,
+    pub version: Option<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Lockfile {
+    #[serde(default, rename = "package")]
+    pub packages: Vec<LockedPackage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: String,
+    pub source: String,
+    pub hash: String,
+}
+
+/// A dependency that was already found and read.
+#[derive(Debug, Clone)]
+pub struct Package {
+    pub name: String,
+    pub version: String,
+    pub source: String,
+    pub root: PathBuf,
+    pub hash: String,
+    pub namespaces: Vec<String>,
+}
+
+impl Package {
+    fn describe(&self) -> String {
+        format!("{} {} ({})", self.name, self.version, self.source)
+    }
+
+    fn to_locked(&self) -> LockedPackage {
+        LockedPackage {
+            name: self.name.clone(),
+            version: self.version.clone(),
+            source: self.source.clone(),
+            hash: self.hash.clone(),
+        }
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|x| format!("{:02x}", x)).collect()
+}
+
+fn read_manifest(session: &Session, root: &Path) -> Option<Manifest> {
+    let path = root.join(MANIFEST);
+
+    let input = match fs::read_to_string(&path) {
+        Ok(res) => res,
+        Err(_) => {
+            let err = Box::new(DriverDiagnostic::CannotFindFile(path.display().to_string()));
+            session.diagnostic_sender.send(err).unwrap();
+            return None;
+        }
+    };
+
+    match toml::from_str(&input) {
+        Ok(manifest) => Some(manifest),
+        Err(err) => {
+            let err = Box::new(DriverDiagnostic::InvalidPackage(
+                path.display().to_string(),
+                err.to_string(),
+            ));
+            session.diagnostic_sender.send(err).unwrap();
+            None
+        }
+    }
+}
+
+/// Collects all of the files that are part of the package (sorted
+/// by their path relative to the root) so we can hash them.
+fn collect_package_files(root: &Path, dir: &Path, files: &mut Vec<(String, PathBuf)>) {
+    let Ok(entries) = fs::read_dir(dir) else { return };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with('.') {
+            continue;
+        }
+        if path.is_dir() {
+            collect_package_files(root, &path, files);
+        } else if path.extension().map(|x| x == "kind2").unwrap_or(false)
+            || (dir == root && name == MANIFEST)
+        {
+            let relative = path.strip_prefix(root).unwrap();
+            let segments: Vec<_> = relative
+                .components()
+                .map(|x| x.as_os_str().to_string_lossy().to_string())
+                .collect();
+            files.push((segments.join("/"), path));
+        }
+    }
+}
+
+/// Hashes the content of a package. It only depends on the relative
+/// paths and contents of the kind2 files and the manifest.
+pub fn hash_package(root: &Path) -> String {
+    let mut files = Vec::new();
+    collect_package_files(root, root, &mut files);
+    files.sort();
+
+    let mut hasher = Sha3::v256();
+    for (name, path) in files {
+        let content = fs::read(path).unwrap_or_default();
+        hasher.update(name.as_bytes());
+        hasher.update(&[0]);
+        hasher.update(&(content.len() as u64).to_le_bytes());
+        hasher.update(&content);
+    }
+
+    let mut output = [0u8; 32];
+    hasher.finalize(&mut output);
+    to_hex(&output)
+}
+
+/// Top level names of a package are the directories and kind2 files
+/// that are in the root. Paths that contain one of the `ignored` roots
+/// are not counted as namespaces of this package.
+fn package_namespaces(root: &Path, ignored: &[PathBuf]) -> Vec<String> {
+    let mut namespaces = Vec::new();
+    let Ok(entries) = fs::read_dir(root) else { return namespaces };
+
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+
+        if name.starts_with('.') || name == "_.kind2" {
+            continue;
+        }
+
+        let canon = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+        if ignored.iter().any(|x| x.starts_with(&canon)) {
+            continue;
+        }
+
+        if path.is_dir() {
+            namespaces.push(name);
+        } else if path.extension().map(|x| x == "kind2").unwrap_or(false) {
+            namespaces.push(path.file_stem().unwrap().to_string_lossy().to_string());
+        }
+    }
+
+    namespaces.sort();
+    namespaces.dedup();
+    namespaces
+}
+
+/// Unpacks a vendored archive inside of the project root and returns
+/// the root of the package that is inside of it.
+fn unpack_archive(session: &Session, name: &str, archive: &Path) -> Option<PathBuf> {
+    let bytes = match fs::read(archive) {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            let err = Box::new(DriverDiagnostic::CannotFindFile(archive.display().to_string()));
+            session.diagnostic_sender.send(err).unwrap();
+            return None;
+        }
+    };
+
+    let mut hasher = Sha3::v256();
+    hasher.update(&bytes);
+    let mut output = [0u8; 32];
+    hasher.finalize(&mut output);
+
+    let dest = session
+        .root
+        .join(VENDOR_DIR)
+        .join(format!("{}-{}", name, &to_hex(&output)[..16]));
+
+    if !dest.is_dir() {
+        let res = fs::create_dir_all(&dest)
+            .and_then(|_| File::open(archive))
+            .and_then(|file| tar::Archive::new(file).unpack(&dest));
+
+        if let Err(reason) = res {
+            let _ = fs::remove_dir_all(&dest);
+            let err = Box::new(DriverDiagnostic::InvalidPackage(
+                archive.display().to_string(),
+                reason.to_string(),
+            ));
+            session.diagnostic_sender.send(err).unwrap();
+            return None;
+        }
+    }
+
+    if dest.join(MANIFEST).is_file() {
+        return Some(dest);
+    }
+
+    // Archives usually contain a single directory with the package inside.
+    let entries: Vec<_> = fs::read_dir(&dest).ok()?.flatten().map(|x| x.path()).collect();
+
+    match entries.as_slice() {
+        [single] if single.join(MANIFEST).is_file() => Some(single.clone()),
+        _ => {
+            let err = Box::new(DriverDiagnostic::InvalidPackage(
+                archive.display().to_string(),
+                format!("the archive does not contain a '{}'", MANIFEST),
+            ));
+            session.diagnostic_sender.send(err).unwrap();
+            None
+        }
+    }
+}
+
+/// Finds the root and the source description of a dependency.
+fn locate_dependency(
+    session: &Session,
+    base: &Path,
+    name: &str,
+    dep: &Dependency,
+) -> Option<(PathBuf, String)> {
+    match (&dep.path, &dep.archive) {
+        (Some(path), None) => {
+            let root = base.join(path);
+            if root.is_dir() {
+                Some((root, format!("path+{}", path.display())))
+            } else {
+                let err = Box::new(DriverDiagnostic::CannotFindFile(root.display().to_string()));
+                session.diagnostic_sender.send(err).unwrap();
+                None
+            }
+        }
+        (None, Some(archive)) => {
+            let root = unpack_archive(session, name, &base.join(archive))?;
+            Some((root, format!("archive+{}", archive.display())))
+        }
+        _ => {
+            let err = Box::new(DriverDiagnostic::InvalidPackage(
+                base.join(MANIFEST).display().to_string(),
+                format!(
+                    "the dependency '{}' should have exactly one of 'path' or 'archive'",
+                    name
+                ),
+            ));
+            session.diagnostic_sender.send(err).unwrap();
+            None
+        }
+    }
+}
+
+fn resolve_packages(session: &Session, manifest: Manifest) -> Option<BTreeMap<String, Package>> {
+    let mut failed = false;
+    let mut resolved: BTreeMap<String, Package> = BTreeMap::new();
+    let mut queue: VecDeque<(PathBuf, String, Dependency)> = manifest
+        .dependencies
+        .into_iter()
+        .map(|(name, dep)| (session.root.clone(), name, dep))
+        .collect();
+
+    while let Some((base, name, dep)) = queue.pop_front() {
+        let Some((root, source)) = locate_dependency(session, &base, &name, &dep) else {
+            failed = true;
+            continue;
+        };
+
+        let Some(manifest) = read_manifest(session, &root) else {
+            failed = true;
+            continue;
+        };
+
+        if manifest.package.name != name {
+            let err = Box::new(DriverDiagnostic::InvalidPackage(
+                root.join(MANIFEST).display().to_string(),
+                format!(
+                    "expected the package '{}' but found '{}'",
+                    name, manifest.package.name
+                ),
+            ));
+            session.diagnostic_sender.send(err).unwrap();
+            failed = true;
+            continue;
+        }
+
+        let package = Package {
+            name: name.clone(),
+            version: manifest.package.version.clone(),
+            source,
+            hash: hash_package(&root),
+            root: fs::canonicalize(&root).unwrap_or(root),
+            namespaces: Vec::new(),
+        };
+
+        if let Some(required) = &dep.version {
+            if *required != package.version {
+                let err = Box::new(DriverDiagnostic::VersionConflict(
+                    name.clone(),
+                    format!("{} {} (required)", name, required),
+                    package.describe(),
+                ));
+                session.diagnostic_sender.send(err).unwrap();
+                failed = true;
+                continue;
+            }
+        }
+
+        if let Some(other) = resolved.get(&name) {
+            if other.version != package.version || other.hash != package.hash {
+                let err = Box::new(DriverDiagnostic::VersionConflict(
+                    name.clone(),
+                    other.describe(),
+                    package.describe(),
+                ));
+                session.diagnostic_sender.send(err).unwrap();
+                failed = true;
+            }
+            continue;
+        }
+
+        for (dep_name, dep) in manifest.dependencies {
+            queue.push_back((package.root.clone(), dep_name, dep));
+        }
+
+        resolved.insert(name, package);
+    }
+
+    if failed {
+        None
+    } else {
+        Some(resolved)
+    }
+}
+
+/// Checks that every namespace is provided by only one package. The
+/// root package owns everything that is inside of its root except
+/// the dependencies that live inside of it.
+fn check_namespaces(session: &Session, packages: &mut BTreeMap<String, Package>) -> bool {
+    let mut failed = false;
+
+    let roots: Vec<PathBuf> = packages.values().map(|x| x.root.clone()).collect();
+    let root = fs::canonicalize(&session.root).unwrap_or_else(|_| session.root.clone());
+
+    let mut owners: FxHashMap<String, String> = FxHashMap::default();
+
+    for namespace in package_namespaces(&root, &roots) {
+        owners.insert(namespace, "the root package".to_string());
+    }
+
+    for package in packages.values_mut() {
+        let ignored: Vec<PathBuf> = roots
+            .iter()
+            .filter(|x| **x != package.root)
+            .cloned()
+            .collect();
+
+        package.namespaces = package_namespaces(&package.root, &ignored);
+
+        for namespace in &package.namespaces {
+            if let Some(owner) = owners.get(namespace) {
+                let err = Box::new(DriverDiagnostic::NamespaceConflict(
+                    namespace.clone(),
+                    owner.clone(),
+                    package.describe(),
+                ));
+                session.diagnostic_sender.send(err).unwrap();
+                failed = true;
+            } else {
+                owners.insert(namespace.clone(), package.describe());
+            }
+        }
+    }
+
+    !failed
+}
+
+/// Compares the resolved packages against the lockfile. It's only
+/// written if `update_lock` is true, so a missing lockfile is an
+/// error unless the project has no dependencies.
+fn check_lockfile(session: &Session, packages: &BTreeMap<String, Package>, update_lock: bool) -> bool {
+    let path = session.root.join(LOCKFILE);
+
+    let current = Lockfile {
+        packages: packages.values().map(|x| x.to_locked()).collect(),
+    };
+
+    if update_lock {
+        let res = toml::to_string(&current).map(|content| fs::write(&path, content));
+        if !matches!(res, Ok(Ok(()))) {
+            let err = Box::new(DriverDiagnostic::InvalidPackage(
+                path.display().to_string(),
+                "cannot write the lockfile".to_string(),
+            ));
+            session.diagnostic_sender.send(err).unwrap();
+            return false;
+        }
+        return true;
+    }
+
+    if !path.exists() {
+        if packages.is_empty() {
+            return true;
+        }
+        session.diagnostic_sender.send(Box::new(DriverDiagnostic::MissingLockfile)).unwrap();
+        return false;
+    }
+
+    let locked: Lockfile = match fs::read_to_string(&path).map(|x| toml::from_str(&x)) {
+        Ok(Ok(locked)) => locked,
+        Ok(Err(err)) => {
+            let err = Box::new(DriverDiagnostic::InvalidPackage(
+                path.display().to_string(),
+                err.to_string(),
+            ));
+            session.diagnostic_sender.send(err).unwrap();
+            return false;
+        }
+        Err(_) => {
+            let err = Box::new(DriverDiagnostic::CannotFindFile(path.display().to_string()));
+            session.diagnostic_sender.send(err).unwrap();
+            return false;
+        }
+    };
+
+    let locked: BTreeMap<String, LockedPackage> = locked
+        .packages
+        .into_iter()
+        .map(|x| (x.name.clone(), x))
+        .collect();
+
+    let mut failed = false;
+
+    for package in packages.values() {
+        match locked.get(&package.name) {
+            Some(lock) if *lock == package.to_locked() => (),
+            lock => {
+                let err = Box::new(DriverDiagnostic::LockMismatch(
+                    package.name.clone(),
+                    lock.map(|x| format!("{} {}", x.version, x.hash)),
+                    Some(format!("{} {}", package.version, package.hash)),
+                ));
+                session.diagnostic_sender.send(err).unwrap();
+                failed = true;
+            }
+        }
+    }
+
+    for lock in locked.values() {
+        if !packages.contains_key(&lock.name) {
+            let err = Box::new(DriverDiagnostic::LockMismatch(
+                lock.name.clone(),
+                Some(format!("{} {}", lock.version, lock.hash)),
+                None,
+            ));
+            session.diagnostic_sender.send(err).unwrap();
+            failed = true;
+        }
+    }
+
+    !failed
+}
+
+/// Loads the manifest in the root of the session (if there's one),
+/// resolves all of the dependencies and registers their namespaces
+/// in the session so the resolution can find them.
+pub fn load_dependencies(session: &mut Session, update_lock: bool) -> anyhow::Result<()> {
+    if !session.root.join(MANIFEST).is_file() {
+        return Ok(());
+    }
+
+    let manifest = read_manifest(session, &session.root).ok_or(ResolutionError)?;
+    let mut packages = resolve_packages(session, manifest).ok_or(ResolutionError)?;
+
+    if !check_namespaces(session, &mut packages) || !check_lockfile(session, &packages, update_lock) {
+        return Err(ResolutionError.into());
+    }
+
+    for package in packages.into_values() {
+        for namespace in package.namespaces {
+            session.dependencies.insert(namespace, package.root.clone());
+        }
+    }
+
+    Ok(())
+}
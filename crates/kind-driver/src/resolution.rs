@@ -16,7 +16,7 @@ use strsim::jaro;
 use kind_pass::unbound::{self, UnboundCollector};
 use kind_report::data::Diagnostic;
 use kind_tree::concrete::visitor::Visitor;
-use kind_tree::concrete::{Book, Module, TopLevel};
+use kind_tree::concrete::{is_private, Book, Module, TopLevel};
 use kind_tree::symbol::{Ident, QualifiedIdent};
 
 use crate::{diagnostic::DriverDiagnostic, session::Session};
@@ -53,7 +53,7 @@ fn accumulate_neighbour_paths(
 
     if canon_path.exists() && dir_path.exists() && canon_path.is_file() && dir_path.is_dir() {
         Err(Box::new(DriverDiagnostic::MultiplePaths(
-            ident.clone(),
+            *ident,
             vec![canon_path, dir_path],
         )))
     } else if canon_path.is_file() {
@@ -97,7 +97,7 @@ fn try_to_insert_new_name<'a>(
 ) -> bool {
     if let Some(first_occorence) = book.names.get(ident.to_string().as_str()) {
         let err = Box::new(DriverDiagnostic::DefinedMultipleTimes(
-            first_occorence.clone(),
+            *first_occorence,
             ident,
         ));
 
@@ -123,32 +123,40 @@ fn module_to_book<'a>(
             TopLevel::SumType(sum) => {
                 let name = sum.name.to_string();
 
-                public_names.insert(name.clone());
+                if !is_private(&sum.attrs) {
+                    public_names.insert(name.clone());
+                }
 
                 for cons in &sum.constructors {
                     let mut cons_ident = sum.name.add_segment(cons.name.to_str());
                     cons_ident.range = cons.name.range;
-                    if try_to_insert_new_name(failed, session, cons_ident.clone(), book) {
+                    if try_to_insert_new_name(failed, session, cons_ident, book) {
                         let cons_name = cons_ident.to_string();
-                        public_names.insert(cons_name.clone());
+                        if !is_private(&sum.attrs) && !is_private(&cons.attrs) {
+                            public_names.insert(cons_name.clone());
+                        }
                         book.meta.insert(cons_name, cons.extract_book_info(&sum));
                     }
                 }
 
-                if try_to_insert_new_name(failed, session, sum.name.clone(), book) {
+                if try_to_insert_new_name(failed, session, sum.name, book) {
                     book.meta.insert(name.clone(), sum.extract_book_info());
                     book.entries.insert(name, TopLevel::SumType(sum));
                 }
             }
             TopLevel::RecordType(rec) => {
                 let name = rec.name.to_string();
-                public_names.insert(name.clone());
+                if !is_private(&rec.attrs) {
+                    public_names.insert(name.clone());
+                }
                 book.meta.insert(name.clone(), rec.extract_book_info());
 
-                try_to_insert_new_name(failed, session, rec.name.clone(), book);
+                try_to_insert_new_name(failed, session, rec.name, book);
 
                 let cons_ident = rec.name.add_segment(rec.constructor.to_str());
-                public_names.insert(cons_ident.to_string());
+                if !is_private(&rec.attrs) && !is_private(&rec.cons_attrs) {
+                    public_names.insert(cons_ident.to_string());
+                }
                 book.meta.insert(
                     cons_ident.to_string(),
                     rec.extract_book_info_of_constructor(),
@@ -161,11 +169,26 @@ fn module_to_book<'a>(
             TopLevel::Entry(entr) => {
                 let name = entr.name.to_string();
 
-                try_to_insert_new_name(failed, session, entr.name.clone(), book);
-                public_names.insert(name.clone());
+                try_to_insert_new_name(failed, session, entr.name, book);
+                if !is_private(&entr.attrs) {
+                    public_names.insert(name.clone());
+                }
                 book.meta.insert(name.clone(), entr.extract_book_info());
                 book.entries.insert(name, TopLevel::Entry(entr));
             }
+            TopLevel::Notation(notation) => {
+                if let Some(first) = book.notations.get(&notation.symbol) {
+                    let err = Box::new(DriverDiagnostic::NotationDefinedMultipleTimes(
+                        notation.symbol.clone(),
+                        first.range,
+                        notation.range,
+                    ));
+                    session.diagnostic_sender.send(err).unwrap();
+                    *failed = true;
+                } else {
+                    book.notations.insert(notation.symbol.clone(), notation);
+                }
+            }
         }
     }
 
@@ -181,7 +204,7 @@ fn parse_and_store_book_by_identifier(
         return false;
     }
 
-    match ident_to_path(&session.root, ident, true) {
+    match ident_to_path(session.root_of(ident.to_str()), ident, true) {
         Ok(Some(path)) => parse_and_store_book_by_path(session, &path, book),
         Ok(None) => false,
         Err(err) => {
@@ -192,6 +215,10 @@ fn parse_and_store_book_by_identifier(
 }
 
 fn parse_and_store_book_by_path(session: &mut Session, path: &PathBuf, book: &mut Book) -> bool {
+    if session.cancel.is_cancelled() {
+        return true;
+    }
+
     if !path.exists() {
         let err = Box::new(DriverDiagnostic::CannotFindFile(
             path.to_str().unwrap().to_string(),
@@ -225,15 +252,16 @@ fn parse_and_store_book_by_path(session: &mut Session, path: &PathBuf, book: &mu
 
     let tx = session.diagnostic_sender.clone();
 
-    let (mut module, mut failed) = kind_parser::parse_book(tx.clone(), ctx_id, &input);
+    let (mut module, mut failed) = kind_parser::parse_book(tx.clone(), ctx_id, &input, &session.cancel);
 
     expand_uses(&mut module, tx.clone());
     expand_module(tx.clone(), &mut module);
 
-    let mut state = UnboundCollector::new(tx.clone(), false);
+    let mut state = UnboundCollector::new(tx.clone(), false, &session.cancel);
     state.visit_module(&mut module);
 
-    module_to_book(&mut failed, session, module, book);
+    let public_names = module_to_book(&mut failed, session, module, book);
+    session.public_names.extend(public_names);
 
     for idents in state.unbound_top_level.values() {
         let fst = idents.iter().next().unwrap();
@@ -246,10 +274,14 @@ fn parse_and_store_book_by_path(session: &mut Session, path: &PathBuf, book: &mu
 }
 
 fn unbound_variable(session: &mut Session, book: &Book, idents: &[Ident]) {
+    // Private names are only suggested inside of the file that defines them.
     let mut similar_names = book
         .names
-        .keys()
-        .map(|x| (jaro(x, idents[0].to_str()).abs(), x))
+        .iter()
+        .filter(|(name, ident)| {
+            session.public_names.contains(*name) || ident.range.ctx == idents[0].range.ctx
+        })
+        .map(|(name, _)| (jaro(name, idents[0].to_str()).abs(), name))
         .filter(|x| x.0 > 0.8)
         .collect::<Vec<_>>();
 
@@ -264,21 +296,43 @@ fn unbound_variable(session: &mut Session, book: &Book, idents: &[Ident]) {
 }
 
 pub fn parse_and_store_book(session: &mut Session, path: &PathBuf) -> anyhow::Result<Book> {
-    let mut book = Book::default();
-    if parse_and_store_book_by_path(session, path, &mut book) {
+    let (book, failed) = parse_and_recover_book(session, path)?;
+    if failed {
         Err(ResolutionError.into())
     } else {
         Ok(book)
     }
 }
 
+/// Parses the book without stopping at the files that have errors. The
+/// parser puts error nodes in place of the code that it could not
+/// parse so the rest of the book can still be resolved and checked.
+/// The boolean is true if some error was reported.
+pub fn parse_and_recover_book(session: &mut Session, path: &PathBuf) -> anyhow::Result<(Book, bool)> {
+    let mut book = Book::default();
+    let failed = parse_and_store_book_by_path(session, path, &mut book);
+    session.cancel.check()?;
+    Ok((book, failed))
+}
+
 pub fn check_unbound_top_level(session: &mut Session, book: &mut Book) -> anyhow::Result<()> {
     let mut failed = false;
 
-    let (unbound_names, unbound_tops) =
-        unbound::get_book_unbound(session.diagnostic_sender.clone(), book, true);
+    let state = unbound::get_book_unbound(
+        session.diagnostic_sender.clone(),
+        book,
+        true,
+        &session.cancel,
+    );
+
+    // The collector stops in the middle of the book so the
+    // unbound names that it found are not reliable.
+    session.cancel.check()?;
+
+    // Uses of private names outside of their files were already reported.
+    failed |= state.failed;
 
-    for unbound in unbound_tops.values() {
+    for unbound in state.unbound_top_level.values() {
         let res: Vec<Ident> = unbound
             .iter()
             .filter(|x| !x.generated)
@@ -291,7 +345,7 @@ pub fn check_unbound_top_level(session: &mut Session, book: &mut Book) -> anyhow
         }
     }
 
-    for unbound in unbound_names.values() {
+    for unbound in state.unbound.values() {
         unbound_variable(session, book, unbound);
         failed = true;
     }
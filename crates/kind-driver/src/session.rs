@@ -2,11 +2,12 @@
 //! model because I want to change it to a query based compiler
 //! later.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::rc::Rc;
 use std::sync::mpsc::Sender;
 
 use fxhash::{FxHashMap, FxHashSet};
+use kind_report::cancel::CancellationToken;
 use kind_report::data::Diagnostic;
 
 #[derive(Debug, Clone)]
@@ -16,14 +17,23 @@ pub struct Session {
 
     pub loaded_paths_map: FxHashMap<PathBuf, usize>,
 
-    /// It will be useful in the future
-    /// to make the public and private decls
+    /// Names that can be used outside of the file that
+    /// defines them (everything without `#private`).
     pub public_names: FxHashSet<String>,
 
     pub diagnostic_sender: Sender<Box<dyn Diagnostic>>,
     pub root: PathBuf,
 
+    /// Maps each namespace provided by a dependency to the
+    /// root of the package that provides it.
+    pub dependencies: FxHashMap<String, PathBuf>,
+
     pub book_counter: usize,
+
+    /// Cancels the compilation from another thread. The passes
+    /// poll it and return [kind_report::cancel::Cancelled] with
+    /// the diagnostics that were already sent.
+    pub cancel: CancellationToken,
 }
 
 impl Session {
@@ -34,8 +44,10 @@ impl Session {
             loaded_paths_map: FxHashMap::default(),
             public_names: FxHashSet::default(),
             root,
+            dependencies: FxHashMap::default(),
             book_counter: 0,
             diagnostic_sender: sender,
+            cancel: CancellationToken::new(),
         }
     }
     pub fn add_path(&mut self, path: Rc<PathBuf>, code: String) -> usize {
@@ -47,4 +59,31 @@ impl Session {
         self.loaded_sources.push(code);
         id
     }
+
+    /// The paths of the loaded files in the order of their
+    /// [kind_span::SyntaxCtxIndex]. They are relative to the current
+    /// directory like in the error messages.
+    pub fn relative_paths(&self) -> Vec<PathBuf> {
+        let base = PathBuf::from(".").canonicalize().ok();
+        self.loaded_paths
+            .iter()
+            .map(|path| {
+                let path = path.as_ref().clone();
+                match &base {
+                    Some(base) => pathdiff::diff_paths(&path, base).unwrap_or(path),
+                    None => path,
+                }
+            })
+            .collect()
+    }
+
+    /// Gets the root of the package that provides the
+    /// namespace of a name.
+    pub fn root_of(&self, name: &str) -> &Path {
+        let namespace = name.split('.').next().unwrap_or(name);
+        self.dependencies
+            .get(namespace)
+            .map(|x| x.as_path())
+            .unwrap_or(&self.root)
+    }
 }
@@ -0,0 +1,184 @@
+//! A lossless view of the source code. The normal lexer throws away
+//! comments, whitespace and the spelling of the literals, this module
+//! keeps all of them attached to the tokens so tools like formatters
+//! can rebuild the exact same file. The [concrete] tree is still the
+//! one that is used to understand the code, and its ranges can be used
+//! to find the tokens that each node came from.
+//!
+//! [concrete]: kind_tree::concrete
+
+use std::sync::mpsc::Sender;
+
+use kind_report::cancel::CancellationToken;
+use kind_report::data::Diagnostic;
+use kind_span::{Range, SyntaxCtxIndex};
+use kind_tree::concrete::Module;
+
+use crate::lexer::is_whitespace;
+use crate::lexer::state::Lexer;
+use crate::lexer::tokens::Token;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaKind {
+    /// Spaces, tabs and carriage returns.
+    Whitespace,
+    /// A single line break. Blank lines are two or more of them.
+    Newline,
+    /// Line or block comments that are not documentation.
+    Comment,
+}
+
+/// Part of the code that does not change the meaning of the program.
+#[derive(Debug, Clone)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub text: String,
+    pub range: Range,
+}
+
+/// A token with the exact text that it was lexed from and all
+/// of the trivia that comes before it.
+#[derive(Debug, Clone)]
+pub struct CstToken {
+    pub leading: Vec<Trivia>,
+    pub token: Token,
+    pub text: String,
+    pub range: Range,
+}
+
+/// The lossless token stream of a file together with the concrete
+/// tree of it. The last token is always [Token::Eof] and its leading
+/// trivia is everything after the last real token.
+#[derive(Debug)]
+pub struct SyntaxTree {
+    pub tokens: Vec<CstToken>,
+    pub module: Module,
+}
+
+impl SyntaxTree {
+    /// Rebuilds the source code. It's the same as the input.
+    pub fn to_source(&self) -> String {
+        let mut res = String::new();
+        for token in &self.tokens {
+            for trivia in &token.leading {
+                res.push_str(&trivia.text);
+            }
+            res.push_str(&token.text);
+        }
+        res
+    }
+
+    /// All of the tokens inside of a range of the concrete tree.
+    pub fn tokens_of(&self, range: Range) -> &[CstToken] {
+        let start = self
+            .tokens
+            .partition_point(|x| x.range.start.index < range.start.index);
+        let end = self
+            .tokens
+            .partition_point(|x| x.range.end.index <= range.end.index);
+        if start < end {
+            &self.tokens[start..end]
+        } else {
+            &[]
+        }
+    }
+
+    /// The exact text of a range of the concrete tree without the
+    /// trivia that comes before it.
+    pub fn text_of(&self, range: Range) -> String {
+        let mut res = String::new();
+        for (i, token) in self.tokens_of(range).iter().enumerate() {
+            if i != 0 {
+                for trivia in &token.leading {
+                    res.push_str(&trivia.text);
+                }
+            }
+            res.push_str(&token.text);
+        }
+        res
+    }
+
+    /// Comments that come right before a range, like the ones on top
+    /// of a definition.
+    pub fn comments_before(&self, range: Range) -> Vec<&Trivia> {
+        match self.tokens_of(range).first() {
+            Some(token) => token
+                .leading
+                .iter()
+                .filter(|x| x.kind == TriviaKind::Comment)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// Lexes a file without discarding anything. Lexical errors are
+/// kept as [Token::Error] tokens.
+pub fn lex_lossless(ctx_id: usize, input: &str) -> Vec<CstToken> {
+    let mut lexer = Lexer::new(input, input.chars().peekable(), SyntaxCtxIndex::new(ctx_id));
+    let mut tokens = Vec::new();
+    let mut leading = Vec::new();
+
+    loop {
+        let start = lexer.span();
+
+        let trivia = match lexer.peekable.peek() {
+            Some('\n') => {
+                lexer.next_char();
+                Some(TriviaKind::Newline)
+            }
+            Some(&c) if is_whitespace(c) => {
+                lexer.accumulate_while(&is_whitespace);
+                Some(TriviaKind::Whitespace)
+            }
+            _ => None,
+        };
+
+        if let Some(kind) = trivia {
+            leading.push(Trivia {
+                kind,
+                text: input[start..lexer.span()].to_string(),
+                range: lexer.mk_range(start),
+            });
+            continue;
+        }
+
+        let (token, _) = lexer.lex_next();
+        let text = input[start..lexer.span()].to_string();
+        let range = lexer.mk_range(start);
+
+        if let Token::Comment(false, _) = token {
+            leading.push(Trivia {
+                kind: TriviaKind::Comment,
+                text,
+                range,
+            });
+            continue;
+        }
+
+        let is_eof = token.is_eof();
+
+        tokens.push(CstToken {
+            leading: std::mem::take(&mut leading),
+            token,
+            text,
+            range,
+        });
+
+        if is_eof {
+            break tokens;
+        }
+    }
+}
+
+/// Parses a file keeping the lossless token stream of it.
+pub fn parse_lossless(
+    errs: Sender<Box<dyn Diagnostic>>,
+    ctx_id: usize,
+    input: &str,
+    cancel: &CancellationToken,
+) -> (SyntaxTree, bool) {
+    let (module, failed) = crate::parse_book(errs, ctx_id, input, cancel);
+    let tokens = lex_lossless(ctx_id, input);
+    (SyntaxTree { tokens, module }, failed)
+}
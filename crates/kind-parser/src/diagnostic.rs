@@ -33,6 +33,9 @@ pub enum SyntaxDiagnostic {
     CannotUseUse(Range),
     ImportsCannotHaveAlias(Range),
     InvalidNumberType(String, Range),
+    NonAssociativeOperator(Range, Range),
+    InvalidNotation(Range),
+    WrongNumberOfCaseHeads(Range, usize),
 }
 
 fn encode_name(encode: EncodeSequence) -> &'static str {
@@ -64,6 +67,9 @@ impl Diagnostic for SyntaxDiagnostic {
             SyntaxDiagnostic::ImportsCannotHaveAlias(range) => Some(range.ctx),
             SyntaxDiagnostic::InvalidNumberType(_, range) => Some(range.ctx),
             SyntaxDiagnostic::MatchScrutineeShouldBeAName(range) => Some(range.ctx),
+            SyntaxDiagnostic::NonAssociativeOperator(range, _) => Some(range.ctx),
+            SyntaxDiagnostic::InvalidNotation(range) => Some(range.ctx),
+            SyntaxDiagnostic::WrongNumberOfCaseHeads(range, _) => Some(range.ctx),
         }
     }
 
@@ -334,6 +340,57 @@ impl Diagnostic for SyntaxDiagnostic {
                     main: true,
                 }],
             },
+            SyntaxDiagnostic::NonAssociativeOperator(fst, snd) => DiagnosticFrame {
+                code: 19,
+                severity: Severity::Error,
+                title: "Comparison operators cannot be chained.".to_string(),
+                subtitles: vec![],
+                hints: vec!["Use parenthesis to group the comparisons".to_string()],
+                positions: vec![
+                    Marker {
+                        position: *fst,
+                        color: Color::Fst,
+                        text: "This operator".to_string(),
+                        no_code: false,
+                        main: true,
+                    },
+                    Marker {
+                        position: *snd,
+                        color: Color::Snd,
+                        text: "cannot be followed by this one".to_string(),
+                        no_code: false,
+                        main: false,
+                    },
+                ],
+            },
+            SyntaxDiagnostic::InvalidNotation(range) => DiagnosticFrame {
+                code: 20,
+                severity: Severity::Error,
+                title: "Invalid notation.".to_string(),
+                subtitles: vec![],
+                hints: vec!["A notation looks like \"xs ++ ys\", two names with an operator made of at least two of the symbols + - * / % & | ^ < > = ! between them".to_string()],
+                positions: vec![Marker {
+                    position: *range,
+                    color: Color::Fst,
+                    text: "Here!".to_string(),
+                    no_code: false,
+                    main: true,
+                }],
+            },
+            SyntaxDiagnostic::WrongNumberOfCaseHeads(range, expected) => DiagnosticFrame {
+                code: 21,
+                severity: Severity::Error,
+                title: format!("This case should match {} values.", expected),
+                subtitles: vec![],
+                hints: vec!["Separate the patterns of each scrutinee with a comma, '_' matches anything".to_string()],
+                positions: vec![Marker {
+                    position: *range,
+                    color: Color::Fst,
+                    text: "Here!".to_string(),
+                    no_code: false,
+                    main: true,
+                }],
+            },
         }
     }
 
@@ -354,7 +411,10 @@ impl Diagnostic for SyntaxDiagnostic {
             | MatchScrutineeShouldBeAName(_)
             | CannotUseUse(_)
             | ImportsCannotHaveAlias(_)
-            | InvalidNumberType(_, _) => Severity::Error,
+            | InvalidNumberType(_, _)
+            | NonAssociativeOperator(_, _)
+            | InvalidNotation(_)
+            | WrongNumberOfCaseHeads(_, _) => Severity::Error,
             | UnusedDocString(_) => Severity::Warning,
         }
     }
@@ -1,13 +1,60 @@
 use kind_span::{Locatable, Range};
 use kind_tree::concrete::expr::{*};
 use kind_tree::symbol::{Ident, QualifiedIdent};
-use kind_tree::Operator;
+use kind_tree::{solve_infix, Operator};
 
 use crate::diagnostic::SyntaxDiagnostic;
 use crate::lexer::tokens::Token;
 use crate::macros::eat_single;
 use crate::state::Parser;
 
+/// Tokens that can be glued together to make the symbol of a notation.
+pub(crate) fn is_symbol_token(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Plus
+            | Token::Minus
+            | Token::Star
+            | Token::Slash
+            | Token::Percent
+            | Token::Ampersand
+            | Token::Bar
+            | Token::Hat
+            | Token::GreaterGreater
+            | Token::LessLess
+            | Token::Less
+            | Token::LessEq
+            | Token::EqEq
+            | Token::GreaterEq
+            | Token::Greater
+            | Token::BangEq
+            | Token::Bang
+            | Token::Eq
+    )
+}
+
+fn to_operator(token: &Token) -> Option<Operator> {
+    match token {
+        Token::Plus => Some(Operator::Add),
+        Token::Minus => Some(Operator::Sub),
+        Token::Star => Some(Operator::Mul),
+        Token::Slash => Some(Operator::Div),
+        Token::Percent => Some(Operator::Mod),
+        Token::Ampersand => Some(Operator::And),
+        Token::Bar => Some(Operator::Or),
+        Token::Hat => Some(Operator::Xor),
+        Token::GreaterGreater => Some(Operator::Shr),
+        Token::LessLess => Some(Operator::Shl),
+        Token::Less => Some(Operator::Ltn),
+        Token::LessEq => Some(Operator::Lte),
+        Token::EqEq => Some(Operator::Eql),
+        Token::GreaterEq => Some(Operator::Gte),
+        Token::Greater => Some(Operator::Gtn),
+        Token::BangEq => Some(Operator::Neq),
+        _ => None,
+    }
+}
+
 impl<'a> Parser<'a> {
     // We always look through the parenthesis in the
     // matching with is_operator
@@ -34,25 +81,51 @@ impl<'a> Parser<'a> {
     }
 
     fn eat_operator(&mut self) -> Result<Operator, SyntaxDiagnostic> {
-        self.eat(|token| match token {
-            Token::Plus => Some(Operator::Add),
-            Token::Minus => Some(Operator::Sub),
-            Token::Star => Some(Operator::Mul),
-            Token::Slash => Some(Operator::Div),
-            Token::Percent => Some(Operator::Mod),
-            Token::Ampersand => Some(Operator::And),
-            Token::Bar => Some(Operator::Or),
-            Token::Hat => Some(Operator::Xor),
-            Token::GreaterGreater => Some(Operator::Shr),
-            Token::LessLess => Some(Operator::Shl),
-            Token::Less => Some(Operator::Ltn),
-            Token::LessEq => Some(Operator::Lte),
-            Token::EqEq => Some(Operator::Eql),
-            Token::GreaterEq => Some(Operator::Gte),
-            Token::Greater => Some(Operator::Gtn),
-            Token::BangEq => Some(Operator::Neq),
-            _ => None,
-        })
+        self.eat(to_operator)
+    }
+
+    /// Checks if the current token and the next one are glued
+    /// together like the `+` and `+` of `++`.
+    fn is_glued(&self) -> bool {
+        self.range().end.index == self.peek_range(1).start.index
+    }
+
+    fn is_notation_token(&self, token: &Token) -> bool {
+        is_symbol_token(token) && !(self.hidden_arg && token.same_variant(&Token::Greater))
+    }
+
+    /// Eats the operator in the infix position. Like the arguments of
+    /// a call, it has to be in the same line if it's not multiline.
+    /// Two or more operator tokens glued together are the symbol of a
+    /// notation.
+    fn eat_infix_operator(&mut self, multiline: bool) -> Option<(InfixOp, Range)> {
+        if self.is_linebreak() && !multiline {
+            return None;
+        }
+
+        if self.is_notation_token(self.get()) && self.is_notation_token(self.peek(1)) && self.is_glued() {
+            let start = self.range();
+            let mut symbol = String::new();
+
+            let end = loop {
+                let glued = self.is_glued();
+                let (token, end) = self.advance();
+                symbol.push_str(&token.to_string());
+                if !glued || !self.is_notation_token(self.get()) {
+                    break end;
+                }
+            };
+
+            return Some((InfixOp::Notation(symbol), start.mix(end)));
+        }
+
+        match self.get() {
+            Token::Greater if self.hidden_arg => None,
+            token => {
+                let op = to_operator(token)?;
+                Some((InfixOp::Operator(op), self.advance().1))
+            }
+        }
     }
 
     fn ignore_docs(&mut self) {
@@ -247,8 +320,11 @@ impl<'a> Parser<'a> {
             "F60" => ExprKind::Lit {
                 lit: Literal::NumTypeF60,
             },
+            "I60" => ExprKind::Lit {
+                lit: Literal::NumTypeI60,
+            },
             _ => ExprKind::Constr {
-                name: id.clone(),
+                name: id,
                 args: vec![],
             },
         };
@@ -269,6 +345,9 @@ impl<'a> Parser<'a> {
             "F60" => ExprKind::Lit {
                 lit: Literal::NumTypeF60,
             },
+            "I60" => ExprKind::Lit {
+                lit: Literal::NumTypeI60,
+            },
             _ => {
                 let (range_end, spine) = self.parse_call_tail(id.range, multiline)?;
                 range = range.mix(range_end);
@@ -292,6 +371,17 @@ impl<'a> Parser<'a> {
         }))
     }
 
+    fn parse_numi60(&mut self, num: i64) -> Result<Box<Expr>, SyntaxDiagnostic> {
+        let range = self.range();
+        self.advance();
+        Ok(Box::new(Expr {
+            range,
+            data: ExprKind::Lit {
+                lit: Literal::NumI60(num),
+            },
+        }))
+    }
+
     fn parse_nat(&mut self, num: u128) -> Result<Box<Expr>, SyntaxDiagnostic> {
         let range = self.range();
         self.advance();
@@ -394,7 +484,10 @@ impl<'a> Parser<'a> {
         } else {
             let range = self.range();
             self.advance(); // '('
-            let mut expr = self.parse_expr(true)?;
+            let hidden_arg = std::mem::replace(&mut self.hidden_arg, false);
+            let expr = self.parse_expr(true);
+            self.hidden_arg = hidden_arg;
+            let mut expr = expr?;
             let end = self.range();
             self.eat_closing_keyword(Token::RPar, range)?;
             expr.range = range.mix(end);
@@ -424,6 +517,33 @@ impl<'a> Parser<'a> {
         }))
     }
 
+    fn parse_interpolation(&mut self, str: String) -> Result<Box<Expr>, SyntaxDiagnostic> {
+        let start = self.range();
+        self.advance(); // '"...${'
+
+        let mut parts = vec![StrPart::Text(str)];
+
+        loop {
+            parts.push(StrPart::Expr(self.parse_expr(true)?));
+            match self.get().clone() {
+                Token::StrMiddle(str) => {
+                    self.advance();
+                    parts.push(StrPart::Text(str));
+                }
+                Token::StrEnd(str) => {
+                    let end = self.range();
+                    self.advance();
+                    parts.push(StrPart::Text(str));
+                    return Ok(Box::new(Expr {
+                        range: start.mix(end),
+                        data: ExprKind::Interpolation { parts },
+                    }));
+                }
+                _ => return self.fail(vec![Token::StrEnd("".to_string())]),
+            }
+        }
+    }
+
     fn parse_num_lit(&mut self) -> Result<usize, SyntaxDiagnostic> {
         self.ignore_docs();
         match self.get().clone() {
@@ -443,8 +563,10 @@ impl<'a> Parser<'a> {
             Token::Num60(num) => self.parse_num60(num),
             Token::Nat(num) => self.parse_nat(num),
             Token::Num120(num) => self.parse_num120(num),
+            Token::NumI60(num) => self.parse_numi60(num),
             Token::Char(chr) => self.parse_char(chr),
             Token::Str(str) => self.parse_str(str),
+            Token::StrStart(str) => self.parse_interpolation(str),
             Token::Help(str) => self.parse_help(str),
             Token::LBracket => self.parse_list(),
             Token::LPar => self.parse_paren(),
@@ -546,8 +668,53 @@ impl<'a> Parser<'a> {
         Ok((end, spine))
     }
 
+    /// Parses a chain of infix operators. The operands are calls so
+    /// `f a + g b` is the same as `(+ (f a) (g b))`. If all of the
+    /// operators are builtin the chain is grouped here, otherwise the
+    /// desugarer does it after it knows the precedence of the notations.
+    fn parse_infix(&mut self, multiline: bool) -> Result<Box<Expr>, SyntaxDiagnostic> {
+        let fst = self.parse_call(multiline)?;
+        let mut rest = Vec::new();
+
+        while let Some((op, range)) = self.eat_infix_operator(multiline) {
+            let snd = self.parse_call(multiline)?;
+            rest.push((op, range, snd));
+        }
+
+        let range = match rest.last() {
+            None => return Ok(fst),
+            Some((_, _, last)) => fst.range.mix(last.range),
+        };
+
+        if rest.iter().any(|x| matches!(x.0, InfixOp::Notation(_))) {
+            return Ok(Box::new(Expr {
+                data: ExprKind::Infix { fst, rest },
+                range,
+            }));
+        }
+
+        let info = |op: &InfixOp| match op {
+            InfixOp::Operator(op) => (op.precedence(), op.associativity()),
+            InfixOp::Notation(_) => unreachable!(),
+        };
+
+        let mut join = |op: InfixOp, _: Range, fst: Box<Expr>, snd: Box<Expr>| {
+            let range = fst.range.mix(snd.range);
+            match op {
+                InfixOp::Operator(op) => Box::new(Expr {
+                    data: ExprKind::Binary { op, fst, snd },
+                    range,
+                }),
+                InfixOp::Notation(_) => unreachable!(),
+            }
+        };
+
+        solve_infix(fst, rest, &info, &mut join)
+            .map_err(|(fst, snd)| SyntaxDiagnostic::NonAssociativeOperator(fst, snd))
+    }
+
     fn parse_arrow(&mut self, multiline: bool) -> Result<Box<Expr>, SyntaxDiagnostic> {
-        let mut expr = self.parse_call(multiline)?;
+        let mut expr = self.parse_infix(multiline)?;
 
         while self.check_and_eat(Token::RightArrow) {
             let body = self.parse_expr(false)?;
@@ -599,7 +766,7 @@ impl<'a> Parser<'a> {
     fn parse_destruct(&mut self) -> Result<Destruct, SyntaxDiagnostic> {
         if self.get().is_upper_id() {
             let upper = self.parse_upper_id()?;
-            let (range, bindings, ignore_rest) = self.parse_pat_destruct_bindings()?;
+            let (range, bindings, ignore_rest) = self.parse_pat_destruct_bindings(false)?;
             Ok(Destruct::Destruct(
                 upper.range.mix(range.unwrap_or(upper.range)),
                 upper,
@@ -695,16 +862,40 @@ impl<'a> Parser<'a> {
         self.advance(); // 'do'
         let typ = self.parse_upper_id()?;
         self.eat_variant(Token::LBrace)?;
-        let sttm = self.parse_sttm()?;
+
+        let data = match self.parse_sttm() {
+            Ok(sttm) => ExprKind::Do { typ, sttm },
+            Err(err) => {
+                self.recover(err, &|parser| parser.get().same_variant(&Token::RBrace));
+                ExprKind::Error
+            }
+        };
+
         let end = self.eat_variant(Token::RBrace)?.1;
         Ok(Box::new(Expr {
-            data: ExprKind::Do { typ, sttm },
+            data,
             range: start.mix(end),
         }))
     }
 
+    /// Start of a nested pattern inside of the bindings of a match case
+    /// e.g. `(Nat.succ n)`, `Nat.zero`, `_` or `2`.
+    fn is_nested_pat_start(&self) -> bool {
+        match self.get() {
+            Token::LPar => !self.peek(2).same_variant(&Token::Eq),
+            Token::UpperId(_, _)
+            | Token::Hole
+            | Token::Num60(_)
+            | Token::Num120(_)
+            | Token::Char(_)
+            | Token::Str(_) => true,
+            _ => false,
+        }
+    }
+
     fn parse_pat_destruct_bindings(
         &mut self,
+        nested: bool,
     ) -> Result<(Option<Range>, Vec<CaseBinding>, Option<Range>), SyntaxDiagnostic> {
         let mut ignore_rest_range = None;
         let mut bindings = Vec::new();
@@ -716,6 +907,11 @@ impl<'a> Parser<'a> {
                     let name = self.parse_id()?;
                     bindings.push(CaseBinding::Field(name));
                 }
+                _ if nested && self.is_nested_pat_start() => {
+                    let pat = self.parse_pat()?;
+                    range = Some(pat.range);
+                    bindings.push(CaseBinding::Pattern(pat));
+                }
                 Token::LPar => {
                     let start = self.range();
                     self.advance();
@@ -741,15 +937,66 @@ impl<'a> Parser<'a> {
         Ok((range, bindings, ignore_rest_range))
     }
 
-    fn parse_match(&mut self) -> Result<Box<Expr>, SyntaxDiagnostic> {
-        let start = self.range();
-        self.advance(); // 'match'
+    fn parse_case_head(&mut self) -> Result<CaseHead, SyntaxDiagnostic> {
+        if self.get().same_variant(&Token::Hole) {
+            let range = self.range();
+            self.advance();
+            return Ok(CaseHead {
+                constructor: Ident::new_static("_", range),
+                bindings: Vec::new(),
+                ignore_rest: None,
+            });
+        }
+        let constructor = self.parse_any_id()?;
+        let (_range, bindings, ignore_rest) = self.parse_pat_destruct_bindings(true)?;
+        Ok(CaseHead {
+            constructor,
+            bindings,
+            ignore_rest,
+        })
+    }
 
+    /// Parses the heads of a case separated by commas, one for each
+    /// scrutinee, until the `=>`.
+    fn parse_case_header(
+        &mut self,
+        scrutinees: usize,
+    ) -> Result<(CaseHead, Vec<CaseHead>), SyntaxDiagnostic> {
+        let head = self.parse_case_head()?;
+        let mut range = head.constructor.range;
+        let mut others = Vec::new();
+        while self.check_and_eat(Token::Comma) {
+            let other = self.parse_case_head()?;
+            range = range.mix(other.constructor.range);
+            others.push(other);
+        }
+        if others.len() + 1 != scrutinees {
+            return Err(SyntaxDiagnostic::WrongNumberOfCaseHeads(range, scrutinees));
+        }
+        self.eat_variant(Token::FatArrow)?;
+        Ok((head, others))
+    }
+
+    /// The closing brace of a match or the start of a case in a new
+    /// line like `succ pred =>` or `zero, _ =>`.
+    fn is_case_boundary(&self) -> bool {
+        let ends_head = |token: &Token| {
+            token.same_variant(&Token::FatArrow) || token.same_variant(&Token::Comma)
+        };
+        self.get().same_variant(&Token::RBrace)
+            || (self.is_linebreak()
+                && (self.get().is_lower_id()
+                    || self.get().is_upper_id()
+                    || self.get().same_variant(&Token::Hole))
+                && (ends_head(self.peek(1)) || ends_head(self.peek(2))))
+    }
+
+    fn parse_scrutinee(&mut self) -> Result<Scrutinee, SyntaxDiagnostic> {
         let typ = self.parse_upper_id()?;
 
         let expr_scrutinee = self.parse_expr(false)?;
 
-        let scrutinee = match expr_scrutinee.data {
+        let name = match expr_scrutinee.data {
             ExprKind::Var { name } => {
                 name
             },
@@ -762,6 +1009,21 @@ impl<'a> Parser<'a> {
             None
         };
 
+        Ok(Scrutinee { typ, name, value })
+    }
+
+    fn parse_match(&mut self) -> Result<Box<Expr>, SyntaxDiagnostic> {
+        let start = self.range();
+        self.advance(); // 'match'
+
+        let Scrutinee { typ, name: scrutinee, value } = self.parse_scrutinee()?;
+
+        let mut others = Vec::new();
+
+        while self.check_and_eat(Token::Comma) {
+            others.push(self.parse_scrutinee()?);
+        }
+
         let mut with_vars = Vec::new();
 
         if self.check_and_eat(Token::With) {
@@ -774,18 +1036,33 @@ impl<'a> Parser<'a> {
 
         let mut cases = Vec::new();
 
-        while !self.get().same_variant(&Token::RBrace) {
-            let constructor = self.parse_any_id()?;
-            let (_range, bindings, ignore_rest) = self.parse_pat_destruct_bindings()?;
-            self.eat_variant(Token::FatArrow)?;
-            let value = self.parse_expr(false)?;
+        // A case that could not be parsed turns the whole match into an
+        // error node, otherwise it would be reported as a missing case.
+        let mut broken = false;
+
+        while !self.get().same_variant(&Token::RBrace) && !self.get().is_eof() {
+            let (head, heads) = match self.parse_case_header(others.len() + 1) {
+                Ok(res) => res,
+                Err(err) => {
+                    self.recover(err, &|parser| parser.is_case_boundary());
+                    broken = true;
+                    continue;
+                }
+            };
+
+            let value = match self.parse_expr(false) {
+                Ok(value) => value,
+                Err(err) => Expr::error(self.recover(err, &|parser| parser.is_case_boundary())),
+            };
+
             self.check_and_eat(Token::Semi);
 
             cases.push(Case {
-                constructor,
-                bindings,
+                constructor: head.constructor,
+                bindings: head.bindings,
                 value,
-                ignore_rest,
+                ignore_rest: head.ignore_rest,
+                others: heads,
             })
         }
 
@@ -799,11 +1076,16 @@ impl<'a> Parser<'a> {
             None
         };
 
+        if broken {
+            return Ok(Expr::error(start.mix(end)));
+        }
+
         let match_ = Box::new(Match {
             typ,
             scrutinee,
             with_vars,
             value,
+            others,
             cases,
             motive,
         });
@@ -6,6 +6,7 @@
 use kind_span::Range;
 
 use crate::diagnostic::{EncodeSequence, SyntaxDiagnostic};
+use crate::lexer::is_whitespace;
 use crate::lexer::tokens::Token;
 use crate::Lexer;
 
@@ -55,12 +56,14 @@ impl<'a> Lexer<'a> {
     }
 
     /// Lexes a number of base @base@, figuring out it's type
-    /// Lexes 0 if not at a digit position
+    /// Lexes 0 if not at a digit position. Only the `i60` type
+    /// accepts @negative@ numbers.
     fn lex_num_and_type_with_base(
         &mut self,
         num_start: usize,
         base: u32,
         err: EncodeSequence,
+        negative: bool,
     ) -> (Token, Range) {
         let num = self.accumulate_while(&|x| x.is_digit(base) || x == '_');
         let num = if num.is_empty() { "0" } else { num };
@@ -111,6 +114,28 @@ impl<'a> Lexer<'a> {
                     ),
                 }
             }
+            Some('I' | 'i') => {
+                self.next_char();
+                let type_ = self.accumulate_while(&|x| x.is_ascii_digit());
+                if type_ != "60" {
+                    return (
+                        Token::Error(Box::new(SyntaxDiagnostic::InvalidNumberType(
+                            format!("i{}", type_),
+                            self.mk_range(type_start),
+                        ))),
+                        self.mk_range(type_start),
+                    );
+                }
+                // The magnitude of the negative numbers goes one further.
+                let limit = if negative { 1 << 59 } else { (1 << 59) - 1 };
+                match u64::from_str_radix(&num.replace('_', ""), base) {
+                    Ok(res) if res <= limit => {
+                        let res = if negative { (res as i64).wrapping_neg() } else { res as i64 };
+                        (Token::NumI60(res), self.mk_range(num_start))
+                    }
+                    _ => make_num_err(self),
+                }
+            }
             Some(_) | None => {
                 if let Ok(res) = u64::from_str_radix(&num.replace('_', ""), base) {
                     (Token::Num60(res), self.mk_range(num_start))
@@ -124,6 +149,17 @@ impl<'a> Lexer<'a> {
     /// Lex numbers with decimal, hexadecimal, binary or octal.
     pub fn lex_number(&mut self) -> (Token, Range) {
         let start = self.span();
+        self.lex_number_from(start, false)
+    }
+
+    /// Lex a `-` followed by a signed number (e.g. `-5i60`).
+    pub fn lex_negative_number(&mut self) -> (Token, Range) {
+        let start = self.span();
+        self.next_char();
+        self.lex_number_from(start, true)
+    }
+
+    fn lex_number_from(&mut self, start: usize, negative: bool) -> (Token, Range) {
         match self.peekable.peek() {
             None => (Token::Eof, self.mk_range(start)),
             Some('0') => {
@@ -131,23 +167,23 @@ impl<'a> Lexer<'a> {
                 match self.peekable.peek() {
                     Some('x' | 'X') => {
                         self.next_char();
-                        self.lex_num_and_type_with_base(start, 16, EncodeSequence::Hexa)
+                        self.lex_num_and_type_with_base(start, 16, EncodeSequence::Hexa, negative)
                     }
                     Some('o' | 'O') => {
                         self.next_char();
-                        self.lex_num_and_type_with_base(start, 8, EncodeSequence::Octal)
+                        self.lex_num_and_type_with_base(start, 8, EncodeSequence::Octal, negative)
                     }
                     Some('b' | 'B') => {
                         self.next_char();
-                        self.lex_num_and_type_with_base(start, 2, EncodeSequence::Binary)
+                        self.lex_num_and_type_with_base(start, 2, EncodeSequence::Binary, negative)
                     }
                     Some('0'..='9' | _) | None => {
-                        self.lex_num_and_type_with_base(start, 10, EncodeSequence::Decimal)
+                        self.lex_num_and_type_with_base(start, 10, EncodeSequence::Decimal, negative)
                     }
                 }
             }
             Some('0'..='9' | _) => {
-                self.lex_num_and_type_with_base(start, 10, EncodeSequence::Decimal)
+                self.lex_num_and_type_with_base(start, 10, EncodeSequence::Decimal, negative)
             }
         }
     }
@@ -180,9 +216,14 @@ impl<'a> Lexer<'a> {
     /// TODO: Accumulate multiple encoding errors?
     pub fn lex_string(&mut self) -> (Token, Range) {
         let start = self.span();
-
         self.next_char();
+        self.lex_string_part(start, true)
+    }
 
+    /// Lexes the text of a string until its end or until the next
+    /// `${`. The expression inside of the `${ }` is lexed as normal
+    /// tokens and the string continues after the `}` that closes it.
+    pub fn lex_string_part(&mut self, start: usize, first: bool) -> (Token, Range) {
         let mut string = String::new();
         let mut error: Option<(Token, Range)> = None;
 
@@ -190,6 +231,20 @@ impl<'a> Lexer<'a> {
             let chr_start = self.span();
             match x {
                 '\"' => break,
+                '$' if self.input[1..].starts_with('{') => {
+                    self.next_char();
+                    self.next_char();
+                    self.interpolations.push(0);
+                    if let Some(err) = error {
+                        return err;
+                    }
+                    let token = if first {
+                        Token::StrStart(string)
+                    } else {
+                        Token::StrMiddle(string)
+                    };
+                    return (token, self.mk_range(start));
+                }
                 '\\' => {
                     self.next_char();
                     match self.lex_escaped_char(chr_start) {
@@ -209,7 +264,8 @@ impl<'a> Lexer<'a> {
 
         match (self.next_char(), error) {
             (_, Some(err)) => err,
-            (Some('"'), _) => (Token::Str(string), self.mk_range(start)),
+            (Some('"'), _) if first => (Token::Str(string), self.mk_range(start)),
+            (Some('"'), _) => (Token::StrEnd(string), self.mk_range(start)),
             _ => (
                 Token::Error(Box::new(SyntaxDiagnostic::UnfinishedString(
                     self.mk_one_column_range(start),
@@ -218,4 +274,141 @@ impl<'a> Lexer<'a> {
             ),
         }
     }
+
+    /// Advances until the byte `end` of the input.
+    fn skip_until(&mut self, end: usize) {
+        while self.pos < end && self.next_char().is_some() {}
+    }
+
+    /// Lexes a raw string like `r"C:\path"` or `r#"{"a": 1}"#`. The
+    /// escapes are not processed and the string only ends at a quote
+    /// followed by the same number of `#` as the start.
+    pub fn lex_raw_string(&mut self) -> (Token, Range) {
+        let start = self.span();
+
+        self.next_char();
+        let hashes = self.accumulate_while(&|x| x == '#').len();
+        self.next_char();
+
+        let closing = format!("\"{}", "#".repeat(hashes));
+
+        match self.input.find(&closing) {
+            Some(size) => {
+                let string = self.input[..size].to_string();
+                self.skip_until(self.pos + size + closing.len());
+                (Token::Str(string), self.mk_range(start))
+            }
+            None => {
+                self.skip_until(self.pos + self.input.len());
+                (
+                    Token::Error(Box::new(SyntaxDiagnostic::UnfinishedString(
+                        self.mk_one_column_range(start),
+                    ))),
+                    self.mk_range(start),
+                )
+            }
+        }
+    }
+
+    /// Lexes a string that starts and ends with three quotes. The
+    /// line break after the opening quotes and the one before the
+    /// closing quotes are removed when the lines around them are
+    /// blank, and so is the indentation that all of the lines share.
+    pub fn lex_multiline_string(&mut self) -> (Token, Range) {
+        let start = self.span();
+
+        self.skip_until(self.pos + 3);
+
+        let mut chars = self.input.char_indices();
+        let mut size = None;
+
+        while let Some((index, chr)) = chars.next() {
+            if chr == '\\' {
+                chars.next();
+            } else if self.input[index..].starts_with("\"\"\"") {
+                size = Some(index);
+                break;
+            }
+        }
+
+        let Some(size) = size else {
+            self.skip_until(self.pos + self.input.len());
+            return (
+                Token::Error(Box::new(SyntaxDiagnostic::UnfinishedString(
+                    self.mk_one_column_range(start),
+                ))),
+                self.mk_range(start),
+            );
+        };
+
+        let lines: Vec<&str> = self.input[..size].split('\n').collect();
+        let is_blank = |line: &str| line.chars().all(is_whitespace);
+
+        let first_blank = lines.len() > 1 && is_blank(lines[0]);
+        let last_blank = lines.len() > 1 && is_blank(lines[lines.len() - 1]);
+
+        let indent = lines
+            .iter()
+            .skip(1)
+            .filter(|line| !is_blank(line))
+            .map(|line| line.len() - line.trim_start_matches(is_whitespace).len())
+            .min()
+            .unwrap_or(0);
+
+        let end = self.pos + size;
+
+        let content_end = if last_blank {
+            end - lines[lines.len() - 1].len() - 1
+        } else {
+            end
+        };
+
+        if first_blank {
+            self.skip_until(self.pos + lines[0].len() + 1);
+        }
+
+        let mut string = String::new();
+        let mut error = None;
+        let mut line_start = first_blank;
+
+        while self.pos < content_end {
+            let chr_start = self.span();
+
+            if line_start {
+                line_start = false;
+                let mut skipped = 0;
+                while skipped < indent && self.pos < content_end {
+                    match self.peekable.peek() {
+                        Some(&x) if is_whitespace(x) => {
+                            self.next_char();
+                            skipped += 1;
+                        }
+                        _ => break,
+                    }
+                }
+                continue;
+            }
+
+            match self.next_char() {
+                Some('\r') if self.peekable.peek() == Some(&'\n') => (),
+                Some('\n') => {
+                    string.push('\n');
+                    line_start = true;
+                }
+                Some('\\') => match self.lex_escaped_char(chr_start) {
+                    Ok(x) => string.push(x),
+                    Err(err) => error = Some(err),
+                },
+                Some(x) => string.push(x),
+                None => break,
+            }
+        }
+
+        self.skip_until(end + 3);
+
+        match error {
+            Some(err) => (Token::Error(Box::new(err)), self.mk_range(start)),
+            None => (Token::Str(string), self.mk_range(start)),
+        }
+    }
 }
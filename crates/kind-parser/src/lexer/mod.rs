@@ -20,7 +20,7 @@ pub mod literals;
 pub mod state;
 pub mod tokens;
 
-fn is_whitespace(chr: char) -> bool {
+pub(crate) fn is_whitespace(chr: char) -> bool {
     matches!(chr, ' ' | '\r' | '\t')
 }
 
@@ -36,6 +36,20 @@ fn is_valid_id_start(chr: char) -> bool {
     chr.is_ascii_alphanumeric() || matches!(chr, '_')
 }
 
+/// Only the signed numbers can start with a minus sign, so `-5i60`
+/// is a single token while `-5` is still a subtraction. The minus is
+/// a sign only if it can't be subtracting from what comes before it,
+/// so `x-5i60` is still a subtraction.
+fn is_negative_literal(previous: Option<char>, input: &str) -> bool {
+    let after_expr = previous.map_or(false, |x| x.is_alphanumeric() || "_.)]}\"'".contains(x));
+    let len = input
+        .find(|x: char| !(x.is_ascii_alphanumeric() || x == '_'))
+        .unwrap_or(input.len());
+    !after_expr
+        && input.starts_with(|x: char| x.is_ascii_digit())
+        && input[..len].ends_with("i60")
+}
+
 impl<'a> Lexer<'a> {
     pub fn single_token(&mut self, token: Token, start: usize) -> (Token, Range) {
         self.next_char();
@@ -115,6 +129,9 @@ impl<'a> Lexer<'a> {
                     self.accumulate_while(&is_valid_id);
                     (Token::Hole, self.mk_range(start))
                 }
+                'r' if self.input[1..].trim_start_matches('#').starts_with('"') => {
+                    self.lex_raw_string()
+                }
                 c if is_valid_id_start(*c) => {
                     let str = self.accumulate_while(&is_valid_id);
                     (Lexer::to_keyword(str), self.mk_range(start))
@@ -124,8 +141,24 @@ impl<'a> Lexer<'a> {
                 '[' => self.single_token(Token::LBracket, start),
                 ']' => self.single_token(Token::RBracket, start),
                 '~' => self.single_token(Token::Tilde, start),
-                '{' => self.single_token(Token::LBrace, start),
-                '}' => self.single_token(Token::RBrace, start),
+                '{' => {
+                    if let Some(depth) = self.interpolations.last_mut() {
+                        *depth += 1;
+                    }
+                    self.single_token(Token::LBrace, start)
+                }
+                '}' => match self.interpolations.last_mut() {
+                    Some(0) => {
+                        self.interpolations.pop();
+                        self.next_char();
+                        self.lex_string_part(start, false)
+                    }
+                    Some(depth) => {
+                        *depth -= 1;
+                        self.single_token(Token::RBrace, start)
+                    }
+                    None => self.single_token(Token::RBrace, start),
+                },
                 '#' => {
                     self.next_char();
                     match self.peekable.peek() {
@@ -189,6 +222,13 @@ impl<'a> Lexer<'a> {
                         _ => (Token::At, self.mk_range(start)),
                     }
                 }
+                '-' if is_negative_literal(
+                    self.source[..self.pos].chars().next_back(),
+                    &self.input[1..],
+                ) =>
+                {
+                    self.lex_negative_number()
+                }
                 '-' => {
                     self.next_char();
                     match self.peekable.peek() {
@@ -201,6 +241,7 @@ impl<'a> Lexer<'a> {
                 '&' => self.single_token(Token::Ampersand, start),
                 '|' => self.single_token(Token::Bar, start),
                 '^' => self.single_token(Token::Hat, start),
+                '"' if self.input.starts_with("\"\"\"") => self.lex_multiline_string(),
                 '"' => self.lex_string(),
                 '?' => {
                     self.next_char();
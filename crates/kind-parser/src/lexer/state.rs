@@ -8,6 +8,8 @@ use crate::lexer::tokens::Token;
 
 /// The lexer state.
 pub struct Lexer<'a> {
+    /// The whole source. `input` is the part that is left to lex.
+    pub source: &'a str,
     pub input: &'a str,
     pub peekable: Peekable<Chars<'a>>,
     pub pos: usize,
@@ -15,16 +17,21 @@ pub struct Lexer<'a> {
 
     // Modes
     pub comment_depth: u16,
+    /// The number of open braces inside of each `${` of the
+    /// strings that are being lexed.
+    pub interpolations: Vec<u16>,
 }
 
 impl<'a> Lexer<'a> {
     pub fn new(input: &'a str, peekable: Peekable<Chars<'a>>, ctx: SyntaxCtxIndex) -> Lexer<'a> {
         Lexer {
+            source: input,
             input,
             pos: 0,
             ctx,
             peekable,
             comment_depth: 0,
+            interpolations: Vec::new(),
         }
     }
 
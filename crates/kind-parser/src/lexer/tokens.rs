@@ -50,8 +50,15 @@ pub enum Token {
     // Literals
     Char(char),
     Str(String),
+    /// The text of a string before its first `${`.
+    StrStart(String),
+    /// The text of a string between a `}` and the next `${`.
+    StrMiddle(String),
+    /// The text of a string after its last `}`.
+    StrEnd(String),
     Num60(u64),
     Num120(u128),
+    NumI60(i64),
     Nat(u128),
     Float(u64, u64),
     Hole,
@@ -154,8 +161,12 @@ impl fmt::Display for Token {
             Token::UpperId(main, None) => write!(f, "{}", main),
             Token::Char(c) => write!(f, "'{}'", c),
             Token::Str(s) => write!(f, "\"{}\"", s),
+            Token::StrStart(s) => write!(f, "\"{}${{", s),
+            Token::StrMiddle(s) => write!(f, "}}{}${{", s),
+            Token::StrEnd(s) => write!(f, "}}{}\"", s),
             Token::Num60(n) => write!(f, "{}", n),
             Token::Num120(n) => write!(f, "{}u120", n),
+            Token::NumI60(n) => write!(f, "{}i60", n),
             Token::Nat(n) => write!(f, "{}n", n),
             Token::Float(start, end) => write!(f, "{}.{}", start, end),
             Token::Hole => write!(f, "_"),
@@ -1,4 +1,5 @@
 //! Crate to parse the kind2 grammar.
+pub mod cst;
 mod diagnostic;
 mod expr;
 mod lexer;
@@ -9,15 +10,23 @@ mod top_level;
 
 use std::sync::mpsc::Sender;
 
+use kind_report::cancel::CancellationToken;
 use kind_report::data::Diagnostic;
 use kind_span::SyntaxCtxIndex;
 use kind_tree::concrete::Module;
 use lexer::state::*;
 use state::Parser;
 
-pub fn parse_book(errs: Sender<Box<dyn Diagnostic>>, ctx_id: usize, input: &str) -> (Module, bool) {
+pub use lexer::tokens::Token;
+
+pub fn parse_book(
+    errs: Sender<Box<dyn Diagnostic>>,
+    ctx_id: usize,
+    input: &str,
+    cancel: &CancellationToken,
+) -> (Module, bool) {
     let peekable = input.chars().peekable();
     let lexer = Lexer::new(input, peekable, SyntaxCtxIndex::new(ctx_id));
-    let mut parser = Parser::new(lexer, errs);
+    let mut parser = Parser::new(lexer, errs, cancel.clone());
     (parser.parse_module(), parser.failed)
 }
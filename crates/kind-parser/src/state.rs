@@ -2,6 +2,7 @@
 
 use std::{collections::VecDeque, sync::mpsc::Sender};
 
+use kind_report::cancel::CancellationToken;
 use kind_report::data::Diagnostic;
 use kind_span::Range;
 
@@ -27,10 +28,20 @@ pub struct Parser<'a> {
     /// lexer.
     eaten: u32,
     pub failed: bool,
+    /// Polled before each top level definition.
+    pub cancel: CancellationToken,
+    /// True while parsing the type of a hidden argument like `<x: t>`
+    /// because there `>` closes the argument instead of being
+    /// an infix operator.
+    pub hidden_arg: bool,
 }
 
 impl<'a> Parser<'a> {
-    pub fn new(mut lexer: Lexer<'a>, sender: Sender<Box<dyn Diagnostic>>) -> Parser<'a> {
+    pub fn new(
+        mut lexer: Lexer<'a>,
+        sender: Sender<Box<dyn Diagnostic>>,
+        cancel: CancellationToken,
+    ) -> Parser<'a> {
         let mut queue = VecDeque::with_capacity(3);
         let mut breaks = VecDeque::with_capacity(3);
 
@@ -47,6 +58,8 @@ impl<'a> Parser<'a> {
             dignostic_channel: sender,
             eaten: 0,
             failed: false,
+            cancel,
+            hidden_arg: false,
         }
     }
 
@@ -82,6 +95,11 @@ impl<'a> Parser<'a> {
         self.queue[0].1
     }
 
+    #[inline]
+    pub fn peek_range(&self, lookhead: usize) -> Range {
+        self.queue[lookhead].1
+    }
+
     #[inline]
     pub fn fail<T>(&mut self, expect: Vec<Token>) -> Result<T, SyntaxDiagnostic> {
         Err(SyntaxDiagnostic::UnexpectedToken(
@@ -165,4 +183,35 @@ impl<'a> Parser<'a> {
             Ok(res) => Ok(Some(res)),
         }
     }
+
+    /// Reports a syntax error and skips tokens until `stop` holds outside
+    /// of the delimiters that were opened while skipping. It returns the
+    /// range of the skipped code so the caller can put an error node
+    /// in place of it.
+    pub fn recover(
+        &mut self,
+        err: SyntaxDiagnostic,
+        stop: &dyn Fn(&Parser<'a>) -> bool,
+    ) -> Range {
+        self.send_dignostic(err);
+
+        let mut range = self.range();
+        let mut depth = 0;
+
+        while !self.get().is_eof() {
+            if depth <= 0 && stop(self) {
+                break;
+            }
+
+            match self.get() {
+                Token::LPar | Token::LBracket | Token::LBrace => depth += 1,
+                Token::RPar | Token::RBracket | Token::RBrace => depth -= 1,
+                _ => (),
+            }
+
+            range = range.mix(self.advance().1);
+        }
+
+        range
+    }
 }
@@ -1,12 +1,15 @@
 use fxhash::FxHashMap;
+use kind_span::{Range, SyntaxCtxIndex};
 use kind_tree::concrete::expr::Expr;
 use kind_tree::concrete::pat::{Pat, PatIdent, PatKind};
 
 use kind_tree::concrete::*;
-use kind_tree::symbol::QualifiedIdent;
+use kind_tree::symbol::{Ident, QualifiedIdent};
 use kind_tree::telescope::Telescope;
 
 use crate::diagnostic::SyntaxDiagnostic;
+use crate::expr::is_symbol_token;
+use crate::lexer::state::Lexer;
 use crate::lexer::tokens::Token;
 use crate::state::Parser;
 
@@ -17,6 +20,36 @@ fn is_hidden_arg(token: &Token) -> bool {
     matches!(token, Token::Greater)
 }
 
+fn lex_all(text: &str) -> Vec<Token> {
+    let mut lexer = Lexer::new(text, text.chars().peekable(), SyntaxCtxIndex::new(0));
+    let mut tokens = Vec::new();
+    loop {
+        match lexer.lex_next().0 {
+            Token::Eof => break tokens,
+            token => tokens.push(token),
+        }
+    }
+}
+
+/// Splits a notation pattern like "xs ++ ys" into the names of both
+/// sides and the symbol between them. The symbol needs two or more
+/// operator tokens so it does not clash with the builtin operators.
+fn split_notation(pattern: &str) -> Option<(String, String, String)> {
+    let is_name = |text: &str| matches!(lex_all(text).as_slice(), [Token::LowerId(x)] if x == text);
+
+    match pattern.split_whitespace().collect::<Vec<_>>().as_slice() {
+        [fst, symbol, snd] if is_name(fst) && is_name(snd) && fst != snd => {
+            let tokens = lex_all(symbol);
+            if tokens.len() >= 2 && tokens.iter().all(is_symbol_token) {
+                Some((fst.to_string(), symbol.to_string(), snd.to_string()))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
 impl<'a> Parser<'a> {
     fn is_top_level_entry_continuation(&self) -> bool {
         self.peek(1).same_variant(&Token::Colon)         // ':'
@@ -34,10 +67,29 @@ impl<'a> Parser<'a> {
     fn is_safe_level_start(&self) -> bool {
         self.check_actual_id("type")
             || self.check_actual_id("record")
+            || self.check_actual_id("notation")
             || self.get().same_variant(&Token::Hash)
             || self.get().is_doc()
     }
 
+    fn is_rule_of(&self, name: &str) -> bool {
+        match self.get() {
+            Token::UpperId(name_id, ext) => {
+                QualifiedIdent::new_static(name_id.as_str(), ext.clone(), self.range()).to_string()
+                    == name
+            }
+            _ => false,
+        }
+    }
+
+    /// Start of another rule of the definition `name` or of the next top
+    /// level definition. It's where the parser restarts after an error
+    /// inside of a definition.
+    fn is_definition_boundary(&self, name: &str) -> bool {
+        self.is_linebreak()
+            && (self.is_safe_level_start() || self.is_top_level_entry() || self.is_rule_of(name))
+    }
+
     fn complement_binding_op(&self) -> Option<Token> {
         match self.get() {
             Token::LPar => Some(Token::RPar),
@@ -62,7 +114,10 @@ impl<'a> Parser<'a> {
         let name = self.parse_id()?;
 
         let typ = if self.check_and_eat(Token::Colon) {
-            Some(self.parse_expr(false)?)
+            self.hidden_arg = hidden;
+            let typ = self.parse_expr(false);
+            self.hidden_arg = false;
+            Some(typ?)
         } else {
             None
         };
@@ -95,21 +150,72 @@ impl<'a> Parser<'a> {
             return self.fail(vec![]);
         }
 
+        match self.parse_rule_body(ident, start) {
+            Ok(rule) => Ok(rule),
+            Err(err) => {
+                let range = self.recover(err, &|parser| parser.is_definition_boundary(&name));
+                Ok(Box::new(Rule {
+                    name: ident,
+                    pats: Vec::new(),
+                    body: Expr::error(range),
+                    wheres: Vec::new(),
+                    range: start.mix(range),
+                }))
+            }
+        }
+    }
+
+    fn parse_rule_body(
+        &mut self,
+        name: QualifiedIdent,
+        start: Range,
+    ) -> Result<Box<Rule>, SyntaxDiagnostic> {
         let mut pats = Vec::new();
         while !self.get().same_variant(&Token::Eq) && !self.get().same_variant(&Token::Eof) {
             pats.push(self.parse_pat()?);
         }
         self.eat_variant(Token::Eq)?;
         let body = self.parse_expr(false)?;
-        let end = start.mix(body.range);
+        let mut end = start.mix(body.range);
+
+        let wheres = if self.check_actual_id("where") {
+            let (wheres, range) = self.parse_wheres()?;
+            end = end.mix(range);
+            wheres
+        } else {
+            Vec::new()
+        };
+
         Ok(Box::new(Rule {
-            name: ident,
+            name,
             pats,
             body,
+            wheres,
             range: end,
         }))
     }
 
+    /// Parses the block of local definitions that comes after the
+    /// body of a rule e.g. `where { Go (n: U60) : U60 ... }`.
+    fn parse_wheres(&mut self) -> Result<(Vec<Entry>, Range), SyntaxDiagnostic> {
+        self.eat_id("where")?;
+
+        let start = self.range();
+        self.eat_variant(Token::LBrace)?;
+
+        let mut wheres = Vec::new();
+        while !self.get().same_variant(&Token::RBrace) && !self.get().is_eof() {
+            let docs = self.parse_docs()?;
+            let attrs = self.parse_attrs()?;
+            wheres.push(self.parse_entry(docs, attrs)?);
+        }
+
+        let end = self.range();
+        self.eat_closing_keyword(Token::RBrace, start)?;
+
+        Ok((wheres, start.mix(end)))
+    }
+
     fn parse_arguments(&mut self) -> Result<Vec<Argument>, SyntaxDiagnostic> {
         let mut args = Vec::new();
         while let Some(res) = self.try_single(&|fun| fun.parse_argument())? {
@@ -148,97 +254,98 @@ impl<'a> Parser<'a> {
         }
 
         let ident = self.parse_upper_id()?;
+        let name = ident.to_string();
 
-        let args = self.parse_arguments()?;
-
-        if !self.get().same_variant(&Token::Colon) && !self.get().same_variant(&Token::LBrace) {
-            return self.fail(vec![])?;
-        }
-
-        let typ = if self.check_and_eat(Token::Colon) {
-            self.parse_expr(false)?
-        } else {
-            Box::new(Expr {
-                data: ExprKind::Hole,
-                range: start,
-            })
+        let (args, typ) = match self.parse_signature(start) {
+            Ok(res) => res,
+            Err(err) => {
+                let range = self.recover(err, &|parser| parser.is_definition_boundary(&name));
+                (Vec::new(), Expr::error(range))
+            }
         };
 
+        let mut rules = Vec::new();
+
         if self.check_actual(Token::LBrace) {
             let start = self.range();
 
             self.eat_variant(Token::LBrace)?;
 
-            let body = self.parse_expr(true)?;
+            let body = match self.parse_expr(true) {
+                Ok(body) => body,
+                Err(err) => {
+                    let range = self.recover(err, &|parser| parser.get().same_variant(&Token::RBrace));
+                    Expr::error(range)
+                }
+            };
 
             let end = self.range();
             self.eat_closing_keyword(Token::RBrace, start)?;
 
-            let mut rules = vec![Box::new(Rule {
-                name: ident.clone(),
+            rules.push(Box::new(Rule {
+                name: ident,
                 pats: args
                     .iter()
                     .map(|x| {
                         Box::new(Pat {
                             range: x.range,
-                            data: PatKind::Var(PatIdent(x.name.clone())),
+                            data: PatKind::Var(PatIdent(x.name)),
                         })
                     })
                     .collect(),
                 body,
+                wheres: Vec::new(),
                 range: end,
-            })];
-            loop {
-                let res = self.try_single(&|parser| parser.parse_rule(ident.to_string()))?;
-                match res {
-                    Some(res) => rules.push(res),
-                    None => break,
-                }
-            }
-            let end = rules.last().as_ref().map(|x| x.range).unwrap_or(typ.range);
+            }));
+        }
 
-            // Better error message when you have change the name of the function
-            if self.get().is_upper_id() && !self.is_top_level_entry_continuation() {
-                return Err(SyntaxDiagnostic::NotAClauseOfDef(ident.range, self.range()));
+        loop {
+            let res = self.try_single(&|parser| parser.parse_rule(name.clone()))?;
+            match res {
+                Some(res) => rules.push(res),
+                None => break,
             }
+        }
 
-            Ok(Entry {
-                name: ident,
-                docs,
-                args: Telescope::new(args),
-                typ,
-                rules,
-                attrs,
-                range: start.mix(end),
-                generated_by: None,
-            })
-        } else {
-            let mut rules = Vec::new();
-            loop {
-                let res = self.try_single(&|parser| parser.parse_rule(ident.to_string()))?;
-                match res {
-                    Some(res) => rules.push(res),
-                    None => break,
-                }
-            }
-            let end = rules.last().as_ref().map(|x| x.range).unwrap_or(typ.range);
+        let end = rules.last().as_ref().map(|x| x.range).unwrap_or(typ.range);
 
-            // Better error message when you have change the name of the function
-            if self.get().is_upper_id() && !self.is_top_level_entry_continuation() {
-                return Err(SyntaxDiagnostic::NotAClauseOfDef(ident.range, self.range()));
-            }
+        // Better error message when you have change the name of the function
+        if self.get().is_upper_id() && !self.is_top_level_entry_continuation() {
+            return Err(SyntaxDiagnostic::NotAClauseOfDef(ident.range, self.range()));
+        }
 
-            Ok(Entry {
-                name: ident,
-                docs,
-                args: Telescope::new(args),
-                typ,
-                rules,
-                attrs,
-                range: start.mix(end),
-                generated_by: None,
-            })
+        Ok(Entry {
+            name: ident,
+            docs,
+            args: Telescope::new(args),
+            typ,
+            rules,
+            attrs,
+            range: start.mix(end),
+            generated_by: None,
+        })
+    }
+
+    fn parse_signature(
+        &mut self,
+        start: Range,
+    ) -> Result<(Vec<Argument>, Box<Expr>), SyntaxDiagnostic> {
+        let args = self.parse_arguments()?;
+
+        if !self.get().same_variant(&Token::Colon) && !self.get().same_variant(&Token::LBrace) {
+            return self.fail(vec![]);
         }
+
+        let typ = if self.check_and_eat(Token::Colon) {
+            self.parse_expr(false)?
+        } else {
+            Box::new(Expr {
+                data: ExprKind::Hole,
+                range: start,
+            })
+        };
+
+        Ok((args, typ))
     }
 
     fn parse_top_level(&mut self) -> Result<TopLevel, SyntaxDiagnostic> {
@@ -249,6 +356,8 @@ impl<'a> Parser<'a> {
             Ok(TopLevel::SumType(self.parse_sum_type_def(docs, attrs)?))
         } else if self.check_actual_id("record") {
             Ok(TopLevel::RecordType(self.parse_record_def(docs, attrs)?))
+        } else if self.check_actual_id("notation") {
+            Ok(TopLevel::Notation(self.parse_notation(docs, attrs)?))
         } else if self.is_top_level_entry_continuation() {
             Ok(TopLevel::Entry(self.parse_entry(docs, attrs)?))
         } else if self.check_actual_id("use") {
@@ -258,6 +367,37 @@ impl<'a> Parser<'a> {
         }
     }
 
+    fn parse_notation(
+        &mut self,
+        docs: Vec<String>,
+        attrs: Vec<Attribute>,
+    ) -> Result<Notation, SyntaxDiagnostic> {
+        let start = self.range();
+        self.eat_id("notation")?;
+
+        let (pattern, pattern_range) = match self.get().clone() {
+            Token::Str(pattern) => (pattern, self.advance().1),
+            _ => return self.fail(vec![Token::Str("".to_string())]),
+        };
+
+        let (fst, symbol, snd) = split_notation(&pattern)
+            .ok_or(SyntaxDiagnostic::InvalidNotation(pattern_range))?;
+
+        self.eat_variant(Token::Eq)?;
+        let body = self.parse_expr(false)?;
+        let range = start.mix(body.range);
+
+        Ok(Notation {
+            symbol,
+            docs,
+            attrs,
+            fst: Ident::new(fst, pattern_range),
+            snd: Ident::new(snd, pattern_range),
+            body,
+            range,
+        })
+    }
+
     fn parse_use(&mut self) -> Result<(String, String), SyntaxDiagnostic> {
         self.eat_id("use")?;
         let origin = self.parse_upper_id()?;
@@ -290,6 +430,11 @@ impl<'a> Parser<'a> {
         }
 
         while !self.get().same_variant(&Token::Eof) {
+            if self.cancel.is_cancelled() {
+                self.failed = true;
+                return Module { entries, uses };
+            }
+
             match self.parse_top_level() {
                 Ok(entry) => entries.push(entry),
                 Err(err) => {
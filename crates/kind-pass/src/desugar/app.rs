@@ -2,7 +2,7 @@ use fxhash::FxHashMap;
 use kind_span::{Locatable, Range};
 use kind_tree::concrete::expr::Expr;
 
-use kind_tree::concrete::{Binding, ExprKind};
+use kind_tree::concrete::{Binding, ExprKind, TopLevel};
 use kind_tree::desugared;
 use kind_tree::symbol::QualifiedIdent;
 
@@ -58,7 +58,7 @@ impl<'a> DesugarState<'a> {
         spine: Vec<Box<desugared::Expr>>,
         create_var_on_hidden: bool,
     ) -> Box<desugared::Expr> {
-        match self.make_desugared_spine(range, head.clone(), spine, create_var_on_hidden) {
+        match self.make_desugared_spine(range, head, spine, create_var_on_hidden) {
             Some(spine) => desugared::Expr::ctr(range, head, spine),
             None => desugared::Expr::err(range),
         }
@@ -71,7 +71,7 @@ impl<'a> DesugarState<'a> {
         spine: Vec<Box<desugared::Expr>>,
         create_var_on_hidden: bool,
     ) -> Box<desugared::Expr> {
-        match self.make_desugared_spine(range, head.clone(), spine, create_var_on_hidden) {
+        match self.make_desugared_spine(range, head, spine, create_var_on_hidden) {
             Some(spine) => desugared::Expr::fun(range, head, spine),
             None => desugared::Expr::err(range),
         }
@@ -80,6 +80,15 @@ impl<'a> DesugarState<'a> {
     pub(crate) fn desugar_app(&mut self, range: Range, head: &Expr) -> Box<desugared::Expr> {
         match &head.data {
             ExprKind::Constr { name, args } => {
+                // The arguments cannot be checked against a signature with
+                // a syntax error, so the call becomes an error too.
+                if let Some(TopLevel::Entry(entry)) = self.old_book.entries.get(&name.to_string()) {
+                    if let ExprKind::Error = entry.typ.data {
+                        self.error_nodes += 1;
+                        return desugared::Expr::err(range);
+                    }
+                }
+
                 let entry = self.old_book.get_count_garanteed(name.to_string().as_str());
 
                 let mut positions = FxHashMap::default();
@@ -165,12 +174,12 @@ impl<'a> DesugarState<'a> {
                 Box::new(desugared::Expr {
                     data: if entry.is_ctr {
                         desugared::ExprKind::Ctr {
-                            name: name.clone(),
+                            name: *name,
                             args: new_spine,
                         }
                     } else {
                         desugared::ExprKind::Fun {
-                            name: name.clone(),
+                            name: *name,
                             args: new_spine,
                         }
                     },
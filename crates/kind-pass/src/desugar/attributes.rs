@@ -1,11 +1,14 @@
 use kind_span::Locatable;
 use kind_tree::concrete::{self, Attribute, AttributeStyle};
-use kind_tree::Attributes;
+use kind_tree::{Attributes, Operator};
 
 use crate::diagnostic::PassDiagnostic;
 
 use super::DesugarState;
 
+/// Notations can bind tighter than all of the builtin operators.
+const MAX_PRECEDENCE: u64 = 9;
+
 impl<'a> DesugarState<'a> {
     fn args_should_be_empty(&mut self, attr: &Attribute) {
         if !attr.args.is_empty() {
@@ -31,6 +34,32 @@ impl<'a> DesugarState<'a> {
         };
     }
 
+    /// Reads the `#prec = n` attribute of a notation. The default
+    /// precedence is the same as the one of `+`.
+    pub fn desugar_notation_attributes(&mut self, attrs: &[concrete::Attribute]) -> u8 {
+        let mut precedence = Operator::Add.precedence();
+
+        for attr in attrs {
+            match attr.name.to_str() {
+                "prec" => {
+                    self.args_should_be_empty(attr);
+                    match &attr.value {
+                        Some(AttributeStyle::Number(_, numb)) if *numb <= MAX_PRECEDENCE => {
+                            precedence = *numb as u8;
+                        }
+                        Some(other) => {
+                            self.send_err(PassDiagnostic::InvalidAttributeArgument(other.locate()))
+                        }
+                        None => self.attr_expects_a_value(attr),
+                    }
+                }
+                _ => self.send_err(PassDiagnostic::AttributeDoesNotExists(attr.range)),
+            }
+        }
+
+        precedence
+    }
+
     pub fn desugar_attributes(&mut self, attrs: &[concrete::Attribute]) -> Attributes {
         let mut attributes: Attributes = Default::default();
 
@@ -39,6 +68,10 @@ impl<'a> DesugarState<'a> {
                 // The derive attribute is treated by the expand
                 // pass so here we just ignore it.
                 "derive" => (),
+                "private" => {
+                    self.args_should_be_empty(attr);
+                    self.attr_without_value(attr);
+                }
                 "inline" => {
                     self.args_should_be_empty(attr);
                     self.attr_without_value(attr);
@@ -73,7 +106,7 @@ impl<'a> DesugarState<'a> {
                     self.args_should_be_empty(attr);
                     match &attr.value {
                         Some(AttributeStyle::Ident(_, ident)) => {
-                            attributes.kdl_name = Some(ident.clone());
+                            attributes.kdl_name = Some(*ident);
                         }
                         Some(_) => self.attr_invalid_argument(attr),
                         None => self.attr_expects_a_value(attr),
@@ -83,7 +116,7 @@ impl<'a> DesugarState<'a> {
                     self.args_should_be_empty(attr);
                     match &attr.value {
                         Some(AttributeStyle::Ident(_, ident)) => {
-                            attributes.kdl_state = Some(ident.clone());
+                            attributes.kdl_state = Some(*ident);
                         }
                         Some(_) => self.attr_invalid_argument(attr),
                         None => self.attr_expects_a_value(attr),
@@ -28,15 +28,17 @@ impl<'a> DesugarState<'a> {
 
         for arg in cases {
             let (name, alias) = match arg {
-                CaseBinding::Field(name) => (name.clone(), name.clone()),
-                CaseBinding::Renamed(name, alias) => (name.clone(), alias.clone()),
+                CaseBinding::Field(name) => (*name, *name),
+                CaseBinding::Renamed(name, alias) => (*name, *alias),
+                // Cases with patterns are ordered by position.
+                CaseBinding::Pattern(_) => continue,
             };
 
             if let Some((idx, _)) = names.get(name.to_str()) {
                 if let (_, Some((range, _))) = ordered_fields[*idx] {
                     self.send_err(PassDiagnostic::DuplicatedNamed(range, name.range));
                 } else {
-                    ordered_fields[*idx] = (name.to_string(), Some((name.locate(), alias.clone())))
+                    ordered_fields[*idx] = (name.to_string(), Some((name.locate(), alias)))
                 }
             } else {
                 self.send_err(PassDiagnostic::CannotFindField(
@@ -75,7 +77,6 @@ impl<'a> DesugarState<'a> {
 
                 let rec = meta
                     .is_record_cons_of
-                    .clone()
                     .and_then(|name| self.old_book.entries.get(&name.to_string()));
 
                 let record = if let Some(TopLevel::RecordType(record)) = rec {
@@ -147,7 +148,7 @@ impl<'a> DesugarState<'a> {
             &|this, name| {
                 desugared::Expr::let_(
                     range,
-                    name.clone(),
+                    *name,
                     res_val.clone(),
                     this.desugar_expr(next),
                 )
@@ -177,6 +178,16 @@ impl<'a> DesugarState<'a> {
             return desugared::Expr::err(matcher.typ.range);
         };
 
+        let nested = !matcher.others.is_empty()
+            || matcher.cases.iter().any(|case| {
+                case.constructor.to_str() == "_"
+                    || case.bindings.iter().any(|x| matches!(x, CaseBinding::Pattern(_)))
+            });
+
+        if nested {
+            return self.desugar_nested_match(range, matcher, &constructors);
+        }
+
         let mut cases_args = Vec::new();
         let mut positions = FxHashMap::default();
 
@@ -235,7 +246,7 @@ impl<'a> DesugarState<'a> {
         let names = matcher
             .with_vars
             .iter()
-            .map(|x| x.0.clone())
+            .map(|x| x.0)
             .collect::<Vec<_>>();
 
         for (i, case_arg) in cases_args.iter().enumerate() {
@@ -276,7 +287,7 @@ impl<'a> DesugarState<'a> {
             .iter()
             .map(|x| {
                 (
-                    x.0.clone(),
+                    x.0,
                     x.1.clone().map(|x| self.desugar_expr(&x))
                         .unwrap_or_else(|| self.gen_hole_expr(range)),
                 )
@@ -286,8 +297,8 @@ impl<'a> DesugarState<'a> {
         let motive = desugared::Expr::unfold_all(&irrelev, &binds, motive);
 
         let prefix = [
-            desugared_value.unwrap_or_else(|| desugared::Expr::var(matcher.scrutinee.clone())),
-            desugared::Expr::lambda(motive.range, matcher.scrutinee.clone(), motive, false),
+            desugared_value.unwrap_or_else(|| desugared::Expr::var(matcher.scrutinee)),
+            desugared::Expr::lambda(motive.range, matcher.scrutinee, motive, false),
         ];
 
         let call = self.mk_desugared_fun(
@@ -305,7 +316,7 @@ impl<'a> DesugarState<'a> {
                     .with_vars
                     .iter()
                     .map(|x| desugared::AppBinding {
-                        data: desugared::Expr::var(x.0.clone()),
+                        data: desugared::Expr::var(x.0),
                         erased: false,
                     })
                     .collect(),
@@ -32,9 +32,10 @@ impl<'a> DesugarState<'a> {
                 desugared::Expr::str(range, string.clone())
             }
             Literal::Type => desugared::Expr::typ(range),
-            Literal::Help(name) => desugared::Expr::hlp(range, name.clone()),
+            Literal::Help(name) => desugared::Expr::hlp(range, *name),
             Literal::NumTypeU60 => desugared::Expr::type_u60(range),
             Literal::NumTypeF60 => desugared::Expr::type_f60(range),
+            Literal::NumTypeI60 => desugared::Expr::type_i60(range),
             Literal::NumU60(num) => desugared::Expr::num_u60(range, *num),
             Literal::Nat(num) => {
                 let list_ident = QualifiedIdent::new_static("Nat", None, range);
@@ -44,7 +45,7 @@ impl<'a> DesugarState<'a> {
                 let mut res = self.mk_desugared_ctr(range, nil_ident, Vec::new(), false);
 
                 for _ in 0..*num {
-                    res = self.mk_desugared_ctr(range, cons_ident.clone(), vec![res], false)
+                    res = self.mk_desugared_ctr(range, cons_ident, vec![res], false)
                 }
 
                 res
@@ -56,6 +57,7 @@ impl<'a> DesugarState<'a> {
                 desugared::Expr::num_u120(range, *num)
             }
             Literal::NumF60(num) => desugared::Expr::num_f60(range, *num),
+            Literal::NumI60(num) => desugared::Expr::num_i60(range, *num),
             Literal::Char(cht) => desugared::Expr::num_u60(range, *cht as u64),
         }
     }
@@ -67,7 +69,7 @@ impl<'a> DesugarState<'a> {
     ) -> Box<desugared::Expr> {
         desugared::Expr::sub(
             range,
-            sub.name.clone(),
+            sub.name,
             sub.indx,
             sub.redx,
             self.desugar_expr(&sub.expr),
@@ -106,8 +108,8 @@ impl<'a> DesugarState<'a> {
                         range,
                         name,
                         vec![
-                            Expr::var(value_ident.clone()),
-                            Expr::lambda(range.clone(), value_ident.clone(), acc, false),
+                            Expr::var(value_ident),
+                            Expr::lambda(range.clone(), value_ident, acc, false),
                         ],
                         false,
                     )
@@ -132,11 +134,11 @@ impl<'a> DesugarState<'a> {
 
                     Expr::lambda(
                         name.range.clone(),
-                        value_ident.clone(),
+                        value_ident,
                         self.mk_desugared_ctr(
                             range,
                             name,
-                            vec![Expr::var(value_ident.clone()), acc],
+                            vec![Expr::var(value_ident), acc],
                             false,
                         ),
                         false,
@@ -191,7 +193,7 @@ impl<'a> DesugarState<'a> {
         let bind = |this: &mut Self, range: Range, name: Ident, expr: Exp, next: Exp| -> Exp {
             this.mk_desugared_fun(
                 range,
-                bind_ident.clone(),
+                *bind_ident,
                 vec![expr, desugared::Expr::lambda(range, name, next, false)],
                 false,
             )
@@ -212,7 +214,7 @@ impl<'a> DesugarState<'a> {
                 let res_destruct = self.desugar_destruct(
                     next.range,
                     &concrete::Destruct::Destruct(*a, b.to_owned(), c.to_owned(), *d),
-                    desugared::Expr::var(name.clone()),
+                    desugared::Expr::var(name),
                     &|this| this.desugar_sttm(bind_ident, pure_ident, next),
                     &|_, _| unreachable!(),
                 );
@@ -222,7 +224,7 @@ impl<'a> DesugarState<'a> {
             concrete::SttmKind::Ask(concrete::Destruct::Ident(name), val, next) => {
                 let res_expr = self.desugar_expr(val);
                 let res_sttm = self.desugar_sttm(bind_ident, pure_ident, next);
-                bind(self, sttm.range, name.clone(), res_expr, res_sttm)
+                bind(self, sttm.range, *name, res_expr, res_sttm)
             }
             concrete::SttmKind::Let(destruct, val, next) => {
                 let res_val = self.desugar_expr(&val.clone());
@@ -234,7 +236,7 @@ impl<'a> DesugarState<'a> {
                     &|this, ident| {
                         desugared::Expr::let_(
                             destruct.locate(),
-                            ident.clone(),
+                            *ident,
                             res_val.clone(),
                             this.desugar_sttm(bind_ident, pure_ident, next),
                         )
@@ -243,7 +245,7 @@ impl<'a> DesugarState<'a> {
             }
             concrete::SttmKind::Return(expr) => {
                 let res_expr = self.desugar_expr(expr);
-                self.mk_desugared_fun(expr.locate(), pure_ident.clone(), vec![res_expr], false)
+                self.mk_desugared_fun(expr.locate(), *pure_ident, vec![res_expr], false)
             }
             concrete::SttmKind::RetExpr(expr) => self.desugar_expr(expr),
         }
@@ -286,7 +288,7 @@ impl<'a> DesugarState<'a> {
         }
 
         let name = match name {
-            Some(ident) => ident.clone(),
+            Some(ident) => *ident,
             None => Ident::generate("_var"),
         };
 
@@ -320,11 +322,54 @@ impl<'a> DesugarState<'a> {
             self.mk_desugared_ctr(range, nil_ident, Vec::new(), false),
             |res, elem| {
                 let spine = vec![self.desugar_expr(elem), res];
-                self.mk_desugared_ctr(range, cons_ident.clone(), spine, false)
+                self.mk_desugared_ctr(range, cons_ident, spine, false)
             },
         )
     }
 
+    /// Joins the pieces of a string with `String.concat`. The
+    /// expressions inside of it are annotated with `String` and
+    /// their ranges are kept so the type checker can point to a
+    /// `show` function when they are not strings.
+    pub(crate) fn desugar_interpolation(
+        &mut self,
+        range: Range,
+        parts: &[expr::StrPart],
+    ) -> Box<desugared::Expr> {
+        let string = QualifiedIdent::new_static("String", None, range);
+        let concat = string.add_segment("concat");
+
+        if !self.check_implementation("String.cons", range, Sugar::String)
+            || !self.check_implementation("String.nil", range, Sugar::String)
+            || !self.check_implementation(concat.to_str(), range, Sugar::Interpolation)
+        {
+            return desugared::Expr::err(range);
+        }
+
+        let typ = expr::Expr::cons(string, Vec::new(), range);
+
+        let mut pieces = Vec::new();
+
+        for part in parts {
+            match part {
+                expr::StrPart::Text(text) if text.is_empty() => (),
+                expr::StrPart::Text(text) => pieces.push(desugared::Expr::str(range, text.clone())),
+                expr::StrPart::Expr(expr) => {
+                    self.new_book.interpolations.insert(expr.range);
+                    let val = self.desugar_expr(expr);
+                    let typ = self.desugar_expr(&typ);
+                    pieces.push(desugared::Expr::ann(expr.range, val, typ));
+                }
+            }
+        }
+
+        let last = pieces.pop().unwrap();
+
+        pieces.into_iter().rfold(last, |res, piece| {
+            self.mk_desugared_fun(range, concat, vec![piece, res], false)
+        })
+    }
+
     pub(crate) fn desugar_if(
         &mut self,
         range: Range,
@@ -392,8 +437,8 @@ impl<'a> DesugarState<'a> {
             .unwrap_or_else(|| self.gen_hole_expr(range));
 
         let spine = vec![
-            desugared::Expr::var(var_name.clone()),
-            desugared::Expr::lambda(range, var_name.clone(), motive, false),
+            desugared::Expr::var(*var_name),
+            desugared::Expr::lambda(range, *var_name, motive, false),
             desugared::Expr::unfold_lambda(&irrelev, &field_names, self.desugar_expr(next)),
         ];
 
@@ -428,7 +473,7 @@ impl<'a> DesugarState<'a> {
                 erased,
             } => desugared::Expr::all(
                 expr.range,
-                param.clone().unwrap_or_else(|| self.gen_name(expr.range)),
+                param.unwrap_or_else(|| self.gen_name(expr.range)),
                 self.desugar_expr(typ),
                 self.desugar_expr(body),
                 *erased,
@@ -439,13 +484,14 @@ impl<'a> DesugarState<'a> {
                 self.desugar_expr(fst),
                 self.desugar_expr(snd),
             ),
+            Infix { fst, rest } => self.desugar_infix(expr.range, fst, rest),
             Lambda {
                 param,
                 typ: None,
                 body,
                 erased,
             } => {
-                desugared::Expr::lambda(expr.range, param.clone(), self.desugar_expr(body), *erased)
+                desugared::Expr::lambda(expr.range, *param, self.desugar_expr(body), *erased)
             }
             Lambda {
                 param,
@@ -456,7 +502,7 @@ impl<'a> DesugarState<'a> {
                 expr.range,
                 desugared::Expr::lambda(
                     expr.range,
-                    param.clone(),
+                    *param,
                     self.desugar_expr(body),
                     *erased,
                 ),
@@ -471,13 +517,18 @@ impl<'a> DesugarState<'a> {
             Ann { val, typ } => {
                 desugared::Expr::ann(expr.range, self.desugar_expr(val), self.desugar_expr(typ))
             }
-            Var { name } => desugared::Expr::var(name.clone()),
+            Var { name } => desugared::Expr::var(*name),
             Hole => desugared::Expr::hole(expr.range, self.gen_hole()),
+            Error => {
+                self.error_nodes += 1;
+                desugared::Expr::err(expr.range)
+            }
             Lit { lit } => self.desugar_literal(expr.range, lit),
             Let { name, val, next } => self.desugar_let(expr.range, name, val, next),
             Do { typ, sttm } => self.desugar_do(expr.range, typ, sttm),
             Sigma { param, fst, snd } => self.desugar_sigma(expr.range, param, fst, snd),
             List { args } => self.desugar_list(expr.range, args),
+            Interpolation { parts } => self.desugar_interpolation(expr.range, parts),
             If { cond, then_, else_ } => self.desugar_if(expr.range, cond, then_, else_),
             Pair { fst, snd } => self.desugar_pair(expr.range, fst, snd),
             Match(matcher) => self.desugar_match(expr.range, matcher),
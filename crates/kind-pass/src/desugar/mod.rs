@@ -8,6 +8,8 @@
 
 use std::sync::mpsc::Sender;
 
+use fxhash::FxHashSet;
+use kind_report::cancel::{Cancelled, CancellationToken};
 use kind_report::data::Diagnostic;
 use kind_span::Range;
 use kind_tree::{
@@ -22,8 +24,11 @@ pub mod app;
 pub mod attributes;
 pub mod destruct;
 pub mod expr;
+pub mod notation;
+pub mod pattern;
 pub mod top_level;
 pub mod record_field;
+pub mod signed;
 
 pub struct DesugarState<'a> {
     pub errors: Sender<Box<dyn Diagnostic>>,
@@ -31,11 +36,20 @@ pub struct DesugarState<'a> {
     pub new_book: desugared::Book,
     pub name_count: u64,
     pub failed: bool,
+    pub cancel: CancellationToken,
+    /// Number of error nodes left by the parser that were found.
+    pub error_nodes: usize,
+    /// The notations whose bodies are being desugared. It's used to
+    /// find the notations that use themselves.
+    pub expanding: Vec<String>,
+    /// The notations that use themselves. They were already reported.
+    pub cyclic_notations: FxHashSet<String>,
 }
 
 pub fn desugar_book(
     errors: Sender<Box<dyn Diagnostic>>,
     book: &concrete::Book,
+    cancel: &CancellationToken,
 ) -> anyhow::Result<desugared::Book> {
     let mut state = DesugarState {
         errors,
@@ -43,13 +57,20 @@ pub fn desugar_book(
         new_book: Default::default(),
         name_count: 0,
         failed: false,
+        cancel: cancel.clone(),
+        error_nodes: 0,
+        expanding: Vec::new(),
+        cyclic_notations: Default::default(),
     };
 
     state.desugar_book(book);
 
-    if state.failed {
+    if cancel.is_cancelled() {
+        Err(Cancelled.into())
+    } else if state.failed {
         Err(GenericPassError.into())
     } else {
+        signed::mark_signed_operations(&mut state.new_book);
         Ok(state.new_book)
     }
 }
@@ -75,7 +96,15 @@ impl<'a> DesugarState<'a> {
     }
 
     pub fn desugar_book(&mut self, book: &concrete::Book) {
+        // The notations come first because the entries need
+        // their precedences.
+        for notation in book.notations.values() {
+            self.desugar_notation(notation)
+        }
         for top_level in book.entries.values() {
+            if self.cancel.is_cancelled() {
+                return;
+            }
             self.desugar_top_level(top_level)
         }
     }
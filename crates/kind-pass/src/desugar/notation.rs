@@ -0,0 +1,154 @@
+use fxhash::{FxHashMap, FxHashSet};
+use kind_span::Range;
+use kind_tree::concrete::expr::{Expr, InfixOp};
+use kind_tree::concrete::visitor::Visitor;
+use kind_tree::concrete::{self};
+use kind_tree::symbol::Ident;
+use kind_tree::{desugared, solve_infix, Associativity};
+
+use crate::diagnostic::PassDiagnostic;
+use crate::subst::{collect_names, rename_binders, subst_on_expr};
+
+use super::DesugarState;
+
+/// Moves all of the ranges of an expression to the same place. It's
+/// used on the body of a notation so the errors inside of it point
+/// to the place that used the notation.
+struct Relocate(Range);
+
+impl Visitor for Relocate {
+    fn visit_range(&mut self, range: &mut Range) {
+        *range = self.0;
+    }
+}
+
+impl<'a> DesugarState<'a> {
+    pub fn desugar_notation(&mut self, notation: &concrete::Notation) {
+        let precedence = self.desugar_notation_attributes(&notation.attrs);
+
+        self.expanding.push(notation.symbol.clone());
+        let body = self.desugar_expr(&notation.body);
+        self.expanding.pop();
+
+        self.new_book.notations.insert(
+            notation.symbol.clone(),
+            desugared::Notation {
+                symbol: notation.symbol.clone(),
+                fst: notation.fst,
+                snd: notation.snd,
+                precedence,
+                body,
+            },
+        );
+    }
+
+    /// Replaces a notation by its body. The body is desugared again
+    /// each time so each use gets its own holes. The binders of the
+    /// body are renamed to names that don't appear in the operands
+    /// or in the body, so the operands can't be captured by them.
+    fn expand_notation(
+        &mut self,
+        symbol: &str,
+        range: Range,
+        fst: Box<desugared::Expr>,
+        snd: Box<desugared::Expr>,
+    ) -> Box<desugared::Expr> {
+        let notation = self.old_book.notations.get(symbol).unwrap();
+
+        let mut body = notation.body.clone();
+        Relocate(range).visit_expr(&mut body);
+
+        self.expanding.push(symbol.to_string());
+        let mut res = self.desugar_expr(&body);
+        self.expanding.pop();
+
+        let mut used = FxHashSet::default();
+        collect_names(&res, &mut used);
+        collect_names(&fst, &mut used);
+        collect_names(&snd, &mut used);
+
+        rename_binders(&mut res, &mut |ident: Ident| loop {
+            let name = self.gen_name(ident.range);
+            if used.insert(name.to_string()) {
+                break name;
+            }
+        });
+
+        let substs = FxHashMap::from_iter([
+            (notation.fst.to_string(), fst),
+            (notation.snd.to_string(), snd),
+        ]);
+
+        subst_on_expr(&mut res, substs);
+        res
+    }
+
+    pub(crate) fn desugar_infix(
+        &mut self,
+        range: Range,
+        fst: &Expr,
+        rest: &[(InfixOp, Range, Box<Expr>)],
+    ) -> Box<desugared::Expr> {
+        let mut precedences = FxHashMap::default();
+        let mut failed = false;
+
+        for (op, op_range, _) in rest {
+            if let InfixOp::Notation(symbol) = op {
+                if let Some(pos) = self.expanding.iter().position(|x| x == symbol) {
+                    let mut cycle = self.expanding[pos..].to_vec();
+                    cycle.push(symbol.clone());
+                    self.cyclic_notations.extend(cycle.iter().cloned());
+                    self.send_err(PassDiagnostic::NotationCycle(cycle, *op_range));
+                    failed = true;
+                    continue;
+                }
+
+                if self.cyclic_notations.contains(symbol) {
+                    failed = true;
+                    continue;
+                }
+
+                match self.new_book.notations.get(symbol) {
+                    Some(notation) => {
+                        precedences.insert(symbol.clone(), notation.precedence);
+                    }
+                    None => {
+                        self.send_err(PassDiagnostic::UnknownNotation(symbol.clone(), *op_range));
+                        failed = true;
+                    }
+                }
+            }
+        }
+
+        if failed {
+            return desugared::Expr::err(range);
+        }
+
+        let fst = self.desugar_expr(fst);
+        let rest = rest
+            .iter()
+            .map(|(op, op_range, expr)| (op.clone(), *op_range, self.desugar_expr(expr)))
+            .collect();
+
+        let info = |op: &InfixOp| match op {
+            InfixOp::Operator(op) => (op.precedence(), op.associativity()),
+            InfixOp::Notation(symbol) => (precedences[symbol], Associativity::Left),
+        };
+
+        let mut join = |op: InfixOp, _: Range, fst: Box<desugared::Expr>, snd: Box<desugared::Expr>| {
+            let range = fst.range.mix(snd.range);
+            match op {
+                InfixOp::Operator(op) => desugared::Expr::binary(range, op, fst, snd),
+                InfixOp::Notation(symbol) => self.expand_notation(&symbol, range, fst, snd),
+            }
+        };
+
+        match solve_infix(fst, rest, &info, &mut join) {
+            Ok(res) => res,
+            Err((fst, snd)) => {
+                self.send_err(PassDiagnostic::NonAssociativeOperator(fst, snd));
+                desugared::Expr::err(range)
+            }
+        }
+    }
+}
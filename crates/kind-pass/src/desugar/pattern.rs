@@ -0,0 +1,836 @@
+//! Compiles the `match` expressions that have nested patterns or
+//! more than one scrutinee into nested eliminators. It works by
+//! splitting a matrix of patterns (one row for each case) on the
+//! first pattern of the first row that is not a variable until the
+//! first row only has variables.
+
+use std::sync::mpsc::channel;
+
+use fxhash::{FxHashMap, FxHashSet};
+use kind_span::Range;
+use kind_tree::concrete::expr::{self, CaseBinding};
+use kind_tree::concrete::pat::{Pat, PatKind};
+use kind_tree::concrete::Constructor;
+use kind_tree::desugared;
+use kind_tree::symbol::{Ident, QualifiedIdent};
+use kind_tree::Operator;
+
+use crate::diagnostic::{PassDiagnostic, Sugar};
+use crate::subst::subst_on_expr;
+
+use super::DesugarState;
+
+#[derive(Clone, Debug)]
+enum Pattern {
+    Wild(Option<Ident>),
+    Ctr(QualifiedIdent, Vec<Pattern>),
+    Num(u64, Range),
+}
+
+struct Row<'b> {
+    index: usize,
+    pats: Vec<Pattern>,
+    binds: Vec<(Ident, Ident)>,
+    body: &'b expr::Expr,
+}
+
+/// What is known about the value of a variable in the current
+/// branch. It's used to show the cases that are missing.
+enum Shape {
+    Ctr(String, Vec<(Ident, bool)>),
+    Num(u64),
+}
+
+/// One of the values that the match scrutinizes.
+struct Root {
+    name: Ident,
+    value: Option<Box<desugared::Expr>>,
+    split: bool,
+}
+
+struct Matrix<'b> {
+    range: Range,
+    roots: Vec<Root>,
+    motive: Box<desugared::Expr>,
+    with_vars: Vec<Ident>,
+    shapes: FxHashMap<String, Shape>,
+    missing: Vec<String>,
+    desugared: FxHashMap<usize, Range>,
+    reported: FxHashSet<Range>,
+    rows: Vec<Row<'b>>,
+}
+
+impl<'b> Matrix<'b> {
+    fn show(&self, var: &Ident, nested: bool) -> String {
+        match self.shapes.get(var.to_str()) {
+            None => "_".to_string(),
+            Some(Shape::Num(numb)) => numb.to_string(),
+            Some(Shape::Ctr(name, fields)) => {
+                let args: Vec<String> = fields
+                    .iter()
+                    .filter(|(_, hidden)| !hidden)
+                    .map(|(field, _)| self.show(field, true))
+                    .collect();
+                if args.is_empty() {
+                    name.clone()
+                } else if nested {
+                    format!("({} {})", name, args.join(" "))
+                } else {
+                    format!("{} {}", name, args.join(" "))
+                }
+            }
+        }
+    }
+}
+
+impl<'a> DesugarState<'a> {
+    fn family_constructors(
+        &self,
+        name: &QualifiedIdent,
+    ) -> Option<(QualifiedIdent, Vec<Constructor>)> {
+        let family = name.pop_last_segment();
+        let constructors = self
+            .old_book
+            .entries
+            .get(family.to_str())?
+            .get_constructors()?;
+        let last = name.to_str().rsplit('.').next().unwrap();
+        if constructors.iter().any(|x| x.name.to_str() == last) {
+            Some((family, constructors.into_owned()))
+        } else {
+            None
+        }
+    }
+
+    /// Converts a nested pattern. The constructors can leave out their
+    /// hidden fields like in the patterns of the rules.
+    fn nested_pattern(&mut self, pat: &Pat) -> Option<Pattern> {
+        match &pat.data {
+            PatKind::Var(name) => Some(Pattern::Wild(Some(name.0))),
+            PatKind::Hole => Some(Pattern::Wild(None)),
+            PatKind::U60(numb) => Some(Pattern::Num(*numb, pat.range)),
+            PatKind::Char(chr) => Some(Pattern::Num(*chr as u64, pat.range)),
+            PatKind::App(name, spine) => {
+                let (family, constructors) = match self.family_constructors(name) {
+                    Some(res) => res,
+                    None => {
+                        let family = name.pop_last_segment();
+                        self.send_err(PassDiagnostic::CannotFindConstructor(
+                            name.range,
+                            name.range,
+                            family.to_string(),
+                        ));
+                        return None;
+                    }
+                };
+
+                let last = name.to_str().rsplit('.').next().unwrap();
+                let cons = constructors
+                    .iter()
+                    .find(|x| x.name.to_str() == last)
+                    .unwrap();
+
+                let hidden = cons.args.iter().filter(|x| x.hidden).count();
+                let fill_hidden = spine.len() == cons.args.len() - hidden;
+
+                if !fill_hidden && spine.len() != cons.args.len() {
+                    self.send_err(PassDiagnostic::IncorrectArity(
+                        name.range,
+                        spine.iter().map(|x| x.range).collect(),
+                        cons.args.len(),
+                        hidden,
+                    ));
+                    return None;
+                }
+
+                let mut spine = spine.iter();
+                let mut args = Vec::new();
+
+                for arg in cons.args.iter() {
+                    if fill_hidden && arg.hidden {
+                        args.push(Pattern::Wild(None));
+                    } else {
+                        args.push(self.nested_pattern(spine.next().unwrap())?);
+                    }
+                }
+
+                let name = family.add_segment(last);
+                Some(Pattern::Ctr(name, args))
+            }
+            PatKind::U120(_)
+            | PatKind::F60(_)
+            | PatKind::Str(_)
+            | PatKind::List(_)
+            | PatKind::Pair(..) => {
+                self.send_err(PassDiagnostic::InvalidMatchPattern(pat.range));
+                None
+            }
+        }
+    }
+
+    /// Converts the bindings of a case with nested patterns. The renamed
+    /// bindings and the ones with the name of a field are placed first
+    /// and the other ones fill the explicit fields that are left in order.
+    fn case_pattern(
+        &mut self,
+        cons: &Constructor,
+        constructor: &Ident,
+        bindings: &[CaseBinding],
+    ) -> Option<Vec<Pattern>> {
+        let mut fields: Vec<Option<(Range, Pattern)>> = vec![None; cons.args.len()];
+        let mut failed = false;
+
+        for binding in bindings {
+            if let CaseBinding::Renamed(name, alias) = binding {
+                match cons
+                    .args
+                    .iter()
+                    .position(|x| x.name.to_str() == name.to_str())
+                {
+                    Some(pos) => match &fields[pos] {
+                        Some((range, _)) => {
+                            self.send_err(PassDiagnostic::DuplicatedNamed(*range, name.range));
+                            failed = true;
+                        }
+                        None => {
+                            fields[pos] = Some((name.range, Pattern::Wild(Some(*alias))))
+                        }
+                    },
+                    None => {
+                        self.send_err(PassDiagnostic::CannotFindField(
+                            name.range,
+                            constructor.range,
+                            constructor.to_string(),
+                        ));
+                        failed = true;
+                    }
+                }
+            }
+        }
+
+        let mut named = FxHashSet::default();
+
+        for (i, binding) in bindings.iter().enumerate() {
+            if let CaseBinding::Field(name) = binding {
+                let pos = cons
+                    .args
+                    .iter()
+                    .position(|x| x.name.to_str() == name.to_str());
+                if let Some(pos) = pos.filter(|pos| fields[*pos].is_none()) {
+                    fields[pos] = Some((name.range, Pattern::Wild(Some(*name))));
+                    named.insert(i);
+                }
+            }
+        }
+
+        let mut positional = Vec::new();
+
+        for (i, binding) in bindings.iter().enumerate() {
+            let (range, pat) = match binding {
+                CaseBinding::Field(_) if named.contains(&i) => continue,
+                CaseBinding::Field(name) => (name.range, Pattern::Wild(Some(*name))),
+                CaseBinding::Pattern(pat) => match self.nested_pattern(pat) {
+                    Some(res) => (pat.range, res),
+                    None => {
+                        failed = true;
+                        continue;
+                    }
+                },
+                CaseBinding::Renamed(..) => continue,
+            };
+
+            let free = cons
+                .args
+                .iter()
+                .zip(&fields)
+                .position(|(arg, field)| !arg.hidden && field.is_none());
+
+            match free {
+                Some(pos) => fields[pos] = Some((range, pat)),
+                None => positional.push(range),
+            }
+        }
+
+        if !positional.is_empty() {
+            let hidden = cons.args.iter().filter(|x| x.hidden).count();
+            self.send_err(PassDiagnostic::IncorrectArity(
+                constructor.range,
+                positional,
+                cons.args.len(),
+                hidden,
+            ));
+            failed = true;
+        }
+
+        if failed {
+            None
+        } else {
+            Some(
+                fields
+                    .into_iter()
+                    .map(|x| x.map(|x| x.1).unwrap_or(Pattern::Wild(None)))
+                    .collect(),
+            )
+        }
+    }
+
+    /// Desugars the body of a case. The body of a case can appear in
+    /// more than one branch, so the errors are reported only once and
+    /// the copies are marked to be compiled only once.
+    fn desugar_case_body(
+        &mut self,
+        matrix: &mut Matrix,
+        index: usize,
+        body: &expr::Expr,
+    ) -> Box<desugared::Expr> {
+        match matrix.desugared.get(&index) {
+            Some(range) if *range != Range::ghost_range() => {
+                self.new_book.shared_cases.insert(*range);
+            }
+            Some(_) => (),
+            None => {
+                let res = self.desugar_expr(body);
+                matrix.desugared.insert(index, res.range);
+                return res;
+            }
+        }
+
+        let (sender, _receiver) = channel();
+        let errors = std::mem::replace(&mut self.errors, sender);
+        let (failed, error_nodes) = (self.failed, self.error_nodes);
+
+        let res = self.desugar_expr(body);
+
+        self.errors = errors;
+        self.failed = failed;
+        self.error_nodes = error_nodes;
+
+        res
+    }
+
+    fn compile_matrix(
+        &mut self,
+        matrix: &mut Matrix,
+        vars: Vec<Ident>,
+        rows: Vec<usize>,
+    ) -> Box<desugared::Expr> {
+        let range = matrix.range;
+
+        let fst = match rows.first() {
+            Some(fst) => *fst,
+            None => {
+                let case = matrix
+                    .roots
+                    .iter()
+                    .map(|root| matrix.show(&root.name, false))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                if !matrix.missing.contains(&case) {
+                    matrix.missing.push(case);
+                }
+                return desugared::Expr::err(range);
+            }
+        };
+
+        let column = matrix.rows[fst]
+            .pats
+            .iter()
+            .position(|x| !matches!(x, Pattern::Wild(_)));
+
+        match column {
+            None => {
+                let row = &matrix.rows[fst];
+                let mut binds = row.binds.clone();
+                for (pat, var) in row.pats.iter().zip(&vars) {
+                    if let Pattern::Wild(Some(name)) = pat {
+                        binds.push((*name, *var));
+                    }
+                }
+
+                let (index, body) = (row.index, row.body);
+                let body = self.desugar_case_body(matrix, index, body);
+
+                binds.into_iter().rev().fold(body, |next, (name, var)| {
+                    desugared::Expr::let_(name.range, name, desugared::Expr::var(var), next)
+                })
+            }
+            Some(column) => match matrix.rows[fst].pats[column].clone() {
+                Pattern::Ctr(name, _) => self.split_constructor(matrix, vars, rows, column, &name),
+                Pattern::Num(numb, range) => {
+                    self.split_number(matrix, vars, rows, column, numb, range)
+                }
+                Pattern::Wild(_) => unreachable!(),
+            },
+        }
+    }
+
+    fn split_constructor(
+        &mut self,
+        matrix: &mut Matrix,
+        vars: Vec<Ident>,
+        rows: Vec<usize>,
+        column: usize,
+        name: &QualifiedIdent,
+    ) -> Box<desugared::Expr> {
+        let range = matrix.range;
+        let var = vars[column];
+        let root = matrix
+            .roots
+            .iter()
+            .position(|root| root.name.to_str() == var.to_str());
+        let is_root = root.is_some();
+
+        let (family, constructors) = self.family_constructors(name).unwrap();
+        let match_id = family.add_segment("match");
+
+        if self.old_book.entries.get(&match_id.to_string()).is_none() {
+            self.send_err(PassDiagnostic::NeedToImplementMethods(
+                range,
+                Sugar::Match(family.to_string()),
+            ));
+            return desugared::Expr::err(range);
+        }
+
+        // Constructors of another type and numbers in the same position.
+        for row in &rows {
+            match &matrix.rows[*row].pats[column] {
+                Pattern::Ctr(other, _) => {
+                    let other_family = other.pop_last_segment();
+                    if other_family.to_str() != family.to_str()
+                        && matrix.reported.insert(other.range)
+                    {
+                        self.send_err(PassDiagnostic::CannotFindConstructor(
+                            other.range,
+                            name.range,
+                            family.to_string(),
+                        ));
+                    }
+                }
+                Pattern::Num(_, other) => {
+                    if matrix.reported.insert(*other) {
+                        self.send_err(PassDiagnostic::MixedPatterns(name.range, *other));
+                    }
+                }
+                Pattern::Wild(_) => (),
+            }
+        }
+
+        let mut lambdas = Vec::new();
+
+        for cons in constructors.iter() {
+            let cons_name = family.add_segment(cons.name.to_str());
+
+            let fields: Vec<Ident> = cons
+                .args
+                .iter()
+                .map(|arg| Ident::generate(&format!("{}.{}", var.to_str(), arg.name.to_str())))
+                .collect();
+
+            let mut cons_rows = Vec::new();
+
+            for row in &rows {
+                let pats = &matrix.rows[*row].pats;
+                let (sub, bind) = match &pats[column] {
+                    Pattern::Ctr(other, args) if other.to_str() == cons_name.to_str() => {
+                        (args.clone(), None)
+                    }
+                    Pattern::Wild(bind) => (vec![Pattern::Wild(None); fields.len()], *bind),
+                    _ => continue,
+                };
+
+                let mut new_pats = pats[..column].to_vec();
+                new_pats.extend(sub);
+                new_pats.extend_from_slice(&pats[column + 1..]);
+
+                let mut binds = matrix.rows[*row].binds.clone();
+                if let Some(bind) = bind {
+                    binds.push((bind, var));
+                }
+
+                cons_rows.push(matrix.rows.len());
+                matrix.rows.push(Row {
+                    index: matrix.rows[*row].index,
+                    pats: new_pats,
+                    binds,
+                    body: matrix.rows[*row].body,
+                });
+            }
+
+            let mut new_vars = vars[..column].to_vec();
+            new_vars.extend(fields.iter().cloned());
+            new_vars.extend_from_slice(&vars[column + 1..]);
+
+            let shown = if is_root {
+                cons.name.to_string()
+            } else {
+                cons_name.to_string()
+            };
+            let hiddens = cons.args.iter().map(|x| x.hidden);
+            matrix.shapes.insert(
+                var.to_string(),
+                Shape::Ctr(shown, fields.iter().cloned().zip(hiddens).collect()),
+            );
+
+            // The other scrutinees are matched inside of the branch, so
+            // their motive is the one of this branch.
+            let motive = if is_root {
+                let args = fields.iter().cloned().map(desugared::Expr::var).collect();
+                let value = self.mk_desugared_ctr(range, cons_name, args, false);
+                let mut motive = matrix.motive.clone();
+                let substs = [(var.to_string(), value)].into_iter().collect();
+                subst_on_expr(&mut motive, substs);
+                Some(std::mem::replace(&mut matrix.motive, motive))
+            } else {
+                None
+            };
+
+            let mut body = self.compile_matrix(matrix, new_vars, cons_rows);
+
+            if let Some(motive) = motive {
+                matrix.motive = motive;
+            }
+
+            matrix.shapes.remove(var.to_str());
+
+            if is_root {
+                let irrelev = vec![false; matrix.with_vars.len()];
+                body = desugared::Expr::unfold_lambda(&irrelev, &matrix.with_vars, body);
+            }
+
+            let irrelev: Vec<bool> = cons.args.iter().map(|x| x.erased).rev().collect();
+            lambdas.push(desugared::Expr::unfold_lambda(&irrelev, &fields, body));
+        }
+
+        let (value, motive) = if let Some(root) = root {
+            let root = &mut matrix.roots[root];
+            root.split = true;
+            (
+                root.value
+                    .clone()
+                    .unwrap_or_else(|| desugared::Expr::var(var)),
+                matrix.motive.clone(),
+            )
+        } else {
+            (desugared::Expr::var(var), self.gen_hole_expr(range))
+        };
+
+        let prefix = [
+            value,
+            desugared::Expr::lambda(motive.range, var, motive, false),
+        ];
+
+        let call = self.mk_desugared_fun(
+            range,
+            match_id,
+            [prefix.as_slice(), lambdas.as_slice()].concat(),
+            false,
+        );
+
+        if is_root && !matrix.with_vars.is_empty() {
+            let args = matrix
+                .with_vars
+                .iter()
+                .map(|x| desugared::AppBinding {
+                    data: desugared::Expr::var(*x),
+                    erased: false,
+                })
+                .collect();
+            desugared::Expr::app(range, call, args)
+        } else {
+            call
+        }
+    }
+
+    /// Splits on a number by comparing it with `U60.if`.
+    fn split_number(
+        &mut self,
+        matrix: &mut Matrix,
+        vars: Vec<Ident>,
+        rows: Vec<usize>,
+        column: usize,
+        numb: u64,
+        numb_range: Range,
+    ) -> Box<desugared::Expr> {
+        let range = matrix.range;
+        let var = vars[column];
+
+        let u60_if = QualifiedIdent::new_static("U60", None, range).add_segment("if");
+
+        if self.old_book.names.get(u60_if.to_str()).is_none() {
+            self.send_err(PassDiagnostic::NeedToImplementMethods(range, Sugar::U60If));
+            return desugared::Expr::err(range);
+        }
+
+        let mut then_rows = Vec::new();
+        let mut else_rows = Vec::new();
+
+        for row in &rows {
+            let pats = &matrix.rows[*row].pats;
+            let bind = match &pats[column] {
+                Pattern::Num(other, _) if *other == numb => None,
+                Pattern::Wild(bind) => {
+                    else_rows.push(*row);
+                    *bind
+                }
+                Pattern::Num(..) => {
+                    else_rows.push(*row);
+                    continue;
+                }
+                Pattern::Ctr(other, _) => {
+                    if matrix.reported.insert(other.range) {
+                        self.send_err(PassDiagnostic::MixedPatterns(numb_range, other.range));
+                    }
+                    continue;
+                }
+            };
+
+            let mut new_pats = pats[..column].to_vec();
+            new_pats.extend_from_slice(&pats[column + 1..]);
+
+            let mut binds = matrix.rows[*row].binds.clone();
+            if let Some(bind) = bind {
+                binds.push((bind, var));
+            }
+
+            then_rows.push(matrix.rows.len());
+            matrix.rows.push(Row {
+                index: matrix.rows[*row].index,
+                pats: new_pats,
+                binds,
+                body: matrix.rows[*row].body,
+            });
+        }
+
+        let mut new_vars = vars.clone();
+        new_vars.remove(column);
+
+        matrix.shapes.insert(var.to_string(), Shape::Num(numb));
+        let then_ = self.compile_matrix(matrix, new_vars, then_rows);
+        matrix.shapes.remove(var.to_str());
+
+        let else_ = self.compile_matrix(matrix, vars, else_rows);
+
+        let cond = desugared::Expr::binary(
+            range,
+            Operator::Eql,
+            desugared::Expr::var(var),
+            desugared::Expr::num_u60(range, numb),
+        );
+
+        self.mk_desugared_fun(range, u60_if, vec![cond, then_, else_], false)
+    }
+
+    /// The constructors of the type of one of the other scrutinees.
+    fn scrutinee_constructors(
+        &mut self,
+        range: Range,
+        typ: &QualifiedIdent,
+    ) -> Option<Vec<Constructor>> {
+        let entry = self.old_book.entries.get(&typ.to_string()).unwrap();
+
+        if self
+            .old_book
+            .entries
+            .get(&typ.add_segment("match").to_string())
+            .is_none()
+        {
+            self.send_err(PassDiagnostic::NeedToImplementMethods(
+                range,
+                Sugar::Match(typ.to_string()),
+            ));
+            return None;
+        }
+
+        match entry.get_constructors() {
+            Some(constructors) => Some(constructors.into_owned()),
+            None => {
+                self.send_err(PassDiagnostic::LetDestructOnlyForSum(typ.range));
+                None
+            }
+        }
+    }
+
+    /// Converts the head of a case that matches a value of the type
+    /// `typ`. The `_` head matches anything and the names that are not
+    /// fields are positional if there are nested patterns or more than
+    /// one scrutinee.
+    fn head_pattern(
+        &mut self,
+        range: Range,
+        typ: &QualifiedIdent,
+        constructors: &[Constructor],
+        constructor: &Ident,
+        bindings: &[CaseBinding],
+        positional: bool,
+    ) -> Option<Pattern> {
+        if constructor.to_str() == "_" {
+            return Some(Pattern::Wild(None));
+        }
+
+        let cons = match constructors
+            .iter()
+            .find(|x| x.name.to_str() == constructor.to_str())
+        {
+            Some(cons) => cons,
+            None => {
+                self.send_err(PassDiagnostic::CannotFindConstructor(
+                    constructor.range,
+                    typ.range,
+                    typ.to_string(),
+                ));
+                return None;
+            }
+        };
+
+        let has_patterns = bindings
+            .iter()
+            .any(|x| matches!(x, CaseBinding::Pattern(_)));
+
+        let fields = if positional || has_patterns {
+            self.case_pattern(cons, constructor, bindings)?
+        } else {
+            let ordered = self.order_case_arguments(
+                (&constructor.range, constructor.to_string()),
+                &cons
+                    .args
+                    .iter()
+                    .map(|x| (x.name.to_string(), x.hidden))
+                    .collect::<Vec<(String, bool)>>(),
+                bindings,
+                Some(range),
+            );
+            ordered
+                .into_iter()
+                .map(|x| Pattern::Wild(x.1.map(|x| x.1)))
+                .collect()
+        };
+
+        Some(Pattern::Ctr(typ.add_segment(cons.name.to_str()), fields))
+    }
+
+    pub(crate) fn desugar_nested_match(
+        &mut self,
+        range: Range,
+        matcher: &expr::Match,
+        constructors: &[Constructor],
+    ) -> Box<desugared::Expr> {
+        let mut columns = vec![(&matcher.typ, constructors.to_vec())];
+
+        for other in &matcher.others {
+            match self.scrutinee_constructors(range, &other.typ) {
+                Some(constructors) => columns.push((&other.typ, constructors)),
+                None => return desugared::Expr::err(range),
+            }
+        }
+
+        let positional = !matcher.others.is_empty();
+
+        let mut rows = Vec::new();
+        let mut failed = false;
+
+        for (index, case) in matcher.cases.iter().enumerate() {
+            let heads = std::iter::once((&case.constructor, &case.bindings)).chain(
+                case.others
+                    .iter()
+                    .map(|head| (&head.constructor, &head.bindings)),
+            );
+
+            let mut pats = Vec::new();
+
+            for ((typ, cons), (constructor, bindings)) in columns.iter().zip(heads) {
+                match self.head_pattern(range, typ, cons, constructor, bindings, positional) {
+                    Some(pat) => pats.push(pat),
+                    None => failed = true,
+                }
+            }
+
+            rows.push(Row {
+                index,
+                pats,
+                binds: Vec::new(),
+                body: &case.value,
+            });
+        }
+
+        if failed {
+            return desugared::Expr::err(range);
+        }
+
+        let motive = matcher.motive.as_ref().map(|x| self.desugar_expr(x));
+
+        let mut roots = vec![Root {
+            name: matcher.scrutinee,
+            value: matcher.value.as_ref().map(|x| self.desugar_expr(x)),
+            split: false,
+        }];
+
+        for other in &matcher.others {
+            roots.push(Root {
+                name: other.name,
+                value: other.value.as_ref().map(|x| self.desugar_expr(x)),
+                split: false,
+            });
+        }
+
+        let binds = matcher
+            .with_vars
+            .iter()
+            .map(|x| {
+                (
+                    x.0,
+                    x.1.clone()
+                        .map(|x| self.desugar_expr(&x))
+                        .unwrap_or_else(|| self.gen_hole_expr(range)),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let motive = motive.unwrap_or_else(|| self.gen_hole_expr(matcher.typ.range));
+        let irrelev = vec![false; binds.len()];
+        let mut motive = desugared::Expr::unfold_all(&irrelev, &binds, motive);
+
+        // The scrutinees with a value that no case looks into are
+        // replaced by their values in the motive.
+        for (column, root) in roots.iter().enumerate() {
+            let unused = rows
+                .iter()
+                .all(|row| matches!(row.pats[column], Pattern::Wild(_)));
+            if let (true, Some(value)) = (unused, &root.value) {
+                let substs = [(root.name.to_string(), value.clone())].into_iter().collect();
+                subst_on_expr(&mut motive, substs);
+            }
+        }
+
+        let indices = (0..rows.len()).collect();
+        let vars = roots.iter().map(|root| root.name).collect();
+
+        let mut matrix = Matrix {
+            range,
+            roots,
+            motive,
+            with_vars: matcher.with_vars.iter().map(|x| x.0).collect(),
+            shapes: Default::default(),
+            missing: Vec::new(),
+            desugared: Default::default(),
+            reported: Default::default(),
+            rows,
+        };
+
+        let mut res = self.compile_matrix(&mut matrix, vars, indices);
+
+        if !matrix.missing.is_empty() {
+            self.send_err(PassDiagnostic::NoCoverage(range, matrix.missing));
+            return desugared::Expr::err(range);
+        }
+
+        // The values of the scrutinees that no case looks into are
+        // still visible to the bodies.
+        for root in matrix.roots.into_iter().rev() {
+            if let (false, Some(value)) = (root.split, root.value) {
+                res = desugared::Expr::let_(root.name.range, root.name, value, res);
+            }
+        }
+
+        res
+    }
+}
@@ -60,7 +60,7 @@ impl<'a> DesugarState<'a> {
                 .iter()
                 .find(|x| x.name.to_str() == fields[0].to_str())
             {
-                let key = field.name.clone();
+                let key = field.name;
 
                 let pair = params
                     .iter()
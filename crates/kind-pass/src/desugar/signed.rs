@@ -0,0 +1,219 @@
+//! Marks the binary operations on `I60` numbers as signed. The
+//! targets don't know the types of the values, so the operations
+//! are marked using the types that can be found in the annotations
+//! and in the signatures of the entries. The type checker fails if
+//! an operation on `I60` numbers was not marked, so the operands of
+//! these operations are stored in the book to explain the error.
+
+use fxhash::{FxHashMap, FxHashSet};
+use kind_span::Range;
+use kind_tree::desugared::{Book, Expr, ExprKind};
+
+/// The part of a type that is useful to know if a value is signed.
+#[derive(Clone)]
+enum Shape {
+    Unknown,
+    I60,
+    Fun(Box<Shape>, Box<Shape>),
+}
+
+impl Shape {
+    fn of(typ: &Expr) -> Shape {
+        match &typ.data {
+            ExprKind::NumTypeI60 => Shape::I60,
+            ExprKind::All { typ, body, .. } => {
+                Shape::Fun(Box::new(Shape::of(typ)), Box::new(Shape::of(body)))
+            }
+            _ => Shape::Unknown,
+        }
+    }
+}
+
+/// The shapes of the arguments and of the result of an entry.
+type Signature = (Vec<Shape>, Shape);
+
+struct Signedness<'a> {
+    signatures: &'a FxHashMap<String, Signature>,
+    ctx: Vec<(String, Shape)>,
+    unsigned: FxHashSet<Range>,
+}
+
+pub fn mark_signed_operations(book: &mut Book) {
+    let signatures = book
+        .entrs
+        .iter()
+        .map(|(name, entry)| {
+            let args = entry.args.iter().map(|arg| Shape::of(&arg.typ)).collect();
+            (name.clone(), (args, Shape::of(&entry.typ)))
+        })
+        .collect();
+
+    let mut state = Signedness {
+        signatures: &signatures,
+        ctx: Vec::new(),
+        unsigned: FxHashSet::default(),
+    };
+
+    for (_, entry) in book.entrs.iter_mut() {
+        for arg in &mut entry.args {
+            state.infer(&mut arg.typ);
+            state.ctx.push((arg.name.to_string(), Shape::of(&arg.typ)));
+        }
+        state.infer(&mut entry.typ);
+        state.ctx.clear();
+
+        let args: Vec<_> = entry.args.iter().map(|arg| Shape::of(&arg.typ)).collect();
+        let typ = Shape::of(&entry.typ);
+
+        for rule in &mut entry.rules {
+            for (pat, shape) in rule.pats.iter().zip(&args) {
+                state.bind_pat(pat, shape.clone());
+            }
+            state.check(&mut rule.body, &typ);
+            state.ctx.clear();
+        }
+    }
+
+    book.unsigned_operands = state.unsigned;
+}
+
+impl<'a> Signedness<'a> {
+    fn find(&self, name: &str) -> Shape {
+        self.ctx
+            .iter()
+            .rev()
+            .find(|(x, _)| x == name)
+            .map(|(_, shape)| shape.clone())
+            .unwrap_or(Shape::Unknown)
+    }
+
+    fn bind_pat(&mut self, pat: &Expr, shape: Shape) {
+        match &pat.data {
+            ExprKind::Var { name } => self.ctx.push((name.to_string(), shape)),
+            ExprKind::Ctr { name, args } => {
+                let fields = self.signatures.get(name.to_str()).map(|x| x.0.clone());
+                let fields = fields
+                    .unwrap_or_default()
+                    .into_iter()
+                    .chain(std::iter::repeat(Shape::Unknown));
+                for (arg, shape) in args.iter().zip(fields) {
+                    self.bind_pat(arg, shape)
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn check(&mut self, expr: &mut Expr, expected: &Shape) {
+        match (&mut expr.data, expected) {
+            (ExprKind::Lambda { param, body, .. }, Shape::Fun(param_shape, body_shape)) => {
+                self.ctx.push((param.to_string(), *param_shape.clone()));
+                self.check(body, body_shape);
+                self.ctx.pop();
+            }
+            _ => {
+                self.infer(expr);
+            }
+        }
+    }
+
+    fn check_args<'b>(&mut self, args: impl Iterator<Item = &'b mut Box<Expr>>, params: &[Shape]) {
+        let params = params.iter().chain(std::iter::repeat(&Shape::Unknown));
+        for (arg, shape) in args.zip(params) {
+            self.check(arg, shape)
+        }
+    }
+
+    fn infer(&mut self, expr: &mut Expr) -> Shape {
+        use ExprKind::*;
+        let signatures = self.signatures;
+        match &mut expr.data {
+            Var { name } => self.find(name.to_str()),
+            NumI60 { .. } => Shape::I60,
+            Binary {
+                op,
+                left,
+                right,
+                signed,
+            } => {
+                let left_shape = self.infer(left);
+                let right_shape = self.infer(right);
+                *signed = matches!(left_shape, Shape::I60) || matches!(right_shape, Shape::I60);
+                if !*signed {
+                    self.unsigned.insert(left.range);
+                    self.unsigned.insert(right.range);
+                }
+                if *signed && !op.is_comparison() {
+                    Shape::I60
+                } else {
+                    Shape::Unknown
+                }
+            }
+            Ann { expr, typ } => {
+                self.infer(typ);
+                let shape = Shape::of(typ);
+                self.check(expr, &shape);
+                shape
+            }
+            Let { name, val, next } => {
+                let shape = self.infer(val);
+                self.ctx.push((name.to_string(), shape));
+                let res = self.infer(next);
+                self.ctx.pop();
+                res
+            }
+            Lambda { param, body, .. } => {
+                self.ctx.push((param.to_string(), Shape::Unknown));
+                let res = self.infer(body);
+                self.ctx.pop();
+                Shape::Fun(Box::new(Shape::Unknown), Box::new(res))
+            }
+            All {
+                param, typ, body, ..
+            } => {
+                self.infer(typ);
+                self.ctx.push((param.to_string(), Shape::of(typ)));
+                self.infer(body);
+                self.ctx.pop();
+                Shape::Unknown
+            }
+            App { fun, args } => {
+                let mut shape = self.infer(fun);
+                for arg in args {
+                    shape = match shape {
+                        Shape::Fun(param, body) => {
+                            self.check(&mut arg.data, &param);
+                            *body
+                        }
+                        _ => {
+                            self.infer(&mut arg.data);
+                            Shape::Unknown
+                        }
+                    }
+                }
+                shape
+            }
+            Fun { name, args } | Ctr { name, args } => match signatures.get(name.to_str()) {
+                Some((params, res)) => {
+                    self.check_args(args.iter_mut(), params);
+                    res.clone()
+                }
+                None => {
+                    self.check_args(args.iter_mut(), &[]);
+                    Shape::Unknown
+                }
+            },
+            Sub { expr, .. } => self.infer(expr),
+            Typ
+            | NumTypeU60
+            | NumTypeF60
+            | NumTypeI60
+            | NumU60 { .. }
+            | NumF60 { .. }
+            | Str { .. }
+            | Hole { .. }
+            | Hlp(_)
+            | Err => Shape::Unknown,
+        }
+    }
+}
@@ -40,7 +40,7 @@ impl<'a> DesugarState<'a> {
         desugared::Argument {
             hidden: argument.hidden,
             erased: argument.erased,
-            name: argument.name.clone(),
+            name: argument.name,
             typ,
             range: argument.range,
         }
@@ -54,7 +54,7 @@ impl<'a> DesugarState<'a> {
         let desugared_indices = indices.map(|arg| self.desugar_argument(arg));
 
         let type_constructor = desugared::Entry {
-            name: sum_type.name.clone(),
+            name: sum_type.name,
             args: desugared_params.extend(&desugared_indices).to_vec(),
             typ: desugared::Expr::typ(sum_type.name.range),
             rules: Vec::new(),
@@ -74,7 +74,7 @@ impl<'a> DesugarState<'a> {
             .to_vec();
 
         let mut family = Family {
-            name: sum_type.name.clone(),
+            name: sum_type.name,
             constructors: Vec::with_capacity(sum_type.constructors.len()),
             parameters: desugared_params.clone(),
         };
@@ -119,15 +119,15 @@ impl<'a> DesugarState<'a> {
                     let args = [irrelevant_params.as_slice(), pre_indices]
                         .concat()
                         .iter()
-                        .map(|x| desugared::Expr::var(x.name.clone()))
+                        .map(|x| desugared::Expr::var(x.name))
                         .collect::<Vec<Box<desugared::Expr>>>();
 
-                    desugared::Expr::ctr(cons.name.range, sum_type.name.clone(), args)
+                    desugared::Expr::ctr(cons.name.range, sum_type.name, args)
                 }
             };
 
             let data_constructor = desugared::Entry {
-                name: cons_ident.clone(),
+                name: cons_ident,
                 args: [
                     irrelevant_params.as_slice(),
                     pre_indices,
@@ -140,7 +140,7 @@ impl<'a> DesugarState<'a> {
                 range: cons.name.range,
             };
 
-            family.constructors.push(cons_ident.clone());
+            family.constructors.push(cons_ident);
 
             self.new_book
                 .entrs
@@ -156,7 +156,7 @@ impl<'a> DesugarState<'a> {
         let desugared_params = params.map(|arg| self.desugar_argument(arg));
 
         let type_constructor = desugared::Entry {
-            name: rec_type.name.clone(),
+            name: rec_type.name,
             args: desugared_params.clone().to_vec(),
             typ: desugared::Expr::typ(rec_type.name.range),
             rules: Vec::new(),
@@ -173,16 +173,16 @@ impl<'a> DesugarState<'a> {
         let args = [irrelevant_params.as_slice()]
             .concat()
             .iter()
-            .map(|x| desugared::Expr::var(x.name.clone()))
+            .map(|x| desugared::Expr::var(x.name))
             .collect::<Vec<Box<desugared::Expr>>>();
 
-        let typ = desugared::Expr::ctr(rec_type.name.range, rec_type.name.clone(), args);
+        let typ = desugared::Expr::ctr(rec_type.name.range, rec_type.name, args);
 
         let cons_ident = rec_type.name.add_segment(rec_type.constructor.to_str());
 
         self.new_book.families.insert(rec_type.name.to_string(), Family {
-            name: rec_type.name.clone(),
-            constructors: vec![cons_ident.clone()],
+            name: rec_type.name,
+            constructors: vec![cons_ident],
             parameters: desugared_params.clone()
         });
 
@@ -199,7 +199,7 @@ impl<'a> DesugarState<'a> {
             .collect::<Vec<desugared::Argument>>();
 
         let data_constructor = desugared::Entry {
-            name: cons_ident.clone(),
+            name: cons_ident,
             args: [irrelevant_params.as_slice(), fields_args.as_slice()].concat(),
             typ,
             rules: Vec::new(),
@@ -253,7 +253,7 @@ impl<'a> DesugarState<'a> {
             self.mk_desugared_ctr(range, nil_ident, Vec::new(), true),
             |res, elem| {
                 let spine = vec![self.desugar_pat(elem), res];
-                self.mk_desugared_ctr(range, cons_ident.clone(), spine, true)
+                self.mk_desugared_ctr(range, cons_ident, spine, true)
             },
         )
     }
@@ -301,13 +301,13 @@ impl<'a> DesugarState<'a> {
                         new_spine.push(self.desugar_pat(arg));
                     }
                 }
-                desugared::Expr::ctr(pat.range, head.clone(), new_spine)
+                desugared::Expr::ctr(pat.range, *head, new_spine)
             }
             PatKind::Hole => {
                 let name = self.gen_name(pat.range);
                 desugared::Expr::var(name)
             }
-            PatKind::Var(ident) => desugared::Expr::var(ident.0.clone()),
+            PatKind::Var(ident) => desugared::Expr::var(ident.0),
             PatKind::U60(n) => desugared::Expr::num_u60(pat.range, *n),
             PatKind::U120(n) => desugared::Expr::num_u120(pat.range, *n),
             PatKind::F60(n) => desugared::Expr::num_f60(pat.range, *n),
@@ -323,6 +323,17 @@ impl<'a> DesugarState<'a> {
         args: &Telescope<concrete::Argument>,
         rule: &concrete::Rule,
     ) -> desugared::Rule {
+        // The parser could not finish this rule so the patterns
+        // are incomplete.
+        if let concrete::expr::ExprKind::Error = rule.body.data {
+            return desugared::Rule {
+                name: rule.name,
+                pats: Vec::new(),
+                body: self.desugar_expr(&rule.body),
+                range: rule.range,
+            };
+        }
+
         let pats = rule
             .pats
             .iter()
@@ -333,7 +344,7 @@ impl<'a> DesugarState<'a> {
 
         if pats.len() == args.len() {
             desugared::Rule {
-                name: rule.name.clone(),
+                name: rule.name,
                 pats,
                 body: self.desugar_expr(&rule.body),
                 range: rule.range,
@@ -349,7 +360,7 @@ impl<'a> DesugarState<'a> {
                 }
             }
             desugared::Rule {
-                name: rule.name.clone(),
+                name: rule.name,
                 pats: res_pats,
                 body: self.desugar_expr(&rule.body),
                 range: rule.range,
@@ -363,7 +374,7 @@ impl<'a> DesugarState<'a> {
             ));
             // TODO: Probably we should just a sentinel rule?
             desugared::Rule {
-                name: rule.name.clone(),
+                name: rule.name,
                 pats,
                 body: self.desugar_expr(&rule.body),
                 range: rule.range,
@@ -374,16 +385,43 @@ impl<'a> DesugarState<'a> {
     pub fn desugar_entry(&mut self, entry: &concrete::Entry) {
         self.name_count = 0;
 
-        let rules = entry
-            .rules
-            .iter()
-            .map(|x| self.desugar_rule(&entry.args, x))
-            .collect();
+        let errors = self.error_nodes;
+
+        // The rules of an entry with a broken signature are not even
+        // desugared because the arguments are not known.
+        let mut rules: Vec<_> = match entry.typ.data {
+            concrete::expr::ExprKind::Error => Vec::new(),
+            _ => entry
+                .rules
+                .iter()
+                .map(|x| self.desugar_rule(&entry.args, x))
+                .collect(),
+        };
+
+        // Rules with syntax errors cannot be checked so the entry
+        // becomes a declaration.
+        let recovered = self.error_nodes != errors || rules.len() != entry.rules.len();
+
+        if recovered {
+            rules.clear();
+        }
+
+        let errors = self.error_nodes;
+
+        let mut args = entry.args.map(|x| self.desugar_argument(x)).to_vec();
+        let mut typ = self.desugar_expr(&entry.typ);
+
+        // An entry with a syntax error in the signature is kept with an
+        // unknown type so the uses of it do not generate more errors.
+        if self.error_nodes != errors {
+            args = Vec::new();
+            typ = self.gen_hole_expr(entry.typ.range);
+        }
 
         let res_entry = desugared::Entry {
-            name: entry.name.clone(),
-            args: entry.args.map(|x| self.desugar_argument(x)).to_vec(),
-            typ: self.desugar_expr(&entry.typ),
+            name: entry.name,
+            args,
+            typ,
             range: entry.range,
             attrs: self.desugar_attributes(&entry.attrs),
             rules,
@@ -397,7 +435,7 @@ impl<'a> DesugarState<'a> {
 
         let diff = rule_numbers.iter().filter(|x| rule_numbers[0].1 != x.1);
 
-        if !rule_numbers.is_empty() && diff.clone().count() >= 1 {
+        if !recovered && !rule_numbers.is_empty() && diff.clone().count() >= 1 {
             self.send_err(PassDiagnostic::RulesWithInconsistentArity(
                 diff.cloned().collect(),
             ));
@@ -413,6 +451,7 @@ impl<'a> DesugarState<'a> {
             concrete::TopLevel::SumType(sum) => self.desugar_sum_type(sum),
             concrete::TopLevel::RecordType(rec) => self.desugar_record_type(rec),
             concrete::TopLevel::Entry(entry) => self.desugar_entry(entry),
+            concrete::TopLevel::Notation(notation) => self.desugar_notation(notation),
         }
     }
 }
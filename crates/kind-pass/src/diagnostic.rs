@@ -21,7 +21,9 @@ pub enum Sugar {
     Sigma,
     Pair,
     BoolIf,
+    U60If,
     String,
+    Interpolation,
     U120,
     Getter(String),
     Mutter(String),
@@ -59,6 +61,12 @@ pub enum PassDiagnostic {
     NeedsAField(Range),
     CannotFindTheField(Range, String),
     CannotAccessType(Range, String),
+    PrivateDefinition(String, Range, Range),
+    UnknownNotation(String, Range),
+    NonAssociativeOperator(Range, Range),
+    InvalidMatchPattern(Range),
+    NotationCycle(Vec<String>, Range),
+    MixedPatterns(Range, Range),
 }
 
 // TODO: A way to build an error message with methods
@@ -93,6 +101,12 @@ impl Diagnostic for PassDiagnostic {
             PassDiagnostic::NeedsAField(range) => Some(range.ctx),
             PassDiagnostic::CannotFindTheField(range, _) => Some(range.ctx),
             PassDiagnostic::CannotAccessType(range, _) => Some(range.ctx),
+            PassDiagnostic::PrivateDefinition(_, range, _) => Some(range.ctx),
+            PassDiagnostic::UnknownNotation(_, range) => Some(range.ctx),
+            PassDiagnostic::NonAssociativeOperator(range, _) => Some(range.ctx),
+            PassDiagnostic::InvalidMatchPattern(range) => Some(range.ctx),
+            PassDiagnostic::NotationCycle(_, range) => Some(range.ctx),
+            PassDiagnostic::MixedPatterns(_, range) => Some(range.ctx),
         }
     }
 
@@ -222,7 +236,9 @@ impl Diagnostic for PassDiagnostic {
                     Sugar::Sigma => "You must implement 'Sigma' in order to use the sigma notation.".to_string(),
                     Sugar::Pair => "You must implement 'Sigma' and 'Sigma.new' in order to use the sigma notation.".to_string(),
                     Sugar::BoolIf => "You must implement 'Bool.if' in order to use the if notation.".to_string(),
+                    Sugar::U60If => "You must implement 'U60.if' in order to match on numbers.".to_string(),
                     Sugar::String => "You must implement 'String.cons' in order to use the string notation.".to_string(),
+                    Sugar::Interpolation => "You must implement 'String.concat' in order to use string interpolation.".to_string(),
                     Sugar::U120 => "You must implement 'U120.new' in order to use the u120 notation.".to_string(),
                     Sugar::Match(_) => "You must implement 'match' in order to use the match notation (or derive match with #derive[match]).".to_string(),
                     Sugar::Mutter(typ) => format!("You must derive 'mutters' for '{}' in order to use this syntax", typ),
@@ -645,6 +661,120 @@ impl Diagnostic for PassDiagnostic {
                     main: true,
                 }],
             },
+            PassDiagnostic::PrivateDefinition(name, range, def) => DiagnosticFrame {
+                code: 215,
+                severity: Severity::Error,
+                title: format!("The definition '{}' is private to the file that defines it.", name),
+                subtitles: vec![],
+                hints: vec!["Remove the '#private' attribute to use it in other files".to_string()],
+                positions: vec![
+                    Marker {
+                        position: *range,
+                        color: Color::Fst,
+                        text: "Used here!".to_string(),
+                        no_code: false,
+                        main: true,
+                    },
+                    Marker {
+                        position: *def,
+                        color: Color::Snd,
+                        text: "Defined here!".to_string(),
+                        no_code: false,
+                        main: false,
+                    },
+                ],
+            },
+            PassDiagnostic::UnknownNotation(symbol, range) => DiagnosticFrame {
+                code: 216,
+                severity: Severity::Error,
+                title: format!("Cannot find the notation '{}'.", symbol),
+                subtitles: vec![],
+                hints: vec!["Notations are only available after the file that declares them is loaded".to_string()],
+                positions: vec![Marker {
+                    position: *range,
+                    color: Color::Fst,
+                    text: "Here!".to_string(),
+                    no_code: false,
+                    main: true,
+                }],
+            },
+            PassDiagnostic::NonAssociativeOperator(fst, snd) => DiagnosticFrame {
+                code: 217,
+                severity: Severity::Error,
+                title: "Comparison operators cannot be chained.".to_string(),
+                subtitles: vec![],
+                hints: vec!["Use parenthesis to group the comparisons".to_string()],
+                positions: vec![
+                    Marker {
+                        position: *fst,
+                        color: Color::Fst,
+                        text: "This operator".to_string(),
+                        no_code: false,
+                        main: true,
+                    },
+                    Marker {
+                        position: *snd,
+                        color: Color::Snd,
+                        text: "cannot be followed by this one".to_string(),
+                        no_code: false,
+                        main: false,
+                    },
+                ],
+            },
+            PassDiagnostic::InvalidMatchPattern(range) => DiagnosticFrame {
+                code: 218,
+                severity: Severity::Error,
+                title: "This pattern cannot be used inside of a match.".to_string(),
+                subtitles: vec![],
+                hints: vec!["Only constructors, variables, holes and U60 numbers can be nested".to_string()],
+                positions: vec![Marker {
+                    position: *range,
+                    color: Color::Fst,
+                    text: "Here!".to_string(),
+                    no_code: false,
+                    main: true,
+                }],
+            },
+            PassDiagnostic::NotationCycle(cycle, range) => DiagnosticFrame {
+                code: 219,
+                severity: Severity::Error,
+                title: format!("The notation '{}' uses itself.", cycle[0]),
+                subtitles: vec![],
+                hints: vec![format!(
+                    "The notations expand in a cycle: {}",
+                    cycle.join(" -> ")
+                )],
+                positions: vec![Marker {
+                    position: *range,
+                    color: Color::Fst,
+                    text: "Here!".to_string(),
+                    no_code: false,
+                    main: true,
+                }],
+            },
+            PassDiagnostic::MixedPatterns(fst, snd) => DiagnosticFrame {
+                code: 220,
+                severity: Severity::Error,
+                title: "Numbers and constructors cannot be matched in the same place.".to_string(),
+                subtitles: vec![],
+                hints: vec!["A field is matched either against numbers or against constructors".to_string()],
+                positions: vec![
+                    Marker {
+                        position: *snd,
+                        color: Color::Fst,
+                        text: "This pattern".to_string(),
+                        no_code: false,
+                        main: true,
+                    },
+                    Marker {
+                        position: *fst,
+                        color: Color::Snd,
+                        text: "cannot be matched in the same place as this one".to_string(),
+                        no_code: false,
+                        main: false,
+                    },
+                ],
+            },
         }
     }
 
@@ -678,6 +808,12 @@ impl Diagnostic for PassDiagnostic {
             | NeedsAField(_)
             | CannotFindTheField(_, _)
             | CannotAccessType(_, _)
+            | PrivateDefinition(_, _, _)
+            | UnknownNotation(_, _)
+            | NonAssociativeOperator(_, _)
+            | InvalidMatchPattern(_)
+            | NotationCycle(_, _)
+            | MixedPatterns(_, _)
             | AttributeDoesNotExists(_) => Severity::Error,
         }
     }
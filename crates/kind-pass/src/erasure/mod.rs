@@ -1,11 +1,13 @@
 use std::sync::mpsc::Sender;
 
 use fxhash::{FxHashMap, FxHashSet};
+use kind_report::cancel::CancellationToken;
 use kind_report::data::Diagnostic;
 use kind_span::Range;
 
+use kind_tree::desugared::visit::{self, Visit};
 use kind_tree::desugared;
-use kind_tree::symbol::QualifiedIdent;
+use kind_tree::symbol::{Ident, QualifiedIdent};
 use kind_tree::untyped::{self};
 
 use crate::diagnostic::{PassDiagnostic, GenericPassError};
@@ -32,6 +34,17 @@ impl Ambient {
     }
 }
 
+/// What happened to the body of a case that a nested match copied
+/// into more than one branch.
+enum Shared {
+    /// The first copy is being compiled.
+    Lifting,
+    /// The body is small, so every copy is compiled in place.
+    Inline,
+    /// The body is an auxiliary entry that receives its free variables.
+    Lifted(QualifiedIdent, Vec<Ident>),
+}
+
 pub struct Edge {
     name: String,
     relevance: FxHashMap<Relevance, Vec<Range>>,
@@ -47,13 +60,47 @@ pub struct ErasureState<'a> {
 
     ctx: im_rc::HashMap<String, Relevance>,
 
+    shared: FxHashMap<Range, Shared>,
+    lifted: Vec<untyped::Entry>,
+
     failed: bool,
+    cancel: CancellationToken,
+}
+
+/// Walks through the parts of the tree that are erased. They are
+/// not compiled but the entries that they use are still connected
+/// to the entry that is being erased.
+struct Irrelevant<'a, 'b> {
+    state: &'a mut ErasureState<'b>,
+    edge: usize,
+}
+
+impl<'a, 'b> Visit for Irrelevant<'a, 'b> {
+    fn visit_expr(&mut self, expr: &desugared::Expr) {
+        use desugared::ExprKind::*;
+
+        match &expr.data {
+            Fun { name, .. } | Ctr { name, .. } => {
+                self.state.connect_with(self.edge, name, Ambient::Irrelevant);
+            }
+            Str { .. } => {
+                let nil = QualifiedIdent::new_static("String.nil", None, expr.range);
+                let cons = QualifiedIdent::new_static("String.cons", None, expr.range);
+                self.state.connect_with(self.edge, &nil, Ambient::Irrelevant);
+                self.state.connect_with(self.edge, &cons, Ambient::Irrelevant);
+            }
+            _ => (),
+        }
+
+        visit::walk_expr(self, expr)
+    }
 }
 
 pub fn erase_book(
     book: &desugared::Book,
     errs: Sender<Box<dyn Diagnostic>>,
     entrypoints: Vec<String>,
+    cancel: &CancellationToken,
 ) -> anyhow::Result<untyped::Book> {
     let mut state = ErasureState {
         errs,
@@ -61,7 +108,10 @@ pub fn erase_book(
         edges: Default::default(),
         names: Default::default(),
         ctx: Default::default(),
+        shared: Default::default(),
+        lifted: Default::default(),
         failed: Default::default(),
+        cancel: cancel.clone(),
     };
 
     state.erase_book(book, entrypoints)
@@ -95,6 +145,15 @@ impl<'a> ErasureState<'a> {
         entry.push((name.range, ambient))
     }
 
+    /// Tells which arguments of the entry are erased.
+    fn erased_args(&self, name: &QualifiedIdent) -> impl Iterator<Item = bool> + 'a {
+        self.book.entrs[name.to_str()].args.iter().map(|arg| arg.erased)
+    }
+
+    fn erase_irrelevant(&mut self, edge: usize, expr: &desugared::Expr) {
+        Irrelevant { state: self, edge }.visit_expr(expr)
+    }
+
     pub fn erase_book(
         &mut self,
         book: &'a desugared::Book,
@@ -130,9 +189,14 @@ impl<'a> ErasureState<'a> {
         }
 
         for entr in book.entrs.values() {
+            self.cancel.check()?;
             vals.insert(entr.name.to_string(), self.erase_entry(entr));
         }
 
+        for entr in self.lifted.drain(..) {
+            vals.insert(entr.name.to_string(), Box::new(entr));
+        }
+
         let mut visited = FxHashSet::<usize>::default();
 
         let mut new_book = untyped::Book {
@@ -204,25 +268,25 @@ impl<'a> ErasureState<'a> {
         let backup = self.ctx.clone();
 
         for arg in &entry.args {
-            self.erase_expr(Ambient::Irrelevant, id, &arg.typ);
+            self.erase_irrelevant(id, &arg.typ);
             self.ctx.insert(arg.name.to_string(), Relevance::Irrelevant);
             if !arg.erased {
                 args.push((arg.name.to_string(), arg.range, false))
             }
         }
 
-        self.erase_expr(Ambient::Irrelevant, id, &entry.typ);
+        self.erase_irrelevant(id, &entry.typ);
 
         self.ctx = backup;
 
         let mut rules = Vec::new();
 
         for rule in &entry.rules {
-            rules.push(self.erase_rule(entry, id, rule));
+            rules.push(self.erase_rule(&entry.name, &entry.args, id, rule));
         }
 
         Box::new(untyped::Entry {
-            name: entry.name.clone(),
+            name: entry.name,
             args,
             rules,
             attrs: entry.attrs.clone(),
@@ -232,7 +296,8 @@ impl<'a> ErasureState<'a> {
 
     fn erase_rule(
         &mut self,
-        entr: &desugared::Entry,
+        name: &QualifiedIdent,
+        args: &[desugared::Argument],
         edge: usize,
         rule: &'a desugared::Rule,
     ) -> untyped::Rule {
@@ -255,7 +320,7 @@ impl<'a> ErasureState<'a> {
         let pats = rule
             .pats
             .iter()
-            .zip(&entr.args)
+            .zip(args)
             .map(|(pat, arg)| (self.erase_pat(relev(arg.erased), edge, pat), arg))
             .filter(|(_, arg)| !arg.erased)
             .map(|res| res.0)
@@ -266,7 +331,7 @@ impl<'a> ErasureState<'a> {
         self.ctx = backup;
 
         untyped::Rule {
-            name: entr.name.clone(),
+            name: *name,
             pats,
             body,
             range: rule.range,
@@ -300,7 +365,7 @@ impl<'a> ErasureState<'a> {
                     },
                 );
 
-                untyped::Expr::var(name.clone())
+                untyped::Expr::var(*name)
             }
             Hole { num: _ } => untyped::Expr::err(expr.range),
             Fun { name, args } => {
@@ -312,17 +377,17 @@ impl<'a> ErasureState<'a> {
                 //     self.set_relevance(edge, Relevance::Irrelevant, expr.range)
                 // }
 
-                let params = self.book.entrs.get(name.to_str()).unwrap();
+                let erased = self.erased_args(name);
 
                 let args = args
                     .iter()
-                    .zip(&params.args)
-                    .map(|(arg, param)| (self.erase_pat(relev(param.erased), edge, arg), param))
-                    .filter(|(_, param)| !param.erased)
+                    .zip(erased)
+                    .map(|(arg, erased)| (self.erase_pat(relev(erased), edge, arg), erased))
+                    .filter(|(_, erased)| !erased)
                     .map(|x| x.0)
                     .collect::<Vec<_>>();
 
-                untyped::Expr::fun(expr.range, name.clone(), args)
+                untyped::Expr::fun(expr.range, *name, args)
             }
             Ctr { name, args } => {
                 self.connect_with(edge, name, relevance);
@@ -333,20 +398,21 @@ impl<'a> ErasureState<'a> {
                 //     self.set_relevance(edge, Relevance::Irrelevant, expr.range)
                 // }
 
-                let params = self.book.entrs.get(name.to_str()).unwrap();
+                let erased = self.erased_args(name);
 
                 let args = args
                     .iter()
-                    .zip(&params.args)
-                    .map(|(arg, param)| (self.erase_pat(relev(param.erased), edge, arg), param))
-                    .filter(|(_, param)| !param.erased)
+                    .zip(erased)
+                    .map(|(arg, erased)| (self.erase_pat(relev(erased), edge, arg), erased))
+                    .filter(|(_, erased)| !erased)
                     .map(|x| x.0)
                     .collect::<Vec<_>>();
 
-                untyped::Expr::ctr(expr.range, name.clone(), args)
+                untyped::Expr::ctr(expr.range, *name, args)
             }
             NumU60 { numb } => untyped::Expr::u60(expr.range, *numb),
             NumF60 { numb } => untyped::Expr::f60(expr.range, *numb),
+            NumI60 { numb } => untyped::Expr::i60(expr.range, *numb),
             Str { val } => {
                 let nil = QualifiedIdent::new_static("String.nil", None, expr.range);
                 let cons = QualifiedIdent::new_static("String.cons", None, expr.range);
@@ -360,12 +426,95 @@ impl<'a> ErasureState<'a> {
         }
     }
 
+    /// Compiles the body of a case that a nested match copied into
+    /// more than one branch. The first copy becomes an auxiliary entry
+    /// and the other ones become calls to it. It returns `None` when
+    /// the copy has to be compiled in place.
+    fn erase_shared(
+        &mut self,
+        ambient: Ambient,
+        edge: usize,
+        expr: &'a desugared::Expr,
+    ) -> Option<Box<untyped::Expr>> {
+        match self.shared.get(&expr.range) {
+            Some(Shared::Lifted(name, params)) => {
+                let name = *name;
+                let args = params.iter().cloned().map(untyped::Expr::var).collect();
+                self.connect_with(edge, &name, ambient);
+                return Some(untyped::Expr::fun(expr.range, name, args));
+            }
+            Some(Shared::Lifting | Shared::Inline) => return None,
+            None => (),
+        }
+
+        self.shared.insert(expr.range, Shared::Lifting);
+
+        let body = self.erase_expr(ambient, edge, expr);
+
+        let small = match &body.data {
+            untyped::ExprKind::Fun { args, .. } | untyped::ExprKind::Ctr { args, .. } => {
+                args.is_empty()
+            }
+            untyped::ExprKind::Var { .. }
+            | untyped::ExprKind::U60 { .. }
+            | untyped::ExprKind::F60 { .. }
+            | untyped::ExprKind::Err => true,
+            _ => false,
+        };
+
+        if small {
+            self.shared.insert(expr.range, Shared::Inline);
+            return Some(body);
+        }
+
+        let parent = self.edges[edge].name.clone();
+        let mut count = 0;
+        let name = loop {
+            let name = format!("{}.case{}_", parent, count);
+            if !self.names.contains_key(&name) && !self.book.entrs.contains_key(&name) {
+                break QualifiedIdent::new_static(&name, None, expr.range);
+            }
+            count += 1;
+        };
+
+        let mut params = Vec::new();
+        free_vars(&body, &mut Vec::new(), &mut params);
+
+        self.lifted.push(untyped::Entry {
+            name,
+            args: params
+                .iter()
+                .map(|x| (x.to_string(), x.range, false))
+                .collect(),
+            rules: vec![untyped::Rule {
+                name,
+                pats: params.iter().cloned().map(untyped::Expr::var).collect(),
+                body,
+                range: expr.range,
+            }],
+            attrs: Default::default(),
+            range: expr.range,
+        });
+
+        let args = params.iter().cloned().map(untyped::Expr::var).collect();
+        self.shared.insert(expr.range, Shared::Lifted(name, params));
+        self.connect_with(edge, &name, ambient);
+
+        Some(untyped::Expr::fun(expr.range, name, args))
+    }
+
     fn erase_expr(
         &mut self,
         ambient: Ambient,
         edge: usize,
         expr: &'a desugared::Expr,
     ) -> Box<untyped::Expr> {
+        if self.book.shared_cases.contains(&expr.range) {
+            if let Some(res) = self.erase_shared(ambient, edge, expr) {
+                return res;
+            }
+        }
+
         use desugared::ExprKind::*;
         match &expr.data {
             All {
@@ -381,8 +530,8 @@ impl<'a> ErasureState<'a> {
                     self.set_relevance(edge, Relevance::Irrelevant, expr.range);
                 }
 
-                self.erase_expr(Ambient::Irrelevant, edge, typ);
-                self.erase_expr(Ambient::Irrelevant, edge, body);
+                self.erase_irrelevant(edge, typ);
+                self.erase_irrelevant(edge, body);
 
                 self.ctx = backup;
 
@@ -410,7 +559,7 @@ impl<'a> ErasureState<'a> {
                 if *erased {
                     body
                 } else {
-                    untyped::Expr::lambda(expr.range, param.clone(), body, *erased)
+                    untyped::Expr::lambda(expr.range, *param, body, *erased)
                 }
             }
             Let { name, val, next } => {
@@ -423,53 +572,39 @@ impl<'a> ErasureState<'a> {
 
                 self.ctx = backup;
 
-                untyped::Expr::let_(expr.range, name.clone(), val, next)
+                untyped::Expr::let_(expr.range, *name, val, next)
             }
             Fun { name, args } => {
                 self.connect_with(edge, name, ambient);
 
-                let params = &self.book.entrs.get(name.to_str()).unwrap().args;
+                let erased = self.erased_args(name);
+                let mut spine = Vec::new();
 
-                let relev = |hidden| {
-                    if hidden {
-                        Ambient::Irrelevant
+                for (arg, erased) in args.iter().zip(erased) {
+                    if erased {
+                        self.erase_irrelevant(edge, arg);
                     } else {
-                        ambient
+                        spine.push(self.erase_expr(ambient, edge, arg));
                     }
-                };
-
-                let args = params
-                    .iter()
-                    .zip(args)
-                    .map(|(param, arg)| (param, self.erase_expr(relev(param.erased), edge, arg)))
-                    .filter(|(param, _)| !param.erased)
-                    .map(|res| res.1)
-                    .collect::<Vec<_>>();
+                }
 
-                untyped::Expr::fun(expr.range, name.clone(), args)
+                untyped::Expr::fun(expr.range, *name, spine)
             }
             Ctr { name, args } => {
                 self.connect_with(edge, name, ambient);
 
-                let params = &self.book.entrs.get(name.to_str()).unwrap().args;
+                let erased = self.erased_args(name);
+                let mut spine = Vec::new();
 
-                let relev = |hidden| {
-                    if hidden {
-                        Ambient::Irrelevant
+                for (arg, erased) in args.iter().zip(erased) {
+                    if erased {
+                        self.erase_irrelevant(edge, arg);
                     } else {
-                        ambient
+                        spine.push(self.erase_expr(ambient, edge, arg));
                     }
-                };
-
-                let args = params
-                    .iter()
-                    .zip(args)
-                    .map(|(param, arg)| (param, self.erase_expr(relev(param.erased), edge, arg)))
-                    .filter(|(param, _)| !param.erased)
-                    .map(|res| res.1)
-                    .collect::<Vec<_>>();
+                }
 
-                untyped::Expr::ctr(expr.range, name.clone(), args)
+                untyped::Expr::ctr(expr.range, *name, spine)
             }
             Var { name } => {
                 let var_rev = self
@@ -481,15 +616,16 @@ impl<'a> ErasureState<'a> {
                     self.set_relevance(edge, Relevance::Irrelevant, name.range)
                 }
 
-                untyped::Expr::var(name.clone())
+                untyped::Expr::var(*name)
             }
             Ann { expr, typ } => {
                 let expr = self.erase_expr(ambient, edge, expr);
-                self.erase_expr(Ambient::Irrelevant, edge, typ);
+                self.erase_irrelevant(edge, typ);
                 expr
             }
             NumU60 { numb } => untyped::Expr::u60(expr.range, *numb),
             NumF60 { numb } => untyped::Expr::f60(expr.range, *numb),
+            NumI60 { numb } => untyped::Expr::i60(expr.range, *numb),
             Str { val } => {
                 let nil = QualifiedIdent::new_static("String.nil", None, expr.range);
                 let cons = QualifiedIdent::new_static("String.cons", None, expr.range);
@@ -503,28 +639,27 @@ impl<'a> ErasureState<'a> {
                 let mut spine = Vec::new();
 
                 for arg in args {
-                    let ambient = if arg.erased {
-                        Ambient::Irrelevant
+                    if arg.erased {
+                        self.erase_irrelevant(edge, &arg.data);
                     } else {
-                        ambient
-                    };
-
-                    let res = self.erase_expr(ambient, edge, &arg.data);
-
-                    if !arg.erased {
-                        spine.push(res)
+                        spine.push(self.erase_expr(ambient, edge, &arg.data));
                     }
                 }
 
                 untyped::Expr::app(expr.range, fun, spine)
             }
             Sub { expr, .. } => self.erase_expr(ambient, edge, expr),
-            Binary { op, left, right } => {
+            Binary {
+                op,
+                left,
+                right,
+                signed,
+            } => {
                 let left = self.erase_expr(ambient, edge, left);
                 let right = self.erase_expr(ambient, edge, right);
-                untyped::Expr::binary(expr.range, *op, left, right)
+                untyped::Expr::binary(expr.range, *op, left, right, *signed)
             }
-            Typ | NumTypeU60 { .. } | NumTypeF60 { .. } | Hole { .. } | Hlp(_) | Err => {
+            Typ | NumTypeU60 | NumTypeF60 | NumTypeI60 | Hole { .. } | Hlp(_) | Err => {
                 if ambient != Ambient::Irrelevant {
                     self.set_relevance(edge, Relevance::Irrelevant, expr.range);
                 }
@@ -533,3 +668,44 @@ impl<'a> ErasureState<'a> {
         }
     }
 }
+
+/// Collects the free variables of an expression in the order that
+/// they appear.
+fn free_vars(expr: &untyped::Expr, bound: &mut Vec<Ident>, vars: &mut Vec<Ident>) {
+    use untyped::ExprKind::*;
+    match &expr.data {
+        Var { name } => {
+            let is_new = |x: &Ident| x.to_str() != name.to_str();
+            if bound.iter().all(is_new) && vars.iter().all(is_new) {
+                vars.push(*name);
+            }
+        }
+        Lambda { param, body, .. } => {
+            bound.push(*param);
+            free_vars(body, bound, vars);
+            bound.pop();
+        }
+        App { fun, args } => {
+            free_vars(fun, bound, vars);
+            for arg in args {
+                free_vars(arg, bound, vars);
+            }
+        }
+        Fun { args, .. } | Ctr { args, .. } => {
+            for arg in args {
+                free_vars(arg, bound, vars);
+            }
+        }
+        Let { name, val, next } => {
+            free_vars(val, bound, vars);
+            bound.push(*name);
+            free_vars(next, bound, vars);
+            bound.pop();
+        }
+        Binary { left, right, .. } => {
+            free_vars(left, bound, vars);
+            free_vars(right, bound, vars);
+        }
+        U60 { .. } | F60 { .. } | Str { .. } | Err => (),
+    }
+}
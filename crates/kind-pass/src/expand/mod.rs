@@ -28,6 +28,9 @@ use crate::diagnostic::PassDiagnostic;
 /// by their module names.
 pub mod uses;
 
+/// Lifts the local definitions of the `where` blocks.
+pub mod wheres;
+
 type Derivations = FxHashMap<Derive, Range>;
 type Channel = Sender<Box<dyn Diagnostic>>;
 
@@ -187,7 +190,7 @@ pub fn expand_module(error_channel: Channel, module: &mut Module) -> bool {
 
     let mut entries = FxHashMap::default();
 
-    for entry in &module.entries {
+    for entry in &mut module.entries {
         match entry {
             TopLevel::SumType(sum) => {
                 if let Some(derive) = expand_derive(error_channel.clone(), &sum.attrs) {
@@ -203,7 +206,13 @@ pub fn expand_module(error_channel: Channel, module: &mut Module) -> bool {
                     failed = true;
                 }
             }
-            TopLevel::Entry(_) => (),
+            TopLevel::Entry(entry) => {
+                for res in wheres::expand_wheres(entry) {
+                    let info = res.extract_book_info();
+                    entries.insert(res.name.to_string(), (res, info));
+                }
+            }
+            TopLevel::Notation(_) => (),
         }
     }
 
@@ -0,0 +1,274 @@
+//! Lifts the local definitions of the `where` blocks to top level
+//! entries. The patterns of the rule that owns the block are copied
+//! to the front of each local definition, so its rules can see the
+//! same variables, and the calls inside of the rule pass the values
+//! that were matched.
+
+use fxhash::{FxHashMap, FxHashSet};
+use kind_derive::subst::{substitute_in_expr, Subst};
+use kind_tree::concrete::expr::{Binding, Expr, ExprKind, Literal};
+use kind_tree::concrete::pat::{Pat, PatIdent, PatKind};
+use kind_tree::concrete::visitor::{walk_expr, Visitor};
+use kind_tree::concrete::{Argument, Attribute, Entry, Rule};
+use kind_tree::symbol::{Ident, QualifiedIdent};
+use kind_tree::telescope::Telescope;
+
+/// Collects the names of the variables of a pattern.
+struct PatVars(FxHashSet<String>);
+
+impl Visitor for PatVars {
+    fn visit_pat_ident(&mut self, ident: &mut PatIdent) {
+        self.0.insert(ident.0.to_string());
+    }
+}
+
+/// Replaces the calls to the local definitions by calls to the lifted
+/// ones with the values of the patterns in front.
+struct Redirect<'a> {
+    names: &'a FxHashMap<String, QualifiedIdent>,
+    captured: &'a [Box<Expr>],
+}
+
+impl<'a> Visitor for Redirect<'a> {
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        if let ExprKind::Constr { name, args } = &mut expr.data {
+            if let Some(lifted) = self.names.get(&name.to_string()) {
+                *name = *lifted;
+                let captured = self.captured.iter().cloned().map(Binding::Positional);
+                args.splice(0..0, captured);
+            }
+        }
+        walk_expr(self, expr)
+    }
+}
+
+fn pat_vars(pats: &mut [Box<Pat>]) -> FxHashSet<String> {
+    let mut vars = PatVars(Default::default());
+    for pat in pats {
+        vars.visit_pat(pat);
+    }
+    vars.0
+}
+
+/// Gives a name to each wildcard so the value that it matches can
+/// be passed to the local definitions.
+fn name_holes(pat: &mut Pat, count: &mut usize) {
+    match &mut pat.data {
+        PatKind::Hole => {
+            *count += 1;
+            let name = Ident::new(format!("_where_{}", count), pat.range);
+            pat.data = PatKind::Var(PatIdent(name));
+        }
+        PatKind::App(_, args) => args.iter_mut().for_each(|x| name_holes(x, count)),
+        PatKind::Pair(fst, snd) => {
+            name_holes(fst, count);
+            name_holes(snd, count);
+        }
+        PatKind::List(args) => args.iter_mut().for_each(|x| name_holes(x, count)),
+        PatKind::Var(_)
+        | PatKind::U60(_)
+        | PatKind::U120(_)
+        | PatKind::F60(_)
+        | PatKind::Str(_)
+        | PatKind::Char(_) => (),
+    }
+}
+
+/// Writes a wildcard for each hidden argument that the rule left out,
+/// so every argument of the entry has a pattern to be passed on.
+fn fill_hidden(args: &Telescope<Argument>, rule: &mut Rule) {
+    let (hidden, _) = args.count_implicits();
+
+    if hidden == 0 || rule.pats.len() != args.len() - hidden {
+        return;
+    }
+
+    let pats = &mut rule.pats;
+    let mut written = std::mem::take(pats).into_iter();
+
+    for arg in args.iter() {
+        if arg.hidden {
+            pats.push(Box::new(Pat {
+                data: PatKind::Hole,
+                range: arg.range,
+            }));
+        } else {
+            pats.push(written.next().unwrap());
+        }
+    }
+}
+
+/// Builds the expression that has the same value that the pattern
+/// matched.
+fn pat_to_expr(pat: &Pat) -> Box<Expr> {
+    let lit = |lit| ExprKind::Lit { lit };
+
+    let data = match &pat.data {
+        PatKind::Var(PatIdent(name)) => ExprKind::Var { name: *name },
+        PatKind::App(name, args) => ExprKind::Constr {
+            name: *name,
+            args: args.iter().map(|x| Binding::Positional(pat_to_expr(x))).collect(),
+        },
+        PatKind::U60(numb) => lit(Literal::NumU60(*numb)),
+        PatKind::U120(numb) => lit(Literal::NumU120(*numb)),
+        PatKind::F60(numb) => lit(Literal::NumF60(*numb)),
+        PatKind::Str(str) => lit(Literal::String(str.clone())),
+        PatKind::Char(chr) => lit(Literal::Char(*chr)),
+        PatKind::Pair(fst, snd) => ExprKind::Pair {
+            fst: pat_to_expr(fst),
+            snd: pat_to_expr(snd),
+        },
+        PatKind::List(args) => ExprKind::List {
+            args: args.iter().map(|x| *pat_to_expr(x)).collect(),
+        },
+        PatKind::Hole => unreachable!("Internal Error: Holes should be named before"),
+    };
+
+    Box::new(Expr {
+        data,
+        range: pat.range,
+    })
+}
+
+/// Adds underscores to the names in `names` that are in `used`.
+fn fresh_names(names: &FxHashSet<String>, used: &FxHashSet<String>) -> FxHashMap<String, String> {
+    let mut renames = FxHashMap::default();
+    for name in names.iter().filter(|x| used.contains(*x)) {
+        let mut new_name = format!("{}_", name);
+        while used.contains(&new_name) || names.contains(&new_name) {
+            new_name.push('_');
+        }
+        renames.insert(name.clone(), new_name);
+    }
+    renames
+}
+
+/// The arguments of the entry that go in front of the arguments of a
+/// lifted definition. They're renamed if the definition has an
+/// argument with the same name.
+fn captured_arguments(outer: &Telescope<Argument>, local: &Entry) -> Vec<Argument> {
+    let used = local.args.iter().map(|x| x.name.to_string()).collect();
+    let names = outer.iter().map(|x| x.name.to_string()).collect();
+    let renames = fresh_names(&names, &used);
+
+    outer
+        .iter()
+        .map(|arg| {
+            let mut arg = arg.clone();
+            if let Some(new_name) = renames.get(arg.name.to_str()) {
+                arg.name = Ident::new(new_name.clone(), arg.name.range);
+            }
+            if let Some(typ) = &mut arg.typ {
+                substitute_in_expr(typ, &renames);
+            }
+            arg.hidden = false;
+            arg
+        })
+        .collect()
+}
+
+/// Removes the `where` blocks of the entry and returns the top level
+/// entries that were created from them.
+pub fn expand_wheres(entry: &mut Entry) -> Vec<Entry> {
+    let mut lifted = Vec::new();
+
+    let mut defined = FxHashMap::<String, usize>::default();
+    for rule in &entry.rules {
+        for local in &rule.wheres {
+            *defined.entry(local.name.to_string()).or_default() += 1;
+        }
+    }
+
+    for (i, rule) in entry.rules.iter_mut().enumerate() {
+        if rule.wheres.is_empty() {
+            continue;
+        }
+
+        fill_hidden(&entry.args, rule);
+
+        let mut count = 0;
+        for pat in &mut rule.pats {
+            name_holes(pat, &mut count);
+        }
+
+        let mut wheres = std::mem::take(&mut rule.wheres);
+
+        let names: FxHashMap<String, QualifiedIdent> = wheres
+            .iter()
+            .map(|local| {
+                let mut name = entry.name.add_segment("where").add_segment(local.name.to_str());
+                if defined[&local.name.to_string()] > 1 {
+                    name = name.add_segment(&i.to_string());
+                }
+                name.range = local.name.range;
+                (local.name.to_string(), name)
+            })
+            .collect();
+
+        let captured: Vec<_> = rule.pats.iter().map(|x| pat_to_expr(x)).collect();
+
+        Redirect {
+            names: &names,
+            captured: &captured,
+        }
+        .visit_expr(&mut rule.body);
+
+        let outer_vars = pat_vars(&mut rule.pats);
+
+        // The lifted definitions are only called with values that
+        // matched the rule, so the other cases cannot be covered.
+        let refutable = rule.pats.iter().any(|x| !matches!(x.data, PatKind::Var(_)));
+
+        for local in &mut wheres {
+            let mut args = captured_arguments(&entry.args, local);
+            args.extend(local.args.iter().cloned());
+            local.args = Telescope::new(args);
+
+            for local_rule in &mut local.rules {
+                // The variables of the outer rule that are shadowed by
+                // the local rule are renamed.
+                let renames = fresh_names(&outer_vars, &pat_vars(&mut local_rule.pats));
+
+                let mut pats = rule.pats.clone();
+                let mut subst = Subst {
+                    context_vars: Default::default(),
+                    names: &renames,
+                };
+                for pat in &mut pats {
+                    subst.visit_pat(pat);
+                }
+
+                let captured: Vec<_> = pats.iter().map(|x| pat_to_expr(x)).collect();
+
+                Redirect {
+                    names: &names,
+                    captured: &captured,
+                }
+                .visit_rule(local_rule);
+
+                pats.append(&mut local_rule.pats);
+                local_rule.pats = pats;
+                local_rule.name = names[&local.name.to_string()];
+            }
+
+            if refutable {
+                local.attrs.push(Attribute {
+                    name: Ident::new_static("partial", local.name.range),
+                    args: Vec::new(),
+                    value: None,
+                    range: local.name.range,
+                });
+            }
+
+            local.name = names[&local.name.to_string()];
+            local.generated_by = Some(entry.name.to_string());
+        }
+
+        for mut local in wheres {
+            lifted.extend(expand_wheres(&mut local));
+            lifted.push(local);
+        }
+    }
+
+    lifted
+}
@@ -1,17 +1,24 @@
 use fxhash::FxHashMap;
 use kind_tree::untyped;
-
-use crate::unbound::subst::subst_on_expr;
+use kind_tree::untyped::arena::{ExprArena, ExprId, NodeKind};
+use kind_tree::untyped::visitor::{self, Visitor};
 
 struct Inlinable {
     names: Vec<String>,
-    body: Box<untyped::Expr>,
+    body: ExprId,
 }
+
+/// The bodies of the inlined functions are stored in an arena so
+/// each call site shares the arguments and the parts of the body
+/// that don't change instead of copying them. Only the calls to
+/// the inlined functions are moved to the arena.
 struct InlineState {
     funs: FxHashMap<String, Inlinable>,
+    arena: ExprArena,
+    inlined: FxHashMap<ExprId, ExprId>,
 }
 
-fn inlinable(entry: &untyped::Entry) -> Option<Inlinable> {
+fn inlinable(arena: &mut ExprArena, entry: &untyped::Entry) -> Option<Inlinable> {
     if entry.rules.len() == 1 {
         let mut names = Vec::new();
         for pat in &entry.rules[0].pats {
@@ -23,7 +30,7 @@ fn inlinable(entry: &untyped::Entry) -> Option<Inlinable> {
         // TODO: Check if is recursive
         Some(Inlinable {
             names,
-            body: entry.rules[0].body.clone(),
+            body: arena.from_boxed(&entry.rules[0].body),
         })
     } else {
         None
@@ -32,69 +39,126 @@ fn inlinable(entry: &untyped::Entry) -> Option<Inlinable> {
 
 pub fn inline_book(book: &mut untyped::Book) {
     let mut funs = FxHashMap::default();
+    let mut arena = ExprArena::new();
 
     let mut to_remove = Vec::new();
 
     for entr in book.entrs.values() {
         if entr.attrs.inlined {
-            if let Some(inlinable) = inlinable(entr) {
+            if let Some(inlinable) = inlinable(&mut arena, entr) {
                 funs.insert(entr.name.to_string(), inlinable);
                 to_remove.push(entr.name.to_string());
             }
         }
     }
 
+    if funs.is_empty() {
+        return;
+    }
+
     for name in &to_remove {
         book.entrs.remove(name);
         book.names.remove(name);
     }
 
-    let mut state = InlineState { funs };
+    let mut state = InlineState {
+        funs,
+        arena,
+        inlined: Default::default(),
+    };
+
+    state.visit_book(book);
+}
+
+impl Visitor for InlineState {
+    fn visit_rule(&mut self, rule: &mut untyped::Rule) {
+        self.visit_expr(&mut rule.body)
+    }
 
-    for entr in &mut book.entrs {
-        state.inline_entry(entr.1)
+    fn visit_expr(&mut self, expr: &mut untyped::Expr) {
+        match &expr.data {
+            untyped::ExprKind::Fun { name, .. } | untyped::ExprKind::Ctr { name, .. }
+                if self.funs.contains_key(name.to_str()) =>
+            {
+                let id = self.arena.from_boxed(expr);
+                let id = self.inline_expr(id);
+                *expr = *self.arena.to_boxed(id);
+            }
+            _ => visitor::walk_expr(self, expr),
+        }
     }
 }
 
 impl InlineState {
-    fn inline_entry(&mut self, entry: &mut untyped::Entry) {
-        for rule in &mut entry.rules {
-            self.inline_expr(&mut rule.body)
-        }
+    fn inline_args(&mut self, args: &[ExprId]) -> Vec<ExprId> {
+        args.iter().map(|arg| self.inline_expr(*arg)).collect()
     }
 
-    fn inline_expr(&mut self, expr: &mut Box<untyped::Expr>) {
-        use untyped::ExprKind::*;
-        match &mut expr.data {
-            Lambda { body, .. } => self.inline_expr(body),
-            App { fun, args } => {
-                self.inline_expr(fun);
-                for arg in args {
-                    self.inline_expr(arg);
-                }
-            }
-            Fun { name, args } | Ctr { name, args } => {
-                if let Some(inlinable) = self.funs.get(name.to_str()) {
-                    let subst =
-                        FxHashMap::from_iter(inlinable.names.iter().cloned().zip(args.clone()));
-                    *expr = inlinable.body.clone();
-                    subst_on_expr(expr, subst);
-                    self.inline_expr(expr)
-                } else {
-                    for arg in args {
-                        self.inline_expr(arg);
-                    }
-                }
-            }
-            Let { val, next, .. } => {
-                self.inline_expr(val);
-                self.inline_expr(next);
-            }
-            Binary { left, right, .. } => {
-                self.inline_expr(left);
-                self.inline_expr(right);
-            }
-            _ => (),
+    fn inline_expr(&mut self, expr: ExprId) -> ExprId {
+        if let Some(res) = self.inlined.get(&expr) {
+            return *res;
         }
+
+        let node = self.arena.get(expr).clone();
+
+        let data = match node.data {
+            NodeKind::Lambda {
+                param,
+                body,
+                erased,
+            } => NodeKind::Lambda {
+                param,
+                body: self.inline_expr(body),
+                erased,
+            },
+            NodeKind::App { fun, args } => NodeKind::App {
+                fun: self.inline_expr(fun),
+                args: self.inline_args(&args),
+            },
+            NodeKind::Fun { name, args } | NodeKind::Ctr { name, args }
+                if self.funs.contains_key(name.to_str()) =>
+            {
+                let inlinable = &self.funs[name.to_str()];
+                let body = inlinable.body;
+                let subst = FxHashMap::from_iter(inlinable.names.iter().cloned().zip(args));
+                let res = self.arena.subst(body, &subst);
+                let res = self.inline_expr(res);
+                self.inlined.insert(expr, res);
+                return res;
+            }
+            NodeKind::Fun { name, args } => NodeKind::Fun {
+                name,
+                args: self.inline_args(&args),
+            },
+            NodeKind::Ctr { name, args } => NodeKind::Ctr {
+                name,
+                args: self.inline_args(&args),
+            },
+            NodeKind::Let { name, val, next } => NodeKind::Let {
+                name,
+                val: self.inline_expr(val),
+                next: self.inline_expr(next),
+            },
+            NodeKind::Binary {
+                op,
+                left,
+                right,
+                signed,
+            } => NodeKind::Binary {
+                op,
+                left: self.inline_expr(left),
+                right: self.inline_expr(right),
+                signed,
+            },
+            NodeKind::Var { .. }
+            | NodeKind::U60 { .. }
+            | NodeKind::F60 { .. }
+            | NodeKind::Str { .. }
+            | NodeKind::Err => return expr,
+        };
+
+        let res = self.arena.alloc(node.range, data);
+        self.inlined.insert(expr, res);
+        res
     }
 }
@@ -1,61 +1,136 @@
-use fxhash::FxHashMap;
+use fxhash::{FxHashMap, FxHashSet};
+use kind_tree::desugared::arena::ExprArena;
 use kind_tree::desugared::*;
+use kind_tree::symbol::Ident;
 
+/// Replaces the free variables of an expression. The expression and
+/// the values are moved into an arena first, so every occurrence of a
+/// variable shares the same value instead of cloning it.
 pub fn subst_on_expr(expr: &mut Expr, substs: FxHashMap<String, Box<Expr>>) {
-    subst(Default::default(), expr, &substs)
+    let mut arena = ExprArena::new();
+    let id = arena.from_boxed(expr);
+
+    let vars = substs
+        .iter()
+        .map(|(name, val)| (name.clone(), arena.from_boxed(val)))
+        .collect();
+
+    let res = arena.subst(id, &vars);
+    *expr = *arena.to_boxed(res);
+}
+
+
+/// Collects the names of all of the variables that appear in an
+/// expression, bound or not.
+pub fn collect_names(expr: &Expr, names: &mut FxHashSet<String>) {
+    use ExprKind::*;
+
+    match &expr.data {
+        Var { name } => {
+            names.insert(name.to_string());
+        },
+        All { param, typ, body, .. } => {
+            names.insert(param.to_string());
+            collect_names(typ, names);
+            collect_names(body, names);
+        },
+        Lambda { param, body, .. } => {
+            names.insert(param.to_string());
+            collect_names(body, names);
+        },
+        App { fun, args } => {
+            collect_names(fun, names);
+            for arg in args {
+                collect_names(&arg.data, names);
+            }
+        },
+        Fun { name: _, args } | Ctr { name: _, args } => {
+            for arg in args {
+                collect_names(arg, names);
+            }
+        },
+        Let { name, val, next } => {
+            names.insert(name.to_string());
+            collect_names(val, names);
+            collect_names(next, names);
+        },
+        Ann { expr, typ } => {
+            collect_names(expr, names);
+            collect_names(typ, names);
+        },
+        Sub { name, expr, .. } => {
+            names.insert(name.to_string());
+            collect_names(expr, names);
+        },
+        Binary { left, right, .. } => {
+            collect_names(left, names);
+            collect_names(right, names);
+        },
+        _ => ()
+    }
+}
+
+/// Gives new names to all of the binders of an expression, so a
+/// substitution inside of it can't capture the free variables of
+/// the substituted values.
+pub fn rename_binders(expr: &mut Expr, fresh: &mut impl FnMut(Ident) -> Ident) {
+    rename(Default::default(), expr, fresh)
 }
 
-fn subst(bindings: im_rc::HashSet<String>, expr: &mut Expr, substs: &FxHashMap<String, Box<Expr>>) {
+fn rename(renames: im_rc::HashMap<String, Ident>, expr: &mut Expr, fresh: &mut impl FnMut(Ident) -> Ident) {
     use ExprKind::*;
 
     match &mut expr.data {
         Var { name } => {
-            if !bindings.contains(name.to_str()) {
-                if let Some(res) = substs.get(name.to_str()) {
-                    *expr = *res.clone();
-                }
+            if let Some(new_name) = renames.get(name.to_str()) {
+                name.data = new_name.data;
             }
         },
+        Sub { name, expr, .. } => {
+            if let Some(new_name) = renames.get(name.to_str()) {
+                name.data = new_name.data;
+            }
+            rename(renames, expr, fresh);
+        },
         All { param, typ, body, .. } => {
-            subst(bindings.clone(), typ, substs);
-            let mut on_body = bindings.clone();
-            on_body.insert(param.to_string());
-            subst(on_body.clone(), body, substs);
+            rename(renames.clone(), typ, fresh);
+            let new_param = fresh(*param);
+            let on_body = renames.update(param.to_string(), new_param);
+            *param = new_param;
+            rename(on_body, body, fresh);
         },
         Lambda { param, body, .. } => {
-            let mut on_body = bindings.clone();
-            on_body.insert(param.to_string());
-            subst(on_body.clone(), body, substs);
+            let new_param = fresh(*param);
+            let on_body = renames.update(param.to_string(), new_param);
+            *param = new_param;
+            rename(on_body, body, fresh);
         },
         App { fun, args } => {
-            subst(bindings.clone(), fun, substs);
+            rename(renames.clone(), fun, fresh);
             for arg in args.iter_mut() {
-                subst(bindings.clone(), &mut arg.data, substs);
+                rename(renames.clone(), &mut arg.data, fresh);
             }
         },
         Fun { name: _, args } | Ctr { name: _, args } => {
             for arg in args.iter_mut() {
-                subst(bindings.clone(), arg, substs);
+                rename(renames.clone(), arg, fresh);
             }
         },
         Let { name, val, next } => {
-            subst(bindings.clone(), val, substs);
-            let mut on_body = bindings.clone();
-            on_body.insert(name.to_string());
-            subst(on_body.clone(), next, substs);
+            rename(renames.clone(), val, fresh);
+            let new_name = fresh(*name);
+            let on_next = renames.update(name.to_string(), new_name);
+            *name = new_name;
+            rename(on_next, next, fresh);
         },
         Ann { expr, typ } => {
-            subst(bindings.clone(), expr, substs);
-            subst(bindings.clone(), typ, substs);
-        },
-        Sub { expr, .. } => {
-            subst(bindings.clone(), expr, substs);
+            rename(renames.clone(), expr, fresh);
+            rename(renames, typ, fresh);
         },
         Binary { left, right, .. } => {
-            subst(bindings.clone(), left, substs);
-            subst(bindings, right, substs);
+            rename(renames.clone(), left, fresh);
+            rename(renames, right, fresh);
         },
         _ => ()
     }
 }
-
@@ -9,13 +9,14 @@
 use std::sync::mpsc::Sender;
 
 use fxhash::{FxHashMap, FxHashSet};
+use kind_report::cancel::CancellationToken;
 use kind_report::data::Diagnostic;
 use kind_span::Range;
 
-use kind_tree::concrete::expr::{Binding, CaseBinding, Destruct, Expr, ExprKind, SttmKind};
+use kind_tree::concrete::expr::{Binding, CaseBinding, Destruct, Expr, ExprKind, SttmKind, StrPart};
 use kind_tree::concrete::pat::{Pat, PatIdent, PatKind};
 use kind_tree::concrete::visitor::Visitor;
-use kind_tree::concrete::{Argument, Book, Entry, Module, Rule, TopLevel};
+use kind_tree::concrete::{is_private, Argument, Book, Entry, Module, Notation, Rule, TopLevel};
 use kind_tree::symbol::{Ident, QualifiedIdent};
 use kind_tree::{visit_opt, visit_vec};
 
@@ -38,12 +39,20 @@ pub struct UnboundCollector {
 
     pub unbound: FxHashMap<String, Vec<Ident>>,
     pub emit_errs: bool,
+
+    // Names marked with `#private` and the place that they were defined.
+    pub private_defs: FxHashMap<String, Range>,
+    pub failed: bool,
+
+    // Polled before visiting each top level definition.
+    pub cancel: CancellationToken,
 }
 
 impl UnboundCollector {
     pub fn new(
         diagnostic_sender: Sender<Box<dyn Diagnostic>>,
         emit_errs: bool,
+        cancel: &CancellationToken,
     ) -> UnboundCollector {
         Self {
             errors: diagnostic_sender,
@@ -54,6 +63,9 @@ impl UnboundCollector {
             record_defs: Default::default(),
             type_defs: Default::default(),
             emit_errs,
+            private_defs: Default::default(),
+            failed: false,
+            cancel: cancel.clone(),
         }
     }
 }
@@ -65,8 +77,9 @@ pub fn collect_module_info(
     diagnostic_sender: Sender<Box<dyn Diagnostic>>,
     module: &mut Module,
     emit_errs: bool,
+    cancel: &CancellationToken,
 ) -> UnboundCollector {
-    let mut state = UnboundCollector::new(diagnostic_sender.clone(), emit_errs);
+    let mut state = UnboundCollector::new(diagnostic_sender.clone(), emit_errs, cancel);
     state.visit_module(module);
 
     for idents in state.unbound.values() {
@@ -88,16 +101,53 @@ pub fn get_book_unbound(
     diagnostic_sender: Sender<Box<dyn Diagnostic>>,
     book: &mut Book,
     emit_errs: bool,
-) -> (
-    FxHashMap<String, Vec<Ident>>,
-    FxHashMap<String, FxHashSet<QualifiedIdent>>,
-) {
-    let mut state = UnboundCollector::new(diagnostic_sender, emit_errs);
+    cancel: &CancellationToken,
+) -> UnboundCollector {
+    let mut state = UnboundCollector::new(diagnostic_sender, emit_errs, cancel);
     state.visit_book(book);
-    (state.unbound, state.unbound_top_level)
+    state
 }
 
 impl UnboundCollector {
+    /// Binds the names of the fields that one of the heads of a case
+    /// matches on a scrutinee.
+    fn visit_case_head(
+        &mut self,
+        typ: &QualifiedIdent,
+        scrutinee: &Ident,
+        constructor: &Ident,
+        bindings: &mut [CaseBinding],
+    ) {
+        let mut bound = FxHashSet::default();
+
+        for binding in bindings {
+            match binding {
+                CaseBinding::Field(ident) | CaseBinding::Renamed(ident, _) => {
+                    bound.insert(ident.to_string());
+                }
+                CaseBinding::Pattern(_) => (),
+            }
+
+            match binding {
+                CaseBinding::Field(ident) | CaseBinding::Renamed(_, ident) => {
+                    self.context_vars.push((ident.range, ident.to_string()));
+                }
+                CaseBinding::Pattern(pat) => self.visit_pat(pat),
+            }
+        }
+
+        let typ_meta = self.type_defs.get(typ.to_str());
+
+        if let Some(fields) = typ_meta.and_then(|x| x.get(constructor.to_str())) {
+            for field in fields {
+                if !bound.contains(field) {
+                    let ident = format!("{}.{}", scrutinee.to_str(), field);
+                    self.context_vars.push((constructor.range, ident));
+                }
+            }
+        }
+    }
+
     fn visit_top_level_names(&mut self, toplevel: &mut TopLevel) {
         match toplevel {
             TopLevel::SumType(sum) => {
@@ -113,11 +163,19 @@ impl UnboundCollector {
                 });
                 self.type_defs.insert(sum.name.to_string(), res.collect());
 
+                if is_private(&sum.attrs) {
+                    self.private_defs.insert(sum.name.get_root(), sum.name.range);
+                }
+
                 for cons in &sum.constructors {
                     let name_cons = sum.name.add_segment(cons.name.to_str());
                     debug_assert!(name_cons.get_aux().is_none());
                     self.top_level_defs
                         .insert(name_cons.get_root(), name_cons.range);
+
+                    if is_private(&sum.attrs) || is_private(&cons.attrs) {
+                        self.private_defs.insert(name_cons.get_root(), cons.name.range);
+                    }
                 }
             }
             TopLevel::RecordType(rec) => {
@@ -144,12 +202,25 @@ impl UnboundCollector {
 
                 self.top_level_defs
                     .insert(name_cons.get_root(), name_cons.range);
+
+                if is_private(&rec.attrs) {
+                    self.private_defs.insert(rec.name.get_root(), rec.name.range);
+                }
+
+                if is_private(&rec.attrs) || is_private(&rec.cons_attrs) {
+                    self.private_defs.insert(name_cons.get_root(), rec.constructor.range);
+                }
             }
             TopLevel::Entry(entry) => {
                 debug_assert!(entry.name.get_aux().is_none());
                 self.top_level_defs
                     .insert(entry.name.get_root(), entry.name.range);
+
+                if is_private(&entry.attrs) {
+                    self.private_defs.insert(entry.name.get_root(), entry.name.range);
+                }
             }
+            TopLevel::Notation(_) => (),
         }
     }
 }
@@ -164,7 +235,7 @@ impl Visitor for UnboundCollector {
                 .unbound
                 .entry(name.to_string())
                 .or_insert_with(Vec::new);
-            entry.push(ident.clone());
+            entry.push(*ident);
         }
     }
 
@@ -172,7 +243,24 @@ impl Visitor for UnboundCollector {
         debug_assert!(ident.get_aux().is_none());
         if !self.top_level_defs.contains_key(&ident.get_root()) {
             let entry = self.unbound_top_level.entry(ident.get_root()).or_default();
-            entry.insert(ident.clone());
+            entry.insert(*ident);
+        }
+
+        // Generated names come from sugars and derivations and their
+        // ranges do not always point to the file that uses them.
+        if let Some(def) = self.private_defs.get(&ident.get_root()) {
+            if !ident.generated && def.ctx != ident.range.ctx {
+                if self.emit_errs {
+                    self.errors
+                        .send(Box::new(PassDiagnostic::PrivateDefinition(
+                            ident.to_string(),
+                            ident.range,
+                            *def,
+                        )))
+                        .unwrap()
+                }
+                self.failed = true;
+            }
         }
     }
 
@@ -295,14 +383,27 @@ impl Visitor for UnboundCollector {
                 self.context_vars = inside_vars;
             }
             TopLevel::Entry(entr) => self.visit_entry(entr),
+            TopLevel::Notation(notation) => self.visit_notation(notation),
         }
     }
 
+    fn visit_notation(&mut self, notation: &mut Notation) {
+        let vars = self.context_vars.clone();
+        self.context_vars.push((notation.fst.range, notation.fst.to_string()));
+        self.context_vars.push((notation.snd.range, notation.snd.to_string()));
+        self.visit_expr(&mut notation.body);
+        self.context_vars = vars;
+    }
+
     fn visit_module(&mut self, book: &mut kind_tree::concrete::Module) {
         for entr in &mut book.entries {
             self.visit_top_level_names(entr);
         }
         for entr in &mut book.entries {
+            if self.cancel.is_cancelled() {
+                self.failed = true;
+                return;
+            }
             self.visit_top_level(entr)
         }
     }
@@ -312,8 +413,15 @@ impl Visitor for UnboundCollector {
             self.visit_top_level_names(entr);
         }
         for entr in book.entries.values_mut() {
+            if self.cancel.is_cancelled() {
+                self.failed = true;
+                return;
+            }
             self.visit_top_level(entr)
         }
+        for (_, notation) in book.notations.iter_mut() {
+            self.visit_notation(notation)
+        }
     }
 
     fn visit_destruct(&mut self, destruct: &mut Destruct) {
@@ -337,6 +445,7 @@ impl Visitor for UnboundCollector {
             CaseBinding::Field(ident) | CaseBinding::Renamed(_, ident) => {
                 self.context_vars.push((ident.range, ident.to_string()))
             }
+            CaseBinding::Pattern(pat) => self.visit_pat(pat),
         }
     }
 
@@ -418,33 +527,34 @@ impl Visitor for UnboundCollector {
             self.visit_ident(&mut matcher.scrutinee);
         }
 
-        for case in &mut matcher.cases {
-            let vars = self.context_vars.clone();
-            let mut bound = FxHashSet::default();
-
-            for binding in &mut case.bindings {
-                match binding {
-                    CaseBinding::Field(ident) | CaseBinding::Renamed(ident, _) => {
-                        bound.insert(ident.to_string());
-                    }
-                }
-
-                match binding {
-                    CaseBinding::Field(ident) | CaseBinding::Renamed(_, ident) => {
-                        self.context_vars.push((ident.range, ident.to_string()));
-                    }
-                }
+        for other in &mut matcher.others {
+            self.visit_qualified_ident(&mut other.typ);
+            if let Some(opt) = &mut other.value {
+                self.visit_expr(opt);
+                self.context_vars
+                    .push((other.name.range, other.name.to_string()))
+            } else {
+                self.visit_ident(&mut other.name);
             }
+        }
 
-            let typ_meta = self.type_defs.get(matcher.typ.to_str());
+        for case in &mut matcher.cases {
+            let vars = self.context_vars.clone();
 
-            if let Some(fields) = typ_meta.and_then(|x| x.get(case.constructor.to_str())) {
-                for field in fields {
-                    if !bound.contains(field) {
-                        let ident = format!("{}.{}", matcher.scrutinee.to_str(), field);
-                        self.context_vars.push((case.constructor.range, ident));
-                    }
-                }
+            self.visit_case_head(
+                &matcher.typ,
+                &matcher.scrutinee,
+                &case.constructor,
+                &mut case.bindings,
+            );
+
+            for (other, head) in matcher.others.iter().zip(&mut case.others) {
+                self.visit_case_head(
+                    &other.typ,
+                    &other.name,
+                    &head.constructor,
+                    &mut head.bindings,
+                );
             }
 
             self.visit_expr(&mut case.value);
@@ -456,6 +566,12 @@ impl Visitor for UnboundCollector {
             None => (),
         }
 
+        for other in &matcher.others {
+            if other.value.is_some() {
+                self.context_vars.pop();
+            }
+        }
+
         if matcher.value.is_some() {
             self.context_vars.pop();
         }
@@ -529,6 +645,12 @@ impl Visitor for UnboundCollector {
                 self.visit_expr(fst);
                 self.visit_expr(snd);
             }
+            ExprKind::Infix { fst, rest } => {
+                self.visit_expr(fst);
+                for (_, _, expr) in rest {
+                    self.visit_expr(expr);
+                }
+            }
             ExprKind::Let { name, val, next } => {
                 self.visit_expr(val);
                 let vars = self.context_vars.clone();
@@ -578,6 +700,7 @@ impl Visitor for UnboundCollector {
                 self.visit_expr(&mut subst.expr)
             }
             ExprKind::Hole => {}
+            ExprKind::Error => {}
             ExprKind::Do { typ, sttm } => {
                 self.visit_qualified_ident(&mut typ.add_segment("pure").to_generated());
                 self.visit_qualified_ident(&mut typ.add_segment("bind").to_generated());
@@ -605,6 +728,20 @@ impl Visitor for UnboundCollector {
 
                 visit_vec!(args.iter_mut(), arg => self.visit_expr(arg));
             }
+            ExprKind::Interpolation { parts } => {
+                let mut typ = QualifiedIdent::new_static("String", None, expr.range);
+
+                self.visit_qualified_ident(&mut typ);
+                self.visit_qualified_ident(&mut typ.add_segment("cons").to_generated());
+                self.visit_qualified_ident(&mut typ.add_segment("nil").to_generated());
+                self.visit_qualified_ident(&mut typ.add_segment("concat").to_generated());
+
+                for part in parts {
+                    if let StrPart::Expr(expr) = part {
+                        self.visit_expr(expr);
+                    }
+                }
+            }
             ExprKind::Open {
                 type_name,
                 var_name,
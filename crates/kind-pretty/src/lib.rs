@@ -0,0 +1,215 @@
+//! A pretty printer based on "A prettier printer" by Philip Wadler.
+//! A [Doc] describes all of the ways that a tree can be laid out
+//! and [Doc::render] chooses the one that fits in the width.
+
+use unicode_width::UnicodeWidthStr;
+
+/// Width used when nothing else is configured.
+pub const DEFAULT_WIDTH: usize = 80;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Doc {
+    Nil,
+    Text(String),
+    /// A line break that is rendered as the string when
+    /// the group that contains it fits in the line.
+    Line(&'static str),
+    /// A line break that is never flattened.
+    HardLine,
+    Nest(usize, Box<Doc>),
+    /// Indents the lines inside of it up to the current column.
+    Align(Box<Doc>),
+    Concat(Vec<Doc>),
+    /// Tries to render everything inside of it in a single line.
+    Group(Box<Doc>),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+type Cmd<'a> = (usize, Mode, &'a Doc);
+
+impl Doc {
+    pub fn text(text: impl Into<String>) -> Doc {
+        Doc::Text(text.into())
+    }
+
+    /// A space or a line break.
+    pub fn line() -> Doc {
+        Doc::Line(" ")
+    }
+
+    /// Nothing or a line break.
+    pub fn softline() -> Doc {
+        Doc::Line("")
+    }
+
+    pub fn concat(docs: impl IntoIterator<Item = Doc>) -> Doc {
+        Doc::Concat(docs.into_iter().collect())
+    }
+
+    pub fn join(docs: impl IntoIterator<Item = Doc>, sep: Doc) -> Doc {
+        let mut res = Vec::new();
+        for (i, doc) in docs.into_iter().enumerate() {
+            if i != 0 {
+                res.push(sep.clone());
+            }
+            res.push(doc);
+        }
+        Doc::Concat(res)
+    }
+
+    pub fn append(self, other: Doc) -> Doc {
+        match self {
+            Doc::Concat(mut docs) => {
+                docs.push(other);
+                Doc::Concat(docs)
+            }
+            _ => Doc::Concat(vec![self, other]),
+        }
+    }
+
+    pub fn nest(self, indent: usize) -> Doc {
+        Doc::Nest(indent, Box::new(self))
+    }
+
+    pub fn align(self) -> Doc {
+        Doc::Align(Box::new(self))
+    }
+
+    pub fn group(self) -> Doc {
+        Doc::Group(Box::new(self))
+    }
+
+    /// Lays out a parenthesized application like `(f a b)`. When it
+    /// does not fit, each one of the arguments goes to its own line
+    /// and is indented from the parenthesis.
+    pub fn app(head: Doc, args: impl IntoIterator<Item = Doc>) -> Doc {
+        let args = args.into_iter().map(|arg| Doc::line().append(arg));
+        Doc::concat([
+            Doc::text("("),
+            head,
+            Doc::concat(args).nest(2),
+            Doc::text(")"),
+        ])
+        .align()
+        .group()
+    }
+
+    /// Lays out things like `(x => body)` or `(a + b)` where the last
+    /// part goes to the next line when it's too big.
+    pub fn binder(head: Doc, body: Doc) -> Doc {
+        Doc::concat([
+            Doc::text("("),
+            head,
+            Doc::concat([Doc::line(), body]).nest(2).group(),
+            Doc::text(")"),
+        ])
+        .align()
+    }
+
+    pub fn render(&self, width: usize) -> String {
+        self.render_from(width, 0)
+    }
+
+    /// Renders the document as if it started at `column`. The lines
+    /// after the first one are indented up to that column.
+    pub fn render_from(&self, width: usize, column: usize) -> String {
+        let mut out = String::new();
+        let mut col = column;
+        let mut stack: Vec<Cmd> = vec![(column, Mode::Break, self)];
+
+        while let Some((indent, mode, doc)) = stack.pop() {
+            match doc {
+                Doc::Nil => (),
+                Doc::Text(text) => {
+                    out.push_str(text);
+                    col = match text.rfind('\n') {
+                        Some(pos) => text[pos + 1..].width(),
+                        None => col + text.width(),
+                    };
+                }
+                Doc::Line(flat) if mode == Mode::Flat => {
+                    out.push_str(flat);
+                    col += flat.width();
+                }
+                Doc::Line(_) | Doc::HardLine => {
+                    out.push('\n');
+                    out.push_str(&" ".repeat(indent));
+                    col = indent;
+                }
+                Doc::Nest(more, doc) => stack.push((indent + more, mode, doc)),
+                Doc::Align(doc) => stack.push((col, mode, doc)),
+                Doc::Concat(docs) => {
+                    for doc in docs.iter().rev() {
+                        stack.push((indent, mode, doc));
+                    }
+                }
+                Doc::Group(doc) => {
+                    let remaining = width as isize - col as isize;
+                    let mode = if mode == Mode::Flat
+                        || fits(remaining, (indent, Mode::Flat, doc), &stack)
+                    {
+                        Mode::Flat
+                    } else {
+                        Mode::Break
+                    };
+                    stack.push((indent, mode, doc));
+                }
+            }
+        }
+
+        out
+    }
+}
+
+/// Checks if everything until the next line break fits in the
+/// remaining width. The `rest` are the commands that come after
+/// the group.
+fn fits(mut remaining: isize, next: Cmd, rest: &[Cmd]) -> bool {
+    let mut rest = rest.iter().rev();
+    let mut cmds = vec![next];
+
+    while remaining >= 0 {
+        let (indent, mode, doc) = match cmds.pop() {
+            Some(cmd) => cmd,
+            None => match rest.next() {
+                Some(cmd) => *cmd,
+                None => return true,
+            },
+        };
+
+        match doc {
+            Doc::Nil => (),
+            Doc::Text(text) => match text.find('\n') {
+                Some(pos) => return text[..pos].width() as isize <= remaining,
+                None => remaining -= text.width() as isize,
+            },
+            Doc::Line(flat) if mode == Mode::Flat => remaining -= flat.width() as isize,
+            Doc::Line(_) => return true,
+            Doc::HardLine => return mode == Mode::Break,
+            Doc::Nest(more, doc) => cmds.push((indent + more, mode, doc)),
+            Doc::Align(doc) => cmds.push((indent, mode, doc)),
+            Doc::Concat(docs) => {
+                for doc in docs.iter().rev() {
+                    cmds.push((indent, mode, doc));
+                }
+            }
+            Doc::Group(doc) => cmds.push((indent, mode, doc)),
+        }
+    }
+
+    false
+}
+
+/// Things that can be turned into a [Doc].
+pub trait Pretty {
+    fn to_doc(&self) -> Doc;
+
+    fn pretty(&self, width: usize) -> String {
+        self.to_doc().render(width)
+    }
+}
@@ -2,14 +2,16 @@ use std::time::Duration;
 
 use kind_span::{Range, SyntaxCtxIndex};
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+use kind_pretty::Doc;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Severity {
     Error,
     Warning,
     Info,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Color {
     Fst,
     Snd,
@@ -18,15 +20,17 @@ pub enum Color {
     Fft,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Word {
     Dimmed(String),
     White(String),
     Normal(String),
     Painted(Color, String),
+    /// A term that is pretty printed in the rest of the line.
+    Pretty(Color, Doc),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Subtitle {
     Normal(Color, String),
     Bold(Color, String),
@@ -34,7 +38,7 @@ pub enum Subtitle {
     LineBreak,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Marker {
     pub position: Range,
     pub color: Color,
@@ -43,7 +47,7 @@ pub struct Marker {
     pub main: bool,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct DiagnosticFrame {
     pub code: u32,
     pub severity: Severity,
@@ -58,6 +62,7 @@ pub enum Log {
     Compiled(Duration),
     Rewrites(u64),
     Failed(Duration),
+    Omitted(usize),
 }
 pub trait Diagnostic {
     fn get_syntax_ctx(&self) -> Option<SyntaxCtxIndex>;
@@ -1,5 +1,7 @@
 use yansi::Paint;
 
+/// Cancellation of compilations
+pub mod cancel;
 /// Data structures
 pub mod data;
 /// Render
@@ -48,6 +50,8 @@ impl Chars {
 pub struct RenderConfig<'a> {
     pub chars: &'a Chars,
     pub indent: usize,
+    /// Width used to pretty print the terms.
+    pub width: usize,
 }
 
 impl<'a> RenderConfig<'a> {
@@ -55,12 +59,14 @@ impl<'a> RenderConfig<'a> {
         RenderConfig {
             chars: Chars::unicode(),
             indent,
+            width: kind_pretty::DEFAULT_WIDTH,
         }
     }
     pub fn ascii(indent: usize) -> RenderConfig<'a> {
         RenderConfig {
             chars: Chars::ascii(),
             indent,
+            width: kind_pretty::DEFAULT_WIDTH,
         }
     }
 }
@@ -10,7 +10,7 @@ use std::path::{Path, PathBuf};
 use std::str;
 
 use fxhash::{FxHashMap, FxHashSet};
-use kind_span::{Pos, SyntaxCtxIndex};
+use kind_span::{Pos, Range, SyntaxCtxIndex};
 use unicode_width::UnicodeWidthStr;
 use yansi::Paint;
 
@@ -81,6 +81,15 @@ fn find_in_line_guide(pos: Pos, guide: &Vec<usize>) -> Point {
     }
 }
 
+/// The column after writing `text` from `column`. A text with many
+/// lines ends at the width of its last line.
+fn column_after(column: usize, text: &str) -> usize {
+    match text.rfind('\n') {
+        Some(pos) => text[pos + 1..].width(),
+        None => column + text.width(),
+    }
+}
+
 // Get color
 fn get_colorizer<T>(color: &Color) -> &dyn Fn(T) -> Paint<T> {
     match color {
@@ -396,6 +405,104 @@ fn render_tag<T: Write + Sized>(severity: &Severity, fmt: &mut T) -> std::fmt::R
     )
 }
 
+/// A position in a file that is shown like `path:line:column`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    pub path: PathBuf,
+    /// Starts at one.
+    pub line: usize,
+    /// Starts at one.
+    pub column: usize,
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}", self.path.display(), self.line, self.column)
+    }
+}
+
+/// Finds the line and column of ranges. The lines of each file
+/// are only computed once.
+pub struct Locator<'a, C: FileCache> {
+    cache: &'a C,
+    guides: FxHashMap<SyntaxCtxIndex, Vec<usize>>,
+    base: Option<PathBuf>,
+}
+
+impl<'a, C: FileCache> Locator<'a, C> {
+    pub fn new(cache: &'a C) -> Self {
+        Locator {
+            cache,
+            guides: Default::default(),
+            base: PathBuf::from(".").canonicalize().ok(),
+        }
+    }
+
+    /// The location of the start of the range. The path is relative
+    /// to the current directory like in the error messages.
+    pub fn locate(&mut self, range: Range) -> Option<Location> {
+        let (file, code) = self.cache.fetch(range.ctx)?;
+        let guide = self
+            .guides
+            .entry(range.ctx)
+            .or_insert_with(|| get_code_line_guide(code));
+        let point = find_in_line_guide(range.start, guide);
+        let path = match &self.base {
+            Some(base) => pathdiff::diff_paths(&file, base).unwrap_or(file),
+            None => file,
+        };
+        Some(Location {
+            path,
+            line: point.line + 1,
+            column: point.column + 1,
+        })
+    }
+}
+
+/// Sorts the diagnostics by file and position (the ones that do not
+/// point to any file come first), removes the ones that generate the
+/// same frame and keeps only the first `limit` of them. It returns the
+/// number of diagnostics that were removed because of the limit.
+pub fn organize_diagnostics<C: FileCache>(
+    cache: &C,
+    diagnostics: &mut Vec<Box<dyn Diagnostic>>,
+    limit: Option<usize>,
+) -> usize {
+    let mut seen = FxHashSet::default();
+    let mut keyed = Vec::new();
+
+    for diagnostic in diagnostics.drain(..) {
+        let frame = diagnostic.to_diagnostic_frame();
+
+        let main = frame
+            .positions
+            .iter()
+            .find(|x| x.main)
+            .or_else(|| frame.positions.first());
+
+        let key = main.map(|marker| {
+            let path = cache.fetch(marker.position.ctx).map(|x| x.0);
+            (path, marker.position.start, marker.position.end)
+        });
+
+        if seen.insert(frame) {
+            keyed.push((key, diagnostic));
+        }
+    }
+
+    keyed.sort_by(|x, y| x.0.cmp(&y.0));
+
+    let omitted = match limit {
+        Some(limit) if keyed.len() > limit => keyed.len() - limit,
+        _ => 0,
+    };
+
+    keyed.truncate(keyed.len() - omitted);
+    diagnostics.extend(keyed.into_iter().map(|x| x.1));
+
+    omitted
+}
+
 pub trait Report {
     fn render<T: Write + Sized, C: FileCache>(
         &self,
@@ -448,6 +555,11 @@ impl Report for Box<dyn Diagnostic> {
                 Subtitle::Phrase(color, words) => {
                     let colorizer = get_colorizer(color);
                     write!(fmt, "{:>5} {} ", "", colorizer(config.chars.bullet))?;
+
+                    // The column is used to indent the lines of the pretty
+                    // printed terms.
+                    let mut column = 8;
+
                     for word in words {
                         match word {
                             Word::Normal(str) => write!(fmt, "{} ", Paint::new(str))?,
@@ -457,7 +569,23 @@ impl Report for Box<dyn Diagnostic> {
                                 let colorizer = get_colorizer(color);
                                 write!(fmt, "{} ", colorizer(str))?
                             }
+                            Word::Pretty(color, doc) => {
+                                let colorizer = get_colorizer(color);
+                                let str = doc.render_from(config.width, column);
+                                write!(fmt, "{} ", colorizer(&str))?;
+                                column = column_after(column, &str);
+                            }
                         }
+
+                        if let Word::Normal(str)
+                        | Word::Dimmed(str)
+                        | Word::White(str)
+                        | Word::Painted(_, str) = word
+                        {
+                            column += str.width();
+                        }
+
+                        column += 1;
                     }
                     writeln!(fmt)?;
                 }
@@ -539,6 +667,14 @@ impl Report for Log {
                     duration.as_secs()
                 )
             }
+            Log::Omitted(count) => {
+                writeln!(
+                    fmt,
+                    "   {} {} more diagnostics were omitted",
+                    Paint::new(" OMITTED ").bg(yansi::Color::Yellow).bold(),
+                    count
+                )
+            }
             Log::Rewrites(u64) => {
                 writeln!(
                     fmt,
@@ -550,3 +686,14 @@ impl Report for Log {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::column_after;
+
+    #[test]
+    fn counts_the_columns_of_the_last_line() {
+        assert_eq!(column_after(8, "List Nat"), 16);
+        assert_eq!(column_after(8, "(Pair\n    Nat)"), 8);
+    }
+}
@@ -1,11 +1,16 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
 /// Position in a syntax context.
-#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Serialize)]
+#[serde(transparent)]
 pub struct Pos {
     pub index: u32,
 }
 
 /// A syntax context index.
-#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, Serialize)]
 pub struct SyntaxCtxIndex(pub usize);
 
 impl SyntaxCtxIndex {
@@ -15,13 +20,13 @@ impl SyntaxCtxIndex {
 }
 
 /// A span in the encoded format that is required by
-/// kind2.
+/// kind2. It's the index of the range inside of a [RangeTable].
 #[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Debug)]
 pub struct EncodedRange(pub u64);
 
 /// Describes a position in a source code (syntax context). It's useful
 /// to generate error messages.
-#[derive(Clone, Debug, Copy, Hash, PartialEq, Eq)]
+#[derive(Clone, Debug, Copy, Hash, PartialEq, Eq, Serialize)]
 pub struct Range {
     pub start: Pos,
     pub end: Pos,
@@ -62,28 +67,90 @@ impl Range {
             ctx,
         }
     }
+}
 
-    #[inline]
-    pub fn encode(&self) -> EncodedRange {
-        EncodedRange(
-            ((self.ctx.0 as u64) << 48)
-                | ((self.start.index as u64) & 0xFFFFFF)
-                | (((self.end.index as u64) & 0xFFFFFF) << 24),
-        )
+/// Keeps all of the ranges that were sent to the type checker so
+/// they can be recovered from the [EncodedRange] in the answer. The
+/// index zero is always the ghost range because the checker uses it
+/// for the terms that it creates.
+#[derive(Clone, Debug)]
+pub struct RangeTable {
+    ranges: Vec<Range>,
+    ids: HashMap<Range, EncodedRange>,
+}
+
+impl Default for RangeTable {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-impl EncodedRange {
-    /// Transforms a encoded span back into a range.
-    pub fn to_range(&self) -> Range {
-        Range {
-            ctx: SyntaxCtxIndex((self.0 >> 48) as usize),
-            start: Pos {
-                index: (self.0 & 0xFFFFFF) as u32,
-            },
-            end: Pos {
-                index: ((self.0 >> 24) & 0xFFFFFF) as u32,
-            },
+impl RangeTable {
+    pub fn new() -> RangeTable {
+        let ghost = Range::ghost_range();
+        RangeTable {
+            ranges: vec![ghost],
+            ids: HashMap::from([(ghost, EncodedRange(0))]),
+        }
+    }
+
+    /// Gets the id of a range. The same range always gets
+    /// the same id.
+    pub fn encode(&mut self, range: Range) -> EncodedRange {
+        let next = EncodedRange(self.ranges.len() as u64);
+        let id = *self.ids.entry(range).or_insert(next);
+        if id == next {
+            self.ranges.push(range);
         }
+        id
+    }
+
+    /// Transforms a encoded span back into a range. Ids that were
+    /// not generated by this table are turned into ghost ranges.
+    pub fn decode(&self, encoded: EncodedRange) -> Range {
+        usize::try_from(encoded.0)
+            .ok()
+            .and_then(|index| self.ranges.get(index))
+            .copied()
+            .unwrap_or_else(Range::ghost_range)
+    }
+
+    /// The ranges that were encoded with their ids, without the
+    /// ghost range.
+    pub fn iter(&self) -> impl Iterator<Item = (EncodedRange, Range)> + '_ {
+        self.ranges
+            .iter()
+            .enumerate()
+            .skip(1)
+            .map(|(id, range)| (EncodedRange(id as u64), *range))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_table_round_trip() {
+        // Positions and contexts that do not fit in the old bit packing.
+        let ranges: Vec<Range> = (0..1000)
+            .map(|i| {
+                let start = Pos { index: (1 << 24) + i * 97 };
+                let end = Pos { index: u32::MAX - i };
+                Range::new(start, end, SyntaxCtxIndex::new((1 << 16) + i as usize))
+            })
+            .collect();
+
+        let mut table = RangeTable::new();
+        let encoded: Vec<_> = ranges.iter().map(|x| table.encode(*x)).collect();
+
+        for (range, id) in ranges.iter().zip(&encoded) {
+            assert_eq!(table.encode(*range), *id);
+            assert_eq!(table.decode(*id), *range);
+        }
+
+        assert_eq!(table.encode(Range::ghost_range()).0, 0);
+        assert_eq!(table.iter().count(), ranges.len());
     }
 }
+
@@ -1,18 +1,44 @@
 use hvm::u60;
 
-use kind_tree::untyped;
+use kind_span::Range;
+use kind_tree::{untyped, Operator};
 
 use hvm::syntax::{File, Rule, Term};
 
+const I60_SIGN: u64 = 0x800000000000000;
+
+/// Maps the generated HVM code back to the Kind source. It's not
+/// stored in [File::smaps] because HVM uses them for the strictness
+/// of the arguments.
+#[derive(Clone, Debug, Default)]
+pub struct SourceMap {
+    /// The range of the Kind rule (or entry) that generated each
+    /// one of the rules of the [File], in the same order.
+    pub rules: Vec<Range>,
+}
+
+impl SourceMap {
+    pub fn rule(&self, index: usize) -> Option<Range> {
+        self.rules.get(index).copied()
+    }
+}
+
 pub fn compile_book(book: untyped::Book, trace: bool) -> File {
+    compile_book_with_source_map(book, trace).0
+}
+
+/// Compiles the book and keeps the location of everything that
+/// was generated.
+pub fn compile_book_with_source_map(book: untyped::Book, trace: bool) -> (File, SourceMap) {
     let mut file = File {
         rules: Default::default(),
         smaps: Default::default(),
     };
+    let mut map = SourceMap::default();
     for (_, entry) in book.entrs {
-        compile_entry(&mut file, entry, trace);
+        compile_entry(&mut file, &mut map, *entry, trace);
     }
-    file
+    (file, map)
 }
 
 pub fn compile_str(val: &str) -> Box<Term> {
@@ -60,15 +86,81 @@ pub fn compile_term(expr: &untyped::Expr) -> Box<Term> {
             numb: u60::new(*numb),
         }),
         F60 { numb: _ } => todo!(),
-        Binary { op, left, right } => Box::new(Term::Ctr {
-            name: op.to_string(),
-            args: vec![compile_term(left), compile_term(right)],
-        }),
+        Binary {
+            op,
+            left,
+            right,
+            signed: false,
+        } => op2(*op, compile_term(left), compile_term(right)),
+        Binary {
+            op,
+            left,
+            right,
+            signed: true,
+        } => compile_signed_binary(*op, compile_term(left), compile_term(right)),
         Str { val } => compile_str(val),
         Err => unreachable!("Internal Error: 'ERR' cannot be a relevant term"),
     }
 }
 
+fn op2(op: Operator, left: Box<Term>, right: Box<Term>) -> Box<Term> {
+    Box::new(Term::Ctr {
+        name: op.to_string(),
+        args: vec![left, right],
+    })
+}
+
+/// Compiles an operation on signed numbers, that are stored as the
+/// two's complement of 60 bits. The operations that need the value
+/// of an operand more than once bind them with lambdas.
+fn compile_signed_binary(op: Operator, left: Box<Term>, right: Box<Term>) -> Box<Term> {
+    let num = |numb| Box::new(Term::U6O { numb });
+    let var = |name: &str| Box::new(Term::Var { name: name.to_string() });
+
+    // All the bits are set if the number is negative.
+    let mask = |x| op2(Operator::Sub, num(0), op2(Operator::Shr, x, num(59)));
+
+    // Negates the number if the mask has all the bits set.
+    let negate_if = |x, mask: Box<Term>| op2(Operator::Sub, op2(Operator::Xor, x, mask.clone()), mask);
+
+    let bind = |body, left, right| {
+        let func = Box::new(Term::Lam {
+            name: "a".to_string(),
+            body: Box::new(Term::Lam {
+                name: "b".to_string(),
+                body,
+            }),
+        });
+        let func = Box::new(Term::App { func, argm: left });
+        Box::new(Term::App { func, argm: right })
+    };
+
+    match op {
+        // Flipping the sign bit keeps the order of the numbers.
+        Operator::Ltn | Operator::Lte | Operator::Gte | Operator::Gtn => op2(
+            op,
+            op2(Operator::Xor, left, num(I60_SIGN)),
+            op2(Operator::Xor, right, num(I60_SIGN)),
+        ),
+        Operator::Div | Operator::Mod => {
+            let abs = |x| negate_if(var(x), mask(var(x)));
+            let res = op2(op, abs("a"), abs("b"));
+            let sign = if op == Operator::Div {
+                op2(Operator::Xor, mask(var("a")), mask(var("b")))
+            } else {
+                mask(var("a"))
+            };
+            bind(negate_if(res, sign), left, right)
+        }
+        Operator::Shr => {
+            let shifted = op2(Operator::Shr, op2(Operator::Xor, var("a"), mask(var("a"))), var("b"));
+            bind(op2(Operator::Xor, shifted, mask(var("a"))), left, right)
+        }
+        // The other operations are the same for the two's complement.
+        _ => op2(op, left, right),
+    }
+}
+
 fn compile_rule(name: String, rule: untyped::Rule) -> Rule {
     Rule {
         lhs: Box::new(Term::Ctr {
@@ -79,12 +171,16 @@ fn compile_rule(name: String, rule: untyped::Rule) -> Rule {
     }
 }
 
-fn compile_entry(file: &mut File, entry: Box<untyped::Entry>, trace: bool) {
+fn compile_entry(file: &mut File, map: &mut SourceMap, entry: untyped::Entry, trace: bool) {
+    let name = entry.name.to_string();
+
     if entry.attrs.trace.is_some() || trace {
         let _with_args = entry.attrs.trace.unwrap_or(false);
 
         let name_trace = format!("{}__trace", entry.name);
+
         for rule in entry.rules {
+            map.rules.push(rule.range);
             file.rules.push(compile_rule(name_trace.clone(), rule))
         }
 
@@ -99,9 +195,10 @@ fn compile_entry(file: &mut File, entry: Box<untyped::Entry>, trace: bool) {
             })
             .collect::<Vec<_>>();
 
+        map.rules.push(entry.range);
         file.rules.push(Rule {
             lhs: Box::new(Term::Ctr {
-                name: entry.name.to_string(),
+                name,
                 args: args.clone(),
             }),
             rhs: Box::new(Term::Ctr {
@@ -116,8 +213,8 @@ fn compile_entry(file: &mut File, entry: Box<untyped::Entry>, trace: bool) {
             }),
         })
     } else {
-        let name = entry.name.to_string();
         for rule in entry.rules {
+            map.rules.push(rule.range);
             file.rules.push(compile_rule(name.clone(), rule))
         }
     }
@@ -13,6 +13,7 @@ use crate::{diagnostic::KdlDiagnostic, GenericCompilationToHVMError};
 
 pub const KDL_NAME_LEN: usize = 12;
 const U60_MAX: kdl::U120 = kdl::U120(0xFFFFFFFFFFFFFFF);
+const I60_SIGN: u128 = 0x800000000000000;
 
 fn char_to_code(chr: char) -> Result<u128, String> {
     let num = match chr {
@@ -115,7 +116,7 @@ fn name_shortener(name: &QualifiedIdent, namespace: &str) -> QualifiedIdent {
         let name_hash = u128_to_kdl_name(name_hash);
         QualifiedIdent::new_static(&name_hash[..max_fn_name], None, name.range)
     } else {
-        name.clone()
+        *name
     }
 }
 
@@ -130,7 +131,6 @@ pub fn compile_book(
         let new_name = entry
             .attrs
             .kdl_name
-            .clone()
             .map(|x| x.to_string())
             .unwrap_or_else(|| name_shortener(&entry.name, namespace).to_string());
 
@@ -186,45 +186,18 @@ pub fn compile_expr(ctx: &mut CompileCtx, expr: &untyped::Expr) -> kdl::Term {
             }
             expr
         }
-        From::Binary { op, left, right } => {
-            use kind_tree::Operator as Op;
-            let oper = compile_oper(op);
-            match op {
-                // These operations occupy more bits on overflow
-                // So we truncate them
-                Op::Add | Op::Sub | Op::Mul => {
-                    let val0 = Box::new(compile_expr(ctx, left));
-                    let val1 = Box::new(compile_expr(ctx, right));
-                    let expr = Box::new(To::Op2 { oper, val0, val1 });
-                    let trunc = Box::new(To::Num { numb: U60_MAX });
-                    To::Op2 {
-                        oper: kdl::Oper::And,
-                        val0: expr,
-                        val1: trunc,
-                    }
-                }
-                // These operations need to wrap around every 60 bits
-                // Eg: (<< n 60) = n
-                Op::Shl | Op::Shr => {
-                    let val0 = Box::new(compile_expr(ctx, left));
-                    let right = Box::new(compile_expr(ctx, right));
-                    let sixty = Box::new(To::Num {
-                        numb: kdl::U120(60),
-                    });
-                    let val1 = Box::new(To::Op2 {
-                        oper: kdl::Oper::Mod,
-                        val0: right,
-                        val1: sixty,
-                    });
-                    To::Op2 { oper, val0, val1 }
-                }
-                // Other operations don't overflow
-                // Div, Mod, And, Or, Xor, Eql, Neq, Gtn, Gte, Ltn, Lte
-                _ => {
-                    let val0 = Box::new(compile_expr(ctx, left));
-                    let val1 = Box::new(compile_expr(ctx, right));
-                    To::Op2 { oper, val0, val1 }
-                }
+        From::Binary {
+            op,
+            left,
+            right,
+            signed,
+        } => {
+            let val0 = compile_expr(ctx, left);
+            let val1 = compile_expr(ctx, right);
+            if *signed {
+                compile_signed_binary(op, val0, val1)
+            } else {
+                compile_binary(op, val0, val1)
             }
         }
         From::Ctr { name, args } => {
@@ -560,6 +533,103 @@ impl Display for File {
     }
 }
 
+fn op2(oper: kdl::Oper, val0: kdl::Term, val1: kdl::Term) -> kdl::Term {
+    kdl::Term::Op2 {
+        oper,
+        val0: Box::new(val0),
+        val1: Box::new(val1),
+    }
+}
+
+fn num(numb: u128) -> kdl::Term {
+    kdl::Term::Num {
+        numb: kdl::U120(numb),
+    }
+}
+
+fn compile_binary(op: &kind_tree::Operator, val0: kdl::Term, val1: kdl::Term) -> kdl::Term {
+    use kind_tree::Operator as Op;
+    let oper = compile_oper(op);
+    match op {
+        // These operations occupy more bits on overflow
+        // So we truncate them
+        Op::Add | Op::Sub | Op::Mul => op2(kdl::Oper::And, op2(oper, val0, val1), num(U60_MAX.0)),
+        // These operations need to wrap around every 60 bits
+        // Eg: (<< n 60) = n
+        Op::Shl | Op::Shr => op2(oper, val0, op2(kdl::Oper::Mod, val1, num(60))),
+        // Other operations don't overflow
+        // Div, Mod, And, Or, Xor, Eql, Neq, Gtn, Gte, Ltn, Lte
+        _ => op2(oper, val0, val1),
+    }
+}
+
+/// Compiles an operation on signed numbers, that are stored as the
+/// two's complement of 60 bits. The operations that need the value
+/// of an operand more than once bind them with lambdas.
+fn compile_signed_binary(op: &kind_tree::Operator, val0: kdl::Term, val1: kdl::Term) -> kdl::Term {
+    use kdl::Oper;
+    use kind_tree::Operator as Op;
+
+    let var = |name: &str| kdl::Term::Var {
+        name: Name::from_str_unsafe(name),
+    };
+
+    // All the bits are set if the number is negative.
+    let mask = |x| op2(Oper::And, op2(Oper::Sub, num(0), op2(Oper::Shr, x, num(59))), num(U60_MAX.0));
+
+    // Negates the number if the mask has all the bits set.
+    let negate_if = |x, mask: kdl::Term| {
+        op2(Oper::And, op2(Oper::Sub, op2(Oper::Xor, x, mask.clone()), mask), num(U60_MAX.0))
+    };
+
+    let bind = |body| {
+        let body = Box::new(kdl::Term::Lam {
+            name: Name::from_str_unsafe("b"),
+            body: Box::new(body),
+        });
+        let func = Box::new(kdl::Term::Lam {
+            name: Name::from_str_unsafe("a"),
+            body,
+        });
+        kdl::Term::App {
+            func: Box::new(kdl::Term::App {
+                func,
+                argm: Box::new(val0.clone()),
+            }),
+            argm: Box::new(val1.clone()),
+        }
+    };
+
+    match op {
+        // Flipping the sign bit keeps the order of the numbers.
+        Op::Ltn | Op::Lte | Op::Gte | Op::Gtn => op2(
+            compile_oper(op),
+            op2(Oper::Xor, val0.clone(), num(I60_SIGN)),
+            op2(Oper::Xor, val1.clone(), num(I60_SIGN)),
+        ),
+        Op::Div | Op::Mod => {
+            let abs = |x: &str| negate_if(var(x), mask(var(x)));
+            let res = op2(compile_oper(op), abs("a"), abs("b"));
+            let sign = if *op == Op::Div {
+                op2(Oper::Xor, mask(var("a")), mask(var("b")))
+            } else {
+                mask(var("a"))
+            };
+            bind(negate_if(res, sign))
+        }
+        Op::Shr => {
+            let shifted = op2(
+                Oper::Shr,
+                op2(Oper::Xor, var("a"), mask(var("a"))),
+                op2(Oper::Mod, var("b"), num(60)),
+            );
+            bind(op2(Oper::Xor, shifted, mask(var("a"))))
+        }
+        // The other operations are the same for the two's complement.
+        _ => compile_binary(op, val0.clone(), val1.clone()),
+    }
+}
+
 fn compile_oper(oper: &kind_tree::Operator) -> kdl::Oper {
     use kdl::Oper as To;
     use kind_tree::Operator as From;
@@ -1,6 +1,7 @@
 use fxhash::{FxHashMap, FxHashSet};
 use kind_span::Range;
 use kind_tree::symbol::{Ident, QualifiedIdent};
+use kind_tree::untyped::fold::Fold;
 use kind_tree::untyped::{self, Book, Entry, Expr, ExprKind, Rule};
 use linked_hash_map::LinkedHashMap;
 
@@ -80,7 +81,7 @@ fn split_rule(
     i: usize,
     name_count: &mut u64,
     skip: &mut FxHashSet<usize>,
-) -> (Rule, Vec<Entry>) {
+) -> (Rule, Entry) {
     let num = *name_count;
     *name_count += 1;
 
@@ -103,7 +104,7 @@ fn split_rule(
         match &pat.data {
             ExprKind::Var { name } => {
                 old_rule_pats.push(pat.clone());
-                old_rule_body_args.push(Expr::var(name.clone()));
+                old_rule_body_args.push(Expr::var(*name));
             }
             ExprKind::U60 { .. } | ExprKind::F60 { .. } => {
                 old_rule_pats.push(pat.clone());
@@ -127,16 +128,16 @@ fn split_rule(
                     old_rule_body_args.push(arg);
                 }
 
-                old_rule_pats.push(Expr::ctr(pat.range, name.clone(), new_pat_args));
+                old_rule_pats.push(Expr::ctr(pat.range, *name, new_pat_args));
             }
             _ => unreachable!("Internal Error: Invalid constructor while decoding pats"),
         }
     }
 
-    let old_rule_body = Expr::fun(rule.range, new_entry_name.clone(), old_rule_body_args);
+    let old_rule_body = Expr::fun(rule.range, new_entry_name, old_rule_body_args);
 
     let old_rule = Rule {
-        name: entry.name.clone(),
+        name: entry.name,
         pats: old_rule_pats,
         body: old_rule_body,
         range: rule.range,
@@ -168,9 +169,9 @@ fn split_rule(
                             new_rule_pats.push(new_arg);
                         }
 
-                        let new_ctr = Expr::ctr(name.range, name.clone(), new_ctr_args);
+                        let new_ctr = Expr::ctr(name.range, *name, new_ctr_args);
 
-                        subst(&mut new_rule_body, opat_name, &new_ctr);
+                        new_rule_body = subst(new_rule_body, opat_name, &new_ctr);
                     }
                     (ExprKind::Var { .. }, _) => {
                         new_rule_pats.push(other_pat.clone());
@@ -178,10 +179,10 @@ fn split_rule(
                     (ExprKind::U60 { .. }, ExprKind::U60 { .. }) => (),
                     (ExprKind::F60 { .. }, ExprKind::F60 { .. }) => (),
                     (ExprKind::U60 { .. }, ExprKind::Var { name }) => {
-                        subst(&mut new_rule_body, &name, rule_pat);
+                        new_rule_body = subst(new_rule_body, name, rule_pat);
                     }
                     (ExprKind::F60 { .. }, ExprKind::Var { name }) => {
-                        subst(&mut new_rule_body, &name, rule_pat);
+                        new_rule_body = subst(new_rule_body, name, rule_pat);
                     }
                     _ => {
                         panic!("Internal error. Please report."); // not possible since it matches
@@ -190,7 +191,7 @@ fn split_rule(
             }
 
             let new_rule = Rule {
-                name: new_entry_name.clone(),
+                name: new_entry_name,
                 pats: new_rule_pats,
                 body: new_rule_body,
                 range: new_entry_name.range,
@@ -213,51 +214,55 @@ fn split_rule(
         range: entry.range,
     };
 
-    let new_split_entries = flatten_entry(&new_entry);
-    (old_rule, new_split_entries)
+    (old_rule, new_entry)
 }
 
-fn flatten_entry(entry: &Entry) -> Vec<Entry> {
-    let mut name_count = 0;
+/// Splits the rules that match on nested patterns. The auxiliary
+/// entries that are created are flattened too and they are kept
+/// in `entries` in the order that they have to be defined.
+#[derive(Default)]
+struct Flatten {
+    entries: Vec<Entry>,
+}
+
+impl Fold for Flatten {
+    fn fold_entry(&mut self, entry: Entry) -> Entry {
+        let mut name_count = 0;
 
-    let mut skip: FxHashSet<usize> = FxHashSet::default();
-    let mut new_entries: Vec<Entry> = Vec::new();
-    let mut old_entry_rules: Vec<Rule> = Vec::new();
+        let mut skip: FxHashSet<usize> = FxHashSet::default();
+        let mut old_entry_rules: Vec<Rule> = Vec::new();
 
-    for i in 0..entry.rules.len() {
-        if !skip.contains(&i) {
-            let rule = &entry.rules[i];
+        for (i, rule) in entry.rules.iter().enumerate() {
+            if skip.contains(&i) {
+                continue;
+            }
             if must_split(rule) {
-                let (old_rule, split_entries) =
-                    split_rule(rule, entry, i, &mut name_count, &mut skip);
+                let (old_rule, new_entry) = split_rule(rule, &entry, i, &mut name_count, &mut skip);
                 old_entry_rules.push(old_rule);
-                new_entries.extend(split_entries);
+                let new_entry = self.fold_entry(new_entry);
+                self.entries.push(new_entry);
             } else {
-                old_entry_rules.push(entry.rules[i].clone());
+                old_entry_rules.push(rule.clone());
             }
         }
-    }
 
-    let old_entry = Entry {
-        name: entry.name.clone(),
-        args: entry.args.clone(),
-        rules: old_entry_rules,
-        range: entry.range,
-        attrs: entry.attrs.clone(),
-    };
-
-    new_entries.push(old_entry);
-    new_entries
+        Entry {
+            rules: old_entry_rules,
+            ..entry
+        }
+    }
 }
 
 pub fn flatten(book: untyped::Book) -> untyped::Book {
     let mut book = book;
     let mut names = FxHashMap::default();
     let mut entrs = LinkedHashMap::default();
+    let mut flatten = Flatten::default();
 
     for name in book.names.keys() {
         let entry = book.entrs.remove(name).unwrap();
-        for entry in flatten_entry(&entry) {
+        let entry = flatten.fold_entry(*entry);
+        for entry in flatten.entries.drain(..).chain([entry]) {
             names.insert(entry.name.to_string(), entrs.len());
             entrs.insert(entry.name.to_string(), Box::new(entry));
         }
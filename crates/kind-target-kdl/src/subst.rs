@@ -1,43 +1,29 @@
-use kind_tree::{symbol::Ident, untyped::Expr};
-
-pub fn subst(term: &mut Expr, from: &Ident, to: &Expr) {
-    use kind_tree::untyped::ExprKind::*;
-    match &mut term.data {
-        Var { name } if from.to_str() == name.to_str() => *term = to.clone(),
-
-        App { fun, args } => {
-            subst(fun, from, to);
-            for arg in args {
-                subst(arg, from, to);
-            }
-        }
-
-        Fun { args, .. } | Ctr { args, .. } => {
-            for arg in args {
-                subst(arg, from, to);
-            }
-        }
+use kind_tree::symbol::Ident;
+use kind_tree::untyped::fold::{self, Fold};
+use kind_tree::untyped::{Expr, ExprKind};
+
+/// Replaces the free occurrences of a variable by an expression.
+struct Subst<'a> {
+    from: &'a Ident,
+    to: &'a Expr,
+}
 
-        Let { name, val, next } => {
-            subst(val, from, to);
-            if name.to_str() != from.to_str() {
-                subst(next, from, to);
+impl<'a> Fold for Subst<'a> {
+    fn fold_expr(&mut self, mut expr: Box<Expr>) -> Box<Expr> {
+        match &mut expr.data {
+            ExprKind::Var { name } if name.data == self.from.data => Box::new(self.to.clone()),
+            ExprKind::Lambda { param, .. } if param.data == self.from.data => expr,
+            ExprKind::Let { name, val, .. } if name.data == self.from.data => {
+                let range = val.range;
+                *val = self.fold_expr(std::mem::replace(val, Expr::err(range)));
+                expr
             }
+            ExprKind::Err => unreachable!("Err should not be used inside the compiler"),
+            _ => fold::walk_expr(self, expr),
         }
-
-        Binary { op: _, left, right } => {
-            subst(left, from, to);
-            subst(right, from, to);
-        }
-
-        Lambda { param, body, .. } if param.to_str() != from.to_str() => subst(body, from, to),
-
-        U60 { .. } => (),
-        F60 { .. } => (),
-        Str { .. } => (),
-        Var { .. } => (),
-        Lambda { .. } => (),
-
-        Err => unreachable!("Err should not be used inside the compiler"),
     }
 }
+
+pub fn subst(term: Box<Expr>, from: &Ident, to: &Expr) -> Box<Expr> {
+    Subst { from, to }.fold_expr(term)
+}
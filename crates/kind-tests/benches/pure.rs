@@ -6,6 +6,8 @@ use std::{fs, path::{PathBuf}};
 use driver::{resolution};
 use kind_driver::session::Session;
 use kind_pass::{expand::{self, uses::expand_uses}, desugar, erasure};
+use kind_report::cancel::CancellationToken;
+use kind_span::RangeTable;
 use test::Bencher;
 
 use kind_driver as driver;
@@ -36,7 +38,7 @@ fn bench_exp_pure_parsing(b: &mut Bencher) {
     b.iter(|| {
         paths.iter().map(|input| {
             let session = new_session();
-            kind_parser::parse_book(session.diagnostic_sender.clone(), 0, &input)
+            kind_parser::parse_book(session.diagnostic_sender.clone(), 0, &input, &session.cancel)
         }).fold(0, |n, _| n + 1)
     })
 }
@@ -49,7 +51,7 @@ fn bench_exp_pure_use_expansion(b: &mut Bencher) {
     let mut paths: Vec<_> = paths.iter().map(|x| {
         let input = fs::read_to_string(x).unwrap();
         let (rx, _) = std::sync::mpsc::channel();
-        let (modu, failed) = kind_parser::parse_book(rx, 0, &input);
+        let (modu, failed) = kind_parser::parse_book(rx, 0, &input, &CancellationToken::new());
         assert!(!failed);
         modu
     }).collect();
@@ -70,7 +72,7 @@ fn bench_exp_pure_derive_expansion(b: &mut Bencher) {
     let mut books: Vec<_> = paths.iter().map(|x| {
         let input = fs::read_to_string(x).unwrap();
         let (rx, _) = std::sync::mpsc::channel();
-        let (mut module, failed) = kind_parser::parse_book(rx.clone(), 0, &input);
+        let (mut module, failed) = kind_parser::parse_book(rx.clone(), 0, &input, &CancellationToken::new());
         assert!(!failed);
         expand_uses(&mut module, rx);
         module
@@ -117,7 +119,7 @@ fn bench_exp_pure_desugar(b: &mut Bencher) {
 
     b.iter(|| {
         books.iter_mut().map(|(session, book)| {
-            desugar::desugar_book(session.diagnostic_sender.clone(), &book).unwrap()
+            desugar::desugar_book(session.diagnostic_sender.clone(), &book, &session.cancel).unwrap()
         }).fold(0, |n, _| n + 1)
     })
 }
@@ -131,7 +133,7 @@ fn bench_exp_pure_erase(b: &mut Bencher) {
         let mut session = new_session();
         let mut book = resolution::parse_and_store_book(&mut session, &PathBuf::from(x)).unwrap();
         let result = resolution::check_unbound_top_level(&mut session, &mut book);
-        let book = desugar::desugar_book(session.diagnostic_sender.clone(), &book).unwrap();
+        let book = desugar::desugar_book(session.diagnostic_sender.clone(), &book, &session.cancel).unwrap();
         assert!(result.is_ok());
 
         (session, book)
@@ -143,6 +145,7 @@ fn bench_exp_pure_erase(b: &mut Bencher) {
                 book,
                 session.diagnostic_sender.clone(),
                 vec!["Main".to_string()],
+                &session.cancel,
             ).unwrap();
         }).fold(0, |n, _| n + 1)
     })
@@ -157,13 +160,14 @@ fn bench_exp_pure_to_hvm(b: &mut Bencher) {
         let mut session = new_session();
         let mut book = resolution::parse_and_store_book(&mut session, &PathBuf::from(x)).unwrap();
         let result = resolution::check_unbound_top_level(&mut session, &mut book);
-        let book = desugar::desugar_book(session.diagnostic_sender.clone(), &book).unwrap();
+        let book = desugar::desugar_book(session.diagnostic_sender.clone(), &book, &session.cancel).unwrap();
         assert!(result.is_ok());
 
         let book = erasure::erase_book(
             &book,
             session.diagnostic_sender.clone(),
             vec!["Main".to_string()],
+            &session.cancel,
         ).unwrap();
 
         (session, book)
@@ -187,7 +191,7 @@ fn bench_exp_pure_gen_checker(b: &mut Bencher) {
         let mut session = new_session();
         let mut book = resolution::parse_and_store_book(&mut session, &PathBuf::from(x)).unwrap();
         let result = resolution::check_unbound_top_level(&mut session, &mut book);
-        let book = desugar::desugar_book(session.diagnostic_sender.clone(), &book).unwrap();
+        let book = desugar::desugar_book(session.diagnostic_sender.clone(), &book, &session.cancel).unwrap();
         assert!(result.is_ok());
 
         (session, book)
@@ -195,7 +199,7 @@ fn bench_exp_pure_gen_checker(b: &mut Bencher) {
 
     b.iter(move || {
         books.iter().map(move |(_, book)| {
-            kind_checker::gen_checker(book, true, book.names.keys().cloned().collect())
+            kind_checker::gen_checker(book, &mut RangeTable::new(), true, book.names.keys().cloned().collect())
         }).fold(0, |n, _| n + 1)
     })
 }
@@ -1,8 +1,10 @@
 #![feature(result_flattening)]
 
 use kind_driver::session::Session;
+use kind_report::cancel::CancellationToken;
 use kind_report::data::Diagnostic;
-use kind_report::report::Report;
+use kind_pretty::Pretty;
+use kind_report::report::{organize_diagnostics, Report};
 use kind_report::RenderConfig;
 
 use std::fs::{self, File};
@@ -39,7 +41,8 @@ fn test_kind2(path: &Path, run: fn(&PathBuf, &mut Session) -> Option<String>) ->
 
                 let res = run(&PathBuf::from(path), &mut session);
 
-                let diagnostics = tx.try_iter().collect::<Vec<Box<dyn Diagnostic>>>();
+                let mut diagnostics = tx.try_iter().collect::<Vec<Box<dyn Diagnostic>>>();
+                organize_diagnostics(&session, &mut diagnostics, None);
                 let render = RenderConfig::ascii(2);
 
                 kind_report::check_if_colors_are_supported(true);
@@ -99,6 +102,21 @@ fn test_run() -> Result<(), Error> {
     Ok(())
 }
 
+#[test]
+#[timeout(15000)]
+fn test_hvm_locations() -> Result<(), Error> {
+    test_kind2(Path::new("./suite/hvm"), |path, session| {
+        let entrypoints = vec!["Main".to_string()];
+        let book = driver::erase_book(session, path, entrypoints).ok()?;
+        let code = driver::compile_book_to_hvm_with_locations(session, book, false);
+
+        // The comments do not change the program.
+        let (res, _) = driver::execute_file(&code, Some(1)).ok()?;
+        Some(format!("{}\n\n{}", code, res))
+    })?;
+    Ok(())
+}
+
 #[test]
 #[timeout(15000)]
 fn test_eval() -> Result<(), Error> {
@@ -160,6 +178,154 @@ fn test_erasure() -> Result<(), Error> {
     Ok(())
 }
 
+#[test]
+#[timeout(15000)]
+fn test_json_concrete() -> Result<(), Error> {
+    test_kind2(Path::new("./suite/json/concrete"), |path, session| {
+        let book = driver::to_book(session, path).ok()?;
+        let files = session.relative_paths();
+        serde_json::to_string_pretty(&driver::JsonBook { files: &files, book: &book }).ok()
+    })?;
+    Ok(())
+}
+
+#[test]
+#[timeout(15000)]
+fn test_json_desugared() -> Result<(), Error> {
+    test_kind2(Path::new("./suite/json/desugared"), |path, session| {
+        let book = driver::desugar_book(session, path).ok()?;
+        let files = session.relative_paths();
+        serde_json::to_string_pretty(&driver::JsonBook { files: &files, book: &book }).ok()
+    })?;
+    Ok(())
+}
+
+#[test]
+#[timeout(15000)]
+fn test_json_erased() -> Result<(), Error> {
+    test_kind2(Path::new("./suite/json/erased"), |path, session| {
+        let entrypoints = vec!["Main".to_string()];
+        let book = driver::erase_book(session, path, entrypoints).ok()?;
+        let files = session.relative_paths();
+        serde_json::to_string_pretty(&driver::JsonBook { files: &files, book: &book }).ok()
+    })?;
+    Ok(())
+}
+
+#[test]
+#[timeout(15000)]
+fn test_pretty_concrete() -> Result<(), Error> {
+    test_kind2(Path::new("./suite/pretty/concrete"), |path, session| {
+        let book = driver::to_book(session, path).ok()?;
+        Some(book.pretty(40))
+    })?;
+    Ok(())
+}
+
+#[test]
+#[timeout(15000)]
+fn test_pretty_desugared() -> Result<(), Error> {
+    test_kind2(Path::new("./suite/pretty/desugared"), |path, session| {
+        let book = driver::desugar_book(session, path).ok()?;
+        Some(book.pretty(40))
+    })?;
+    Ok(())
+}
+
+#[test]
+#[timeout(15000)]
+fn test_pretty_untyped() -> Result<(), Error> {
+    test_kind2(Path::new("./suite/pretty/untyped"), |path, session| {
+        let entrypoints = vec!["Main".to_string()];
+        let book = driver::erase_book(session, path, entrypoints).ok()?;
+        Some(book.pretty(40))
+    })?;
+    Ok(())
+}
+
+#[test]
+#[timeout(15000)]
+fn test_alpha() -> Result<(), Error> {
+    test_kind2(Path::new("./suite/alpha"), |path, session| {
+        let book = driver::desugar_book(session, path).ok()?;
+        let entries: Vec<_> = book.entrs.values().collect();
+
+        // The hashes must not change between builds and targets.
+        if let Some(id) = book.entrs.get("Id") {
+            assert_eq!(id.alpha_hash(), 0xb935_4f94_f090_ca69);
+        }
+
+        // Constructors and axioms with the same type are different.
+        for (fst, snd) in [("Bool.true", "Bool.false"), ("Axiom.fst", "Axiom.snd")] {
+            if let (Some(fst), Some(snd)) = (book.entrs.get(fst), book.entrs.get(snd)) {
+                assert!(!fst.alpha_eq(snd));
+            }
+        }
+
+        let mut res = String::new();
+        for (i, entry) in entries.iter().enumerate() {
+            let same: Vec<_> = entries[i + 1..]
+                .iter()
+                .filter(|other| other.alpha_eq(entry))
+                .collect();
+
+            for other in &same {
+                assert_eq!(entry.alpha_hash(), other.alpha_hash());
+            }
+
+            if !same.is_empty() {
+                let names: Vec<_> = same.iter().map(|x| x.name.to_string()).collect();
+                res.push_str(&format!("{} = {}\n", entry.name, names.join(" ")));
+            }
+        }
+        Some(res)
+    })?;
+    Ok(())
+}
+
+#[test]
+#[timeout(30000)]
+fn test_error_limit() -> Result<(), Error> {
+    use kind_report::data::Log;
+
+    for entry in WalkDir::new("./suite/limit") {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_file() && path.extension().map(|x| x == "kind2").unwrap_or(false) {
+            golden_test(path, &|path| {
+                let (rx, tx) = std::sync::mpsc::channel();
+                let root = PathBuf::from("./suite/lib").canonicalize().unwrap();
+                let mut session = Session::new(root, rx);
+
+                let entrypoints = vec!["Main".to_string()];
+                let _ = driver::type_check_book(&mut session, &PathBuf::from(path), entrypoints, Some(1), true);
+
+                let mut diagnostics = tx.try_iter().collect::<Vec<Box<dyn Diagnostic>>>();
+                let reported = diagnostics.len();
+                let omitted = organize_diagnostics(&session, &mut diagnostics, Some(3));
+                let render = RenderConfig::ascii(2);
+
+                kind_report::check_if_colors_are_supported(true);
+
+                let mut res = format!(
+                    "Reported {}, kept {}, omitted {}\n\n",
+                    reported,
+                    diagnostics.len(),
+                    omitted
+                );
+
+                for diag in diagnostics {
+                    diag.render(&session, &render, &mut res).unwrap();
+                }
+
+                Log::Omitted(omitted).render(&session, &render, &mut res).unwrap();
+                res
+            });
+        }
+    }
+    Ok(())
+}
+
 #[test]
 #[timeout(15000)]
 fn test_coverage() -> Result<(), Error> {
@@ -170,3 +336,254 @@ fn test_coverage() -> Result<(), Error> {
     })?;
     Ok(())
 }
+
+/// Copies a project to a temporary directory so the files that the
+/// compiler writes (lockfiles and unpacked archives) stay out of the
+/// suite.
+fn copy_to_temp(path: &Path, dir: &str) -> PathBuf {
+    let dest = std::env::temp_dir().join(dir).join(path.file_name().unwrap());
+    let _ = fs::remove_dir_all(&dest);
+
+    for entry in WalkDir::new(path) {
+        let entry = entry.unwrap();
+        let target = dest.join(entry.path().strip_prefix(path).unwrap());
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target).unwrap();
+        } else {
+            fs::copy(entry.path(), &target).unwrap();
+        }
+    }
+
+    dest.canonicalize().unwrap()
+}
+
+#[test]
+#[timeout(30000)]
+fn test_packages() -> Result<(), Error> {
+    for entry in fs::read_dir("./suite/packages").unwrap() {
+        let path = entry.unwrap().path();
+        if !path.is_dir() {
+            continue;
+        }
+        golden_test(&path, &|path| {
+            let (rx, tx) = std::sync::mpsc::channel();
+            let root = copy_to_temp(path, "kind2-packages");
+            let mut session = Session::new(root.clone(), rx);
+
+            let entrypoints = vec!["Main".to_string()];
+            let res = driver::package::load_dependencies(&mut session, false)
+                .and_then(|_| {
+                    driver::type_check_book(&mut session, &root.join("Main.kind2"), entrypoints, Some(1), true)
+                })
+                .map(|_| "Ok!".to_string());
+
+            let mut diagnostics = tx.try_iter().collect::<Vec<Box<dyn Diagnostic>>>();
+            organize_diagnostics(&session, &mut diagnostics, None);
+            let render = RenderConfig::ascii(2);
+
+            kind_report::check_if_colors_are_supported(true);
+
+            match res {
+                Ok(res) if diagnostics.is_empty() => res,
+                _ => {
+                    let mut res_string = String::new();
+
+                    for diag in diagnostics {
+                        diag.render(&session, &render, &mut res_string).unwrap();
+                    }
+
+                    res_string
+                }
+            }
+        });
+    }
+    Ok(())
+}
+
+#[test]
+#[timeout(30000)]
+fn test_update_lock() -> Result<(), Error> {
+    let root = copy_to_temp(Path::new("./suite/packages/MissingLock"), "kind2-update-lock");
+
+    let load = |update_lock| {
+        let (rx, tx) = std::sync::mpsc::channel();
+        let mut session = Session::new(root.clone(), rx);
+        let res = driver::package::load_dependencies(&mut session, update_lock);
+        (res.is_ok(), tx.try_iter().count())
+    };
+
+    assert_eq!(load(false), (false, 1));
+    assert!(!root.join(driver::package::LOCKFILE).exists());
+
+    assert_eq!(load(true), (true, 0));
+    assert!(root.join(driver::package::LOCKFILE).exists());
+
+    assert_eq!(load(false), (true, 0));
+    Ok(())
+}
+
+#[test]
+#[timeout(30000)]
+fn test_compiler_api() -> Result<(), Error> {
+    let compiler = driver::compiler::Compiler::new(PathBuf::from("./suite/lib").canonicalize().unwrap())
+        .tids(1)
+        .coverage(true);
+
+    kind_report::check_if_colors_are_supported(true);
+
+    for entry in WalkDir::new("./suite/checker").follow_links(true) {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_file() && path.extension().map(|x| x == "kind2").unwrap_or(false) {
+            golden_test(path, &|path| {
+                let report = compiler.check(path);
+                if report.succeeded() && report.outcome.diagnostics.is_empty() {
+                    "Ok!".to_string()
+                } else {
+                    report.outcome.render(&RenderConfig::ascii(2))
+                }
+            });
+        }
+    }
+    Ok(())
+}
+
+#[test]
+#[timeout(30000)]
+fn test_cancellation() -> Result<(), Error> {
+    let token = CancellationToken::new();
+    let compiler = driver::compiler::Compiler::new(PathBuf::from("./suite/lib").canonicalize().unwrap())
+        .tids(1)
+        .cancellation(token.clone());
+
+    let path = PathBuf::from("./suite/checker/Inspection.kind2");
+
+    let report = compiler.check(&path);
+    assert!(!report.outcome.cancelled);
+
+    token.cancel();
+
+    let report = compiler.check(&path);
+    assert!(report.outcome.cancelled);
+    assert!(report.book.is_none());
+    Ok(())
+}
+
+#[test]
+#[timeout(30000)]
+fn test_cancellation_while_checking() -> Result<(), Error> {
+    let token = CancellationToken::new();
+    let compiler = driver::compiler::Compiler::new(PathBuf::from("./suite/lib").canonicalize().unwrap())
+        .tids(1)
+        .cancellation(token.clone());
+
+    // The checker takes a few seconds to run on this file.
+    let path = PathBuf::from("./suite/cancel/Slow.kind2");
+
+    let canceller = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(300));
+        token.cancel();
+    });
+
+    let report = compiler.check(path);
+    canceller.join().unwrap();
+
+    let phases: Vec<_> = report.outcome.timings.phases.iter().map(|x| x.0).collect();
+
+    assert!(report.outcome.cancelled);
+    assert!(phases.contains(&"type check"));
+    assert!(!phases.contains(&"erasure"));
+    assert!(report.outcome.diagnostics.is_empty());
+    assert!(report.book.is_none());
+    assert!(report.rewrites.is_none());
+    Ok(())
+}
+
+#[test]
+#[timeout(30000)]
+fn test_large_files() -> Result<(), Error> {
+    // The error is past the 24 bits that were used for positions.
+    let dir = std::env::temp_dir().join("kind2-large-files");
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("Main.kind2");
+
+    let lines = 1 << 18;
+    let mut input = String::with_capacity(lines * 64 + 32);
+    for _ in 0..lines {
+        input.push_str("// ");
+        input.push_str(&"x".repeat(60));
+        input.push('\n');
+    }
+    input.push_str("Main : U60\nMain = Type\n");
+    assert!(input.len() > 1 << 24);
+    fs::write(&path, input).unwrap();
+
+    let (rx, tx) = std::sync::mpsc::channel();
+    let root = PathBuf::from("./suite/lib").canonicalize().unwrap();
+    let mut session = Session::new(root, rx);
+
+    let entrypoints = vec!["Main".to_string()];
+    let res = driver::type_check_book(&mut session, &path, entrypoints, Some(1), true);
+    assert!(res.is_err());
+
+    let diagnostics = tx.try_iter().collect::<Vec<Box<dyn Diagnostic>>>();
+    assert_eq!(diagnostics.len(), 1);
+
+    let mut rendered = String::new();
+    diagnostics[0].render(&session, &RenderConfig::ascii(2), &mut rendered).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert!(rendered.contains(&format!("Main.kind2:{}:8]", lines + 2)), "{}", rendered);
+    Ok(())
+}
+
+#[test]
+#[timeout(15000)]
+fn test_lossless() -> Result<(), Error> {
+    use kind_parser::cst;
+    use kind_tree::concrete::TopLevel;
+
+    // Every file of the suite can be rebuilt from the tokens.
+    for entry in WalkDir::new("./suite").follow_links(true) {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_file() && path.extension().map(|x| x == "kind2").unwrap_or(false) {
+            let input = fs::read_to_string(path).unwrap();
+            let tokens = cst::lex_lossless(0, &input);
+            let source: String = tokens
+                .iter()
+                .flat_map(|x| x.leading.iter().map(|x| x.text.as_str()).chain([x.text.as_str()]))
+                .collect();
+            assert_eq!(source, input, "Testing file '{}'", path.display());
+        }
+    }
+
+    for entry in WalkDir::new("./suite/cst").follow_links(true) {
+        let entry = entry?;
+        let path = entry.path();
+        if path.is_file() && path.extension().map(|x| x == "kind2").unwrap_or(false) {
+            golden_test(path, &|path| {
+                let input = fs::read_to_string(path).unwrap();
+                let (rx, _) = std::sync::mpsc::channel();
+                let (tree, _) = cst::parse_lossless(rx, 0, &input, &CancellationToken::new());
+
+                assert_eq!(tree.to_source(), input);
+
+                let mut res = String::new();
+                for top_level in &tree.module.entries {
+                    if let TopLevel::Entry(entry) = top_level {
+                        res.push_str(&format!("{}\n", entry.name));
+                        for comment in tree.comments_before(entry.range) {
+                            res.push_str(&format!("  comment {:?}\n", comment.text));
+                        }
+                        for rule in &entry.rules {
+                            res.push_str(&format!("  rule {:?}\n", tree.text_of(rule.range)));
+                        }
+                    }
+                }
+                res
+            });
+        }
+    }
+    Ok(())
+}
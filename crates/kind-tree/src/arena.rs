@@ -0,0 +1,151 @@
+//! Arenas store the nodes of a tree in a single vector and the
+//! nodes refer to each other by index. It avoids one allocation
+//! per node and makes a copy of a subtree as cheap as copying
+//! the index.
+//!
+//! Each tree has its own arena representation, with conversions
+//! from and to the boxed one: [crate::concrete::arena] is used by
+//! the derivations to rename variables, [crate::desugared::arena]
+//! by the substitutions of the notations and of the match compiler
+//! and [crate::untyped::arena] by the inliner. The parser and the
+//! checker compiler still build and consume the boxed trees.
+
+use std::fmt::Debug;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::Index;
+
+use fxhash::FxHashMap;
+
+/// Index of a node inside an [Arena].
+pub struct Id<T> {
+    index: u32,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Id<T> {
+    fn new(index: usize) -> Id<T> {
+        Id {
+            index: index as u32,
+            marker: PhantomData,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+}
+
+impl<T> Clone for Id<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Id<T> {}
+
+impl<T> PartialEq for Id<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl<T> Eq for Id<T> {}
+
+impl<T> Hash for Id<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u32(self.index)
+    }
+}
+
+impl<T> Debug for Id<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}", self.index)
+    }
+}
+
+/// Stores the nodes and gives the same index to nodes that
+/// are equal, so identical subtrees are only stored once.
+///
+/// Each node is stored only in `nodes`. The `shared` table maps
+/// the hash of a node to the last node allocated with that hash
+/// and `next` links it to the previous one, so the nodes with
+/// colliding hashes form a chain.
+#[derive(Clone, Debug)]
+pub struct Arena<T> {
+    nodes: Vec<T>,
+    next: Vec<Option<Id<T>>>,
+    shared: FxHashMap<u64, Id<T>>,
+}
+
+impl<T> Default for Arena<T> {
+    fn default() -> Self {
+        Self {
+            nodes: Default::default(),
+            next: Default::default(),
+            shared: Default::default(),
+        }
+    }
+}
+
+impl<T: Hash + Eq> Arena<T> {
+    pub fn new() -> Arena<T> {
+        Default::default()
+    }
+
+    pub fn alloc(&mut self, node: T) -> Id<T> {
+        let hash = fxhash::hash64(&node);
+        let head = self.shared.get(&hash).copied();
+
+        let mut current = head;
+        while let Some(id) = current {
+            if self.nodes[id.index()] == node {
+                return id;
+            }
+            current = self.next[id.index()];
+        }
+
+        let id = Id::new(self.nodes.len());
+        self.nodes.push(node);
+        self.next.push(head);
+        self.shared.insert(hash, id);
+        id
+    }
+
+    pub fn get(&self, id: Id<T>) -> &T {
+        &self.nodes[id.index()]
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+}
+
+impl<T: Hash + Eq> Index<Id<T>> for Arena<T> {
+    type Output = T;
+
+    fn index(&self, id: Id<T>) -> &T {
+        self.get(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Arena;
+
+    #[test]
+    fn shares_equal_nodes() {
+        let mut arena = Arena::new();
+        let a = arena.alloc("a".to_string());
+        let b = arena.alloc("b".to_string());
+
+        assert_eq!(arena.alloc("a".to_string()), a);
+        assert_ne!(a, b);
+        assert_eq!(arena.len(), 2);
+        assert_eq!(arena[b], "b");
+    }
+}
@@ -0,0 +1,885 @@
+//! The concrete tree stored inside of an [Arena]. The derivations
+//! build and rename a lot of small expressions that repeat, so they
+//! convert the boxed expressions to this representation and back.
+//!
+//! The patterns, the destructors and the bindings of the cases are
+//! stored as they are because they don't have expressions inside.
+
+use fxhash::FxHashMap;
+use kind_span::Range;
+
+use super::expr::{
+    AppBinding, Binding, Case, CaseBinding, CaseHead, Destruct, Expr, ExprKind, InfixOp, Literal,
+    Match, Scrutinee, SeqOperation, SeqRecord, Sttm, SttmKind, StrPart, Substitution,
+};
+use super::pat::{Pat, PatIdent, PatKind};
+use crate::arena::{Arena, Id};
+use crate::symbol::{Ident, QualifiedIdent, Symbol};
+use crate::Operator;
+
+pub type ExprId = Id<Node>;
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum NodeBinding {
+    Positional(ExprId),
+    Named(Range, Ident, ExprId),
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct NodeAppBinding {
+    pub data: ExprId,
+    pub erased: bool,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct NodeCase {
+    pub constructor: Ident,
+    pub bindings: Vec<CaseBinding>,
+    pub value: ExprId,
+    pub ignore_rest: Option<Range>,
+    pub others: Vec<CaseHead>,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct NodeScrutinee {
+    pub typ: QualifiedIdent,
+    pub name: Ident,
+    pub value: Option<ExprId>,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct NodeMatch {
+    pub typ: QualifiedIdent,
+    pub scrutinee: Ident,
+    pub value: Option<ExprId>,
+    pub others: Vec<NodeScrutinee>,
+    pub with_vars: Vec<(Ident, Option<ExprId>)>,
+    pub cases: Vec<NodeCase>,
+    pub motive: Option<ExprId>,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum NodeStrPart {
+    Text(String),
+    Expr(ExprId),
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct NodeSubstitution {
+    pub name: Ident,
+    pub redx: usize,
+    pub indx: usize,
+    pub expr: ExprId,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum NodeSttmKind {
+    Expr(ExprId, Box<NodeSttm>),
+    Ask(Destruct, ExprId, Box<NodeSttm>),
+    Let(Destruct, ExprId, Box<NodeSttm>),
+    Return(ExprId),
+    RetExpr(ExprId),
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct NodeSttm {
+    pub data: NodeSttmKind,
+    pub range: Range,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum NodeSeqOperation {
+    Set(ExprId),
+    Mut(ExprId),
+    Get,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct NodeSeqRecord {
+    pub typ: ExprId,
+    pub expr: ExprId,
+    pub fields: Vec<Ident>,
+    pub operation: NodeSeqOperation,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum NodeKind {
+    Var {
+        name: Ident,
+    },
+    Constr {
+        name: QualifiedIdent,
+        args: Vec<NodeBinding>,
+    },
+    All {
+        param: Option<Ident>,
+        typ: ExprId,
+        body: ExprId,
+        erased: bool,
+    },
+    Sigma {
+        param: Option<Ident>,
+        fst: ExprId,
+        snd: ExprId,
+    },
+    Lambda {
+        param: Ident,
+        typ: Option<ExprId>,
+        body: ExprId,
+        erased: bool,
+    },
+    App {
+        fun: ExprId,
+        args: Vec<NodeAppBinding>,
+    },
+    Let {
+        name: Destruct,
+        val: ExprId,
+        next: ExprId,
+    },
+    Ann {
+        val: ExprId,
+        typ: ExprId,
+    },
+    Lit {
+        lit: Literal,
+    },
+    Binary {
+        op: Operator,
+        fst: ExprId,
+        snd: ExprId,
+    },
+    Infix {
+        fst: ExprId,
+        rest: Vec<(InfixOp, Range, ExprId)>,
+    },
+    Hole,
+    Do {
+        typ: QualifiedIdent,
+        sttm: NodeSttm,
+    },
+    If {
+        cond: ExprId,
+        then_: ExprId,
+        else_: ExprId,
+    },
+    Pair {
+        fst: ExprId,
+        snd: ExprId,
+    },
+    List {
+        args: Vec<ExprId>,
+    },
+    Interpolation {
+        parts: Vec<NodeStrPart>,
+    },
+    Subst(NodeSubstitution),
+    Match(Box<NodeMatch>),
+    Open {
+        type_name: QualifiedIdent,
+        var_name: Ident,
+        motive: Option<ExprId>,
+        next: ExprId,
+    },
+    SeqRecord(NodeSeqRecord),
+    Error,
+}
+
+/// A single node of an expression. The children are indices
+/// in the same [ExprArena].
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Node {
+    pub data: NodeKind,
+    pub range: Range,
+}
+
+/// An arena of concrete expressions. Equal subtrees share the
+/// same [ExprId].
+#[derive(Clone, Debug, Default)]
+pub struct ExprArena {
+    nodes: Arena<Node>,
+}
+
+impl ExprArena {
+    pub fn new() -> ExprArena {
+        Default::default()
+    }
+
+    pub fn alloc(&mut self, range: Range, data: NodeKind) -> ExprId {
+        self.nodes.alloc(Node { data, range })
+    }
+
+    pub fn get(&self, id: ExprId) -> &Node {
+        self.nodes.get(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    fn alloc_opt(&mut self, expr: &Option<Box<Expr>>) -> Option<ExprId> {
+        expr.as_ref().map(|expr| self.from_boxed(expr))
+    }
+
+    fn alloc_sttm(&mut self, sttm: &Sttm) -> NodeSttm {
+        let data = match &sttm.data {
+            SttmKind::Expr(expr, next) => {
+                NodeSttmKind::Expr(self.from_boxed(expr), Box::new(self.alloc_sttm(next)))
+            }
+            SttmKind::Ask(name, val, next) => NodeSttmKind::Ask(
+                name.clone(),
+                self.from_boxed(val),
+                Box::new(self.alloc_sttm(next)),
+            ),
+            SttmKind::Let(name, val, next) => NodeSttmKind::Let(
+                name.clone(),
+                self.from_boxed(val),
+                Box::new(self.alloc_sttm(next)),
+            ),
+            SttmKind::Return(expr) => NodeSttmKind::Return(self.from_boxed(expr)),
+            SttmKind::RetExpr(expr) => NodeSttmKind::RetExpr(self.from_boxed(expr)),
+        };
+
+        NodeSttm {
+            data,
+            range: sttm.range,
+        }
+    }
+
+    fn alloc_match(&mut self, matcher: &Match) -> NodeMatch {
+        NodeMatch {
+            typ: matcher.typ,
+            scrutinee: matcher.scrutinee,
+            value: self.alloc_opt(&matcher.value),
+            others: matcher
+                .others
+                .iter()
+                .map(|other| NodeScrutinee {
+                    typ: other.typ,
+                    name: other.name,
+                    value: self.alloc_opt(&other.value),
+                })
+                .collect(),
+            with_vars: matcher
+                .with_vars
+                .iter()
+                .map(|(name, typ)| (*name, self.alloc_opt(typ)))
+                .collect(),
+            cases: matcher
+                .cases
+                .iter()
+                .map(|case| NodeCase {
+                    constructor: case.constructor,
+                    bindings: case.bindings.clone(),
+                    value: self.from_boxed(&case.value),
+                    ignore_rest: case.ignore_rest,
+                    others: case.others.clone(),
+                })
+                .collect(),
+            motive: self.alloc_opt(&matcher.motive),
+        }
+    }
+
+    /// Moves a boxed expression into the arena.
+    pub fn from_boxed(&mut self, expr: &Expr) -> ExprId {
+        use ExprKind::*;
+
+        let data = match &expr.data {
+            Var { name } => NodeKind::Var { name: *name },
+            Constr { name, args } => NodeKind::Constr {
+                name: *name,
+                args: args
+                    .iter()
+                    .map(|arg| match arg {
+                        Binding::Positional(expr) => NodeBinding::Positional(self.from_boxed(expr)),
+                        Binding::Named(range, name, expr) => {
+                            NodeBinding::Named(*range, *name, self.from_boxed(expr))
+                        }
+                    })
+                    .collect(),
+            },
+            All {
+                param,
+                typ,
+                body,
+                erased,
+            } => NodeKind::All {
+                param: *param,
+                typ: self.from_boxed(typ),
+                body: self.from_boxed(body),
+                erased: *erased,
+            },
+            Sigma { param, fst, snd } => NodeKind::Sigma {
+                param: *param,
+                fst: self.from_boxed(fst),
+                snd: self.from_boxed(snd),
+            },
+            Lambda {
+                param,
+                typ,
+                body,
+                erased,
+            } => NodeKind::Lambda {
+                param: *param,
+                typ: self.alloc_opt(typ),
+                body: self.from_boxed(body),
+                erased: *erased,
+            },
+            App { fun, args } => NodeKind::App {
+                fun: self.from_boxed(fun),
+                args: args
+                    .iter()
+                    .map(|arg| NodeAppBinding {
+                        data: self.from_boxed(&arg.data),
+                        erased: arg.erased,
+                    })
+                    .collect(),
+            },
+            Let { name, val, next } => NodeKind::Let {
+                name: name.clone(),
+                val: self.from_boxed(val),
+                next: self.from_boxed(next),
+            },
+            Ann { val, typ } => NodeKind::Ann {
+                val: self.from_boxed(val),
+                typ: self.from_boxed(typ),
+            },
+            Lit { lit } => NodeKind::Lit { lit: lit.clone() },
+            Binary { op, fst, snd } => NodeKind::Binary {
+                op: *op,
+                fst: self.from_boxed(fst),
+                snd: self.from_boxed(snd),
+            },
+            Infix { fst, rest } => NodeKind::Infix {
+                fst: self.from_boxed(fst),
+                rest: rest
+                    .iter()
+                    .map(|(op, range, expr)| (op.clone(), *range, self.from_boxed(expr)))
+                    .collect(),
+            },
+            Hole => NodeKind::Hole,
+            Do { typ, sttm } => NodeKind::Do {
+                typ: *typ,
+                sttm: self.alloc_sttm(sttm),
+            },
+            If { cond, then_, else_ } => NodeKind::If {
+                cond: self.from_boxed(cond),
+                then_: self.from_boxed(then_),
+                else_: self.from_boxed(else_),
+            },
+            Pair { fst, snd } => NodeKind::Pair {
+                fst: self.from_boxed(fst),
+                snd: self.from_boxed(snd),
+            },
+            List { args } => NodeKind::List {
+                args: args.iter().map(|arg| self.from_boxed(arg)).collect(),
+            },
+            Interpolation { parts } => NodeKind::Interpolation {
+                parts: parts
+                    .iter()
+                    .map(|part| match part {
+                        StrPart::Text(text) => NodeStrPart::Text(text.clone()),
+                        StrPart::Expr(expr) => NodeStrPart::Expr(self.from_boxed(expr)),
+                    })
+                    .collect(),
+            },
+            Subst(subst) => NodeKind::Subst(NodeSubstitution {
+                name: subst.name,
+                redx: subst.redx,
+                indx: subst.indx,
+                expr: self.from_boxed(&subst.expr),
+            }),
+            Match(matcher) => NodeKind::Match(Box::new(self.alloc_match(matcher))),
+            Open {
+                type_name,
+                var_name,
+                motive,
+                next,
+            } => NodeKind::Open {
+                type_name: *type_name,
+                var_name: *var_name,
+                motive: self.alloc_opt(motive),
+                next: self.from_boxed(next),
+            },
+            SeqRecord(seq) => NodeKind::SeqRecord(NodeSeqRecord {
+                typ: self.from_boxed(&seq.typ),
+                expr: self.from_boxed(&seq.expr),
+                fields: seq.fields.clone(),
+                operation: match &seq.operation {
+                    SeqOperation::Set(expr) => NodeSeqOperation::Set(self.from_boxed(expr)),
+                    SeqOperation::Mut(expr) => NodeSeqOperation::Mut(self.from_boxed(expr)),
+                    SeqOperation::Get => NodeSeqOperation::Get,
+                },
+            }),
+            Error => NodeKind::Error,
+        };
+
+        self.alloc(expr.range, data)
+    }
+
+    fn to_opt(&self, id: Option<ExprId>) -> Option<Box<Expr>> {
+        id.map(|id| self.to_boxed(id))
+    }
+
+    fn to_sttm(&self, sttm: &NodeSttm) -> Box<Sttm> {
+        let data = match &sttm.data {
+            NodeSttmKind::Expr(expr, next) => {
+                SttmKind::Expr(self.to_boxed(*expr), self.to_sttm(next))
+            }
+            NodeSttmKind::Ask(name, val, next) => {
+                SttmKind::Ask(name.clone(), self.to_boxed(*val), self.to_sttm(next))
+            }
+            NodeSttmKind::Let(name, val, next) => {
+                SttmKind::Let(name.clone(), self.to_boxed(*val), self.to_sttm(next))
+            }
+            NodeSttmKind::Return(expr) => SttmKind::Return(self.to_boxed(*expr)),
+            NodeSttmKind::RetExpr(expr) => SttmKind::RetExpr(self.to_boxed(*expr)),
+        };
+
+        Box::new(Sttm {
+            data,
+            range: sttm.range,
+        })
+    }
+
+    fn to_match(&self, matcher: &NodeMatch) -> Match {
+        Match {
+            typ: matcher.typ,
+            scrutinee: matcher.scrutinee,
+            value: self.to_opt(matcher.value),
+            others: matcher
+                .others
+                .iter()
+                .map(|other| Scrutinee {
+                    typ: other.typ,
+                    name: other.name,
+                    value: self.to_opt(other.value),
+                })
+                .collect(),
+            with_vars: matcher
+                .with_vars
+                .iter()
+                .map(|(name, typ)| (*name, self.to_opt(*typ)))
+                .collect(),
+            cases: matcher
+                .cases
+                .iter()
+                .map(|case| Case {
+                    constructor: case.constructor,
+                    bindings: case.bindings.clone(),
+                    value: self.to_boxed(case.value),
+                    ignore_rest: case.ignore_rest,
+                    others: case.others.clone(),
+                })
+                .collect(),
+            motive: self.to_opt(matcher.motive),
+        }
+    }
+
+    /// Builds a boxed expression from a node of the arena. The
+    /// shared subtrees are copied.
+    pub fn to_boxed(&self, id: ExprId) -> Box<Expr> {
+        let node = self.get(id);
+
+        let data = match &node.data {
+            NodeKind::Var { name } => ExprKind::Var { name: *name },
+            NodeKind::Constr { name, args } => ExprKind::Constr {
+                name: *name,
+                args: args
+                    .iter()
+                    .map(|arg| match arg {
+                        NodeBinding::Positional(expr) => Binding::Positional(self.to_boxed(*expr)),
+                        NodeBinding::Named(range, name, expr) => {
+                            Binding::Named(*range, *name, self.to_boxed(*expr))
+                        }
+                    })
+                    .collect(),
+            },
+            NodeKind::All {
+                param,
+                typ,
+                body,
+                erased,
+            } => ExprKind::All {
+                param: *param,
+                typ: self.to_boxed(*typ),
+                body: self.to_boxed(*body),
+                erased: *erased,
+            },
+            NodeKind::Sigma { param, fst, snd } => ExprKind::Sigma {
+                param: *param,
+                fst: self.to_boxed(*fst),
+                snd: self.to_boxed(*snd),
+            },
+            NodeKind::Lambda {
+                param,
+                typ,
+                body,
+                erased,
+            } => ExprKind::Lambda {
+                param: *param,
+                typ: self.to_opt(*typ),
+                body: self.to_boxed(*body),
+                erased: *erased,
+            },
+            NodeKind::App { fun, args } => ExprKind::App {
+                fun: self.to_boxed(*fun),
+                args: args
+                    .iter()
+                    .map(|arg| AppBinding {
+                        data: self.to_boxed(arg.data),
+                        erased: arg.erased,
+                    })
+                    .collect(),
+            },
+            NodeKind::Let { name, val, next } => ExprKind::Let {
+                name: name.clone(),
+                val: self.to_boxed(*val),
+                next: self.to_boxed(*next),
+            },
+            NodeKind::Ann { val, typ } => ExprKind::Ann {
+                val: self.to_boxed(*val),
+                typ: self.to_boxed(*typ),
+            },
+            NodeKind::Lit { lit } => ExprKind::Lit { lit: lit.clone() },
+            NodeKind::Binary { op, fst, snd } => ExprKind::Binary {
+                op: *op,
+                fst: self.to_boxed(*fst),
+                snd: self.to_boxed(*snd),
+            },
+            NodeKind::Infix { fst, rest } => ExprKind::Infix {
+                fst: self.to_boxed(*fst),
+                rest: rest
+                    .iter()
+                    .map(|(op, range, expr)| (op.clone(), *range, self.to_boxed(*expr)))
+                    .collect(),
+            },
+            NodeKind::Hole => ExprKind::Hole,
+            NodeKind::Do { typ, sttm } => ExprKind::Do {
+                typ: *typ,
+                sttm: self.to_sttm(sttm),
+            },
+            NodeKind::If { cond, then_, else_ } => ExprKind::If {
+                cond: self.to_boxed(*cond),
+                then_: self.to_boxed(*then_),
+                else_: self.to_boxed(*else_),
+            },
+            NodeKind::Pair { fst, snd } => ExprKind::Pair {
+                fst: self.to_boxed(*fst),
+                snd: self.to_boxed(*snd),
+            },
+            NodeKind::List { args } => ExprKind::List {
+                args: args.iter().map(|arg| *self.to_boxed(*arg)).collect(),
+            },
+            NodeKind::Interpolation { parts } => ExprKind::Interpolation {
+                parts: parts
+                    .iter()
+                    .map(|part| match part {
+                        NodeStrPart::Text(text) => StrPart::Text(text.clone()),
+                        NodeStrPart::Expr(expr) => StrPart::Expr(self.to_boxed(*expr)),
+                    })
+                    .collect(),
+            },
+            NodeKind::Subst(subst) => ExprKind::Subst(Substitution {
+                name: subst.name,
+                redx: subst.redx,
+                indx: subst.indx,
+                expr: self.to_boxed(subst.expr),
+            }),
+            NodeKind::Match(matcher) => ExprKind::Match(Box::new(self.to_match(matcher))),
+            NodeKind::Open {
+                type_name,
+                var_name,
+                motive,
+                next,
+            } => ExprKind::Open {
+                type_name: *type_name,
+                var_name: *var_name,
+                motive: self.to_opt(*motive),
+                next: self.to_boxed(*next),
+            },
+            NodeKind::SeqRecord(seq) => ExprKind::SeqRecord(SeqRecord {
+                typ: self.to_boxed(seq.typ),
+                expr: self.to_boxed(seq.expr),
+                fields: seq.fields.clone(),
+                operation: match &seq.operation {
+                    NodeSeqOperation::Set(expr) => SeqOperation::Set(self.to_boxed(*expr)),
+                    NodeSeqOperation::Mut(expr) => SeqOperation::Mut(self.to_boxed(*expr)),
+                    NodeSeqOperation::Get => SeqOperation::Get,
+                },
+            }),
+            NodeKind::Error => ExprKind::Error,
+        };
+
+        Box::new(Expr {
+            data,
+            range: node.range,
+        })
+    }
+
+    /// Renames the free variables of an expression. The variables
+    /// bound inside of it (by lambdas, lets, cases and so on) keep
+    /// their names.
+    pub fn rename(&mut self, id: ExprId, names: &FxHashMap<String, String>) -> ExprId {
+        let mut renamer = Renamer {
+            names,
+            bound: Vec::new(),
+        };
+        self.rename_on(id, &mut renamer)
+    }
+
+    fn rename_opt(&mut self, id: Option<ExprId>, renamer: &mut Renamer) -> Option<ExprId> {
+        id.map(|id| self.rename_on(id, renamer))
+    }
+
+    fn rename_sttm(&mut self, sttm: &NodeSttm, renamer: &mut Renamer) -> NodeSttm {
+        let data = match &sttm.data {
+            NodeSttmKind::Expr(expr, next) => {
+                let expr = self.rename_on(*expr, renamer);
+                NodeSttmKind::Expr(expr, Box::new(self.rename_sttm(next, renamer)))
+            }
+            NodeSttmKind::Ask(name, val, next) | NodeSttmKind::Let(name, val, next) => {
+                let val = self.rename_on(*val, renamer);
+                let len = renamer.bound.len();
+                let name = renamer.destruct(name);
+                let next = Box::new(self.rename_sttm(next, renamer));
+                renamer.bound.truncate(len);
+                if let NodeSttmKind::Ask(..) = sttm.data {
+                    NodeSttmKind::Ask(name, val, next)
+                } else {
+                    NodeSttmKind::Let(name, val, next)
+                }
+            }
+            NodeSttmKind::Return(expr) => NodeSttmKind::Return(self.rename_on(*expr, renamer)),
+            NodeSttmKind::RetExpr(expr) => NodeSttmKind::RetExpr(self.rename_on(*expr, renamer)),
+        };
+
+        NodeSttm {
+            data,
+            range: sttm.range,
+        }
+    }
+
+    fn rename_match(&mut self, matcher: &mut NodeMatch, renamer: &mut Renamer) {
+        renamer.ident(&mut matcher.scrutinee);
+        matcher.value = self.rename_opt(matcher.value, renamer);
+
+        for other in &mut matcher.others {
+            renamer.ident(&mut other.name);
+            other.value = self.rename_opt(other.value, renamer);
+        }
+
+        for (name, typ) in &mut matcher.with_vars {
+            renamer.ident(name);
+            renamer.bound.push(name.to_string());
+            *typ = self.rename_opt(*typ, renamer);
+            renamer.bound.pop();
+        }
+
+        for case in &mut matcher.cases {
+            let len = renamer.bound.len();
+            for binding in &mut case.bindings {
+                renamer.case_binding(binding);
+            }
+            for head in &mut case.others {
+                for binding in &mut head.bindings {
+                    renamer.case_binding(binding);
+                }
+            }
+            case.value = self.rename_on(case.value, renamer);
+            renamer.bound.truncate(len);
+        }
+
+        matcher.motive = self.rename_opt(matcher.motive, renamer);
+    }
+
+    fn rename_on(&mut self, id: ExprId, renamer: &mut Renamer) -> ExprId {
+        let mut node = self.get(id).clone();
+
+        match &mut node.data {
+            NodeKind::Var { name } => renamer.ident(name),
+            NodeKind::Constr { args, .. } => {
+                for arg in args {
+                    match arg {
+                        NodeBinding::Positional(expr) | NodeBinding::Named(_, _, expr) => {
+                            *expr = self.rename_on(*expr, renamer)
+                        }
+                    }
+                }
+            }
+            NodeKind::All { param, typ, body, .. } => {
+                *typ = self.rename_on(*typ, renamer);
+                *body = renamer.under(*param, |renamer| self.rename_on(*body, renamer));
+            }
+            NodeKind::Sigma { param, fst, snd } => {
+                *fst = self.rename_on(*fst, renamer);
+                *snd = renamer.under(*param, |renamer| self.rename_on(*snd, renamer));
+            }
+            NodeKind::Lambda {
+                param, typ, body, ..
+            } => {
+                *typ = self.rename_opt(*typ, renamer);
+                *body = renamer.under(Some(*param), |renamer| self.rename_on(*body, renamer));
+            }
+            NodeKind::App { fun, args } => {
+                *fun = self.rename_on(*fun, renamer);
+                for arg in args {
+                    arg.data = self.rename_on(arg.data, renamer);
+                }
+            }
+            NodeKind::Let { name, val, next } => {
+                *val = self.rename_on(*val, renamer);
+                let len = renamer.bound.len();
+                *name = renamer.destruct(name);
+                *next = self.rename_on(*next, renamer);
+                renamer.bound.truncate(len);
+            }
+            NodeKind::Ann { val: fst, typ: snd }
+            | NodeKind::Binary { fst, snd, .. }
+            | NodeKind::Pair { fst, snd } => {
+                *fst = self.rename_on(*fst, renamer);
+                *snd = self.rename_on(*snd, renamer);
+            }
+            NodeKind::Infix { fst, rest } => {
+                *fst = self.rename_on(*fst, renamer);
+                for (_, _, expr) in rest {
+                    *expr = self.rename_on(*expr, renamer);
+                }
+            }
+            NodeKind::Do { sttm, .. } => *sttm = self.rename_sttm(sttm, renamer),
+            NodeKind::If { cond, then_, else_ } => {
+                *cond = self.rename_on(*cond, renamer);
+                *then_ = self.rename_on(*then_, renamer);
+                *else_ = self.rename_on(*else_, renamer);
+            }
+            NodeKind::List { args } => {
+                for arg in args {
+                    *arg = self.rename_on(*arg, renamer);
+                }
+            }
+            NodeKind::Interpolation { parts } => {
+                for part in parts {
+                    if let NodeStrPart::Expr(expr) = part {
+                        *expr = self.rename_on(*expr, renamer);
+                    }
+                }
+            }
+            NodeKind::Subst(subst) => {
+                renamer.ident(&mut subst.name);
+                let name = subst.name.to_str();
+                if let Some(pos) = renamer.bound.iter().position(|x| x == name) {
+                    subst.indx = pos;
+                }
+                subst.expr = self.rename_on(subst.expr, renamer);
+            }
+            NodeKind::Match(matcher) => self.rename_match(matcher, renamer),
+            NodeKind::Open {
+                var_name,
+                motive,
+                next,
+                ..
+            } => {
+                renamer.ident(var_name);
+                *next = self.rename_on(*next, renamer);
+                *motive = self.rename_opt(*motive, renamer);
+            }
+            NodeKind::SeqRecord(seq) => {
+                seq.expr = self.rename_on(seq.expr, renamer);
+                seq.typ = self.rename_on(seq.typ, renamer);
+                match &mut seq.operation {
+                    NodeSeqOperation::Set(expr) | NodeSeqOperation::Mut(expr) => {
+                        *expr = self.rename_on(*expr, renamer)
+                    }
+                    NodeSeqOperation::Get => (),
+                }
+            }
+            NodeKind::Lit { .. } | NodeKind::Hole | NodeKind::Error => return id,
+        }
+
+        self.alloc(node.range, node.data)
+    }
+}
+
+/// The state of [ExprArena::rename]: the new names and the variables
+/// bound in the current position, from the outermost to the innermost.
+struct Renamer<'a> {
+    names: &'a FxHashMap<String, String>,
+    bound: Vec<String>,
+}
+
+impl<'a> Renamer<'a> {
+    fn ident(&self, ident: &mut Ident) {
+        let name = ident.to_str();
+        if self.bound.iter().all(|x| x != name) {
+            if let Some(res) = self.names.get(name) {
+                ident.data = Symbol::new(res.clone())
+            }
+        }
+    }
+
+    fn under<T>(&mut self, param: Option<Ident>, fun: impl FnOnce(&mut Self) -> T) -> T {
+        match param {
+            Some(param) => {
+                self.bound.push(param.to_string());
+                let res = fun(self);
+                self.bound.pop();
+                res
+            }
+            None => fun(self),
+        }
+    }
+
+    fn pat(&self, pat: &mut Pat) {
+        match &mut pat.data {
+            PatKind::Var(PatIdent(ident)) => self.ident(ident),
+            PatKind::App(_, pats) => {
+                for pat in pats {
+                    self.pat(pat)
+                }
+            }
+            PatKind::List(pats) => {
+                for pat in pats {
+                    self.pat(pat)
+                }
+            }
+            PatKind::Pair(fst, snd) => {
+                self.pat(fst);
+                self.pat(snd);
+            }
+            PatKind::U60(_)
+            | PatKind::U120(_)
+            | PatKind::F60(_)
+            | PatKind::Str(_)
+            | PatKind::Char(_)
+            | PatKind::Hole => (),
+        }
+    }
+
+    fn case_binding(&mut self, binding: &mut CaseBinding) {
+        match binding {
+            CaseBinding::Field(ident) | CaseBinding::Renamed(_, ident) => {
+                self.bound.push(ident.to_string())
+            }
+            CaseBinding::Pattern(pat) => self.pat(pat),
+        }
+    }
+
+    /// Binds the names of a destructor. The patterns inside of it
+    /// are renamed like the ones of the rules.
+    fn destruct(&mut self, destruct: &Destruct) -> Destruct {
+        let mut destruct = destruct.clone();
+        match &mut destruct {
+            Destruct::Destruct(_, _, bindings, _) => {
+                for binding in bindings {
+                    self.case_binding(binding)
+                }
+            }
+            Destruct::Ident(ident) => self.bound.push(ident.to_string()),
+        }
+        destruct
+    }
+}
@@ -3,16 +3,17 @@
 //! we have to statically analyse the tree in order to generate
 //! better error messages.
 
-use super::pat::PatIdent;
+use super::pat::{Pat, PatIdent};
 use crate::symbol::{Ident, QualifiedIdent};
 use crate::Operator;
 
 use kind_span::{Locatable, Range};
+use serde::Serialize;
 use std::fmt::{Display, Error, Formatter};
 
 /// A binding express the positional or named argument of
 /// a constructor or function.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum Binding {
     Positional(Box<Expr>),
     Named(Range, Ident, Box<Expr>),
@@ -22,7 +23,7 @@ pub enum Binding {
 pub type Spine = Vec<Binding>;
 
 /// A binding that is used inside applications.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct AppBinding {
     pub data: Box<Expr>,
     pub erased: bool,
@@ -45,39 +46,69 @@ impl AppBinding {
 }
 
 /// A case binding is a field or a rename of some field
-/// inside a match expression.
-#[derive(Clone, Debug)]
+/// inside a match expression. A case with a nested pattern
+/// binds the fields by position instead of by name.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize)]
 pub enum CaseBinding {
     Field(Ident),
     Renamed(Ident, Ident),
+    Pattern(Box<Pat>),
 }
 
 /// A match case with a constructor that will match the
 /// strutinizer, bindings to the names of each arguments and
 /// a right-hand side value. The ignore_rest flag useful to just
 /// fill all of the case bindings that are not used with a default name.
-#[derive(Clone, Debug)]
+/// The constructor `_` matches any value.
+#[derive(Clone, Debug, Serialize)]
 pub struct Case {
     pub constructor: Ident,
     pub bindings: Vec<CaseBinding>,
     pub value: Box<Expr>,
     pub ignore_rest: Option<Range>,
+    pub others: Vec<CaseHead>,
+}
+
+/// The constructor and the bindings that a case expects
+/// for one of the other scrutinees of the match.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize)]
+pub struct CaseHead {
+    pub constructor: Ident,
+    pub bindings: Vec<CaseBinding>,
+    pub ignore_rest: Option<Range>,
+}
+
+/// Another value that is matched at the same time as the
+/// first one (e.g. the `Nat b` in `match Nat a, Nat b { .. }`).
+#[derive(Clone, Debug, Serialize)]
+pub struct Scrutinee {
+    pub typ: QualifiedIdent,
+    pub name: Ident,
+    pub value: Option<Box<Expr>>,
 }
 
 /// A match block that will be desugared
 /// into an eliminator of a datatype.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct Match {
     pub typ: QualifiedIdent,
     pub scrutinee: Ident,
     pub value: Option<Box<Expr>>,
+    pub others: Vec<Scrutinee>,
     pub with_vars: Vec<(Ident, Option<Box<Expr>>)>,
     pub cases: Vec<Case>,
     pub motive: Option<Box<Expr>>,
 }
 
+/// A piece of a string with interpolations.
+#[derive(Clone, Debug, Serialize)]
+pub enum StrPart {
+    Text(String),
+    Expr(Box<Expr>),
+}
+
 /// Substitution
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct Substitution {
     pub name: Ident,
     pub redx: usize,
@@ -85,7 +116,7 @@ pub struct Substitution {
     pub expr: Box<Expr>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize)]
 pub enum Literal {
     /// The universe of types (e.g. Type)
     Type,
@@ -95,6 +126,8 @@ pub enum Literal {
     /// The type literal of 60 bit numbers (e.g. 2 : U60)
     NumTypeU60,
     NumTypeF60,
+    /// The type literal of signed 60 bit numbers (e.g. -2i60 : I60)
+    NumTypeI60,
     // Char literal
     Char(char),
     /// A 60 bit number literal (e.g 32132)
@@ -103,6 +136,8 @@ pub enum Literal {
     NumU120(u128),
     // A 60 bit floating point number literal
     NumF60(u64),
+    /// A signed 60 bit number literal (e.g -32i60)
+    NumI60(i64),
     // Naturals represented by u128
     Nat(u128),
     // A String literal
@@ -111,13 +146,13 @@ pub enum Literal {
 
 /// A destruct of a single constructor. It's a flat destruct
 /// and just translates into a eliminator for records.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize)]
 pub enum Destruct {
     Destruct(Range, QualifiedIdent, Vec<CaseBinding>, Option<Range>),
     Ident(Ident),
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum SttmKind {
     Expr(Box<Expr>, Box<Sttm>),
     Ask(Destruct, Box<Expr>, Box<Sttm>),
@@ -130,20 +165,20 @@ pub enum SttmKind {
 /// describes the idea of `sequence` inside a monad
 /// each monadic action contains a `next` element that is
 /// desugared into a 'monadic bind'.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct Sttm {
     pub data: SttmKind,
     pub range: Range,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum SeqOperation {
     Set(Box<Expr>),
     Mut(Box<Expr>),
     Get
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct SeqRecord {
     pub typ: Box<Expr>,
     pub expr: Box<Expr>,
@@ -151,7 +186,15 @@ pub struct SeqRecord {
     pub operation: SeqOperation
 }
 
-#[derive(Clone, Debug)]
+/// An operator in the infix position. Notations are declared by
+/// the user so they are only solved after all of the files are loaded.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize)]
+pub enum InfixOp {
+    Operator(Operator),
+    Notation(String),
+}
+
+#[derive(Clone, Debug, Serialize)]
 pub enum ExprKind {
     /// Name of a variable
     Var { name: Ident },
@@ -198,6 +241,13 @@ pub enum ExprKind {
         fst: Box<Expr>,
         snd: Box<Expr>,
     },
+    /// A chain of infix operators with at least one notation
+    /// (e.g. xs ++ ys ++ zs). The desugarer groups it using the
+    /// precedence of each notation.
+    Infix {
+        fst: Box<Expr>,
+        rest: Vec<(InfixOp, Range, Box<Expr>)>,
+    },
     /// A expression open to unification (e.g. _)
     Hole,
     /// Do notation
@@ -215,6 +265,8 @@ pub enum ExprKind {
     Pair { fst: Box<Expr>, snd: Box<Expr> },
     /// Array
     List { args: Vec<Expr> },
+    /// String with expressions inside of it (e.g. "Hello ${name}")
+    Interpolation { parts: Vec<StrPart> },
     /// Substituion
     Subst(Substitution),
     /// A match block that will be translated
@@ -228,11 +280,15 @@ pub enum ExprKind {
         next: Box<Expr>
     },
 
-    SeqRecord(SeqRecord)
+    SeqRecord(SeqRecord),
+
+    /// Part of the code that could not be parsed. The syntax
+    /// error was already reported by the parser.
+    Error,
 }
 
 /// Describes a single expression inside Kind2.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct Expr {
     pub data: ExprKind,
     pub range: Range,
@@ -310,6 +366,13 @@ impl Expr {
         })
     }
 
+    pub fn error(range: Range) -> Box<Expr> {
+        Box::new(Expr {
+            data: ExprKind::Error,
+            range,
+        })
+    }
+
 }
 
 impl Locatable for Binding {
@@ -350,6 +413,7 @@ impl Locatable for CaseBinding {
         match self {
             CaseBinding::Field(i) => i.locate(),
             CaseBinding::Renamed(i, renamed) => i.locate().mix(renamed.locate()),
+            CaseBinding::Pattern(pat) => pat.range,
         }
     }
 }
@@ -400,18 +464,53 @@ impl Display for Literal {
             Literal::Type => write!(f, "Type"),
             Literal::NumTypeU60 => write!(f, "U60"),
             Literal::NumTypeF60 => write!(f, "F60"),
+            Literal::NumTypeI60 => write!(f, "I60"),
             Literal::Char(c) => write!(f, "'{}'", c),
             Literal::NumU60(numb) => write!(f, "{}", numb),
             Literal::Nat(numb) => write!(f, "{}numb", numb),
             Literal::NumU120(numb) => write!(f, "{}u120", numb),
             Literal::NumF60(numb) => write!(f, "{}f60", numb),
+            Literal::NumI60(numb) => write!(f, "{}i60", numb),
             Literal::String(str) => {
-                write!(f, "{:?}", str)
+                write!(f, "\"{}\"", escape_str(str))
             }
         }
     }
 }
 
+/// Escapes the text of a string literal (without the quotes) with
+/// the escapes that the lexer reads, so a `${` is not read back as an
+/// interpolation and the control characters are written as `\uXXXX`.
+pub fn escape_str(text: &str) -> String {
+    let mut escaped = String::new();
+    let mut chars = text.chars().peekable();
+
+    while let Some(chr) = chars.next() {
+        match chr {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            '\0' => escaped.push_str("\\0"),
+            '$' if chars.peek() == Some(&'{') => escaped.push_str("\\$"),
+            chr if chr.is_control() => escaped.push_str(&format!("\\u{:04x}", chr as u32)),
+            chr => escaped.push(chr),
+        }
+    }
+
+    escaped
+}
+
+impl Display for InfixOp {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        match self {
+            InfixOp::Operator(op) => write!(f, "{}", op),
+            InfixOp::Notation(symbol) => write!(f, "{}", symbol),
+        }
+    }
+}
+
 impl Display for Destruct {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -463,7 +562,21 @@ impl Display for CaseBinding {
         match self {
             CaseBinding::Field(n) => write!(f, "{}", n),
             CaseBinding::Renamed(m, n) => write!(f, "({} = {})", m, n),
+            CaseBinding::Pattern(pat) => write!(f, "{}", pat),
+        }
+    }
+}
+
+impl Display for CaseHead {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "{}", self.constructor)?;
+        for bind in &self.bindings {
+            write!(f, " {}", bind)?
+        }
+        if self.ignore_rest.is_some() {
+            write!(f, " ..")?;
         }
+        Ok(())
     }
 }
 
@@ -476,14 +589,31 @@ impl Display for Case {
         if self.ignore_rest.is_some() {
             write!(f, " ..")?;
         }
+        for head in &self.others {
+            write!(f, ", {}", head)?
+        }
         write!(f, " => {}", self.value)
     }
 }
 
+impl Display for Scrutinee {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        write!(f, "{} {}", self.typ, self.name)?;
+        if let Some(value) = &self.value {
+            write!(f, " = {}", value)?;
+        }
+        Ok(())
+    }
+}
+
 impl Display for Match {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
         write!(f, "match {} {}", self.typ, self.scrutinee)?;
 
+        for other in &self.others {
+            write!(f, ", {}", other)?
+        }
+
         match &self.motive {
             None => Ok(()),
             Some(res) => write!(f, " : {}", res),
@@ -586,11 +716,29 @@ impl Display for Expr {
                     .collect::<Vec<String>>()
                     .join(" ")
             ),
+            Interpolation { parts } => {
+                write!(f, "\"")?;
+                for part in parts {
+                    match part {
+                        StrPart::Text(text) => write!(f, "{}", escape_str(text))?,
+                        StrPart::Expr(expr) => write!(f, "${{{}}}", expr)?,
+                    }
+                }
+                write!(f, "\"")
+            }
             Ann { val: name, typ } => write!(f, "({} :: {})", name, typ),
-            Binary { op, fst, snd } => write!(f, "({} {} {})", op, fst, snd),
+            Binary { op, fst, snd } => write!(f, "({} {} {})", fst, op, snd),
+            Infix { fst, rest } => {
+                write!(f, "({}", fst)?;
+                for (op, _, expr) in rest {
+                    write!(f, " {} {}", op, expr)?;
+                }
+                write!(f, ")")
+            }
             Match(matcher) => write!(f, "({})", matcher),
             Subst(subst) => write!(f, "({})", subst),
             Hole => write!(f, "_"),
+            Error => write!(f, "ERR"),
             SeqRecord(rec) => {
                 use SeqOperation::*;
                 write!(f, "(!({}) {} {}", rec.typ, rec.expr, rec.fields.iter().map(|x| format!(".{}", x.to_str())).collect::<Vec<_>>().join(","))?;
@@ -12,17 +12,21 @@ use expr::Expr;
 use fxhash::FxHashMap;
 use kind_span::{Locatable, Range};
 use linked_hash_map::LinkedHashMap;
+use serde::Serialize;
 
 use self::pat::Pat;
 
+pub mod arena;
 pub mod expr;
 pub mod pat;
 pub mod visitor;
 
+mod pretty;
+
 pub use expr::*;
 
 /// A value of a attribute
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum AttributeStyle {
     Ident(Range, Ident),
     String(Range, String),
@@ -34,7 +38,7 @@ pub enum AttributeStyle {
 /// that usually is on the top of a declaration
 /// and can be attached to a function declaration
 /// it express some compiler properties
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct Attribute {
     pub name: Ident,
     pub args: Vec<AttributeStyle>,
@@ -42,13 +46,19 @@ pub struct Attribute {
     pub range: Range,
 }
 
+/// Checks if a list of attributes contains the `#private`
+/// attribute that hides a name from other files.
+pub fn is_private(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|x| x.name.to_str() == "private")
+}
+
 /// An argument is a 'binding' of a name to a type
 /// it has some other options like
 /// eras: that express the erasure of this type when
 /// compiled.
 /// hide: that express a implicit argument (that will
 /// be discovered through unification).
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct Argument {
     pub hidden: bool,
     pub erased: bool,
@@ -59,12 +69,14 @@ pub struct Argument {
 
 /// A rule is a equation that in the left-hand-side
 /// contains a list of patterns @pats@ and on the
-/// right hand side a value.
-#[derive(Clone, Debug)]
+/// right hand side a value. The local definitions of
+/// the `where` block can see the variables of the patterns.
+#[derive(Clone, Debug, Serialize)]
 pub struct Rule {
     pub name: QualifiedIdent,
     pub pats: Vec<Box<Pat>>,
     pub body: Box<Expr>,
+    pub wheres: Vec<Entry>,
     pub range: Range,
 }
 
@@ -72,7 +84,7 @@ pub struct Rule {
 /// and has rules. The type of the function
 /// consists of the arguments @args@ and the
 /// return type @typ@.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct Entry {
     pub name: QualifiedIdent,
     pub docs: Vec<String>,
@@ -86,7 +98,7 @@ pub struct Entry {
 
 /// A single cosntructor inside the algebraic data
 /// type definition.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct Constructor {
     pub name: Ident,
     pub docs: Vec<String>,
@@ -97,7 +109,7 @@ pub struct Constructor {
 
 /// An algebraic data type definition that supports
 /// parametric and indexed data type definitions.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct SumTypeDecl {
     pub name: QualifiedIdent,
     pub docs: Vec<String>,
@@ -108,7 +120,7 @@ pub struct SumTypeDecl {
 }
 
 /// A single constructor data type.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct RecordDecl {
     pub name: QualifiedIdent,
     pub docs: Vec<String>,
@@ -121,16 +133,31 @@ pub struct RecordDecl {
 
 impl RecordDecl {
     pub fn get_constructor(&self) -> Constructor {
-        Constructor { name: self.constructor.clone(), docs: vec![], attrs:  self.cons_attrs.clone(), args: self.fields_to_arguments(), typ: None }
+        Constructor { name: self.constructor, docs: vec![], attrs:  self.cons_attrs.clone(), args: self.fields_to_arguments(), typ: None }
     }
 }
 
+/// A user defined infix operator like `notation "xs ++ ys" = List.concat _ xs ys`.
+/// Each use of the operator is replaced by the body with `fst` and
+/// `snd` replaced by the two sides of it.
+#[derive(Clone, Debug, Serialize)]
+pub struct Notation {
+    pub symbol: String,
+    pub docs: Vec<String>,
+    pub attrs: Vec<Attribute>,
+    pub fst: Ident,
+    pub snd: Ident,
+    pub body: Box<Expr>,
+    pub range: Range,
+}
+
 /// All of the structures
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub enum TopLevel {
     SumType(SumTypeDecl),
     RecordType(RecordDecl),
     Entry(Entry),
+    Notation(Notation),
 }
 
 impl TopLevel {
@@ -138,7 +165,7 @@ impl TopLevel {
         match self {
             TopLevel::SumType(sum) => Some(Cow::Borrowed(&sum.constructors)),
             TopLevel::RecordType(rec) => Some(Cow::Owned(vec![rec.get_constructor()])),
-            TopLevel::Entry(_) => None,
+            TopLevel::Entry(_) | TopLevel::Notation(_) => None,
         }
     }
 
@@ -146,7 +173,7 @@ impl TopLevel {
         match self {
             TopLevel::SumType(sum) => Some(Cow::Borrowed(&sum.indices)),
             TopLevel::RecordType(_) => Some(Cow::Owned(Default::default())),
-            TopLevel::Entry(_) => None,
+            TopLevel::Entry(_) | TopLevel::Notation(_) => None,
         }
     }
 
@@ -166,9 +193,10 @@ impl TopLevel {
 /// A module is a collection of top level entries
 /// that contains syntatic sugars. In the future
 /// it will contain a HashMap to local renames.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct Module {
     pub entries: Vec<TopLevel>,
+    #[serde(serialize_with = "crate::serialize_sorted")]
     pub uses: FxHashMap<String, String>,
 }
 
@@ -176,7 +204,7 @@ pub struct Module {
 /// are trying to desugar something that does not contains
 /// a lot of information like a record definition or a sum
 /// type definition.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct EntryMeta {
     pub hiddens: usize,
     pub erased: usize,
@@ -188,16 +216,21 @@ pub struct EntryMeta {
 
 /// A book stores definitions by name. It's generated
 /// by joining a bunch of books that are already resolved.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize)]
 pub struct Book {
     // Ordered hashset
     pub names: LinkedHashMap<String, QualifiedIdent>,
 
     // Probably deterministic order everytime
+    #[serde(serialize_with = "crate::serialize_sorted")]
     pub entries: FxHashMap<String, TopLevel>,
 
     // Stores some important information in order to desugarize
+    #[serde(serialize_with = "crate::serialize_sorted")]
     pub meta: FxHashMap<String, EntryMeta>,
+
+    // Notations of all of the loaded files by their symbol
+    pub notations: LinkedHashMap<String, Notation>,
 }
 
 impl Book {
@@ -280,10 +313,27 @@ impl Display for TopLevel {
                 writeln!(f, "}}\n")
             }
             TopLevel::Entry(entr) => writeln!(f, "{}", entr),
+            TopLevel::Notation(notation) => writeln!(f, "{}", notation),
         }
     }
 }
 
+impl Display for Notation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for doc in &self.docs {
+            writeln!(f, "/// {}", doc)?;
+        }
+        for attr in &self.attrs {
+            writeln!(f, "{}", attr)?;
+        }
+        writeln!(
+            f,
+            "notation \"{} {} {}\" = {}",
+            self.fst, self.symbol, self.snd, self.body
+        )
+    }
+}
+
 impl Display for Module {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
         for entr in &self.entries {
@@ -300,6 +350,9 @@ impl Display for Book {
                 _ => write!(f, "{}", entr)?,
             }
         }
+        for notation in self.notations.values() {
+            write!(f, "{}", notation)?;
+        }
         Ok(())
     }
 }
@@ -387,7 +440,15 @@ impl Display for Rule {
         for pat in &self.pats {
             write!(f, " {}", pat)?;
         }
-        write!(f, " = {}", self.body)
+        write!(f, " = {}", self.body)?;
+        if !self.wheres.is_empty() {
+            writeln!(f, "\n  where {{")?;
+            for entry in &self.wheres {
+                write!(f, "{}", entry)?;
+            }
+            write!(f, "}}")?;
+        }
+        Ok(())
     }
 }
 
@@ -495,7 +556,7 @@ impl RecordDecl {
                 .iter()
                 .map(|(name, _docs, typ)| {
                     Argument::new_explicit(
-                        name.clone(),
+                        *name,
                         typ.clone(),
                         name.locate().mix(typ.locate()),
                     )
@@ -538,7 +599,7 @@ impl RecordDecl {
             .fields
             .iter()
             .map(|(name, _docs, typ)| {
-                Argument::new_explicit(name.clone(), typ.clone(), name.locate().mix(typ.locate()))
+                Argument::new_explicit(*name, typ.clone(), name.locate().mix(typ.locate()))
             })
             .collect();
 
@@ -550,7 +611,7 @@ impl RecordDecl {
             arguments,
             is_ctr: true,
             range: self.name.range,
-            is_record_cons_of: Some(self.name.clone()),
+            is_record_cons_of: Some(self.name),
         }
     }
 }
@@ -593,7 +654,7 @@ impl Argument {
         Argument {
             hidden: true,
             erased: true,
-            name: self.name.clone(),
+            name: self.name,
             typ: self.typ.clone(),
             range: self.range,
         }
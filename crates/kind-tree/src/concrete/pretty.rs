@@ -0,0 +1,417 @@
+//! Pretty printing of the concrete tree. It's used to show the
+//! code after the parsing and the expressions render to the same
+//! text as [Display] when they fit in a single line.
+//!
+//! [Display]: std::fmt::Display
+
+use kind_pretty::{Doc, Pretty};
+
+use super::expr::{AppBinding, Binding, Case, Expr, ExprKind, Match};
+use super::{Argument, Book, Constructor, Entry, Notation, Rule, TopLevel};
+
+/// Lays out the body of a pi or sigma type and the types that
+/// come after it like `(a : A) -> [b : B] -> C`.
+fn pi_types(expr: &Expr, binders: &mut Vec<Doc>) {
+    match &expr.data {
+        ExprKind::All {
+            param,
+            typ,
+            body,
+            erased,
+        } => {
+            let tilde = if *erased { "~" } else { "" };
+            match param {
+                None => binders.push(Doc::text(tilde).append(typ.to_doc())),
+                Some(binder) => binders.push(Doc::concat([
+                    Doc::text(format!("{}({} : ", tilde, binder)),
+                    typ.to_doc(),
+                    Doc::text(")"),
+                ])),
+            }
+            pi_types(body, binders)
+        }
+        ExprKind::Sigma { param, fst, snd } => {
+            match param {
+                None => binders.push(fst.to_doc()),
+                Some(binder) => binders.push(Doc::concat([
+                    Doc::text(format!("[{} : ", binder)),
+                    fst.to_doc(),
+                    Doc::text("]"),
+                ])),
+            }
+            pi_types(snd, binders)
+        }
+        _ => binders.push(expr.to_doc()),
+    }
+}
+
+fn lines<T: ToString>(prefix: &str, items: &[T]) -> Doc {
+    Doc::concat(
+        items
+            .iter()
+            .map(|x| Doc::concat([Doc::text(format!("{}{}", prefix, x.to_string())), Doc::HardLine])),
+    )
+}
+
+/// Lays out a name with its arguments and its type. When it does
+/// not fit, each argument goes to its own line.
+fn signature<'a>(name: String, args: impl Iterator<Item = &'a Argument>, typ: Option<&Expr>) -> Doc {
+    let typ = match typ {
+        Some(typ) => Doc::concat([
+            Doc::text(" :"),
+            Doc::concat([Doc::line(), typ.to_doc()]).nest(2).group(),
+        ]),
+        None => Doc::Nil,
+    };
+
+    let head = Doc::concat([
+        Doc::text(name),
+        Doc::concat(args.map(|x| Doc::line().append(x.to_doc()))).nest(2),
+    ]);
+
+    Doc::concat([head.group(), typ])
+}
+
+impl Pretty for Binding {
+    fn to_doc(&self) -> Doc {
+        match self {
+            Binding::Positional(e) => e.to_doc(),
+            Binding::Named(_, i, e) => Doc::binder(Doc::text(format!("{} :", i)), e.to_doc()),
+        }
+    }
+}
+
+impl Pretty for AppBinding {
+    fn to_doc(&self) -> Doc {
+        if self.erased {
+            Doc::concat([Doc::text("~("), self.data.to_doc(), Doc::text(")")])
+        } else {
+            self.data.to_doc()
+        }
+    }
+}
+
+impl Pretty for Case {
+    fn to_doc(&self) -> Doc {
+        let mut head = self.constructor.to_string();
+        for bind in &self.bindings {
+            head.push_str(&format!(" {}", bind));
+        }
+        if self.ignore_rest.is_some() {
+            head.push_str(" ..");
+        }
+        for other in &self.others {
+            head.push_str(&format!(", {}", other));
+        }
+        Doc::concat([
+            Doc::text(format!("{} =>", head)),
+            Doc::concat([Doc::line(), self.value.to_doc()]).nest(2),
+        ])
+        .group()
+    }
+}
+
+impl Pretty for Match {
+    fn to_doc(&self) -> Doc {
+        let mut head = format!("(match {} {}", self.typ, self.scrutinee);
+        for other in &self.others {
+            head.push_str(&format!(", {}", other));
+        }
+
+        let motive = match &self.motive {
+            None => Doc::Nil,
+            Some(res) => Doc::text(" : ").append(res.to_doc()),
+        };
+
+        let cases = self
+            .cases
+            .iter()
+            .map(|x| Doc::concat([Doc::line(), x.to_doc(), Doc::text(";")]));
+
+        Doc::concat([
+            Doc::text(head),
+            motive,
+            Doc::text(" {"),
+            Doc::concat(cases).nest(2),
+            Doc::line(),
+            Doc::text("})"),
+        ])
+        .group()
+    }
+}
+
+impl Pretty for Expr {
+    fn to_doc(&self) -> Doc {
+        use ExprKind::*;
+        match &self.data {
+            All { .. } | Sigma { .. } => {
+                let mut binders = Vec::new();
+                pi_types(self, &mut binders);
+                Doc::concat([
+                    Doc::text("("),
+                    Doc::join(binders, Doc::text(" ->").append(Doc::line())).align(),
+                    Doc::text(")"),
+                ])
+                .group()
+            }
+            Constr { name, args } => {
+                Doc::app(Doc::text(name.to_string()), args.iter().map(|x| x.to_doc()))
+            }
+            Lambda {
+                param,
+                typ,
+                body,
+                erased,
+            } => {
+                let head = match (typ, erased) {
+                    (None, false) => Doc::text(param.to_string()),
+                    (None, true) => Doc::text(format!("~({})", param)),
+                    (Some(typ), false) => Doc::concat([
+                        Doc::text(format!("({} : ", param)),
+                        typ.to_doc(),
+                        Doc::text(")"),
+                    ]),
+                    (Some(typ), true) => Doc::concat([
+                        Doc::text(format!("{{{} : ", param)),
+                        typ.to_doc(),
+                        Doc::text("}"),
+                    ]),
+                };
+                Doc::binder(head.append(Doc::text(" =>")), body.to_doc())
+            }
+            Pair { fst, snd } => Doc::app(Doc::text("$"), [fst.to_doc(), snd.to_doc()]),
+            App { fun, args } => Doc::app(fun.to_doc(), args.iter().map(|x| x.to_doc())),
+            Let { name, val, next } => Doc::concat([
+                Doc::text(format!("(let {} =", name)),
+                Doc::concat([Doc::line(), val.to_doc(), Doc::text(";")]).nest(2).group(),
+                Doc::concat([Doc::line(), next.to_doc()]).nest(1),
+                Doc::text(")"),
+            ])
+            .align()
+            .group(),
+            Open {
+                type_name,
+                var_name,
+                motive,
+                next,
+            } => {
+                let motive = match motive {
+                    Some(motive) => Doc::text(" : ").append(motive.to_doc()),
+                    None => Doc::Nil,
+                };
+                Doc::concat([
+                    Doc::text(format!("(open {} {}", type_name, var_name)),
+                    motive,
+                    Doc::text(";"),
+                    Doc::line(),
+                    next.to_doc(),
+                    Doc::text(")"),
+                ])
+                .group()
+            }
+            If { cond, then_, else_ } => Doc::concat([
+                Doc::text("(if "),
+                cond.to_doc(),
+                Doc::text(" {"),
+                Doc::concat([Doc::softline(), then_.to_doc()]).nest(2),
+                Doc::softline(),
+                Doc::text("} else {"),
+                Doc::concat([Doc::softline(), else_.to_doc()]).nest(2),
+                Doc::softline(),
+                Doc::text("})"),
+            ])
+            .group(),
+            List { args } => Doc::concat([
+                Doc::text("["),
+                Doc::join(args.iter().map(|x| x.to_doc()), Doc::line()).nest(1),
+                Doc::text("]"),
+            ])
+            .group(),
+            Ann { val, typ } => Doc::binder(val.to_doc(), Doc::text(":: ").append(typ.to_doc())),
+            Binary { op, fst, snd } => Doc::binder(
+                fst.to_doc(),
+                Doc::text(format!("{} ", op)).append(snd.to_doc()),
+            ),
+            Infix { fst, rest } => {
+                let rest = rest.iter().map(|(op, _, expr)| {
+                    Doc::concat([Doc::line(), Doc::text(format!("{} ", op)), expr.to_doc()])
+                });
+                Doc::concat([
+                    Doc::text("("),
+                    fst.to_doc(),
+                    Doc::concat(rest).nest(2),
+                    Doc::text(")"),
+                ])
+                .group()
+            }
+            Match(matcher) => matcher.to_doc(),
+            _ => Doc::text(self.to_string()),
+        }
+    }
+}
+
+impl Pretty for Argument {
+    fn to_doc(&self) -> Doc {
+        let (open, close) = match (self.erased, self.hidden) {
+            (false, false) => ("(", ")"),
+            (false, true) => ("+<", ">"),
+            (true, false) => ("-(", ")"),
+            (true, true) => ("<", ">"),
+        };
+        match &self.typ {
+            Some(typ) => Doc::concat([
+                Doc::text(format!("{}{}: ", open, self.name)),
+                typ.to_doc(),
+                Doc::text(close),
+            ]),
+            None => Doc::text(format!("{}{}{}", open, self.name, close)),
+        }
+    }
+}
+
+impl Pretty for Rule {
+    fn to_doc(&self) -> Doc {
+        let lhs = Doc::concat([
+            Doc::text(self.name.to_string()),
+            Doc::concat(self.pats.iter().map(|x| Doc::line().append(Doc::text(x.to_string())))).nest(2),
+        ]);
+
+        let rule = Doc::concat([
+            lhs.group(),
+            Doc::text(" ="),
+            Doc::concat([Doc::line(), self.body.to_doc()]).nest(2).group(),
+        ]);
+
+        if self.wheres.is_empty() {
+            rule
+        } else {
+            Doc::concat([
+                rule,
+                Doc::HardLine,
+                Doc::text("  where {"),
+                Doc::HardLine,
+                Doc::concat(self.wheres.iter().map(|x| x.to_doc())),
+                Doc::text("}"),
+            ])
+        }
+    }
+}
+
+impl Pretty for Entry {
+    fn to_doc(&self) -> Doc {
+        Doc::concat([
+            lines("/// ", &self.docs),
+            lines("", &self.attrs),
+            signature(self.name.to_string(), self.args.iter(), Some(&self.typ)),
+            Doc::HardLine,
+            Doc::concat(self.rules.iter().map(|x| x.to_doc().append(Doc::HardLine))),
+        ])
+    }
+}
+
+impl Pretty for Constructor {
+    fn to_doc(&self) -> Doc {
+        Doc::concat([
+            lines("/// ", &self.docs),
+            signature(self.name.to_string(), self.args.iter(), self.typ.as_deref()),
+        ])
+    }
+}
+
+impl Pretty for Notation {
+    fn to_doc(&self) -> Doc {
+        Doc::concat([
+            lines("/// ", &self.docs),
+            lines("", &self.attrs),
+            Doc::text(format!(
+                "notation \"{} {} {}\" =",
+                self.fst, self.symbol, self.snd
+            )),
+            Doc::concat([Doc::line(), self.body.to_doc()]).nest(2).group(),
+            Doc::HardLine,
+        ])
+    }
+}
+
+impl Pretty for TopLevel {
+    fn to_doc(&self) -> Doc {
+        match self {
+            TopLevel::SumType(sum) => {
+                let indices = if sum.indices.is_empty() {
+                    Doc::Nil
+                } else {
+                    Doc::concat([
+                        Doc::text(" ~"),
+                        Doc::concat(sum.indices.iter().map(|x| Doc::line().append(x.to_doc()))),
+                    ])
+                };
+
+                let head = Doc::concat([
+                    Doc::text(format!("type {}", sum.name)),
+                    Doc::concat(sum.parameters.iter().map(|x| Doc::line().append(x.to_doc()))),
+                    indices,
+                ])
+                .nest(2)
+                .group();
+
+                let constructors = sum.constructors.iter().map(|cons| {
+                    Doc::concat([Doc::text("  "), cons.to_doc().nest(2), Doc::HardLine])
+                });
+
+                Doc::concat([
+                    lines("/// ", &sum.docs),
+                    lines("", &sum.attrs),
+                    head,
+                    Doc::text(" {"),
+                    Doc::HardLine,
+                    Doc::concat(constructors),
+                    Doc::text("}"),
+                    Doc::HardLine,
+                    Doc::HardLine,
+                ])
+            }
+            TopLevel::RecordType(rec) => {
+                let head = Doc::concat([
+                    Doc::text(format!("record {}", rec.name)),
+                    Doc::concat(rec.parameters.iter().map(|x| Doc::line().append(x.to_doc()))),
+                ])
+                .nest(2)
+                .group();
+
+                let fields = rec.fields.iter().map(|(name, docs, typ)| {
+                    Doc::concat([
+                        lines("  /// ", docs),
+                        Doc::text(format!("  {} :", name)),
+                        Doc::concat([Doc::line(), typ.to_doc()]).nest(4).group(),
+                        Doc::HardLine,
+                    ])
+                });
+
+                Doc::concat([
+                    lines("/// ", &rec.docs),
+                    lines("", &rec.attrs),
+                    head,
+                    Doc::text(" {"),
+                    Doc::HardLine,
+                    Doc::text(format!("  constructor {}", rec.constructor)),
+                    Doc::HardLine,
+                    Doc::concat(fields),
+                    Doc::text("}"),
+                    Doc::HardLine,
+                    Doc::HardLine,
+                ])
+            }
+            TopLevel::Entry(entr) => entr.to_doc().append(Doc::HardLine),
+            TopLevel::Notation(notation) => notation.to_doc().append(Doc::HardLine),
+        }
+    }
+}
+
+impl Pretty for Book {
+    fn to_doc(&self) -> Doc {
+        Doc::concat([
+            Doc::concat(self.entries.values().map(|x| x.to_doc())),
+            Doc::concat(self.notations.values().map(|x| x.to_doc())),
+        ])
+    }
+}
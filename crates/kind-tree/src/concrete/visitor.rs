@@ -13,7 +13,7 @@ use crate::{concrete::expr::*, concrete::Book};
 
 use super::pat::{Pat, PatIdent, PatKind};
 use super::TopLevel;
-use super::{Argument, Attribute, AttributeStyle, Constructor, Entry, Module, Rule};
+use super::{Argument, Attribute, AttributeStyle, Constructor, Entry, Module, Notation, Rule};
 
 #[macro_export]
 macro_rules! visit_vec {
@@ -116,6 +116,10 @@ pub trait Visitor: Sized {
         walk_rule(self, rule);
     }
 
+    fn visit_notation(&mut self, notation: &mut Notation) {
+        walk_notation(self, notation);
+    }
+
     fn visit_module(&mut self, module: &mut Module) {
         walk_module(self, module);
     }
@@ -155,6 +159,7 @@ pub fn walk_constructor<T: Visitor>(ctx: &mut T, cons: &mut Constructor) {
 
 pub fn walk_book<T: Visitor>(ctx: &mut T, book: &mut Book) {
     visit_vec!(&mut book.entries, (_, arg) => ctx.visit_top_level(arg));
+    visit_vec!(&mut book.notations, (_, arg) => ctx.visit_notation(arg));
 }
 
 pub fn walk_pat_ident<T: Visitor>(ctx: &mut T, ident: &mut PatIdent) {
@@ -202,6 +207,7 @@ pub fn walk_case_binding<T: Visitor>(ctx: &mut T, binding: &mut CaseBinding) {
             ctx.visit_ident(ident);
             ctx.visit_ident(rename);
         }
+        CaseBinding::Pattern(pat) => ctx.visit_pat(pat),
     }
 }
 
@@ -210,6 +216,12 @@ pub fn walk_case<T: Visitor>(ctx: &mut T, case: &mut Case) {
     for binding in &mut case.bindings {
         ctx.visit_case_binding(binding);
     }
+    for head in &mut case.others {
+        ctx.visit_ident(&mut head.constructor);
+        for binding in &mut head.bindings {
+            ctx.visit_case_binding(binding);
+        }
+    }
     ctx.visit_expr(&mut case.value)
 }
 
@@ -222,6 +234,14 @@ pub fn walk_match<T: Visitor>(ctx: &mut T, matcher: &mut Match) {
         ctx.visit_expr(opt);
     }
 
+    for other in &mut matcher.others {
+        ctx.visit_qualified_ident(&mut other.typ);
+        ctx.visit_ident(&mut other.name);
+        if let Some(opt) = &mut other.value {
+            ctx.visit_expr(opt);
+        }
+    }
+
     for (name, expr) in &mut matcher.with_vars {
         ctx.visit_ident(name);
         if let Some(expr) = expr {
@@ -321,6 +341,9 @@ pub fn walk_rule<T: Visitor>(ctx: &mut T, rule: &mut Rule) {
         ctx.visit_pat(pat);
     }
     ctx.visit_expr(&mut rule.body);
+    for entry in &mut rule.wheres {
+        ctx.visit_entry(entry);
+    }
     ctx.visit_range(&mut rule.range);
 }
 
@@ -345,9 +368,20 @@ pub fn walk_top_level<T: Visitor>(ctx: &mut T, toplevel: &mut TopLevel) {
         super::TopLevel::Entry(entry) => {
             ctx.visit_entry(entry);
         }
+        super::TopLevel::Notation(notation) => {
+            ctx.visit_notation(notation);
+        }
     }
 }
 
+pub fn walk_notation<T: Visitor>(ctx: &mut T, notation: &mut Notation) {
+    visit_vec!(&mut notation.attrs, arg => ctx.visit_attr(arg));
+    ctx.visit_ident(&mut notation.fst);
+    ctx.visit_ident(&mut notation.snd);
+    ctx.visit_expr(&mut notation.body);
+    ctx.visit_range(&mut notation.range);
+}
+
 pub fn walk_module<T: Visitor>(ctx: &mut T, module: &mut Module) {
     for toplevel in &mut module.entries {
         walk_top_level(ctx, toplevel)
@@ -481,6 +515,13 @@ pub fn walk_expr<T: Visitor>(ctx: &mut T, expr: &mut Expr) {
             ctx.visit_expr(fst);
             ctx.visit_expr(snd);
         }
+        ExprKind::Infix { fst, rest } => {
+            ctx.visit_expr(fst);
+            for (_, range, expr) in rest {
+                ctx.visit_range(range);
+                ctx.visit_expr(expr);
+            }
+        }
         ExprKind::Open { type_name, var_name, motive, next } => {
             ctx.visit_qualified_ident(type_name);
             ctx.visit_ident(var_name);
@@ -489,7 +530,15 @@ pub fn walk_expr<T: Visitor>(ctx: &mut T, expr: &mut Expr) {
                 ctx.visit_expr(res);
             }
         },
+        ExprKind::Interpolation { parts } => {
+            for part in parts {
+                if let StrPart::Expr(expr) = part {
+                    ctx.visit_expr(expr);
+                }
+            }
+        }
         ExprKind::Hole => {}
+        ExprKind::Error => {}
         ExprKind::Subst(subst) => ctx.visit_substitution(subst),
         ExprKind::Match(matcher) => ctx.visit_match(matcher),
         ExprKind::SeqRecord(seq) => {
@@ -0,0 +1,346 @@
+//! Structural hashing and alpha-equivalence. Both of them look at
+//! the same stream of tokens that ignores ranges, the names of the
+//! bound variables (they are turned into de Bruijn indices) and the
+//! numbers of the holes. Inside of an entry, the references to the
+//! entry itself are also anonymous, so two lemmas with the same
+//! statement and proof but different names are equivalent.
+//!
+//! The hash only depends on the tokens, that are written with fixed
+//! size integers, so it's the same in every build and target.
+
+use std::hash::Hasher;
+
+use fxhash::FxHasher64;
+
+use super::{Argument, Entry, Expr, ExprKind, Rule};
+use crate::symbol::{Ident, QualifiedIdent, Symbol};
+use crate::Operator;
+
+#[derive(PartialEq, Eq)]
+enum Token<'a> {
+    /// The kind of the node, see [node_tag].
+    Node(u8),
+    Flag(bool),
+    Len(u32),
+    /// A variable that is bound at the index (counting from
+    /// the innermost binder).
+    Bound(u32),
+    Free(&'a str),
+    This,
+    Num(u64),
+    Text(&'a str),
+    Op(Operator),
+}
+
+impl<'a> Token<'a> {
+    fn tag(&self) -> u8 {
+        match self {
+            Token::Node(_) => 0,
+            Token::Flag(_) => 1,
+            Token::Len(_) => 2,
+            Token::Bound(_) => 3,
+            Token::Free(_) => 4,
+            Token::This => 5,
+            Token::Num(_) => 6,
+            Token::Text(_) => 7,
+            Token::Op(_) => 8,
+        }
+    }
+
+    /// Writes the token with integers of fixed size. The bytes of
+    /// the strings are written one by one because [Hasher::write]
+    /// depends on the endianness of the target in [FxHasher64].
+    fn hash_into(&self, hasher: &mut impl Hasher) {
+        hasher.write_u8(self.tag());
+        match self {
+            Token::Node(tag) => hasher.write_u8(*tag),
+            Token::Flag(flag) => hasher.write_u8(*flag as u8),
+            Token::Len(len) | Token::Bound(len) => hasher.write_u32(*len),
+            Token::Free(text) | Token::Text(text) => {
+                hasher.write_u32(text.len() as u32);
+                for byte in text.bytes() {
+                    hasher.write_u8(byte);
+                }
+            }
+            Token::This => (),
+            Token::Num(numb) => hasher.write_u64(*numb),
+            Token::Op(op) => hasher.write_u8(operator_tag(*op)),
+        }
+    }
+}
+
+/// A fixed number for each kind of expression. The numbers must not
+/// change, otherwise the hashes change too.
+fn node_tag(kind: &ExprKind) -> u8 {
+    use ExprKind::*;
+
+    match kind {
+        Var { .. } => 0,
+        All { .. } => 1,
+        Lambda { .. } => 2,
+        App { .. } => 3,
+        Fun { .. } => 4,
+        Ctr { .. } => 5,
+        Let { .. } => 6,
+        Ann { .. } => 7,
+        Sub { .. } => 8,
+        Typ => 9,
+        NumTypeU60 => 10,
+        NumTypeF60 => 11,
+        NumTypeI60 => 12,
+        NumU60 { .. } => 13,
+        NumF60 { .. } => 14,
+        NumI60 { .. } => 15,
+        Str { .. } => 16,
+        Binary { .. } => 17,
+        Hole { .. } => 22,
+        Hlp(_) => 23,
+        Err => 24,
+    }
+}
+
+fn operator_tag(op: Operator) -> u8 {
+    use Operator::*;
+
+    match op {
+        Add => 0,
+        Sub => 1,
+        Mul => 2,
+        Div => 3,
+        Mod => 4,
+        And => 5,
+        Or => 6,
+        Xor => 7,
+        Shl => 8,
+        Shr => 9,
+        Ltn => 10,
+        Lte => 11,
+        Eql => 12,
+        Gte => 13,
+        Gtn => 14,
+        Neq => 15,
+    }
+}
+
+#[derive(Default)]
+struct Tokens<'a> {
+    tokens: Vec<Token<'a>>,
+    env: Vec<Symbol>,
+    this: Option<&'a str>,
+}
+
+impl<'a> Tokens<'a> {
+    fn with(&mut self, name: &Ident, fun: impl FnOnce(&mut Self)) {
+        self.env.push(name.data);
+        fun(self);
+        self.env.pop();
+    }
+
+    fn var(&mut self, name: &'a Ident) {
+        let token = match self.env.iter().rev().position(|x| *x == name.data) {
+            Some(index) => Token::Bound(index as u32),
+            None => Token::Free(name.to_str()),
+        };
+        self.tokens.push(token)
+    }
+
+    fn qualified(&mut self, name: &'a QualifiedIdent) {
+        let token = match self.this {
+            Some(this) if this == name.to_str() => Token::This,
+            _ => Token::Free(name.to_str()),
+        };
+        self.tokens.push(token)
+    }
+
+    fn exprs(&mut self, exprs: impl ExactSizeIterator<Item = &'a Box<Expr>>) {
+        self.tokens.push(Token::Len(exprs.len() as u32));
+        for expr in exprs {
+            self.expr(expr);
+        }
+    }
+
+    fn expr(&mut self, expr: &'a Expr) {
+        use ExprKind::*;
+
+        self.tokens.push(Token::Node(node_tag(&expr.data)));
+
+        match &expr.data {
+            Var { name } => self.var(name),
+            All {
+                param,
+                typ,
+                body,
+                erased,
+            } => {
+                self.tokens.push(Token::Flag(*erased));
+                self.expr(typ);
+                self.with(param, |this| this.expr(body));
+            }
+            Lambda {
+                param,
+                body,
+                erased,
+            } => {
+                self.tokens.push(Token::Flag(*erased));
+                self.with(param, |this| this.expr(body));
+            }
+            App { fun, args } => {
+                self.expr(fun);
+                self.tokens.push(Token::Len(args.len() as u32));
+                for arg in args {
+                    self.tokens.push(Token::Flag(arg.erased));
+                    self.expr(&arg.data);
+                }
+            }
+            Fun { name, args } | Ctr { name, args } => {
+                self.qualified(name);
+                self.exprs(args.iter());
+            }
+            Let { name, val, next } => {
+                self.expr(val);
+                self.with(name, |this| this.expr(next));
+            }
+            Ann { expr, typ } => {
+                self.expr(expr);
+                self.expr(typ);
+            }
+            Sub {
+                name,
+                indx,
+                redx,
+                expr,
+            } => {
+                self.var(name);
+                self.tokens.push(Token::Num(*indx as u64));
+                self.tokens.push(Token::Num(*redx as u64));
+                self.expr(expr);
+            }
+            NumU60 { numb } | NumF60 { numb } => self.tokens.push(Token::Num(*numb)),
+            NumI60 { numb } => self.tokens.push(Token::Num(*numb as u64)),
+            Str { val } => self.tokens.push(Token::Text(val)),
+            Binary {
+                op,
+                left,
+                right,
+                signed,
+            } => {
+                self.tokens.push(Token::Op(*op));
+                self.tokens.push(Token::Flag(*signed));
+                self.expr(left);
+                self.expr(right);
+            }
+            Hlp(name) => self.tokens.push(Token::Free(name.to_str())),
+            Typ | NumTypeU60 | NumTypeF60 | NumTypeI60 | Hole { .. } | Err => (),
+        }
+    }
+
+    /// The variables of a pattern are binders for the body of the
+    /// rule so they are added to the environment in order.
+    fn pattern(&mut self, pat: &'a Expr) {
+        match &pat.data {
+            ExprKind::Var { name } => {
+                self.tokens.push(Token::Node(node_tag(&pat.data)));
+                self.env.push(name.data);
+            }
+            ExprKind::Fun { name, args } | ExprKind::Ctr { name, args } => {
+                self.tokens.push(Token::Node(node_tag(&pat.data)));
+                self.qualified(name);
+                self.tokens.push(Token::Len(args.len() as u32));
+                for arg in args {
+                    self.pattern(arg);
+                }
+            }
+            _ => self.expr(pat),
+        }
+    }
+
+    fn rule(&mut self, rule: &'a Rule) {
+        let len = self.env.len();
+        self.tokens.push(Token::Len(rule.pats.len() as u32));
+        for pat in &rule.pats {
+            self.pattern(pat);
+        }
+        self.expr(&rule.body);
+        self.env.truncate(len);
+    }
+
+    fn argument(&mut self, arg: &'a Argument) {
+        self.tokens.push(Token::Flag(arg.hidden));
+        self.tokens.push(Token::Flag(arg.erased));
+        self.expr(&arg.typ);
+        self.env.push(arg.name.data);
+    }
+
+    fn entry(&mut self, entry: &'a Entry) {
+        self.this = Some(entry.name.to_str());
+
+        self.tokens.push(Token::Len(entry.args.len() as u32));
+        for arg in &entry.args {
+            self.argument(arg);
+        }
+        self.expr(&entry.typ);
+        self.env.clear();
+
+        // Constructors and axioms are only defined by their names.
+        if entry.rules.is_empty() {
+            self.tokens.push(Token::Free(entry.name.to_str()));
+        }
+
+        self.tokens.push(Token::Len(entry.rules.len() as u32));
+        for rule in &entry.rules {
+            self.rule(rule);
+        }
+    }
+
+    fn finish(self) -> u64 {
+        let mut hasher = FxHasher64::default();
+        hasher.write_u32(self.tokens.len() as u32);
+        for token in &self.tokens {
+            token.hash_into(&mut hasher);
+        }
+        hasher.finish()
+    }
+}
+
+impl Expr {
+    /// A hash that is the same for alpha-equivalent expressions.
+    pub fn alpha_hash(&self) -> u64 {
+        let mut tokens = Tokens::default();
+        tokens.expr(self);
+        tokens.finish()
+    }
+
+    /// Checks if the expressions are the same up to the names of
+    /// the bound variables, the ranges and the numbers of the holes.
+    pub fn alpha_eq(&self, other: &Expr) -> bool {
+        let mut left = Tokens::default();
+        let mut right = Tokens::default();
+        left.expr(self);
+        right.expr(other);
+        left.tokens == right.tokens
+    }
+}
+
+impl Entry {
+    /// A hash that is the same for alpha-equivalent entries. It does
+    /// not depend on the name of the entry (unless it has no rules) or
+    /// on its attributes, so it can be used to find duplicated
+    /// definitions.
+    pub fn alpha_hash(&self) -> u64 {
+        let mut tokens = Tokens::default();
+        tokens.entry(self);
+        tokens.finish()
+    }
+
+    /// Checks if the entries have the same arguments, type and rules
+    /// up to alpha-equivalence. The names of the entries are ignored,
+    /// except for the entries without rules (constructors and axioms)
+    /// that are only equivalent to themselves.
+    pub fn alpha_eq(&self, other: &Entry) -> bool {
+        let mut left = Tokens::default();
+        let mut right = Tokens::default();
+        left.entry(self);
+        right.entry(other);
+        left.tokens == right.tokens
+    }
+}
@@ -0,0 +1,485 @@
+//! The desugared tree stored inside of an [Arena]. The passes
+//! that copy a lot of subtrees (like the substitutions made by the
+//! notations and by the match compiler) convert the boxed
+//! expressions to this representation and back.
+
+use fxhash::FxHashMap;
+use kind_span::Range;
+
+use super::{AppBinding, Expr, ExprKind, Operator};
+use crate::arena::{Arena, Id};
+use crate::symbol::{Ident, QualifiedIdent};
+
+pub type ExprId = Id<Node>;
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct NodeAppBinding {
+    pub data: ExprId,
+    pub erased: bool,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum NodeKind {
+    Var {
+        name: Ident,
+    },
+    All {
+        param: Ident,
+        typ: ExprId,
+        body: ExprId,
+        erased: bool,
+    },
+    Lambda {
+        param: Ident,
+        body: ExprId,
+        erased: bool,
+    },
+    App {
+        fun: ExprId,
+        args: Vec<NodeAppBinding>,
+    },
+    Fun {
+        name: QualifiedIdent,
+        args: Vec<ExprId>,
+    },
+    Ctr {
+        name: QualifiedIdent,
+        args: Vec<ExprId>,
+    },
+    Let {
+        name: Ident,
+        val: ExprId,
+        next: ExprId,
+    },
+    Ann {
+        expr: ExprId,
+        typ: ExprId,
+    },
+    Sub {
+        name: Ident,
+        indx: usize,
+        redx: usize,
+        expr: ExprId,
+    },
+    Typ,
+    NumTypeU60,
+    NumTypeF60,
+    NumTypeI60,
+    NumU60 {
+        numb: u64,
+    },
+    NumF60 {
+        numb: u64,
+    },
+    NumI60 {
+        numb: i64,
+    },
+    Str {
+        val: String,
+    },
+    Binary {
+        op: Operator,
+        left: ExprId,
+        right: ExprId,
+        signed: bool,
+    },
+    Hole {
+        num: u64,
+    },
+    Hlp(Ident),
+    Err,
+}
+
+/// A single node of an expression. The children are indices
+/// in the same [ExprArena].
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Node {
+    pub data: NodeKind,
+    pub range: Range,
+}
+
+/// An arena of desugared expressions. Equal subtrees share the
+/// same [ExprId].
+#[derive(Clone, Debug, Default)]
+pub struct ExprArena {
+    nodes: Arena<Node>,
+}
+
+impl ExprArena {
+    pub fn new() -> ExprArena {
+        Default::default()
+    }
+
+    pub fn alloc(&mut self, range: Range, data: NodeKind) -> ExprId {
+        self.nodes.alloc(Node { data, range })
+    }
+
+    pub fn get(&self, id: ExprId) -> &Node {
+        self.nodes.get(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    fn alloc_spine(&mut self, args: &[Box<Expr>]) -> Vec<ExprId> {
+        args.iter().map(|arg| self.from_boxed(arg)).collect()
+    }
+
+    /// Moves a boxed expression into the arena.
+    pub fn from_boxed(&mut self, expr: &Expr) -> ExprId {
+        use ExprKind::*;
+
+        let data = match &expr.data {
+            Var { name } => NodeKind::Var { name: *name },
+            All {
+                param,
+                typ,
+                body,
+                erased,
+            } => NodeKind::All {
+                param: *param,
+                typ: self.from_boxed(typ),
+                body: self.from_boxed(body),
+                erased: *erased,
+            },
+            Lambda {
+                param,
+                body,
+                erased,
+            } => NodeKind::Lambda {
+                param: *param,
+                body: self.from_boxed(body),
+                erased: *erased,
+            },
+            App { fun, args } => NodeKind::App {
+                fun: self.from_boxed(fun),
+                args: args
+                    .iter()
+                    .map(|arg| NodeAppBinding {
+                        data: self.from_boxed(&arg.data),
+                        erased: arg.erased,
+                    })
+                    .collect(),
+            },
+            Fun { name, args } => NodeKind::Fun {
+                name: *name,
+                args: self.alloc_spine(args),
+            },
+            Ctr { name, args } => NodeKind::Ctr {
+                name: *name,
+                args: self.alloc_spine(args),
+            },
+            Let { name, val, next } => NodeKind::Let {
+                name: *name,
+                val: self.from_boxed(val),
+                next: self.from_boxed(next),
+            },
+            Ann { expr, typ } => NodeKind::Ann {
+                expr: self.from_boxed(expr),
+                typ: self.from_boxed(typ),
+            },
+            Sub {
+                name,
+                indx,
+                redx,
+                expr,
+            } => NodeKind::Sub {
+                name: *name,
+                indx: *indx,
+                redx: *redx,
+                expr: self.from_boxed(expr),
+            },
+            Typ => NodeKind::Typ,
+            NumTypeU60 => NodeKind::NumTypeU60,
+            NumTypeF60 => NodeKind::NumTypeF60,
+            NumTypeI60 => NodeKind::NumTypeI60,
+            NumU60 { numb } => NodeKind::NumU60 { numb: *numb },
+            NumF60 { numb } => NodeKind::NumF60 { numb: *numb },
+            NumI60 { numb } => NodeKind::NumI60 { numb: *numb },
+            Str { val } => NodeKind::Str { val: val.clone() },
+            Binary {
+                op,
+                left,
+                right,
+                signed,
+            } => NodeKind::Binary {
+                op: *op,
+                left: self.from_boxed(left),
+                right: self.from_boxed(right),
+                signed: *signed,
+            },
+            Hole { num } => NodeKind::Hole { num: *num },
+            Hlp(name) => NodeKind::Hlp(*name),
+            Err => NodeKind::Err,
+        };
+
+        self.alloc(expr.range, data)
+    }
+
+    /// Builds a boxed expression from a node of the arena. The
+    /// shared subtrees are copied.
+    pub fn to_boxed(&self, id: ExprId) -> Box<Expr> {
+        let node = self.get(id);
+        let to_boxed = |args: &[ExprId]| args.iter().map(|arg| self.to_boxed(*arg)).collect();
+
+        let data = match &node.data {
+            NodeKind::Var { name } => ExprKind::Var { name: *name },
+            NodeKind::All {
+                param,
+                typ,
+                body,
+                erased,
+            } => ExprKind::All {
+                param: *param,
+                typ: self.to_boxed(*typ),
+                body: self.to_boxed(*body),
+                erased: *erased,
+            },
+            NodeKind::Lambda {
+                param,
+                body,
+                erased,
+            } => ExprKind::Lambda {
+                param: *param,
+                body: self.to_boxed(*body),
+                erased: *erased,
+            },
+            NodeKind::App { fun, args } => ExprKind::App {
+                fun: self.to_boxed(*fun),
+                args: args
+                    .iter()
+                    .map(|arg| AppBinding {
+                        data: self.to_boxed(arg.data),
+                        erased: arg.erased,
+                    })
+                    .collect(),
+            },
+            NodeKind::Fun { name, args } => ExprKind::Fun {
+                name: *name,
+                args: to_boxed(args),
+            },
+            NodeKind::Ctr { name, args } => ExprKind::Ctr {
+                name: *name,
+                args: to_boxed(args),
+            },
+            NodeKind::Let { name, val, next } => ExprKind::Let {
+                name: *name,
+                val: self.to_boxed(*val),
+                next: self.to_boxed(*next),
+            },
+            NodeKind::Ann { expr, typ } => ExprKind::Ann {
+                expr: self.to_boxed(*expr),
+                typ: self.to_boxed(*typ),
+            },
+            NodeKind::Sub {
+                name,
+                indx,
+                redx,
+                expr,
+            } => ExprKind::Sub {
+                name: *name,
+                indx: *indx,
+                redx: *redx,
+                expr: self.to_boxed(*expr),
+            },
+            NodeKind::Typ => ExprKind::Typ,
+            NodeKind::NumTypeU60 => ExprKind::NumTypeU60,
+            NodeKind::NumTypeF60 => ExprKind::NumTypeF60,
+            NodeKind::NumTypeI60 => ExprKind::NumTypeI60,
+            NodeKind::NumU60 { numb } => ExprKind::NumU60 { numb: *numb },
+            NodeKind::NumF60 { numb } => ExprKind::NumF60 { numb: *numb },
+            NodeKind::NumI60 { numb } => ExprKind::NumI60 { numb: *numb },
+            NodeKind::Str { val } => ExprKind::Str { val: val.clone() },
+            NodeKind::Binary {
+                op,
+                left,
+                right,
+                signed,
+            } => ExprKind::Binary {
+                op: *op,
+                left: self.to_boxed(*left),
+                right: self.to_boxed(*right),
+                signed: *signed,
+            },
+            NodeKind::Hole { num } => ExprKind::Hole { num: *num },
+            NodeKind::Hlp(name) => ExprKind::Hlp(*name),
+            NodeKind::Err => ExprKind::Err,
+        };
+
+        Box::new(Expr {
+            data,
+            range: node.range,
+        })
+    }
+
+    /// Replaces the free variables of an expression. The values are
+    /// not copied, they are shared by all of the occurrences.
+    pub fn subst(&mut self, id: ExprId, vars: &FxHashMap<String, ExprId>) -> ExprId {
+        let mut bound = Vec::new();
+        self.subst_on(id, vars, &mut bound)
+    }
+
+    fn subst_on(
+        &mut self,
+        id: ExprId,
+        vars: &FxHashMap<String, ExprId>,
+        bound: &mut Vec<Ident>,
+    ) -> ExprId {
+        let node = self.get(id).clone();
+
+        let data = match node.data {
+            NodeKind::Var { name } => {
+                if bound.iter().any(|x| x.data == name.data) {
+                    return id;
+                }
+                return vars.get(name.to_str()).copied().unwrap_or(id);
+            }
+            NodeKind::All {
+                param,
+                typ,
+                body,
+                erased,
+            } => {
+                let typ = self.subst_on(typ, vars, bound);
+                bound.push(param);
+                let body = self.subst_on(body, vars, bound);
+                bound.pop();
+                NodeKind::All {
+                    param,
+                    typ,
+                    body,
+                    erased,
+                }
+            }
+            NodeKind::Lambda {
+                param,
+                body,
+                erased,
+            } => {
+                bound.push(param);
+                let body = self.subst_on(body, vars, bound);
+                bound.pop();
+                NodeKind::Lambda {
+                    param,
+                    body,
+                    erased,
+                }
+            }
+            NodeKind::App { fun, args } => NodeKind::App {
+                fun: self.subst_on(fun, vars, bound),
+                args: args
+                    .iter()
+                    .map(|arg| NodeAppBinding {
+                        data: self.subst_on(arg.data, vars, bound),
+                        erased: arg.erased,
+                    })
+                    .collect(),
+            },
+            NodeKind::Fun { name, args } => NodeKind::Fun {
+                name,
+                args: self.subst_args(&args, vars, bound),
+            },
+            NodeKind::Ctr { name, args } => NodeKind::Ctr {
+                name,
+                args: self.subst_args(&args, vars, bound),
+            },
+            NodeKind::Let { name, val, next } => {
+                let val = self.subst_on(val, vars, bound);
+                bound.push(name);
+                let next = self.subst_on(next, vars, bound);
+                bound.pop();
+                NodeKind::Let { name, val, next }
+            }
+            NodeKind::Ann { expr, typ } => NodeKind::Ann {
+                expr: self.subst_on(expr, vars, bound),
+                typ: self.subst_on(typ, vars, bound),
+            },
+            NodeKind::Sub {
+                name,
+                indx,
+                redx,
+                expr,
+            } => NodeKind::Sub {
+                name,
+                indx,
+                redx,
+                expr: self.subst_on(expr, vars, bound),
+            },
+            NodeKind::Binary {
+                op,
+                left,
+                right,
+                signed,
+            } => NodeKind::Binary {
+                op,
+                left: self.subst_on(left, vars, bound),
+                right: self.subst_on(right, vars, bound),
+                signed,
+            },
+            NodeKind::Typ
+            | NodeKind::NumTypeU60
+            | NodeKind::NumTypeF60
+            | NodeKind::NumTypeI60
+            | NodeKind::NumU60 { .. }
+            | NodeKind::NumF60 { .. }
+            | NodeKind::NumI60 { .. }
+            | NodeKind::Str { .. }
+            | NodeKind::Hole { .. }
+            | NodeKind::Hlp(_)
+            | NodeKind::Err => return id,
+        };
+
+        self.alloc(node.range, data)
+    }
+
+    fn subst_args(
+        &mut self,
+        args: &[ExprId],
+        vars: &FxHashMap<String, ExprId>,
+        bound: &mut Vec<Ident>,
+    ) -> Vec<ExprId> {
+        args.iter()
+            .map(|arg| self.subst_on(*arg, vars, bound))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fxhash::FxHashMap;
+    use kind_span::Range;
+
+    use super::ExprArena;
+    use crate::desugared::Expr;
+    use crate::symbol::Ident;
+    use crate::Operator;
+
+    #[test]
+    fn substitutes_only_the_free_variables() {
+        let range = Range::ghost_range();
+        let x = Ident::generate("x");
+        let body = Expr::binary(range, Operator::Add, Expr::var(x), Expr::num_u60(range, 1));
+        let lambda = Expr::lambda(range, x, body, false);
+        let expr = Expr::binary(range, Operator::Mul, Expr::var(x), lambda);
+
+        let mut arena = ExprArena::new();
+        let id = arena.from_boxed(&expr);
+        let two = arena.from_boxed(&Expr::num_u60(range, 2));
+
+        let mut vars = FxHashMap::default();
+        vars.insert("x".to_string(), two);
+        let res = arena.subst(id, &vars);
+
+        let body = Expr::binary(range, Operator::Add, Expr::var(x), Expr::num_u60(range, 1));
+        let lambda = Expr::lambda(range, x, body, false);
+        let expected = Expr::binary(range, Operator::Mul, Expr::num_u60(range, 2), lambda);
+
+        assert_eq!(arena.to_boxed(res), expected);
+        assert_eq!(arena.to_boxed(id), expr);
+    }
+}
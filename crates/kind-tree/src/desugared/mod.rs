@@ -3,13 +3,23 @@
 
 use std::fmt::{Display, Error, Formatter};
 
-use fxhash::FxHashMap;
+use fxhash::{FxHashMap, FxHashSet};
 use kind_span::Range;
 use linked_hash_map::LinkedHashMap;
+use serde::Serialize;
+
+pub mod arena;
+pub mod fold;
+pub mod visit;
+pub mod visitor;
+
+mod alpha;
+mod pretty;
 
 pub use crate::Operator;
 
 use crate::{
+    concrete::expr::escape_str,
     symbol::{Ident, QualifiedIdent},
     Attributes, telescope::Telescope,
 };
@@ -19,13 +29,13 @@ use crate::{
 /// as ((((a b) c) d) e) that looks like a spine.
 pub type Spine = Vec<Box<Expr>>;
 
-#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize)]
 pub struct AppBinding {
     pub data: Box<Expr>,
     pub erased: bool,
 }
 
-#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize)]
 pub enum ExprKind {
     /// Name of a variable
     Var { name: Ident },
@@ -72,17 +82,23 @@ pub enum ExprKind {
     NumTypeU60,
     /// 60 bit floating point number type
     NumTypeF60,
+    /// Signed 60 bit integer type
+    NumTypeI60,
     /// 60 bit integer
     NumU60 { numb: u64 },
     /// 60 bit floating point number
     NumF60 { numb: u64 },
+    /// Signed 60 bit integer
+    NumI60 { numb: i64 },
     /// Very special constructor :)
     Str { val: String },
-    /// Binary operation (e.g. 2 + 3)
+    /// Binary operation (e.g. 2 + 3). It's signed if the
+    /// operands are `I60` numbers.
     Binary {
         op: Operator,
         left: Box<Expr>,
         right: Box<Expr>,
+        signed: bool,
     },
     /// A expression open to unification (e.g. _)
     Hole { num: u64 },
@@ -94,7 +110,7 @@ pub enum ExprKind {
     Err,
 }
 
-#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize)]
 pub struct Expr {
     pub data: ExprKind,
     pub range: Range,
@@ -153,7 +169,7 @@ impl Expr {
         Box::new(Expr {
             range: ident.range,
             data: ExprKind::Lambda {
-                param: ident.clone(),
+                param: ident,
                 body: Self::var(ident),
                 erased: false,
             },
@@ -166,7 +182,7 @@ impl Expr {
             .rev()
             .zip(irrelev)
             .fold(body, |body, (ident, irrelev)| {
-                Expr::lambda(ident.range, ident.clone(), body, *irrelev)
+                Expr::lambda(ident.range, *ident, body, *irrelev)
             })
     }
 
@@ -177,7 +193,7 @@ impl Expr {
             .rev()
             .zip(irrelev)
             .fold(body, |body, ((ident, typ), irrelev)| {
-                Expr::all(ident.range, ident.clone(), typ.clone(), body, *irrelev)
+                Expr::all(ident.range, *ident, typ.clone(), body, *irrelev)
             })
     }
 
@@ -237,6 +253,13 @@ impl Expr {
         })
     }
 
+    pub fn type_i60(range: Range) -> Box<Expr> {
+        Box::new(Expr {
+            range,
+            data: ExprKind::NumTypeI60,
+        })
+    }
+
     pub fn num_u60(range: Range, numb: u64) -> Box<Expr> {
         Box::new(Expr {
             range,
@@ -264,10 +287,22 @@ impl Expr {
         })
     }
 
+    pub fn num_i60(range: Range, numb: i64) -> Box<Expr> {
+        Box::new(Expr {
+            range,
+            data: ExprKind::NumI60 { numb },
+        })
+    }
+
     pub fn binary(range: Range, op: Operator, left: Box<Expr>, right: Box<Expr>) -> Box<Expr> {
         Box::new(Expr {
             range,
-            data: ExprKind::Binary { op, left, right },
+            data: ExprKind::Binary {
+                op,
+                left,
+                right,
+                signed: false,
+            },
         })
     }
 
@@ -306,7 +341,7 @@ impl Expr {
 /// compiled.
 /// hide: that express a implicit argument (that will
 /// be discovered through unification).
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct Argument {
     pub hidden: bool,
     pub erased: bool,
@@ -318,7 +353,7 @@ pub struct Argument {
 /// A rule is a equation that in the left-hand-side
 /// contains a list of patterns @pats@ and on the
 /// right hand side a value.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct Rule {
     pub name: QualifiedIdent,
     pub pats: Vec<Box<Expr>>,
@@ -330,7 +365,7 @@ pub struct Rule {
 /// and has rules. The type of the function
 /// consists of the arguments @args@ and the
 /// return type @typ@.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct Entry {
     pub name: QualifiedIdent,
     pub args: Vec<Argument>,
@@ -341,20 +376,48 @@ pub struct Entry {
 }
 
 /// Type family information
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct Family {
     pub name: QualifiedIdent,
     pub parameters: Telescope<Argument>,
     pub constructors: Vec<QualifiedIdent>
 }
 
+/// A desugared notation. The body is used as a pattern to find
+/// the notation again in the expressions shown to the user.
+#[derive(Clone, Debug, Serialize)]
+pub struct Notation {
+    pub symbol: String,
+    pub fst: Ident,
+    pub snd: Ident,
+    pub precedence: u8,
+    pub body: Box<Expr>,
+}
+
 /// A book is a collection of desugared entries.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize)]
 pub struct Book {
     pub entrs: LinkedHashMap<String, Box<Entry>>,
+    #[serde(serialize_with = "crate::serialize_sorted")]
     pub names: FxHashMap<String, usize>,
+    #[serde(serialize_with = "crate::serialize_sorted")]
     pub families: FxHashMap<String, Family>,
+    pub notations: LinkedHashMap<String, Notation>,
     pub holes: u64,
+    /// The ranges of the expressions inside of interpolated strings.
+    /// The type checker uses them to explain the errors on them.
+    #[serde(skip)]
+    pub interpolations: FxHashSet<Range>,
+    /// The ranges of the operands of the binary operations that were
+    /// not marked as signed. The type checker uses them to explain
+    /// the errors when they turn out to be `I60` numbers.
+    #[serde(skip)]
+    pub unsigned_operands: FxHashSet<Range>,
+    /// The ranges of the bodies of the cases that a nested match
+    /// copies into more than one branch. The erasure compiles each
+    /// of them only once.
+    #[serde(skip)]
+    pub shared_cases: FxHashSet<Range>,
 }
 
 impl Expr {
@@ -418,9 +481,11 @@ impl Display for Expr {
             Typ => write!(f, "Type"),
             NumTypeU60 => write!(f, "U60"),
             NumTypeF60 => write!(f, "F60"),
-            Str { val } => write!(f, "\"{}\"", val),
+            NumTypeI60 => write!(f, "I60"),
+            Str { val } => write!(f, "\"{}\"", escape_str(val)),
             NumU60 { numb } => write!(f, "{}", numb),
             NumF60 { numb: _ } => todo!(),
+            NumI60 { numb } => write!(f, "{}i60", numb),
             All { .. } => write!(f, "({})", self.traverse_pi_types()),
             Var { name } => write!(f, "{}", name),
             Lambda {
@@ -460,7 +525,7 @@ impl Display for Expr {
             }
             Let { name, val, next } => write!(f, "(let {} = {}; {})", name, val, next),
             Ann { expr, typ } => write!(f, "({} :: {})", expr, typ),
-            Binary { op, left, right } => write!(f, "({} {} {})", op, left, right),
+            Binary { op, left, right, .. } => write!(f, "({} {} {})", left, op, right),
             Hole { .. } => write!(f, "_"),
             Hlp(name) => write!(f, "?{}", name),
             Err => write!(f, "ERR"),
@@ -522,7 +587,7 @@ impl Argument {
         Argument {
             hidden: true,
             erased: true,
-            name: self.name.clone(),
+            name: self.name,
             typ: self.typ.clone(),
             range: self.range,
         }
@@ -532,7 +597,7 @@ impl Argument {
         Argument {
             hidden: false,
             erased: false,
-            name: name.clone(),
+            name: *name,
             typ,
             range,
         }
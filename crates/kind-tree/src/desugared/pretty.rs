@@ -0,0 +1,193 @@
+//! Pretty printing of the desugared tree. The documents render to
+//! the same text as [Display] when they fit in a single line.
+//!
+//! [Expr::to_doc_with] lets the caller lay out some of the
+//! subexpressions in another way, the type checker uses it to show
+//! the syntax sugars that the desugarer removed.
+//!
+//! [Display]: std::fmt::Display
+
+use kind_pretty::{Doc, Pretty};
+
+use super::{try_desugar_to_nat, AppBinding, Argument, Book, Entry, Expr, ExprKind, Rule};
+
+/// Lays out the body of a pi type and the types that come after it
+/// like `(a : A) -> (b : B) -> C`.
+fn pi_types(expr: &Expr, sugar: Sugar, binders: &mut Vec<Doc>) {
+    match &expr.data {
+        ExprKind::All {
+            param,
+            typ,
+            body,
+            erased,
+        } => {
+            let tilde = if *erased { "~" } else { "" };
+            if param.to_string().starts_with('_') {
+                binders.push(Doc::text(tilde).append(typ.to_doc_with(sugar)));
+            } else {
+                binders.push(Doc::concat([
+                    Doc::text(format!("{}({} : ", tilde, param)),
+                    typ.to_doc_with(sugar),
+                    Doc::text(")"),
+                ]));
+            }
+            pi_types(body, sugar, binders)
+        }
+        _ => binders.push(expr.to_doc_with(sugar)),
+    }
+}
+
+/// Lays out a subexpression in place of the printer. It returns
+/// `None` to use the normal layout.
+pub type Sugar<'a> = &'a dyn Fn(&Expr) -> Option<Doc>;
+
+impl AppBinding {
+    fn to_doc_with(&self, sugar: Sugar) -> Doc {
+        if self.erased {
+            Doc::concat([Doc::text("~("), self.data.to_doc_with(sugar), Doc::text(")")])
+        } else {
+            self.data.to_doc_with(sugar)
+        }
+    }
+}
+
+impl Pretty for AppBinding {
+    fn to_doc(&self) -> Doc {
+        self.to_doc_with(&|_| None)
+    }
+}
+
+impl Expr {
+    /// Lays out the expression but asks `sugar` for the layout of
+    /// each subexpression first.
+    pub fn to_doc_with(&self, sugar: Sugar) -> Doc {
+        use ExprKind::*;
+
+        if let Some(doc) = sugar(self) {
+            return doc;
+        }
+
+        match &self.data {
+            All { .. } => {
+                let mut binders = Vec::new();
+                pi_types(self, sugar, &mut binders);
+                Doc::concat([
+                    Doc::text("("),
+                    Doc::join(binders, Doc::text(" ->").append(Doc::line())).align(),
+                    Doc::text(")"),
+                ])
+                .group()
+            }
+            Lambda {
+                param,
+                body,
+                erased,
+            } => {
+                let tilde = if *erased { "~" } else { "" };
+                Doc::binder(Doc::text(format!("{}{} =>", tilde, param)), body.to_doc_with(sugar))
+            }
+            Sub {
+                name, redx, expr, ..
+            } => Doc::binder(Doc::text(format!("## {}/{}", name, redx)), expr.to_doc_with(sugar)),
+            App { fun, args } => Doc::app(
+                fun.to_doc_with(sugar),
+                args.iter().map(|x| x.to_doc_with(sugar)),
+            ),
+            Fun { name, args } | Ctr { name, args } => {
+                if try_desugar_to_nat(name, args, 0).is_some() || args.is_empty() {
+                    Doc::text(self.to_string())
+                } else {
+                    Doc::app(
+                        Doc::text(name.to_string()),
+                        args.iter().map(|x| x.to_doc_with(sugar)),
+                    )
+                }
+            }
+            Let { name, val, next } => Doc::concat([
+                Doc::text(format!("(let {} =", name)),
+                Doc::concat([Doc::line(), val.to_doc_with(sugar), Doc::text(";")])
+                    .nest(2)
+                    .group(),
+                Doc::concat([Doc::line(), next.to_doc_with(sugar)]).nest(1),
+                Doc::text(")"),
+            ])
+            .align()
+            .group(),
+            Ann { expr, typ } => Doc::binder(
+                expr.to_doc_with(sugar),
+                Doc::text(":: ").append(typ.to_doc_with(sugar)),
+            ),
+            Binary { op, left, right, .. } => Doc::binder(
+                left.to_doc_with(sugar),
+                Doc::text(format!("{} ", op)).append(right.to_doc_with(sugar)),
+            ),
+            _ => Doc::text(self.to_string()),
+        }
+    }
+}
+
+impl Pretty for Expr {
+    fn to_doc(&self) -> Doc {
+        self.to_doc_with(&|_| None)
+    }
+}
+
+impl Pretty for Argument {
+    fn to_doc(&self) -> Doc {
+        let (open, close) = match (self.erased, self.hidden) {
+            (false, false) => ("(", ")"),
+            (false, true) => ("+<", ">"),
+            (true, false) => ("-(", ")"),
+            (true, true) => ("<", ">"),
+        };
+        Doc::concat([
+            Doc::text(format!("{}{}: ", open, self.name)),
+            self.typ.to_doc(),
+            Doc::text(close),
+        ])
+    }
+}
+
+impl Pretty for Rule {
+    fn to_doc(&self) -> Doc {
+        let lhs = Doc::concat([
+            Doc::text(self.name.to_string()),
+            Doc::concat(self.pats.iter().map(|x| Doc::line().append(x.to_doc()))).nest(2),
+        ]);
+
+        Doc::concat([
+            lhs.group(),
+            Doc::text(" ="),
+            Doc::concat([Doc::line(), self.body.to_doc()]).nest(2).group(),
+        ])
+    }
+}
+
+impl Pretty for Entry {
+    fn to_doc(&self) -> Doc {
+        let head = Doc::concat([
+            Doc::text(self.name.to_string()),
+            Doc::concat(self.args.iter().map(|x| Doc::line().append(x.to_doc()))).nest(2),
+        ]);
+
+        let signature = Doc::concat([
+            head.group(),
+            Doc::text(" :"),
+            Doc::concat([Doc::line(), self.typ.to_doc()]).nest(2).group(),
+        ]);
+
+        let rules = self.rules.iter().map(|x| Doc::HardLine.append(x.to_doc()));
+
+        signature.append(Doc::concat(rules))
+    }
+}
+
+impl Pretty for Book {
+    fn to_doc(&self) -> Doc {
+        Doc::concat(
+            self.entrs
+                .values()
+                .map(|x| Doc::concat([x.to_doc(), Doc::HardLine, Doc::HardLine])),
+        )
+    }
+}
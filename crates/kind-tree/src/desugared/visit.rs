@@ -0,0 +1,182 @@
+//! A visitor for the desugared tree that only reads it. It's like
+//! [Visitor](super::visitor::Visitor) but it walks through shared
+//! references, so an analysis doesn't need to own the book.
+
+use kind_span::Range;
+
+use crate::symbol::{Ident, QualifiedIdent};
+
+use super::{AppBinding, Argument, Book, Entry, Expr, ExprKind, Rule};
+
+pub trait Visit: Sized {
+    fn visit_range(&mut self, range: &Range) {
+        walk_range(self, range);
+    }
+
+    fn visit_ident(&mut self, ident: &Ident) {
+        walk_ident(self, ident);
+    }
+
+    fn visit_qualified_ident(&mut self, ident: &QualifiedIdent) {
+        walk_qualified_ident(self, ident);
+    }
+
+    fn visit_app_binding(&mut self, binding: &AppBinding) {
+        walk_app_binding(self, binding);
+    }
+
+    fn visit_argument(&mut self, argument: &Argument) {
+        walk_argument(self, argument);
+    }
+
+    fn visit_rule(&mut self, rule: &Rule) {
+        walk_rule(self, rule);
+    }
+
+    fn visit_entry(&mut self, entry: &Entry) {
+        walk_entry(self, entry);
+    }
+
+    fn visit_book(&mut self, book: &Book) {
+        walk_book(self, book);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        walk_expr(self, expr);
+    }
+}
+
+pub fn walk_range<T: Visit>(_: &mut T, _: &Range) {}
+
+pub fn walk_ident<T: Visit>(ctx: &mut T, ident: &Ident) {
+    ctx.visit_range(&ident.range);
+}
+
+pub fn walk_qualified_ident<T: Visit>(ctx: &mut T, ident: &QualifiedIdent) {
+    ctx.visit_range(&ident.range);
+}
+
+pub fn walk_app_binding<T: Visit>(ctx: &mut T, binding: &AppBinding) {
+    ctx.visit_expr(&binding.data);
+}
+
+pub fn walk_argument<T: Visit>(ctx: &mut T, argument: &Argument) {
+    ctx.visit_ident(&argument.name);
+    ctx.visit_expr(&argument.typ);
+    ctx.visit_range(&argument.range);
+}
+
+pub fn walk_rule<T: Visit>(ctx: &mut T, rule: &Rule) {
+    ctx.visit_qualified_ident(&rule.name);
+    for pat in &rule.pats {
+        ctx.visit_expr(pat);
+    }
+    ctx.visit_expr(&rule.body);
+    ctx.visit_range(&rule.range);
+}
+
+pub fn walk_entry<T: Visit>(ctx: &mut T, entry: &Entry) {
+    ctx.visit_qualified_ident(&entry.name);
+    for arg in &entry.args {
+        ctx.visit_argument(arg);
+    }
+    ctx.visit_expr(&entry.typ);
+    for rule in &entry.rules {
+        ctx.visit_rule(rule);
+    }
+    ctx.visit_range(&entry.range);
+}
+
+pub fn walk_book<T: Visit>(ctx: &mut T, book: &Book) {
+    for entry in book.entrs.values() {
+        ctx.visit_entry(entry);
+    }
+}
+
+pub fn walk_expr<T: Visit>(ctx: &mut T, expr: &Expr) {
+    ctx.visit_range(&expr.range);
+    match &expr.data {
+        ExprKind::Var { name } | ExprKind::Hlp(name) => ctx.visit_ident(name),
+        ExprKind::All {
+            param, typ, body, ..
+        } => {
+            ctx.visit_ident(param);
+            ctx.visit_expr(typ);
+            ctx.visit_expr(body);
+        }
+        ExprKind::Lambda { param, body, .. } => {
+            ctx.visit_ident(param);
+            ctx.visit_expr(body);
+        }
+        ExprKind::App { fun, args } => {
+            ctx.visit_expr(fun);
+            for arg in args {
+                ctx.visit_app_binding(arg);
+            }
+        }
+        ExprKind::Fun { name, args } | ExprKind::Ctr { name, args } => {
+            ctx.visit_qualified_ident(name);
+            for arg in args {
+                ctx.visit_expr(arg);
+            }
+        }
+        ExprKind::Let { name, val, next } => {
+            ctx.visit_ident(name);
+            ctx.visit_expr(val);
+            ctx.visit_expr(next);
+        }
+        ExprKind::Ann { expr, typ } => {
+            ctx.visit_expr(expr);
+            ctx.visit_expr(typ);
+        }
+        ExprKind::Sub { name, expr, .. } => {
+            ctx.visit_ident(name);
+            ctx.visit_expr(expr);
+        }
+        ExprKind::Binary { left, right, .. } => {
+            ctx.visit_expr(left);
+            ctx.visit_expr(right);
+        }
+        ExprKind::Typ
+        | ExprKind::NumTypeU60
+        | ExprKind::NumTypeF60
+        | ExprKind::NumTypeI60
+        | ExprKind::NumU60 { .. }
+        | ExprKind::NumF60 { .. }
+        | ExprKind::NumI60 { .. }
+        | ExprKind::Str { .. }
+        | ExprKind::Hole { .. }
+        | ExprKind::Err => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use kind_span::Range;
+
+    use super::{walk_expr, Visit};
+    use crate::desugared::{Expr, ExprKind};
+    use crate::symbol::Ident;
+
+    struct Holes(u64);
+
+    impl Visit for Holes {
+        fn visit_expr(&mut self, expr: &Expr) {
+            if let ExprKind::Hole { .. } = expr.data {
+                self.0 += 1;
+            }
+            walk_expr(self, expr)
+        }
+    }
+
+    #[test]
+    fn walks_all_of_the_children() {
+        let range = Range::ghost_range();
+        let val = Expr::ann(range, Expr::hole(range, 0), Expr::hole(range, 1));
+        let expr = Expr::let_(range, Ident::generate("x"), val, Expr::hole(range, 2));
+
+        let mut holes = Holes(0);
+        holes.visit_expr(&expr);
+        assert_eq!(holes.0, 3);
+    }
+}
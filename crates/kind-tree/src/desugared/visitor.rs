@@ -0,0 +1,182 @@
+//! Specifies a visitor trait for the desugared tree. It changes
+//! the tree in place and the default implementations just walk
+//! through all of the children, so a pass only has to implement
+//! the nodes that it cares about.
+
+use kind_span::Range;
+
+use crate::symbol::{Ident, QualifiedIdent};
+
+use super::{AppBinding, Argument, Book, Entry, Expr, ExprKind, Rule};
+
+pub trait Visitor: Sized {
+    fn visit_range(&mut self, range: &mut Range) {
+        walk_range(self, range);
+    }
+
+    fn visit_ident(&mut self, ident: &mut Ident) {
+        walk_ident(self, ident);
+    }
+
+    fn visit_qualified_ident(&mut self, ident: &mut QualifiedIdent) {
+        walk_qualified_ident(self, ident);
+    }
+
+    fn visit_app_binding(&mut self, binding: &mut AppBinding) {
+        walk_app_binding(self, binding);
+    }
+
+    fn visit_argument(&mut self, argument: &mut Argument) {
+        walk_argument(self, argument);
+    }
+
+    fn visit_rule(&mut self, rule: &mut Rule) {
+        walk_rule(self, rule);
+    }
+
+    fn visit_entry(&mut self, entry: &mut Entry) {
+        walk_entry(self, entry);
+    }
+
+    fn visit_book(&mut self, book: &mut Book) {
+        walk_book(self, book);
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        walk_expr(self, expr);
+    }
+}
+
+pub fn walk_range<T: Visitor>(_: &mut T, _: &mut Range) {}
+
+pub fn walk_ident<T: Visitor>(ctx: &mut T, ident: &mut Ident) {
+    ctx.visit_range(&mut ident.range);
+}
+
+pub fn walk_qualified_ident<T: Visitor>(ctx: &mut T, ident: &mut QualifiedIdent) {
+    ctx.visit_range(&mut ident.range);
+}
+
+pub fn walk_app_binding<T: Visitor>(ctx: &mut T, binding: &mut AppBinding) {
+    ctx.visit_expr(&mut binding.data);
+}
+
+pub fn walk_argument<T: Visitor>(ctx: &mut T, argument: &mut Argument) {
+    ctx.visit_ident(&mut argument.name);
+    ctx.visit_expr(&mut argument.typ);
+    ctx.visit_range(&mut argument.range);
+}
+
+pub fn walk_rule<T: Visitor>(ctx: &mut T, rule: &mut Rule) {
+    ctx.visit_qualified_ident(&mut rule.name);
+    for pat in &mut rule.pats {
+        ctx.visit_expr(pat);
+    }
+    ctx.visit_expr(&mut rule.body);
+    ctx.visit_range(&mut rule.range);
+}
+
+pub fn walk_entry<T: Visitor>(ctx: &mut T, entry: &mut Entry) {
+    ctx.visit_qualified_ident(&mut entry.name);
+    for arg in &mut entry.args {
+        ctx.visit_argument(arg);
+    }
+    ctx.visit_expr(&mut entry.typ);
+    for rule in &mut entry.rules {
+        ctx.visit_rule(rule);
+    }
+    ctx.visit_range(&mut entry.range);
+}
+
+pub fn walk_book<T: Visitor>(ctx: &mut T, book: &mut Book) {
+    for (_, entry) in book.entrs.iter_mut() {
+        ctx.visit_entry(entry);
+    }
+}
+
+pub fn walk_expr<T: Visitor>(ctx: &mut T, expr: &mut Expr) {
+    ctx.visit_range(&mut expr.range);
+    match &mut expr.data {
+        ExprKind::Var { name } | ExprKind::Hlp(name) => ctx.visit_ident(name),
+        ExprKind::All {
+            param, typ, body, ..
+        } => {
+            ctx.visit_ident(param);
+            ctx.visit_expr(typ);
+            ctx.visit_expr(body);
+        }
+        ExprKind::Lambda { param, body, .. } => {
+            ctx.visit_ident(param);
+            ctx.visit_expr(body);
+        }
+        ExprKind::App { fun, args } => {
+            ctx.visit_expr(fun);
+            for arg in args {
+                ctx.visit_app_binding(arg);
+            }
+        }
+        ExprKind::Fun { name, args } | ExprKind::Ctr { name, args } => {
+            ctx.visit_qualified_ident(name);
+            for arg in args {
+                ctx.visit_expr(arg);
+            }
+        }
+        ExprKind::Let { name, val, next } => {
+            ctx.visit_ident(name);
+            ctx.visit_expr(val);
+            ctx.visit_expr(next);
+        }
+        ExprKind::Ann { expr, typ } => {
+            ctx.visit_expr(expr);
+            ctx.visit_expr(typ);
+        }
+        ExprKind::Sub { name, expr, .. } => {
+            ctx.visit_ident(name);
+            ctx.visit_expr(expr);
+        }
+        ExprKind::Binary { left, right, .. } => {
+            ctx.visit_expr(left);
+            ctx.visit_expr(right);
+        }
+        ExprKind::Typ
+        | ExprKind::NumTypeU60
+        | ExprKind::NumTypeF60
+        | ExprKind::NumTypeI60
+        | ExprKind::NumU60 { .. }
+        | ExprKind::NumF60 { .. }
+        | ExprKind::NumI60 { .. }
+        | ExprKind::Str { .. }
+        | ExprKind::Hole { .. }
+        | ExprKind::Err => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use kind_span::Range;
+
+    use super::Visitor;
+    use crate::desugared::{AppBinding, Expr};
+    use crate::symbol::Ident;
+
+    struct Idents(Vec<String>);
+
+    impl Visitor for Idents {
+        fn visit_ident(&mut self, ident: &mut Ident) {
+            self.0.push(ident.to_string())
+        }
+    }
+
+    #[test]
+    fn walks_all_of_the_children() {
+        let range = Range::ghost_range();
+        let var = |name| Expr::var(Ident::generate(name));
+        let app = Expr::app(range, var("f"), vec![AppBinding { data: var("x"), erased: false }]);
+        let typ = Expr::all(range, Ident::generate("y"), Expr::typ(range), var("y"), false);
+        let mut expr = Expr::ann(range, Expr::lambda(range, Ident::generate("x"), app, false), typ);
+
+        let mut idents = Idents(Vec::new());
+        idents.visit_expr(&mut expr);
+        assert_eq!(idents.0, ["x", "f", "x", "y", "y"]);
+    }
+}
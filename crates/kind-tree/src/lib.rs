@@ -14,21 +14,28 @@ pub mod desugared;
 /// The untyped AST.
 pub mod untyped;
 
+/// Arenas with index handles to store the trees.
+pub mod arena;
+
 /// Telescope (Iterated sigma type representation)
 pub mod telescope;
 
-/// Describes symbols (identifiers) on the language. It will
-/// be really useful when we change the Symbol to take a number
-/// instead of a string due to optimizations.
+/// Describes symbols (identifiers) on the language. Symbols are
+/// interned so they are just a number.
 pub mod symbol;
 
+use std::collections::BTreeMap;
 use std::fmt::{Formatter, Display, Error};
+use std::iter::Peekable;
 
+use fxhash::FxHashMap;
+use kind_span::Range;
+use serde::{Serialize, Serializer};
 use symbol::Ident;
 
 /// Attributes describes some compiler specific aspects
 /// like inlining and derivations.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize)]
 pub struct Attributes {
     pub inlined: bool,
     pub kdl_run: bool,
@@ -41,8 +48,17 @@ pub struct Attributes {
     pub axiom: bool,
 }
 
+/// Serializes a hash map with the keys in order so the output
+/// doesn't change with the hashing.
+pub(crate) fn serialize_sorted<S: Serializer, V: Serialize>(
+    map: &FxHashMap<String, V>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
+}
+
 /// Enum of binary operators.
-#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, Serialize)]
 pub enum Operator {
     Add,
     Sub,
@@ -62,6 +78,99 @@ pub enum Operator {
     Neq,
 }
 
+/// How a chain of operators with the same precedence is grouped.
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, Serialize)]
+pub enum Associativity {
+    /// `a - b - c` is `(a - b) - c`
+    Left,
+    /// `a < b < c` is not allowed
+    None,
+}
+
+impl Operator {
+    /// Precedence of the infix form of the operator. Bigger
+    /// numbers bind tighter. It follows the same order as Rust.
+    pub fn precedence(&self) -> u8 {
+        use Operator::*;
+
+        match self {
+            Mul | Div | Mod => 7,
+            Add | Sub => 6,
+            Shl | Shr => 5,
+            And => 4,
+            Xor => 3,
+            Or => 2,
+            Ltn | Lte | Eql | Gte | Gtn | Neq => 1,
+        }
+    }
+
+    /// The comparisons result in a `U60` for every type of operand.
+    pub fn is_comparison(&self) -> bool {
+        use Operator::*;
+        matches!(self, Ltn | Lte | Eql | Gte | Gtn | Neq)
+    }
+
+    pub fn associativity(&self) -> Associativity {
+        use Operator::*;
+
+        match self {
+            Ltn | Lte | Eql | Gte | Gtn | Neq => Associativity::None,
+            _ => Associativity::Left,
+        }
+    }
+}
+
+/// Groups a chain of infix operators like `a + b * c` by precedence
+/// climbing. `info` gives the precedence and associativity of each
+/// operator and `join` builds a node from an operator and its two
+/// sides. It fails with the ranges of two non associative operators
+/// that are chained without parenthesis.
+pub fn solve_infix<O, T>(
+    fst: T,
+    rest: Vec<(O, Range, T)>,
+    info: &impl Fn(&O) -> (u8, Associativity),
+    join: &mut impl FnMut(O, Range, T, T) -> T,
+) -> Result<T, (Range, Range)> {
+    let mut rest = rest.into_iter().peekable();
+    let res = climb_infix(fst, &mut rest, 0, info, join)?;
+    debug_assert!(rest.peek().is_none());
+    Ok(res)
+}
+
+fn climb_infix<O, T>(
+    mut fst: T,
+    rest: &mut Peekable<std::vec::IntoIter<(O, Range, T)>>,
+    min_prec: u8,
+    info: &impl Fn(&O) -> (u8, Associativity),
+    join: &mut impl FnMut(O, Range, T, T) -> T,
+) -> Result<T, (Range, Range)> {
+    let mut last: Option<(u8, Range)> = None;
+
+    while let Some((op, _, _)) = rest.peek() {
+        let (prec, assoc) = info(op);
+        if prec < min_prec {
+            break;
+        }
+
+        let (op, range, mut snd) = rest.next().unwrap();
+
+        if let Some((last_prec, last_range)) = last {
+            if last_prec == prec && assoc == Associativity::None {
+                return Err((last_range, range));
+            }
+        }
+
+        if matches!(rest.peek(), Some((next, _, _)) if info(next).0 > prec) {
+            snd = climb_infix(snd, rest, prec + 1, info, join)?;
+        }
+
+        fst = join(op, range, fst, snd);
+        last = Some((prec, range));
+    }
+
+    Ok(fst)
+}
+
 impl Display for Operator {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
         use Operator::*;
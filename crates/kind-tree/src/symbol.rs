@@ -1,44 +1,79 @@
 //! Describes identifiers and symbols inside the language.
 
-use kind_span::{Range, SyntaxCtxIndex};
 use std::fmt::Display;
-use std::hash::Hash;
+use std::sync::RwLock;
+
+use fxhash::FxHashMap;
+use kind_span::{Range, SyntaxCtxIndex};
+use once_cell::sync::Lazy;
+use serde::{Serialize, Serializer};
+
+/// Stores all of the names that were created during the execution
+/// of the compiler. The strings are leaked and never freed, so a
+/// symbol can keep a reference to its string and read it without
+/// taking the lock.
+#[derive(Default)]
+struct Interner {
+    indices: FxHashMap<&'static str, u32>,
+}
+
+static INTERNER: Lazy<RwLock<Interner>> = Lazy::new(Default::default);
 
-/// Stores the name of a variable or constructor.
-/// It's simply a string because in the future i plan
-/// to store all the names and only reference them with
-/// a u64.
-#[derive(Clone, Debug)]
+/// Stores the name of a variable or constructor. It's an index
+/// in the global interner so copying, comparing and hashing
+/// a symbol doesn't touch the string.
+#[derive(Clone, Copy)]
 pub struct Symbol {
-    data: String,
-    hash: u64,
+    index: u32,
+    str: &'static str,
 }
 
 impl Symbol {
     pub fn new(str: String) -> Symbol {
-        Symbol {
-            hash: fxhash::hash64(&str),
-            data: str,
+        if let Some((str, index)) = INTERNER.read().unwrap().indices.get_key_value(str.as_str()) {
+            return Symbol { index: *index, str };
         }
+
+        let mut interner = INTERNER.write().unwrap();
+
+        // Someone could have added the same string while the lock was free.
+        if let Some((str, index)) = interner.indices.get_key_value(str.as_str()) {
+            return Symbol { index: *index, str };
+        }
+
+        let index = interner.indices.len() as u32;
+        let str: &'static str = Box::leak(str.into_boxed_str());
+        interner.indices.insert(str, index);
+        Symbol { index, str }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        self.str
     }
 }
 
 impl PartialEq for Symbol {
     fn eq(&self, other: &Self) -> bool {
-        self.hash == other.hash
+        self.index == other.index
     }
 }
 
-impl Hash for Symbol {
+impl Eq for Symbol {}
+
+impl std::hash::Hash for Symbol {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        state.write_u64(self.hash);
+        self.index.hash(state)
     }
 }
 
-impl Eq for Symbol {}
+impl std::fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self.as_str())
+    }
+}
 
 /// Identifier inside a syntax context.
-#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, Serialize)]
 pub struct Ident {
     pub data: Symbol,
     pub range: Range,
@@ -47,7 +82,7 @@ pub struct Ident {
 
 /// Qualified Identifiers always refer to top level
 /// constructions.
-#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, Serialize)]
 pub struct QualifiedIdent {
     root: Symbol,
     aux: Option<Symbol>,
@@ -75,17 +110,17 @@ impl QualifiedIdent {
     /// and avoid a copy of the string.
     #[inline]
     pub fn to_str(&self) -> &str {
-        &self.root.data
+        self.root.as_str()
     }
 
     #[inline]
     pub fn get_root(&self) -> String {
-        self.root.data.clone()
+        self.root.as_str().to_string()
     }
 
     #[inline]
     pub fn get_aux(&self) -> Option<Symbol> {
-        self.aux.clone()
+        self.aux
     }
 
     #[inline]
@@ -98,7 +133,7 @@ impl QualifiedIdent {
     }
 
     pub fn to_generated(&self) -> Self {
-        let mut new = self.clone();
+        let mut new = *self;
         new.generated = true;
         new
     }
@@ -131,11 +166,11 @@ impl QualifiedIdent {
     }
 
     pub fn pop_last_segment(&self) -> QualifiedIdent {
-        let mut segments = self.root.data.split(".").collect::<Vec<_>>();
+        let mut segments = self.root.as_str().split('.').collect::<Vec<_>>();
         segments.pop();
         QualifiedIdent {
             root: Symbol::new(segments.join(".")),
-            aux: self.aux.clone(),
+            aux: self.aux,
             range: self.range,
             generated: self.generated,
         }
@@ -143,8 +178,8 @@ impl QualifiedIdent {
 
     pub fn add_segment(&self, extension: &str) -> QualifiedIdent {
         QualifiedIdent {
-            root: Symbol::new(format!("{}.{}", self.root.data, extension)),
-            aux: self.aux.clone(),
+            root: Symbol::new(format!("{}.{}", self.root, extension)),
+            aux: self.aux,
             range: self.range,
             generated: self.generated,
         }
@@ -177,31 +212,31 @@ impl Ident {
     }
 
     pub fn with_name(&self, f: fn(String) -> String) -> Ident {
-        let mut new = self.clone();
-        new.data = Symbol::new(f(new.data.data));
+        let mut new = *self;
+        new.data = Symbol::new(f(new.data.as_str().to_string()));
         new
     }
 
     pub fn add_underscore(&self) -> Ident {
-        let mut new = self.clone();
-        new.data = Symbol::new(format!("{}_", new.data.data));
+        let mut new = *self;
+        new.data = Symbol::new(format!("{}_", new.data));
         new
     }
 
     #[inline]
     pub fn to_str(&self) -> &str {
-        &self.data.data
+        self.data.as_str()
     }
 
     pub fn to_generated(&self) -> Self {
-        let mut old = self.clone();
+        let mut old = *self;
         old.generated = true;
         old
     }
 
     pub fn to_qualified_ident(&self) -> QualifiedIdent {
         QualifiedIdent {
-            root: self.data.clone(),
+            root: self.data,
             aux: None,
             range: self.range,
             generated: false,
@@ -255,7 +290,7 @@ impl Ident {
         let range = self.range;
         range.set_ctx(ctx);
         Ident {
-            data: self.data.clone(),
+            data: self.data,
             range,
             generated: false,
         }
@@ -263,7 +298,7 @@ impl Ident {
 
     pub fn add_segment(&self, name: &str) -> Ident {
         Ident {
-            data: Symbol::new(format!("{}.{}", self.data.data, name)),
+            data: Symbol::new(format!("{}.{}", self.data, name)),
             range: self.range,
             generated: false,
         }
@@ -280,7 +315,13 @@ impl Ident {
 
 impl Display for Symbol {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.data)
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl Serialize for Symbol {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
     }
 }
 
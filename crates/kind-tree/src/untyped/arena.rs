@@ -0,0 +1,271 @@
+//! The untyped tree stored inside of an [Arena]. The passes
+//! that copy a lot of subtrees (like inlining) convert the boxed
+//! expressions to this representation and back.
+
+use fxhash::FxHashMap;
+use kind_span::Range;
+
+use super::{Expr, ExprKind, Operator};
+use crate::arena::{Arena, Id};
+use crate::symbol::{Ident, QualifiedIdent};
+
+pub type ExprId = Id<Node>;
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub enum NodeKind {
+    Var {
+        name: Ident,
+    },
+    Lambda {
+        param: Ident,
+        body: ExprId,
+        erased: bool,
+    },
+    App {
+        fun: ExprId,
+        args: Vec<ExprId>,
+    },
+    Fun {
+        name: QualifiedIdent,
+        args: Vec<ExprId>,
+    },
+    Ctr {
+        name: QualifiedIdent,
+        args: Vec<ExprId>,
+    },
+    Let {
+        name: Ident,
+        val: ExprId,
+        next: ExprId,
+    },
+    U60 {
+        numb: u64,
+    },
+    F60 {
+        numb: u64,
+    },
+    Str {
+        val: String,
+    },
+    Binary {
+        op: Operator,
+        left: ExprId,
+        right: ExprId,
+        signed: bool,
+    },
+    Err,
+}
+
+/// A single node of an expression. The children are indices
+/// in the same [ExprArena].
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct Node {
+    pub data: NodeKind,
+    pub range: Range,
+}
+
+/// An arena of untyped expressions. Equal subtrees share the
+/// same [ExprId].
+#[derive(Clone, Debug, Default)]
+pub struct ExprArena {
+    nodes: Arena<Node>,
+}
+
+impl ExprArena {
+    pub fn new() -> ExprArena {
+        Default::default()
+    }
+
+    pub fn alloc(&mut self, range: Range, data: NodeKind) -> ExprId {
+        self.nodes.alloc(Node { data, range })
+    }
+
+    pub fn get(&self, id: ExprId) -> &Node {
+        self.nodes.get(id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Moves a boxed expression into the arena.
+    pub fn from_boxed(&mut self, expr: &Expr) -> ExprId {
+        use ExprKind::*;
+
+        let data = match &expr.data {
+            Var { name } => NodeKind::Var { name: *name },
+            Lambda {
+                param,
+                body,
+                erased,
+            } => NodeKind::Lambda {
+                param: *param,
+                body: self.from_boxed(body),
+                erased: *erased,
+            },
+            App { fun, args } => NodeKind::App {
+                fun: self.from_boxed(fun),
+                args: args.iter().map(|arg| self.from_boxed(arg)).collect(),
+            },
+            Fun { name, args } => NodeKind::Fun {
+                name: *name,
+                args: args.iter().map(|arg| self.from_boxed(arg)).collect(),
+            },
+            Ctr { name, args } => NodeKind::Ctr {
+                name: *name,
+                args: args.iter().map(|arg| self.from_boxed(arg)).collect(),
+            },
+            Let { name, val, next } => NodeKind::Let {
+                name: *name,
+                val: self.from_boxed(val),
+                next: self.from_boxed(next),
+            },
+            U60 { numb } => NodeKind::U60 { numb: *numb },
+            F60 { numb } => NodeKind::F60 { numb: *numb },
+            Str { val } => NodeKind::Str { val: val.clone() },
+            Binary {
+                op,
+                left,
+                right,
+                signed,
+            } => NodeKind::Binary {
+                op: *op,
+                left: self.from_boxed(left),
+                right: self.from_boxed(right),
+                signed: *signed,
+            },
+            Err => NodeKind::Err,
+        };
+
+        self.alloc(expr.range, data)
+    }
+
+    /// Builds a boxed expression from a node of the arena. The
+    /// shared subtrees are copied.
+    pub fn to_boxed(&self, id: ExprId) -> Box<Expr> {
+        let node = self.get(id);
+        let range = node.range;
+        let to_boxed = |args: &[ExprId]| args.iter().map(|arg| self.to_boxed(*arg)).collect();
+
+        match &node.data {
+            NodeKind::Var { name } => Expr::var(*name),
+            NodeKind::Lambda {
+                param,
+                body,
+                erased,
+            } => Expr::lambda(range, *param, self.to_boxed(*body), *erased),
+            NodeKind::App { fun, args } => Expr::app(range, self.to_boxed(*fun), to_boxed(args)),
+            NodeKind::Fun { name, args } => Expr::fun(range, *name, to_boxed(args)),
+            NodeKind::Ctr { name, args } => Expr::ctr(range, *name, to_boxed(args)),
+            NodeKind::Let { name, val, next } => {
+                Expr::let_(range, *name, self.to_boxed(*val), self.to_boxed(*next))
+            }
+            NodeKind::U60 { numb } => Expr::u60(range, *numb),
+            NodeKind::F60 { numb } => Expr::f60(range, *numb),
+            NodeKind::Str { val } => Expr::str(range, val.clone()),
+            NodeKind::Binary {
+                op,
+                left,
+                right,
+                signed,
+            } => Expr::binary(
+                range,
+                *op,
+                self.to_boxed(*left),
+                self.to_boxed(*right),
+                *signed,
+            ),
+            NodeKind::Err => Expr::err(range),
+        }
+    }
+
+    /// Replaces the free variables of an expression. The values are
+    /// not copied, they are shared by all of the occurrences.
+    pub fn subst(&mut self, id: ExprId, vars: &FxHashMap<String, ExprId>) -> ExprId {
+        let mut bound = Vec::new();
+        self.subst_on(id, vars, &mut bound)
+    }
+
+    fn subst_on(
+        &mut self,
+        id: ExprId,
+        vars: &FxHashMap<String, ExprId>,
+        bound: &mut Vec<Ident>,
+    ) -> ExprId {
+        let node = self.get(id).clone();
+
+        let data = match node.data {
+            NodeKind::Var { name } => {
+                if bound.iter().any(|x| x.data == name.data) {
+                    return id;
+                }
+                return vars.get(name.to_str()).copied().unwrap_or(id);
+            }
+            NodeKind::Lambda {
+                param,
+                body,
+                erased,
+            } => {
+                bound.push(param);
+                let body = self.subst_on(body, vars, bound);
+                bound.pop();
+                NodeKind::Lambda {
+                    param,
+                    body,
+                    erased,
+                }
+            }
+            NodeKind::App { fun, args } => NodeKind::App {
+                fun: self.subst_on(fun, vars, bound),
+                args: self.subst_args(&args, vars, bound),
+            },
+            NodeKind::Fun { name, args } => NodeKind::Fun {
+                name,
+                args: self.subst_args(&args, vars, bound),
+            },
+            NodeKind::Ctr { name, args } => NodeKind::Ctr {
+                name,
+                args: self.subst_args(&args, vars, bound),
+            },
+            NodeKind::Let { name, val, next } => {
+                bound.push(name);
+                let val = self.subst_on(val, vars, bound);
+                let next = self.subst_on(next, vars, bound);
+                bound.pop();
+                NodeKind::Let { name, val, next }
+            }
+            NodeKind::Binary {
+                op,
+                left,
+                right,
+                signed,
+            } => NodeKind::Binary {
+                op,
+                left: self.subst_on(left, vars, bound),
+                right: self.subst_on(right, vars, bound),
+                signed,
+            },
+            NodeKind::U60 { .. }
+            | NodeKind::F60 { .. }
+            | NodeKind::Str { .. }
+            | NodeKind::Err => return id,
+        };
+
+        self.alloc(node.range, data)
+    }
+
+    fn subst_args(
+        &mut self,
+        args: &[ExprId],
+        vars: &FxHashMap<String, ExprId>,
+        bound: &mut Vec<Ident>,
+    ) -> Vec<ExprId> {
+        args.iter()
+            .map(|arg| self.subst_on(*arg, vars, bound))
+            .collect()
+    }
+}
@@ -0,0 +1,132 @@
+//! Specifies a fold trait for the untyped tree. It takes the
+//! ownership of each node and builds a new one, so a pass can
+//! replace whole subtrees without cloning them. The default
+//! implementations rebuild the same node from its folded children.
+
+use super::{Book, Entry, Expr, ExprKind, Rule};
+
+pub trait Fold: Sized {
+    fn fold_rule(&mut self, rule: Rule) -> Rule {
+        walk_rule(self, rule)
+    }
+
+    fn fold_entry(&mut self, entry: Entry) -> Entry {
+        walk_entry(self, entry)
+    }
+
+    fn fold_book(&mut self, book: Book) -> Book {
+        walk_book(self, book)
+    }
+
+    fn fold_expr(&mut self, expr: Box<Expr>) -> Box<Expr> {
+        walk_expr(self, expr)
+    }
+}
+
+pub fn walk_rule<T: Fold>(ctx: &mut T, rule: Rule) -> Rule {
+    Rule {
+        pats: rule.pats.into_iter().map(|x| ctx.fold_expr(x)).collect(),
+        body: ctx.fold_expr(rule.body),
+        ..rule
+    }
+}
+
+pub fn walk_entry<T: Fold>(ctx: &mut T, entry: Entry) -> Entry {
+    Entry {
+        rules: entry.rules.into_iter().map(|x| ctx.fold_rule(x)).collect(),
+        ..entry
+    }
+}
+
+pub fn walk_book<T: Fold>(ctx: &mut T, book: Book) -> Book {
+    Book {
+        entrs: book
+            .entrs
+            .into_iter()
+            .map(|(name, entry)| (name, Box::new(ctx.fold_entry(*entry))))
+            .collect(),
+        ..book
+    }
+}
+
+/// Folds the children of the expression. The box of the
+/// expression is reused for the result.
+pub fn walk_expr<T: Fold>(ctx: &mut T, mut expr: Box<Expr>) -> Box<Expr> {
+    let data = std::mem::replace(&mut expr.data, ExprKind::Err);
+
+    expr.data = match data {
+        ExprKind::Lambda {
+            param,
+            body,
+            erased,
+        } => ExprKind::Lambda {
+            param,
+            body: ctx.fold_expr(body),
+            erased,
+        },
+        ExprKind::App { fun, args } => ExprKind::App {
+            fun: ctx.fold_expr(fun),
+            args: args.into_iter().map(|x| ctx.fold_expr(x)).collect(),
+        },
+        ExprKind::Fun { name, args } => ExprKind::Fun {
+            name,
+            args: args.into_iter().map(|x| ctx.fold_expr(x)).collect(),
+        },
+        ExprKind::Ctr { name, args } => ExprKind::Ctr {
+            name,
+            args: args.into_iter().map(|x| ctx.fold_expr(x)).collect(),
+        },
+        ExprKind::Let { name, val, next } => ExprKind::Let {
+            name,
+            val: ctx.fold_expr(val),
+            next: ctx.fold_expr(next),
+        },
+        ExprKind::Binary {
+            op,
+            left,
+            right,
+            signed,
+        } => ExprKind::Binary {
+            op,
+            left: ctx.fold_expr(left),
+            right: ctx.fold_expr(right),
+            signed,
+        },
+        other => other,
+    };
+
+    expr
+}
+
+#[cfg(test)]
+mod tests {
+    use kind_span::Range;
+
+    use super::{walk_expr, Fold};
+    use crate::symbol::Ident;
+    use crate::untyped::{Expr, ExprKind};
+
+    /// Replaces every variable by zero.
+    struct Zero;
+
+    impl Fold for Zero {
+        fn fold_expr(&mut self, expr: Box<Expr>) -> Box<Expr> {
+            match expr.data {
+                ExprKind::Var { .. } => Expr::u60(expr.range, 0),
+                _ => walk_expr(self, expr),
+            }
+        }
+    }
+
+    #[test]
+    fn rebuilds_all_of_the_children() {
+        let range = Range::ghost_range();
+        let x = Ident::generate("x");
+        let expr = Expr::app(range, Expr::var(x), vec![Expr::let_(range, x, Expr::var(x), Expr::var(x))]);
+
+        let zero = || Expr::u60(range, 0);
+        let expected = Expr::app(range, zero(), vec![Expr::let_(range, x, zero(), zero())]);
+
+        assert_eq!(Zero.fold_expr(expr), expected);
+    }
+}
@@ -6,6 +6,13 @@ use std::fmt::{Display, Error, Formatter};
 use fxhash::FxHashMap;
 use kind_span::Range;
 use linked_hash_map::LinkedHashMap;
+use serde::Serialize;
+
+pub mod arena;
+pub mod fold;
+pub mod visitor;
+
+mod pretty;
 
 pub use crate::Operator;
 use crate::{
@@ -18,7 +25,7 @@ use crate::{
 /// as ((((a b) c) d) e) that looks like a spine.
 pub type Spine = Vec<Box<Expr>>;
 
-#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize)]
 pub enum ExprKind {
     /// Name of a variable
     Var {
@@ -63,17 +70,19 @@ pub enum ExprKind {
     Str {
         val: String,
     },
-    /// Binary operation (e.g. 2 + 3)
+    /// Binary operation (e.g. 2 + 3). The signed operations work
+    /// on the two's complement of the numbers.
     Binary {
         op: Operator,
         left: Box<Expr>,
         right: Box<Expr>,
+        signed: bool,
     },
 
     Err,
 }
 
-#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+#[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize)]
 pub struct Expr {
     pub data: ExprKind,
     pub range: Range,
@@ -147,10 +156,27 @@ impl Expr {
         })
     }
 
-    pub fn binary(range: Range, op: Operator, left: Box<Expr>, right: Box<Expr>) -> Box<Expr> {
+    /// The signed numbers are stored as the two's complement of
+    /// 60 bits, so they can use the same operations as `U60`.
+    pub fn i60(range: Range, numb: i64) -> Box<Expr> {
+        Expr::u60(range, numb as u64 & 0xFFFFFFFFFFFFFFF)
+    }
+
+    pub fn binary(
+        range: Range,
+        op: Operator,
+        left: Box<Expr>,
+        right: Box<Expr>,
+        signed: bool,
+    ) -> Box<Expr> {
         Box::new(Expr {
             range,
-            data: ExprKind::Binary { op, left, right },
+            data: ExprKind::Binary {
+                op,
+                left,
+                right,
+                signed,
+            },
         })
     }
 
@@ -168,7 +194,7 @@ impl Expr {
 /// compiled.
 /// hide: that express a implicit argument (that will
 /// be discovered through unification).
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct Argument {
     pub hidden: bool,
     pub erased: bool,
@@ -180,7 +206,7 @@ pub struct Argument {
 /// A rule is a equation that in the left-hand-side
 /// contains a list of patterns @pats@ and on the
 /// right hand side a value.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct Rule {
     pub name: QualifiedIdent,
     pub pats: Vec<Box<Expr>>,
@@ -192,7 +218,7 @@ pub struct Rule {
 /// and has rules. The type of the function
 /// consists of the arguments @args@ and the
 /// return type @typ@.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
 pub struct Entry {
     pub name: QualifiedIdent,
     pub args: Vec<(String, Range, bool)>,
@@ -202,9 +228,10 @@ pub struct Entry {
 }
 
 /// A book is a collection of desugared entries.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize)]
 pub struct Book {
     pub entrs: LinkedHashMap<String, Box<Entry>>,
+    #[serde(serialize_with = "crate::serialize_sorted")]
     pub names: FxHashMap<String, usize>,
 }
 
@@ -255,7 +282,7 @@ impl Display for Expr {
                 }
             }
             Let { name, val, next } => write!(f, "(let {} = {}; {})", name, val, next),
-            Binary { op, left, right } => write!(f, "({} {} {})", op, left, right),
+            Binary { op, left, right, .. } => write!(f, "({} {} {})", left, op, right),
         }
     }
 }
@@ -310,7 +337,7 @@ impl Argument {
         Argument {
             hidden: true,
             erased: true,
-            name: self.name.clone(),
+            name: self.name,
             typ: self.typ.clone(),
             range: self.range,
         }
@@ -320,7 +347,7 @@ impl Argument {
         Argument {
             hidden: false,
             erased: false,
-            name: name.clone(),
+            name: *name,
             typ,
             range,
         }
@@ -0,0 +1,74 @@
+//! Pretty printing of the untyped tree. The documents render to
+//! the same text as [Display] when they fit in a single line.
+//!
+//! [Display]: std::fmt::Display
+
+use kind_pretty::{Doc, Pretty};
+
+use super::{Book, Entry, Expr, ExprKind, Rule};
+
+impl Pretty for Expr {
+    fn to_doc(&self) -> Doc {
+        use ExprKind::*;
+        match &self.data {
+            Lambda {
+                param,
+                body,
+                erased,
+            } => {
+                let tilde = if *erased { "~" } else { "" };
+                Doc::binder(Doc::text(format!("{}{} =>", tilde, param)), body.to_doc())
+            }
+            App { fun, args } => Doc::app(fun.to_doc(), args.iter().map(|x| x.to_doc())),
+            Fun { name, args } | Ctr { name, args } if !args.is_empty() => {
+                Doc::app(Doc::text(name.to_string()), args.iter().map(|x| x.to_doc()))
+            }
+            Let { name, val, next } => Doc::concat([
+                Doc::text(format!("(let {} =", name)),
+                Doc::concat([Doc::line(), val.to_doc(), Doc::text(";")]).nest(2).group(),
+                Doc::concat([Doc::line(), next.to_doc()]).nest(1),
+                Doc::text(")"),
+            ])
+            .align()
+            .group(),
+            Binary { op, left, right, .. } => Doc::binder(
+                left.to_doc(),
+                Doc::text(format!("{} ", op)).append(right.to_doc()),
+            ),
+            _ => Doc::text(self.to_string()),
+        }
+    }
+}
+
+impl Pretty for Rule {
+    fn to_doc(&self) -> Doc {
+        let lhs = Doc::concat([
+            Doc::text(self.name.to_string()),
+            Doc::concat(self.pats.iter().map(|x| Doc::line().append(x.to_doc()))).nest(2),
+        ]);
+
+        Doc::concat([
+            lhs.group(),
+            Doc::text(" ="),
+            Doc::concat([Doc::line(), self.body.to_doc()]).nest(2).group(),
+        ])
+    }
+}
+
+impl Pretty for Entry {
+    fn to_doc(&self) -> Doc {
+        Doc::concat(self.rules.iter().map(|x| Doc::HardLine.append(x.to_doc())))
+    }
+}
+
+impl Pretty for Book {
+    fn to_doc(&self) -> Doc {
+        Doc::concat(self.entrs.values().map(|entr| {
+            if !entr.rules.is_empty() {
+                Doc::concat([entr.to_doc(), Doc::HardLine])
+            } else {
+                Doc::concat([Doc::text(format!("ctr {}", entr.name)), Doc::HardLine])
+            }
+        }))
+    }
+}
@@ -0,0 +1,139 @@
+//! Specifies a visitor trait for the untyped tree. It changes
+//! the tree in place and the default implementations just walk
+//! through all of the children, so a pass only has to implement
+//! the nodes that it cares about.
+
+use kind_span::Range;
+
+use crate::symbol::{Ident, QualifiedIdent};
+
+use super::{Book, Entry, Expr, ExprKind, Rule};
+
+pub trait Visitor: Sized {
+    fn visit_range(&mut self, range: &mut Range) {
+        walk_range(self, range);
+    }
+
+    fn visit_ident(&mut self, ident: &mut Ident) {
+        walk_ident(self, ident);
+    }
+
+    fn visit_qualified_ident(&mut self, ident: &mut QualifiedIdent) {
+        walk_qualified_ident(self, ident);
+    }
+
+    fn visit_rule(&mut self, rule: &mut Rule) {
+        walk_rule(self, rule);
+    }
+
+    fn visit_entry(&mut self, entry: &mut Entry) {
+        walk_entry(self, entry);
+    }
+
+    fn visit_book(&mut self, book: &mut Book) {
+        walk_book(self, book);
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        walk_expr(self, expr);
+    }
+}
+
+pub fn walk_range<T: Visitor>(_: &mut T, _: &mut Range) {}
+
+pub fn walk_ident<T: Visitor>(ctx: &mut T, ident: &mut Ident) {
+    ctx.visit_range(&mut ident.range);
+}
+
+pub fn walk_qualified_ident<T: Visitor>(ctx: &mut T, ident: &mut QualifiedIdent) {
+    ctx.visit_range(&mut ident.range);
+}
+
+pub fn walk_rule<T: Visitor>(ctx: &mut T, rule: &mut Rule) {
+    ctx.visit_qualified_ident(&mut rule.name);
+    for pat in &mut rule.pats {
+        ctx.visit_expr(pat);
+    }
+    ctx.visit_expr(&mut rule.body);
+    ctx.visit_range(&mut rule.range);
+}
+
+pub fn walk_entry<T: Visitor>(ctx: &mut T, entry: &mut Entry) {
+    ctx.visit_qualified_ident(&mut entry.name);
+    for rule in &mut entry.rules {
+        ctx.visit_rule(rule);
+    }
+    ctx.visit_range(&mut entry.range);
+}
+
+pub fn walk_book<T: Visitor>(ctx: &mut T, book: &mut Book) {
+    for (_, entry) in book.entrs.iter_mut() {
+        ctx.visit_entry(entry);
+    }
+}
+
+pub fn walk_expr<T: Visitor>(ctx: &mut T, expr: &mut Expr) {
+    ctx.visit_range(&mut expr.range);
+    match &mut expr.data {
+        ExprKind::Var { name } => ctx.visit_ident(name),
+        ExprKind::Lambda { param, body, .. } => {
+            ctx.visit_ident(param);
+            ctx.visit_expr(body);
+        }
+        ExprKind::App { fun, args } => {
+            ctx.visit_expr(fun);
+            for arg in args {
+                ctx.visit_expr(arg);
+            }
+        }
+        ExprKind::Fun { name, args } | ExprKind::Ctr { name, args } => {
+            ctx.visit_qualified_ident(name);
+            for arg in args {
+                ctx.visit_expr(arg);
+            }
+        }
+        ExprKind::Let { name, val, next } => {
+            ctx.visit_ident(name);
+            ctx.visit_expr(val);
+            ctx.visit_expr(next);
+        }
+        ExprKind::Binary { left, right, .. } => {
+            ctx.visit_expr(left);
+            ctx.visit_expr(right);
+        }
+        ExprKind::U60 { .. } | ExprKind::F60 { .. } | ExprKind::Str { .. } | ExprKind::Err => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use kind_span::Range;
+
+    use super::Visitor;
+    use crate::symbol::{Ident, QualifiedIdent};
+    use crate::untyped::Expr;
+
+    struct Names(Vec<String>);
+
+    impl Visitor for Names {
+        fn visit_ident(&mut self, ident: &mut Ident) {
+            self.0.push(ident.to_string())
+        }
+
+        fn visit_qualified_ident(&mut self, ident: &mut QualifiedIdent) {
+            self.0.push(ident.to_string())
+        }
+    }
+
+    #[test]
+    fn walks_all_of_the_children() {
+        let range = Range::ghost_range();
+        let name = QualifiedIdent::new_static("Pair.new", None, range);
+        let body = Expr::ctr(range, name, vec![Expr::var(Ident::generate("x")), Expr::u60(range, 1)]);
+        let mut expr = Expr::lambda(range, Ident::generate("x"), body, false);
+
+        let mut names = Names(Vec::new());
+        names.visit_expr(&mut expr);
+        assert_eq!(names.0, ["x", "Pair.new", "x"]);
+    }
+}